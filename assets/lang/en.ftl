@@ -0,0 +1,375 @@
+language-name = English
+
+## Menus
+
+menu-play = Play
+menu-settings = Settings
+menu-back = Back
+menu-main-menu = Main Menu
+menu-singleplayer = Singleplayer
+menu-multiplayer = Multiplayer
+menu-room = Room:
+menu-local-port = Local Port:
+menu-bestiary = Bestiary
+menu-watch-replay = Watch Replay
+menu-players = Players:
+menu-local-players = Players on this machine:
+menu-player-number = You are player:
+menu-remote-address = Player { $player }'s Address:
+menu-invalid-address = Should look like 192.168.1.2:2222
+menu-arena = Arena
+menu-lobby = Lobby
+menu-leave = Leave
+menu-best-arena-score = Best arena score: { $score }
+menu-input-delay = Input Delay (frames):
+menu-prediction-window = Prediction Window (frames):
+menu-sparse-saving = Only save confirmed frames
+menu-seed = Seed:
+menu-random-seed = Random
+menu-friendly-fire = Friendly fire
+difficulty-easy = Easy
+difficulty-normal = Normal
+difficulty-hard = Hard
+mode-dungeon = Dungeon
+mode-arena = Arena
+
+## Networking
+
+net-error-bad-address = "{ $addr }" isn't an address like 192.168.1.2:2222
+net-error-bind = Couldn't use local port { $port }: { $error }
+net-error-unreachable = Couldn't reach { $addr }: { $error }
+net-error-session = Couldn't start the session: { $error }
+
+## Lobby
+
+lobby-waiting-for-players = Waiting for everyone to join...
+lobby-peer = { $addr }: { $status }
+lobby-synchronizing = Connecting ({ $count }/{ $total })
+lobby-synchronized = Connected
+lobby-interrupted = Connection interrupted
+lobby-disconnected = Disconnected
+lobby-player = Player { $player }
+lobby-you = Player { $player } (you)
+lobby-ready = Ready
+lobby-not-ready = Not ready
+lobby-different-seed = Playing seed { $seed }, not yours
+lobby-different-mode = Playing { $mode } mode, not yours
+lobby-different-difficulty = Playing on { $difficulty }, not yours
+lobby-friendly-fire-on = Has friendly fire on, unlike you
+lobby-friendly-fire-off = Has friendly fire off, unlike you
+lobby-ready-toggle = I'm ready
+disconnect-title = Connection lost
+disconnect-waiting = Waiting for them to reconnect...
+disconnect-continue-solo = Continue solo
+
+## Chat
+
+chat-message = Player { $player }: { $text }
+
+## HUD
+
+hud-hp = HP: { $hp }
+hud-mp = MP: { $mp }
+hud-spell = Spell: { $spell }
+hud-level-up = Level { $level }!
+hud-cycling-spell = Cycling Spell...
+hud-hidden = Hidden
+hud-seen = Seen
+hud-ammo = { $item }: { $count }
+hud-wave = Wave { $wave }
+hud-score = Score: { $score }
+hud-wave-time-left = Next wave in { $seconds }s, ready or not
+hud-next-wave = Next wave in { $seconds }s
+hud-downed = Downed! Bleeding out in { $seconds }s
+hud-dead = Dead
+level-up-title = Level { $level }! Pick a boon
+level-up-boon = [{ $key }] { $boon }
+boon-max-hp = +{ $amount } max HP
+boon-max-mp = +{ $amount } max MP
+boon-speed = Move faster
+boon-willpower = +{ $amount } willpower
+boon-spell = Learn { $spell }
+hud-seed = Seed: { $seed }
+hud-desynced = Out of sync with the other players since frame { $frame }
+
+## Classes and spells
+
+class-warrior = Warrior
+class-wizard = Wizard
+class-rogue = Rogue
+class-cleric = Cleric
+class-ranger = Ranger
+
+spell-blinding-light = Blinding Light
+spell-blink = Blink
+spell-fireball = Fireball
+spell-frost-nova = Frost Nova
+spell-magic-missile = Magic Missile
+spell-radiant-burst = Radiant Burst
+spell-summon-skeleton = Summon Skeleton
+
+## Monsters
+
+monster-small-rat =
+    { $count ->
+        [one] Small Rat
+       *[other] Small Rats
+    }
+monster-green-slime =
+    { $count ->
+        [one] Green Slime
+       *[other] Green Slimes
+    }
+
+monster-rat-king =
+    { $count ->
+        [one] Rat King
+       *[other] Rat Kings
+    }
+monster-slime-queen =
+    { $count ->
+        [one] Slime Queen
+       *[other] Slime Queens
+    }
+monster-rat-nest =
+    { $count ->
+        [one] Rat Nest
+       *[other] Rat Nests
+    }
+monster-slime-pool =
+    { $count ->
+        [one] Slime Pool
+       *[other] Slime Pools
+    }
+monster-skeleton-archer =
+    { $count ->
+        [one] Skeleton Archer
+       *[other] Skeleton Archers
+    }
+monster-bat =
+    { $count ->
+        [one] Bat
+       *[other] Bats
+    }
+monster-mimic =
+    { $count ->
+        [one] Mimic
+       *[other] Mimics
+    }
+monster-necromancer =
+    { $count ->
+        [one] Necromancer
+       *[other] Necromancers
+    }
+monster-zombie =
+    { $count ->
+        [one] Zombie
+       *[other] Zombies
+    }
+monster-gelatinous-cube =
+    { $count ->
+        [one] Gelatinous Cube
+       *[other] Gelatinous Cubes
+    }
+monster-spider =
+    { $count ->
+        [one] Spider
+       *[other] Spiders
+    }
+monster-wolf =
+    { $count ->
+        [one] Wolf
+       *[other] Wolves
+    }
+door-scratched = *scratch scratch*
+
+## Bestiary
+
+bestiary-unknown = ???
+bestiary-kills = Killed: { $kills }
+bestiary-damage-taken = Damage taken: { $damage }
+bestiary-resistances = Resistances: { $resistances }
+bestiary-resistance = { $name } ({ $resistance })
+bestiary-no-resistances = Nothing
+bestiary-lore-locked = Kill { $kills } more to learn about it
+
+lore-small_rat = Rats have always lived under the city, but never this many, and never this bold. They hunt in packs now, as if something were leading them.
+lore-green_slime = Slimes have no eyes to blind and nothing to stick to. They spit globs of themselves at anything warm.
+lore-rat_king = Dozens of rats, their tails knotted together into one creature. Every rat in the dungeon answers to it.
+lore-slime_queen = Every slime in the dungeon once dripped off of her. She sits on the stairs down, and lets nothing past.
+lore-rat_nest = A heap of rags, straw and gnawed bones. The rats keep coming out of it for as long as it stands.
+lore-slime_pool = Still, green and warm. Anything that wanders too close finds the pool has started to crawl after it.
+lore-skeleton_archer = Whoever it was, it was buried with its bow. It never lets anyone get close, and the creak of the string is the only warning it gives.
+lore-bat = Bats never come alone. Hit one and it's gone up into the dark, but it'll be back, and it'll bring the others.
+lore-mimic = It looks just like treasure, right up until it bites. Adventurers who've met one poke every chest with a stick first.
+lore-necromancer = It never fights fair, and it never fights alone. Whatever you've killed, it'll send back at you, and it's gone before you can reach it.
+lore-zombie = Slow, rotten and stubborn. It doesn't remember what it used to be, only who killed it.
+lore-gelatinous_cube = It cleans the dungeon floor better than any servant, swallowing coins, potions and bones alike. Cut it in half and you've only made two of them.
+lore-spider = Its bite barely breaks the skin. It's the hours afterwards, sweating out the venom, that adventurers remember.
+lore-wolf = Rangers raise them from pups, and a wolf raised that way will follow its ranger down any stairs. The wild ones only follow the smell of blood.
+
+enchantment-blinded = Blindness
+enchantment-sticky = Slowness
+enchantment-regenerating = Regeneration
+enchantment-stunned = Stuns
+enchantment-burning = Burning
+enchantment-poisoned = Poison
+enchantment-chilled = Chill
+enchantment-frozen = Freezing
+
+damage-slash = Slashing
+damage-pierce = Piercing
+damage-blunt = Blunt
+damage-magic = Magic
+damage-fire = Fire
+damage-ice = Ice
+
+resistance-immune = immune
+resistance-resistant = resistant
+resistance-vulnerable = vulnerable
+
+## Shrines
+
+shrine-cost-gold = { $gold } gold
+shrine-cost-hp = { $hp } HP
+shrine-blessed = Blessed with regeneration!
+shrine-max-hp-up = +{ $amount } max HP
+shrine-max-mp-up = +{ $amount } max MP
+shrine-potion = A potion appears
+shrine-blinded = Cursed with blindness!
+shrine-max-hp-down = Cursed: -{ $amount } max HP
+
+## Downed
+
+player-downed = Down!
+player-revived = Back on their feet!
+player-died = Bled out
+
+## Ambushes
+
+secret-found = A secret passage!
+ambush-started = Ambush!
+ambush-cleared = Ambush cleared!
+
+## Arena
+
+arena-wave-started = Wave { $wave }!
+arena-wave-cleared = Wave { $wave } cleared!
+exit-unlocked = The way down is open!
+descent-slay-guardian = Slay the floor's guardian to open the way down
+descent-sigil = Find the floor's sigil to open the way down
+descent-gathered = Everyone has to stand on the way down together
+floor-reached = { $biome }, floor { $floor }
+biome-sewer = The Sewers
+biome-cave = The Caves
+biome-crypt = The Crypt
+biome-library = The Library
+floor-modifier-darkness = The lights are out
+floor-modifier-infestation = Something is breeding down here
+floor-modifier-treasure = Gold glints in every room
+shop-price = { $gold }g
+npc-vendor = Vendor
+
+## Quests
+
+quest-log-title = Quests:
+quest-log-empty = Find a notice board for work
+quest-kill = Kill { $amount } { $monster } on floor { $floor } ({ $progress }/{ $amount })
+quest-find = Find the { $item }
+quest-done = { $quest } - Done!
+quest-reward = { $quest } - { $gold } gold, { $xp } XP
+
+## Items
+
+item-gold = { $amount } gold
+item-gold-description = Gold! Currency! Can be used at shops to purchase items
+item-cursed-description = Malevolant energy slithers from it.
+item-unknown = Unknown item
+item-unknown-description = Whatever this is, it isn't in the game's list of items.
+
+item-short_sword = Short Sword
+item-short_sword-description = A sturdy short sword, passed down from many generations.
+item-spear = Spear
+item-spear-description = A long ash spear. Slow to bring back, but it keeps trouble at arm's length.
+item-wizards_dagger = Wizard's Dagger
+item-wizards_dagger-description = A dagger engraved with mystical runes
+item-wizard_glove = Wizard's Glove
+item-wizard_glove-description = A glove wielded by mighty sorcerers. Thiey alow magic users to directly tough the energy around them and manipulate it to their will.
+item-throwing_knife = Throwing Knife
+item-throwing_knife-description = A small but very sharp knife
+item-bow = Bow
+item-bow-description = A short hunting bow. Useless without arrows.
+item-arrow = Arrow
+item-arrow-description = A plain wooden arrow. Ones that miss can usually be picked up again.
+item-potion_regeneration = Potion of Regeneration
+item-potion_regeneration-description = Helps the body to recover from damage
+item-potion_mana = Potion of Mana
+item-potion_mana-description = Restores some of the drinker's magic
+item-lost_ring = Lost Ring
+item-lost_ring-description = A plain gold ring. Someone is looking for it.
+item-floor_sigil = Floor Sigil
+item-floor_sigil-description = A stone tablet carved with the floor's seal. Whoever carries it can open the way down.
+item-warriors_shield = Warrior's Shield
+item-warriors_shield-description = A battered iron shield. Raise it to catch blows from the front.
+item-war_axe = War Axe
+item-war_axe-description = A heavy double-headed axe. Swing it round and round to carve through a crowd.
+item-mace = Mace
+item-mace-description = A flanged iron mace, blessed at the temple. Wind it up and bring it down hard enough, and the light comes with it.
+item-holy_symbol = Holy Symbol
+item-holy_symbol-description = A worn silver sunburst on a chain. Held up to the light, it mends the wounds of whichever ally is closest.
+item-leather_cap = Leather Cap
+item-leather_cap-description = Boiled leather, stitched tight. It won't stop much, but it stops a little of everything.
+item-chainmail = Chainmail
+item-chainmail-description = Thousands of iron rings, and heavy with every one of them. Hits land softer, and so do your steps.
+item-ring_of_focus = Ring of Focus
+item-ring_of_focus-description = A plain band of grey stone, cool to the touch. Wearing it, your mind settles and your magic comes back quicker.
+item-swift_boots = Swift Boots
+item-swift_boots-description = Soft-soled and light as slippers. Whoever made them was in a hurry.
+item-wand_fireball = Wand of Fireball
+item-wand_fireball-description = A charred stick that still smells of smoke. Anyone can point it and cast a fireball.
+item-wand_frost_nova = Wand of Frost
+item-wand_frost_nova-description = A wand rimed with frost. Anyone can point it and cast a frost nova.
+item-charges = { $item } ({ $charges })
+item-knife_bandolier = Knife Bandolier
+item-knife_bandolier-description = A leather strap with loops for a dozen knives. Lets you throw three at once.
+item-vampiric_dagger = Vampiric Dagger
+item-vampiric_dagger-description = A black dagger that's always warm. Whoever holds it heals a little with every wound it makes.
+item-slime_sword = Slime-Coated Sword
+item-slime_sword-description = A sword that's spent too long at the bottom of a slime pool. What it cuts often ends up stuck in place.
+item-venom_vial = Vial of Venom
+item-venom_vial-description = Milked from a spider's fangs. Any throwing knives you carry are coated in it, and poison whatever they hit.
+
+## Equipment
+
+equipment-slot-head = Head
+equipment-slot-body = Body
+equipment-slot-ring = Ring
+equipment-slot-feet = Feet
+
+## Victory
+
+victory-title = Victory!
+victory-subtitle = The rat king is dead, and the dungeon is quiet at last
+
+credits-title = Roguelite
+credits-made-by = Made by billyb2 and contributors
+credits-built-with = Built with macroquad, ggrs and egui
+credits-thanks = Thanks for playing!
+
+## Game Over
+
+game-over-title = Game Over
+game-over-subtitle = The dungeon claims another party
+
+summary-title = Run Summary
+summary-time = Time: { $time }
+summary-floors = Floors cleared: { $floors }
+summary-kills = Monsters killed: { $kills }
+summary-gold = Gold collected: { $gold }
+summary-level = Level reached: { $level }
+summary-quests = Quests completed: { $quests }
+summary-unlocked = Unlocked: { $unlock }
+
+unlock-rat-slayer = Rat Slayer
+unlock-speedrunner = Speedrunner
+unlock-veteran = Veteran
@@ -0,0 +1,375 @@
+language-name = Español
+
+## Menus
+
+menu-play = Jugar
+menu-settings = Ajustes
+menu-back = Volver
+menu-main-menu = Menú principal
+menu-singleplayer = Un jugador
+menu-multiplayer = Multijugador
+menu-room = Sala:
+menu-local-port = Puerto local:
+menu-bestiary = Bestiario
+menu-watch-replay = Ver repetición
+menu-players = Jugadores:
+menu-local-players = Jugadores en este equipo:
+menu-player-number = Eres el jugador:
+menu-remote-address = Dirección del jugador { $player }:
+menu-invalid-address = Debe tener la forma 192.168.1.2:2222
+menu-arena = Arena
+menu-lobby = Sala de espera
+menu-leave = Salir
+menu-best-arena-score = Mejor puntuación en la arena: { $score }
+menu-input-delay = Retraso de entrada (fotogramas):
+menu-prediction-window = Ventana de predicción (fotogramas):
+menu-sparse-saving = Guardar solo fotogramas confirmados
+menu-seed = Semilla:
+menu-random-seed = Aleatoria
+menu-friendly-fire = Fuego amigo
+difficulty-easy = Fácil
+difficulty-normal = Normal
+difficulty-hard = Difícil
+mode-dungeon = Mazmorra
+mode-arena = Arena
+
+## Networking
+
+net-error-bad-address = "{ $addr }" no es una dirección como 192.168.1.2:2222
+net-error-bind = No se pudo usar el puerto local { $port }: { $error }
+net-error-unreachable = No se pudo alcanzar { $addr }: { $error }
+net-error-session = No se pudo iniciar la sesión: { $error }
+
+## Lobby
+
+lobby-waiting-for-players = Esperando a que se unan todos...
+lobby-peer = { $addr }: { $status }
+lobby-synchronizing = Conectando ({ $count }/{ $total })
+lobby-synchronized = Conectado
+lobby-interrupted = Conexión interrumpida
+lobby-disconnected = Desconectado
+lobby-player = Jugador { $player }
+lobby-you = Jugador { $player } (tú)
+lobby-ready = Listo
+lobby-not-ready = No está listo
+lobby-different-seed = Juega con la semilla { $seed }, no con la tuya
+lobby-different-mode = Juega en modo { $mode }, no en el tuyo
+lobby-different-difficulty = Juega en dificultad { $difficulty }, no en la tuya
+lobby-friendly-fire-on = Tiene el fuego amigo activado y tú no
+lobby-friendly-fire-off = Tiene el fuego amigo desactivado y tú no
+lobby-ready-toggle = Estoy listo
+disconnect-title = Conexión perdida
+disconnect-waiting = Esperando a que se vuelvan a conectar...
+disconnect-continue-solo = Seguir solo
+
+## Chat
+
+chat-message = Jugador { $player }: { $text }
+
+## HUD
+
+hud-hp = PV: { $hp }
+hud-mp = PM: { $mp }
+hud-spell = Hechizo: { $spell }
+hud-level-up = ¡Nivel { $level }!
+hud-cycling-spell = Cambiando de hechizo...
+hud-hidden = Oculto
+hud-seen = A la vista
+hud-ammo = { $item }: { $count }
+hud-wave = Oleada { $wave }
+hud-score = Puntuación: { $score }
+hud-wave-time-left = Siguiente oleada en { $seconds }s, lista o no
+hud-next-wave = Siguiente oleada en { $seconds }s
+hud-downed = ¡Caído! Te desangras en { $seconds }s
+hud-dead = Muerto
+level-up-title = ¡Nivel { $level }! Elige una mejora
+level-up-boon = [{ $key }] { $boon }
+boon-max-hp = +{ $amount } de PV máximos
+boon-max-mp = +{ $amount } de PM máximos
+boon-speed = Moverte más rápido
+boon-willpower = +{ $amount } de voluntad
+boon-spell = Aprender { $spell }
+hud-seed = Semilla: { $seed }
+hud-desynced = Desincronizado con los demás jugadores desde el fotograma { $frame }
+
+## Classes and spells
+
+class-warrior = Guerrero
+class-wizard = Mago
+class-rogue = Pícaro
+class-cleric = Clérigo
+class-ranger = Explorador
+
+spell-blinding-light = Luz cegadora
+spell-blink = Traslación
+spell-fireball = Bola de fuego
+spell-frost-nova = Nova de escarcha
+spell-magic-missile = Proyectil mágico
+spell-radiant-burst = Estallido radiante
+spell-summon-skeleton = Invocar esqueleto
+
+## Monsters
+
+monster-small-rat =
+    { $count ->
+        [one] rata pequeña
+       *[other] ratas pequeñas
+    }
+monster-green-slime =
+    { $count ->
+        [one] limo verde
+       *[other] limos verdes
+    }
+
+monster-rat-king =
+    { $count ->
+        [one] rey rata
+       *[other] reyes rata
+    }
+monster-slime-queen =
+    { $count ->
+        [one] reina limo
+       *[other] reinas limo
+    }
+monster-rat-nest =
+    { $count ->
+        [one] nido de ratas
+       *[other] nidos de ratas
+    }
+monster-slime-pool =
+    { $count ->
+        [one] charco de limo
+       *[other] charcos de limo
+    }
+monster-skeleton-archer =
+    { $count ->
+        [one] arquero esqueleto
+       *[other] arqueros esqueleto
+    }
+monster-bat =
+    { $count ->
+        [one] murciélago
+       *[other] murciélagos
+    }
+monster-mimic =
+    { $count ->
+        [one] mímico
+       *[other] mímicos
+    }
+monster-necromancer =
+    { $count ->
+        [one] nigromante
+       *[other] nigromantes
+    }
+monster-zombie =
+    { $count ->
+        [one] zombi
+       *[other] zombis
+    }
+monster-gelatinous-cube =
+    { $count ->
+        [one] cubo gelatinoso
+       *[other] cubos gelatinosos
+    }
+monster-spider =
+    { $count ->
+        [one] araña
+       *[other] arañas
+    }
+monster-wolf =
+    { $count ->
+        [one] lobo
+       *[other] lobos
+    }
+door-scratched = *ras ras*
+
+## Bestiary
+
+bestiary-unknown = ???
+bestiary-kills = Muertos: { $kills }
+bestiary-damage-taken = Daño recibido: { $damage }
+bestiary-resistances = Resistencias: { $resistances }
+bestiary-resistance = { $name } ({ $resistance })
+bestiary-no-resistances = Nada
+bestiary-lore-locked = Mata { $kills } más para saber más
+
+lore-small_rat = Siempre ha habido ratas bajo la ciudad, pero nunca tantas ni tan atrevidas. Ahora cazan en manada, como si algo las guiara.
+lore-green_slime = Los limos no tienen ojos que cegar ni nada a lo que pegarse. Escupen pedazos de sí mismos a todo lo que tenga calor.
+lore-rat_king = Docenas de ratas con las colas anudadas en una sola criatura. Todas las ratas de la mazmorra le obedecen.
+lore-slime_queen = Todos los limos de la mazmorra salieron de ella. Se sienta sobre la escalera y no deja pasar a nadie.
+lore-rat_nest = Un montón de trapos, paja y huesos roídos. Las ratas no dejan de salir de él mientras siga en pie.
+lore-slime_pool = Quieto, verde y templado. Lo que se acerca demasiado descubre que el charco ha empezado a arrastrarse tras él.
+lore-skeleton_archer = Quienquiera que fuese, lo enterraron con su arco. Nunca deja que nadie se acerque, y el crujido de la cuerda es el único aviso que da.
+lore-bat = Los murciélagos nunca vienen solos. Golpea a uno y desaparecerá en la oscuridad, pero volverá, y traerá a los demás.
+lore-mimic = Parece un tesoro hasta el momento en que muerde. Los aventureros que se han topado con uno tantean cada cofre con un palo antes de abrirlo.
+lore-necromancer = Nunca lucha limpio, y nunca lucha solo. Todo lo que hayas matado te lo devolverá, y desaparecerá antes de que puedas alcanzarlo.
+lore-zombie = Lento, podrido y terco. No recuerda lo que fue, solo quién lo mató.
+lore-gelatinous_cube = Limpia el suelo de la mazmorra mejor que cualquier sirviente, y se traga monedas, pociones y huesos por igual. Pártelo por la mitad y solo habrás conseguido dos.
+lore-spider = Su mordisco apenas atraviesa la piel. Lo que los aventureros recuerdan son las horas siguientes, sudando el veneno.
+lore-wolf = Los exploradores los crían desde cachorros, y un lobo criado así sigue a su explorador escaleras abajo a donde sea. Los salvajes solo siguen el olor de la sangre.
+
+enchantment-blinded = Ceguera
+enchantment-sticky = Lentitud
+enchantment-regenerating = Regeneración
+enchantment-stunned = Aturdimiento
+enchantment-burning = Quemadura
+enchantment-poisoned = Veneno
+enchantment-chilled = Escarcha
+enchantment-frozen = Congelación
+
+damage-slash = Cortante
+damage-pierce = Perforante
+damage-blunt = Contundente
+damage-magic = Mágico
+damage-fire = Fuego
+damage-ice = Hielo
+
+resistance-immune = inmune
+resistance-resistant = resistente
+resistance-vulnerable = vulnerable
+
+## Shrines
+
+shrine-cost-gold = { $gold } de oro
+shrine-cost-hp = { $hp } PV
+shrine-blessed = ¡Bendecido con regeneración!
+shrine-max-hp-up = +{ $amount } PV máximos
+shrine-max-mp-up = +{ $amount } PM máximos
+shrine-potion = Aparece una poción
+shrine-blinded = ¡Maldito con ceguera!
+shrine-max-hp-down = Maldición: -{ $amount } PV máximos
+
+## Downed
+
+player-downed = ¡Caído!
+player-revived = ¡De nuevo en pie!
+player-died = Se desangró
+
+## Ambushes
+
+secret-found = ¡Un pasaje secreto!
+ambush-started = ¡Emboscada!
+ambush-cleared = ¡Emboscada superada!
+
+## Arena
+
+arena-wave-started = ¡Oleada { $wave }!
+arena-wave-cleared = ¡Oleada { $wave } superada!
+exit-unlocked = ¡El camino hacia abajo está abierto!
+descent-slay-guardian = Derrota al guardián del piso para abrir el camino hacia abajo
+descent-sigil = Encuentra el sello del piso para abrir el camino hacia abajo
+descent-gathered = Todos tienen que estar juntos sobre el camino hacia abajo
+floor-reached = { $biome }, piso { $floor }
+biome-sewer = Las Alcantarillas
+biome-cave = Las Cuevas
+biome-crypt = La Cripta
+biome-library = La Biblioteca
+floor-modifier-darkness = Las luces se han apagado
+floor-modifier-infestation = Algo se está criando aquí abajo
+floor-modifier-treasure = El oro brilla en cada sala
+shop-price = { $gold }o
+npc-vendor = Vendedor
+
+## Quests
+
+quest-log-title = Misiones:
+quest-log-empty = Busca un tablón de anuncios para encontrar trabajo
+quest-kill = Mata { $amount } { $monster } en el piso { $floor } ({ $progress }/{ $amount })
+quest-find = Encuentra { $item }
+quest-done = { $quest } - ¡Hecho!
+quest-reward = { $quest } - { $gold } de oro, { $xp } PX
+
+## Items
+
+item-gold = { $amount } de oro
+item-gold-description = ¡Oro! ¡Dinero! Se puede gastar en las tiendas
+item-cursed-description = Una energía malévola emana de él.
+item-unknown = Objeto desconocido
+item-unknown-description = Sea lo que sea, no está en la lista de objetos del juego.
+
+item-short_sword = Espada corta
+item-short_sword-description = Una robusta espada corta, heredada durante generaciones.
+item-spear = Lanza
+item-spear-description = Una larga lanza de fresno. Cuesta recogerla, pero mantiene los problemas a distancia.
+item-wizards_dagger = Daga de mago
+item-wizards_dagger-description = Una daga grabada con runas místicas
+item-wizard_glove = Guante de mago
+item-wizard_glove-description = Un guante empuñado por poderosos hechiceros. Permite a los magos tocar la energía que los rodea y moldearla a su voluntad.
+item-throwing_knife = Cuchillo arrojadizo
+item-throwing_knife-description = Un cuchillo pequeño pero muy afilado
+item-bow = Arco
+item-bow-description = Un arco corto de caza. Inútil sin flechas.
+item-arrow = Flecha
+item-arrow-description = Una sencilla flecha de madera. Las que fallan normalmente se pueden recoger.
+item-potion_regeneration = Poción de regeneración
+item-potion_regeneration-description = Ayuda al cuerpo a recuperarse del daño
+item-potion_mana = Poción de maná
+item-potion_mana-description = Restaura parte de la magia de quien la bebe
+item-lost_ring = Anillo perdido
+item-lost_ring-description = Un sencillo anillo de oro. Alguien lo está buscando.
+item-floor_sigil = Sello del piso
+item-floor_sigil-description = Una tablilla de piedra con el sello del piso tallado. Quien la lleve puede abrir el camino hacia abajo.
+item-warriors_shield = Escudo del guerrero
+item-warriors_shield-description = Un abollado escudo de hierro. Levántalo para detener los golpes de frente.
+item-war_axe = Hacha de guerra
+item-war_axe-description = Una pesada hacha de doble filo. Hazla girar una y otra vez para abrirte paso entre la multitud.
+item-mace = Maza
+item-mace-description = Una maza de hierro con pestañas, bendecida en el templo. Cárgala y descárgala con fuerza suficiente, y la luz la acompañará.
+item-holy_symbol = Símbolo sagrado
+item-holy_symbol-description = Un sol de plata gastado colgado de una cadena. Alzado hacia la luz, cura las heridas del aliado más cercano.
+item-leather_cap = Gorro de cuero
+item-leather_cap-description = Cuero hervido y bien cosido. No detiene mucho, pero detiene un poco de todo.
+item-chainmail = Cota de malla
+item-chainmail-description = Miles de anillas de hierro, y cada una pesa. Los golpes llegan más suaves, y tus pasos también.
+item-ring_of_focus = Anillo de concentración
+item-ring_of_focus-description = Un sencillo aro de piedra gris, frío al tacto. Al llevarlo, tu mente se calma y tu magia vuelve antes.
+item-swift_boots = Botas ligeras
+item-swift_boots-description = De suela blanda y ligeras como zapatillas. Quien las hizo tenía prisa.
+item-wand_fireball = Varita de bola de fuego
+item-wand_fireball-description = Un palo chamuscado que aún huele a humo. Cualquiera puede apuntarla y lanzar una bola de fuego.
+item-wand_frost_nova = Varita de escarcha
+item-wand_frost_nova-description = Una varita cubierta de escarcha. Cualquiera puede apuntarla y lanzar una nova de escarcha.
+item-charges = { $item } ({ $charges })
+item-knife_bandolier = Bandolera de cuchillos
+item-knife_bandolier-description = Una correa de cuero con presillas para una docena de cuchillos. Te deja lanzar tres a la vez.
+item-vampiric_dagger = Daga vampírica
+item-vampiric_dagger-description = Una daga negra que siempre está tibia. Quien la empuña se cura un poco con cada herida que abre.
+item-slime_sword = Espada babosa
+item-slime_sword-description = Una espada que pasó demasiado tiempo en el fondo de un charco de limo. Lo que corta suele quedarse pegado en el sitio.
+item-venom_vial = Frasco de veneno
+item-venom_vial-description = Extraído de los colmillos de una araña. Los cuchillos arrojadizos que lleves quedan untados con él y envenenan todo lo que alcanzan.
+
+## Equipment
+
+equipment-slot-head = Cabeza
+equipment-slot-body = Cuerpo
+equipment-slot-ring = Anillo
+equipment-slot-feet = Pies
+
+## Victory
+
+victory-title = ¡Victoria!
+victory-subtitle = El rey rata ha muerto, y por fin reina el silencio en la mazmorra
+
+credits-title = Roguelite
+credits-made-by = Hecho por billyb2 y colaboradores
+credits-built-with = Creado con macroquad, ggrs y egui
+credits-thanks = ¡Gracias por jugar!
+
+## Game Over
+
+game-over-title = Fin de la partida
+game-over-subtitle = La mazmorra se cobra otro grupo
+
+summary-title = Resumen de la partida
+summary-time = Tiempo: { $time }
+summary-floors = Pisos superados: { $floors }
+summary-kills = Monstruos abatidos: { $kills }
+summary-gold = Oro recogido: { $gold }
+summary-level = Nivel alcanzado: { $level }
+summary-quests = Misiones completadas: { $quests }
+summary-unlocked = Desbloqueado: { $unlock }
+
+unlock-rat-slayer = Matarratas
+unlock-speedrunner = Veloz
+unlock-veteran = Veterano
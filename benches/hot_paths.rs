@@ -0,0 +1,120 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use macroquad::prelude::*;
+
+use roguelite::difficulty::Difficulty;
+use roguelite::map::{FloorInfo, Scratch, VisibilityCache};
+use roguelite::monsters::update_monsters;
+use roguelite::player::{Player, PlayerClass};
+use roguelite::rng::Rng;
+use roguelite::telegraphs::Telegraphs;
+
+const SEED: u64 = 1000;
+
+/// Generates the same 50x50 floor every time, with a single player standing on
+/// the spawn point
+fn generate_floor() -> (FloorInfo, Vec<Player>) {
+	let floor_info = FloorInfo::new(0, Difficulty::default(), &mut Rng::new(SEED));
+	let players = vec![Player::new(
+		PlayerClass::Warrior,
+		floor_info.current_spawn(),
+	)];
+
+	(floor_info, players)
+}
+
+fn collision(c: &mut Criterion) {
+	let (floor_info, players) = generate_floor();
+	let player = &players[0];
+
+	c.bench_function("Floor::collision", |b| {
+		b.iter(|| {
+			floor_info
+				.floor
+				.collision(black_box(player), black_box(Vec2::new(2.2, 0.0)))
+		})
+	});
+
+	c.bench_function("Floor::collision_dir", |b| {
+		b.iter(|| {
+			floor_info
+				.floor
+				.collision_dir(black_box(player), black_box(Vec2::new(2.2, 2.2)))
+		})
+	});
+}
+
+fn visible_objects(c: &mut Criterion) {
+	let (mut floor_info, players) = generate_floor();
+	let player = &players[0];
+
+	c.bench_function("Floor::visible_objects", |b| {
+		b.iter(|| floor_info.floor.visible_objects(black_box(player), None))
+	});
+
+	c.bench_function("VisibilityCache::set_visible_objects", |b| {
+		let mut scratch = Scratch::default();
+
+		b.iter(|| {
+			let objects = floor_info.floor.objects_mut();
+			objects
+				.iter_mut()
+				.for_each(|obj| obj.clear_currently_visible());
+
+			// A new cache every time, so the rays actually get cast
+			VisibilityCache::default().set_visible_objects(
+				black_box(players.as_slice()),
+				None,
+				objects,
+				&mut scratch,
+			);
+		})
+	});
+}
+
+fn find_path(c: &mut Criterion) {
+	let (floor_info, players) = generate_floor();
+	let player = &players[0];
+	let exit = floor_info.exit().expect("only the final floor has no exit");
+
+	c.bench_function("Floor::find_path (spawn to exit)", |b| {
+		b.iter(|| {
+			floor_info
+				.floor
+				.find_path(black_box(player), black_box(exit), false, true, None)
+		})
+	});
+}
+
+fn monsters(c: &mut Criterion) {
+	let (floor_info, players) = generate_floor();
+
+	c.bench_function("update_monsters", |b| {
+		b.iter_batched(
+			|| {
+				(
+					floor_info.clone(),
+					players.clone(),
+					Vec::new(),
+					Telegraphs::default(),
+					Vec::new(),
+					Rng::new(SEED),
+				)
+			},
+			|(mut floor_info, mut players, mut attacks, mut telegraphs, mut events, mut rng)| {
+				update_monsters(
+					&mut players,
+					&mut floor_info,
+					&mut attacks,
+					&mut telegraphs,
+					&mut events,
+					&mut rng,
+				);
+				(floor_info, players, attacks, telegraphs, events, rng)
+			},
+			BatchSize::SmallInput,
+		)
+	});
+}
+
+criterion_group!(benches, collision, visible_objects, find_path, monsters);
+criterion_main!(benches);
@@ -8,11 +8,13 @@ fn main() {
 	fs::read_dir("assets")
 		.unwrap()
 		.filter_map(|file| {
-			if let Ok(file) = file {
-				let file_name = file.file_name().to_str().unwrap().to_string();
-				Some(file_name)
-			} else {
-				None
+			let file = file.ok()?;
+			let file_name = file.file_name().to_str().unwrap().to_string();
+
+			// Only textures are baked in, scripts are loaded at runtime
+			match file_name.ends_with(".webp") {
+				true => Some(file_name),
+				false => None,
 			}
 		})
 		.for_each(|asset| {
@@ -0,0 +1,144 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::events::GameEvent;
+use crate::items::ItemType;
+use crate::map::{pos_to_tile, FloorInfo, TILE_SIZE};
+use crate::player::Player;
+use crate::rng::Rng;
+
+const MIN_WAVES: u32 = 2;
+const MAX_WAVES: u32 = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+enum AmbushState {
+	/// Nobody's walked in yet
+	Waiting,
+	Fighting,
+	Cleared,
+}
+
+/// A room that locks everyone inside once a player walks in, and keeps
+/// spawning waves of monsters until they've all been killed
+#[derive(Clone, Debug, Serialize)]
+pub struct Ambush {
+	/// The index of the room in the floor's rooms
+	room: usize,
+	state: AmbushState,
+	waves_left: u32,
+	wave_size: u32,
+	/// Gold dropped once the last wave is dead
+	reward: u32,
+}
+
+impl Ambush {
+	pub fn room(&self) -> usize { self.room }
+
+	pub fn generate(room: usize, floor_num: usize, rng: &mut Rng) -> Self {
+		let scale = floor_num as u32 + 1;
+
+		Self {
+			room,
+			state: AmbushState::Waiting,
+			waves_left: rng.gen_range(MIN_WAVES, MAX_WAVES + 1),
+			wave_size: 2 + scale,
+			reward: 30 * scale,
+		}
+	}
+}
+
+fn lock_room_doors(floor_info: &mut FloorInfo, room: usize, locked: bool) {
+	let door_positions: Vec<IVec2> = floor_info.rooms()[room]
+		.doors()
+		.iter()
+		.map(|door| door.tile_pos())
+		.collect();
+
+	door_positions.into_iter().for_each(|tile_pos| {
+		if let Some(object) = floor_info.floor.get_object_from_pos_mut(tile_pos) {
+			object.set_door_locked(locked);
+		}
+	});
+}
+
+fn spawn_wave(floor_info: &mut FloorInfo, ambush: &Ambush, rng: &mut Rng) {
+	let room_center = floor_info.rooms()[ambush.room].center();
+
+	let wave: Vec<_> = (0..ambush.wave_size)
+		.map(|_| {
+			let pos = floor_info.random_room_pos(Some(room_center), rng);
+			floor_info.new_monster(pos, rng)
+		})
+		.collect();
+
+	floor_info.monsters.extend(wave);
+}
+
+/// Starts any ambush a player has walked into, sends in the next wave once
+/// the last one is dead, and unlocks the room with some loot once there
+/// aren't any waves left
+pub fn update_ambushes(
+	players: &[Player], floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	let mut ambushes = std::mem::take(&mut floor_info.ambushes);
+
+	ambushes.iter_mut().for_each(|ambush| {
+		let room = &floor_info.rooms()[ambush.room];
+		let room_center = room.center();
+		let pos = (room_center * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+		match ambush.state {
+			AmbushState::Waiting => {
+				let player_inside = players
+					.iter()
+					.any(|player| room.inside_room(pos_to_tile(player)));
+
+				// Don't shut a door on anyone standing in it
+				let player_in_doorway = players.iter().any(|player| {
+					let tile_pos = pos_to_tile(player);
+					room.doors().iter().any(|door| door.tile_pos() == tile_pos)
+				});
+
+				if player_inside && !player_in_doorway {
+					ambush.state = AmbushState::Fighting;
+					lock_room_doors(floor_info, ambush.room, true);
+
+					events.push(GameEvent::AmbushStarted { pos });
+				}
+			},
+			AmbushState::Fighting => {
+				let wave_alive = floor_info
+					.monsters
+					.iter()
+					.any(|monster| monster.hostile() && room.inside_room(pos_to_tile(monster)));
+
+				if wave_alive {
+					return;
+				}
+
+				match ambush.waves_left {
+					0 => {
+						ambush.state = AmbushState::Cleared;
+						lock_room_doors(floor_info, ambush.room, false);
+
+						floor_info
+							.floor
+							.drop_item(room_center, ItemType::Gold(ambush.reward));
+						floor_info
+							.floor
+							.drop_item(room_center, ItemType::from_key("potion_regeneration"));
+
+						events.push(GameEvent::AmbushCleared { pos });
+					},
+					_ => {
+						ambush.waves_left -= 1;
+						spawn_wave(floor_info, ambush, rng);
+					},
+				}
+			},
+			AmbushState::Cleared => (),
+		}
+	});
+
+	floor_info.ambushes = ambushes;
+}
@@ -0,0 +1,84 @@
+use macroquad::prelude::*;
+
+/// How something looks on one frame, relative to how it'd be drawn without any
+/// animation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pose {
+	/// Grows or shrinks it around its center
+	pub scale: f32,
+	/// Added on top of its own rotation
+	pub rotation: f32,
+	pub offset: Vec2,
+	/// Which frame of a horizontal sprite strip to draw
+	pub texture_frame: u8,
+}
+
+impl Pose {
+	pub const IDENTITY: Pose = Pose {
+		scale: 1.0,
+		rotation: 0.0,
+		offset: Vec2::ZERO,
+		texture_frame: 0,
+	};
+}
+
+/// A pose that's hit exactly on a given frame. Frames in between are blended
+/// from the keyframes either side
+#[derive(Copy, Clone, Debug)]
+pub struct Keyframe {
+	pub frame: u16,
+	pub pose: Pose,
+}
+
+/// Everything an animation does, from its first frame to its last. Nothing
+/// here is timed by the clock, only by how many frames something's existed
+/// for, so every player sees the same thing on the same frame
+#[derive(Copy, Clone, Debug)]
+pub struct Timeline {
+	/// Sorted by frame, starting from 0
+	pub keyframes: &'static [Keyframe],
+	/// How many frames the texture's sprite strip has
+	pub texture_frames: u8,
+	/// Starts over after the last keyframe instead of holding it
+	pub looping: bool,
+}
+
+impl Timeline {
+	/// How long one play through takes, in frames
+	pub fn length(&self) -> u16 { self.keyframes.last().map(|k| k.frame).unwrap_or(0) }
+
+	pub fn sample(&self, time: u16) -> Pose {
+		let time = match self.looping && self.length() > 0 {
+			true => time % self.length(),
+			false => time.min(self.length()),
+		};
+
+		let next_index = self
+			.keyframes
+			.iter()
+			.position(|k| k.frame > time)
+			.unwrap_or(self.keyframes.len());
+
+		let (prev, next) = match next_index {
+			0 => {
+				return self
+					.keyframes
+					.first()
+					.map(|k| k.pose)
+					.unwrap_or(Pose::IDENTITY)
+			},
+			i if i == self.keyframes.len() => return self.keyframes[i - 1].pose,
+			i => (self.keyframes[i - 1], self.keyframes[i]),
+		};
+
+		let t = (time - prev.frame) as f32 / (next.frame - prev.frame) as f32;
+
+		Pose {
+			scale: prev.pose.scale + (next.pose.scale - prev.pose.scale) * t,
+			rotation: prev.pose.rotation + (next.pose.rotation - prev.pose.rotation) * t,
+			offset: prev.pose.offset.lerp(next.pose.offset, t),
+			// Sprites can't be blended, so each one's held until the next
+			texture_frame: prev.pose.texture_frame,
+		}
+	}
+}
@@ -0,0 +1,153 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::events::GameEvent;
+use crate::items::ItemType;
+use crate::map::{FloorInfo, TILE_SIZE};
+use crate::rng::Rng;
+use crate::shop::{ShopItem, SHOP_STOCK};
+use crate::FPS;
+
+const BREAK_FRAMES: u32 = (5.0 * FPS) as u32;
+const SHOPPING_FRAMES: u32 = (20.0 * FPS) as u32;
+/// If a wave isn't dead by then, the next one gets sent in anyway
+const WAVE_FRAMES: u32 = (45.0 * FPS) as u32;
+/// The shop opens after every few waves
+const WAVES_PER_SHOP: u32 = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ArenaPhase {
+	/// Waiting for the next wave
+	Break,
+	Wave,
+	/// A longer break, with the shop open
+	Shopping,
+}
+
+/// The arena game mode, where waves of monsters keep getting bigger until
+/// every player is dead
+#[derive(Clone, Debug, Serialize)]
+pub struct Arena {
+	phase: ArenaPhase,
+	/// Until the phase is over
+	frames_left: u32,
+	/// How many waves have been sent in
+	wave: u32,
+	score: u32,
+}
+
+impl Default for Arena {
+	fn default() -> Self { Self::new() }
+}
+
+impl Arena {
+	pub fn new() -> Self {
+		Self {
+			phase: ArenaPhase::Break,
+			frames_left: BREAK_FRAMES,
+			wave: 0,
+			score: 0,
+		}
+	}
+
+	pub fn phase(&self) -> ArenaPhase { self.phase }
+
+	pub fn wave(&self) -> u32 { self.wave }
+
+	pub fn score(&self) -> u32 { self.score }
+
+	pub fn seconds_left(&self) -> u32 { (self.frames_left as f64 / FPS).ceil() as u32 }
+
+	fn start_wave(
+		&mut self, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>, rng: &mut Rng,
+	) {
+		self.wave += 1;
+		self.phase = ArenaPhase::Wave;
+		self.frames_left = WAVE_FRAMES;
+
+		floor_info.shop.clear();
+
+		let center = floor_info.rooms()[0].center();
+		let wave: Vec<_> = (0..3 + self.wave * 2)
+			.map(|_| {
+				let pos = floor_info.random_room_pos(Some(center), rng);
+				floor_info.new_monster(pos, rng)
+			})
+			.collect();
+
+		floor_info.monsters.extend(wave);
+
+		events.push(GameEvent::WaveStarted {
+			wave: self.wave,
+			pos: tile_to_pos(center),
+		});
+	}
+
+	fn clear_wave(&mut self, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>) {
+		let center = floor_info.rooms()[0].center();
+
+		// Finishing a wave early is worth more
+		self.score += 50 * self.wave + self.frames_left / FPS as u32;
+
+		floor_info
+			.floor
+			.drop_item(center, ItemType::Gold(10 * self.wave));
+
+		match self.wave % WAVES_PER_SHOP {
+			0 => {
+				self.phase = ArenaPhase::Shopping;
+				self.frames_left = SHOPPING_FRAMES;
+
+				floor_info.shop = SHOP_STOCK
+					.iter()
+					.enumerate()
+					.map(|(i, key)| {
+						let x = i as i32 * 2 - SHOP_STOCK.len() as i32 + 1;
+						let tile_pos = center + IVec2::new(x, -3);
+						ShopItem::new(tile_pos, ItemType::from_key(key))
+					})
+					.collect();
+			},
+			_ => {
+				self.phase = ArenaPhase::Break;
+				self.frames_left = BREAK_FRAMES;
+			},
+		};
+
+		events.push(GameEvent::WaveCleared {
+			wave: self.wave,
+			pos: tile_to_pos(center),
+		});
+	}
+}
+
+fn tile_to_pos(tile_pos: IVec2) -> Vec2 { (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2() }
+
+/// Scores this frame's kills, and sends in the next wave once the last one's
+/// dead or its time is up
+pub fn update_arena(
+	arena: &mut Arena, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	let kills = events
+		.iter()
+		.filter(|event| matches!(event, GameEvent::MonsterDied { .. }))
+		.count() as u32;
+
+	arena.score += kills * 10 * arena.wave.max(1);
+	arena.frames_left = arena.frames_left.saturating_sub(1);
+
+	match arena.phase {
+		ArenaPhase::Break | ArenaPhase::Shopping => {
+			if arena.frames_left == 0 {
+				arena.start_wave(floor_info, events, rng);
+			}
+		},
+		// Summoned allies don't count towards the wave
+		ArenaPhase::Wave => match floor_info.monsters.iter().all(|m| !m.hostile()) {
+			true => arena.clear_wave(floor_info, events),
+			// Whatever's left of the last wave sticks around
+			false if arena.frames_left == 0 => arena.start_wave(floor_info, events, rng),
+			false => (),
+		},
+	}
+}
@@ -0,0 +1,119 @@
+use std::f32::consts::PI;
+
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::monsters::MonsterKind;
+use crate::player::{DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, Team};
+
+const HALF_SIZE: Vec2 = Vec2::new(8.0, 1.5);
+const SPEED: f32 = 5.0;
+const DAMAGE: u16 = 8;
+// Long enough to cross any room or corridor
+const MAX_TIME: u16 = 90;
+
+/// Shot by arrow traps and skeleton archers, flying in a straight line until it
+/// hits a wall or whoever's on the other team
+#[derive(Clone, Serialize)]
+pub struct Arrow {
+	// The middle of the arrow
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	team: Team,
+}
+
+impl Arrow {
+	pub fn shot_by(mut self, shooter: MonsterKind) -> Self {
+		self.team = Team::Monsters(Some(shooter));
+		self
+	}
+
+	/// For summoned skeletons, so the player they're fighting for gets the
+	/// credit for whatever the arrow hits
+	pub fn fired_for(mut self, player: usize) -> Self {
+		self.team = Team::Player(player);
+		self
+	}
+}
+
+impl Attack for Arrow {
+	fn new(
+		aabb: &dyn AsPolygon, _index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			angle,
+			time: 0,
+			team: Team::Monsters(None),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		let movement = Vec2::new(self.angle.cos(), self.angle.sin()) * SPEED;
+
+		if floor_info
+			.floor
+			.projectile_hit(self, movement, self.team.player(), events) ||
+			self.time >= MAX_TIME
+		{
+			return true;
+		}
+
+		self.pos += movement;
+		self.time += 1;
+
+		self.team
+			.hit_first(
+				self,
+				DAMAGE,
+				DamageType::Pierce,
+				floor_info,
+				players,
+				events,
+				rng,
+			)
+			.is_some()
+	}
+
+	fn cooldown(&self) -> u16 { 0 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+
+	fn team(&self) -> Option<Team> { Some(self.team) }
+
+	fn turn_against(&mut self, team: Team) {
+		self.team = team;
+		self.angle += PI;
+	}
+}
+
+impl AsPolygon for Arrow {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, HALF_SIZE, self.angle) }
+}
+
+impl Drawable for Arrow {
+	fn pos(&self) -> Vec2 { self.pos - HALF_SIZE }
+
+	fn size(&self) -> Vec2 { HALF_SIZE * 2.0 }
+
+	fn rotation(&self) -> f32 { self.angle }
+
+	fn draw(&self) {
+		let direction = Vec2::new(self.angle.cos(), self.angle.sin()) * HALF_SIZE.x;
+		let tip = self.pos + direction;
+		let tail = self.pos - direction;
+
+		draw_line(tail.x, tail.y, tip.x, tip.y, 2.0, BROWN);
+		draw_circle(tip.x, tip.y, 2.0, LIGHTGRAY);
+	}
+}
@@ -0,0 +1,93 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::player::{DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, Team};
+
+/// How far in front of the biter its jaws close
+const REACH: f32 = 10.0;
+const HALF_SIZE: Vec2 = Vec2::splat(5.0);
+const DAMAGE: u16 = 4;
+/// How long the jaws are drawn for, in frames. They only hurt on the first
+const LIFETIME: u16 = 8;
+
+/// A companion's snap at whatever's in front of it. It hurts monsters on
+/// behalf of its owner, so they get the XP and their on-hit effects
+#[derive(Clone, Serialize)]
+pub struct Bite {
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	team: Team,
+}
+
+impl Attack for Bite {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center() + Vec2::new(angle.cos(), angle.sin()) * REACH,
+			angle,
+			time: 0,
+			team: Team::Player(index.unwrap()),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			self.team.hit_first(
+				&self.as_polygon(),
+				DAMAGE,
+				DamageType::Pierce,
+				floor_info,
+				players,
+				events,
+				rng,
+			);
+		}
+
+		self.time += 1;
+
+		self.time >= LIFETIME
+	}
+
+	fn cooldown(&self) -> u16 { 0 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+
+	fn team(&self) -> Option<Team> { Some(self.team) }
+}
+
+impl AsPolygon for Bite {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, HALF_SIZE, self.angle) }
+}
+
+impl Drawable for Bite {
+	fn pos(&self) -> Vec2 { self.pos - HALF_SIZE }
+
+	fn size(&self) -> Vec2 { HALF_SIZE * 2.0 }
+
+	fn draw(&self) {
+		let alpha = 1.0 - self.time as f32 / LIFETIME as f32;
+		let color = Color::new(1.0, 1.0, 1.0, alpha);
+
+		// Two rows of teeth, snapping shut
+		let forward = Vec2::new(self.angle.cos(), self.angle.sin()) * HALF_SIZE.x;
+		let side = forward.perp() * alpha;
+
+		[side, -side].into_iter().for_each(|side| {
+			let back = self.pos - forward + side;
+			let front = self.pos + forward + side * 0.5;
+
+			draw_line(back.x, back.y, front.x, front.y, 2.0, color);
+		});
+	}
+}
@@ -1,8 +1,11 @@
+use crate::animation::{Keyframe, Pose, Timeline};
 use crate::draw::{load_my_image, Drawable};
 use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
 use crate::map::{Floor, FloorInfo};
 use crate::math::{aabb_collision, easy_polygon, AsPolygon, Polygon};
 use crate::player::{Player, PLAYER_SIZE};
+use crate::rng::Rng;
 use macroquad::prelude::*;
 use serde::Serialize;
 
@@ -10,10 +13,68 @@ use super::Attack;
 
 const HALF_SIZE: Vec2 = Vec2::new(45.0, 45.0);
 const SIZE: Vec2 = Vec2::new(90.0, 90.0);
+const LIFETIME: u16 = 60;
+
+/// Flashes out, then throbs until it's gone
+static PULSE: Timeline = Timeline {
+	keyframes: &[
+		Keyframe {
+			frame: 0,
+			pose: Pose {
+				scale: 0.3,
+				..Pose::IDENTITY
+			},
+		},
+		Keyframe {
+			frame: 6,
+			pose: Pose {
+				scale: 1.15,
+				..Pose::IDENTITY
+			},
+		},
+		Keyframe {
+			frame: 15,
+			pose: Pose::IDENTITY,
+		},
+		Keyframe {
+			frame: 25,
+			pose: Pose {
+				scale: 1.1,
+				..Pose::IDENTITY
+			},
+		},
+		Keyframe {
+			frame: 35,
+			pose: Pose::IDENTITY,
+		},
+		Keyframe {
+			frame: 45,
+			pose: Pose {
+				scale: 1.1,
+				..Pose::IDENTITY
+			},
+		},
+		Keyframe {
+			frame: 55,
+			pose: Pose::IDENTITY,
+		},
+		Keyframe {
+			frame: LIFETIME,
+			pose: Pose {
+				scale: 0.8,
+				..Pose::IDENTITY
+			},
+		},
+	],
+	texture_frames: 1,
+	looping: false,
+};
 
 #[derive(Clone, Serialize)]
 pub struct BlindingLight {
 	pos: Vec2,
+	/// Where the light was cast from. It can't reach around walls from here
+	origin: Vec2,
 	angle: f32,
 	time: u16,
 }
@@ -24,15 +85,19 @@ impl Attack for BlindingLight {
 	) -> Self {
 		Self {
 			pos: aabb.center() + (Vec2::new(angle.cos(), angle.sin()) * PLAYER_SIZE),
+			origin: aabb.center(),
 			angle,
 			time: 0,
 		}
 	}
 
-	fn update(&mut self, floor: &mut FloorInfo, _players: &mut [Player]) -> bool {
+	fn update(
+		&mut self, floor: &mut FloorInfo, _players: &mut [Player], _events: &mut Vec<GameEvent>,
+		_rng: &mut Rng,
+	) -> bool {
 		self.time += 1;
 
-		if self.time >= 60 {
+		if self.time >= LIFETIME {
 			return true;
 		}
 
@@ -40,7 +105,10 @@ impl Attack for BlindingLight {
 		floor
 			.monsters
 			.iter_mut()
-			.filter(|m| aabb_collision(self, &m.as_polygon(), Vec2::ZERO))
+			.filter(|m| {
+				aabb_collision(self, &m.as_polygon(), Vec2::ZERO) &&
+					floor.floor.line_of_sight(self.origin, m.center())
+			})
 			.for_each(|monster| {
 				monster.apply_enchantment(Enchantment {
 					kind: EnchantmentKind::Blinded,
@@ -55,8 +123,6 @@ impl Attack for BlindingLight {
 
 	fn mana_cost(&self) -> u16 { 3 }
 
-	fn side_effects(&self, _player: &mut Player, _floor: &Floor) {}
-
 	fn as_polygon_optional(&self) -> Option<Polygon> { Some(self.as_polygon()) }
 }
 
@@ -71,5 +137,7 @@ impl Drawable for BlindingLight {
 
 	fn rotation(&self) -> f32 { self.angle }
 
+	fn animation(&self) -> Option<(&'static Timeline, u16)> { Some((&PULSE, self.time)) }
+
 	fn texture(&self) -> Option<Texture2D> { Some(load_my_image("blinding_light.webp")) }
 }
@@ -0,0 +1,92 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::flow_field::FlowField;
+use crate::map::{pos_to_tile, Floor, FloorInfo, TILE_SIZE};
+use crate::math::{easy_polygon, points_on_line, AsPolygon, Polygon};
+use crate::player::{Player, PLAYER_SIZE};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+/// How far the wizard blinks, in tiles
+const DISTANCE: f32 = 4.0;
+/// How long the trail left behind hangs around, in frames
+const LIFETIME: u16 = 15;
+
+/// Teleports the wizard a few tiles the way they're aiming, stopping short at
+/// the last open tile if there's a wall or door in the way
+#[derive(Clone, Serialize)]
+pub struct Blink {
+	/// Where the wizard was, and where they're going, as centers
+	from: Vec2,
+	to: Vec2,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for Blink {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, floor: &Floor, _is_primary: bool,
+	) -> Self {
+		let from = aabb.center();
+		let end = from + Vec2::new(angle.cos(), angle.sin()) * DISTANCE * TILE_SIZE as f32;
+		let end_tile = (end / Vec2::splat(TILE_SIZE as f32)).floor().as_ivec2();
+
+		// Worked out straight away from the floor as it is when the spell's
+		// cast, so every peer ends up in the same place
+		let to = points_on_line(pos_to_tile(&aabb.as_polygon()), end_tile)
+			.into_iter()
+			.skip(1)
+			.take_while(|tile_pos| {
+				floor
+					.get_object_from_pos(*tile_pos)
+					.is_some_and(|obj| !obj.is_collidable())
+			})
+			.last()
+			.map_or(from, FlowField::tile_center);
+
+		Self {
+			from,
+			to,
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, _floor_info: &mut FloorInfo, players: &mut [Player],
+		_events: &mut Vec<GameEvent>, _rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			players[self.player_index].pos = self.to - Vec2::splat(PLAYER_SIZE * 0.5);
+		}
+
+		self.time += 1;
+
+		self.time >= LIFETIME
+	}
+
+	fn cooldown(&self) -> u16 { 60 }
+
+	fn mana_cost(&self) -> u16 { 2 }
+}
+
+impl AsPolygon for Blink {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.to, Vec2::splat(PLAYER_SIZE * 0.5), 0.0) }
+}
+
+impl Drawable for Blink {
+	fn pos(&self) -> Vec2 { self.to - Vec2::splat(PLAYER_SIZE * 0.5) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(PLAYER_SIZE) }
+
+	fn draw(&self) {
+		let color = Color::new(0.7, 0.5, 1.0, 1.0 - self.time as f32 / LIFETIME as f32);
+
+		draw_line(self.from.x, self.from.y, self.to.x, self.to.y, 2.0, color);
+		draw_circle_lines(self.from.x, self.from.y, PLAYER_SIZE * 0.5, 1.0, color);
+		draw_circle_lines(self.to.x, self.to.y, PLAYER_SIZE * 0.5, 1.0, color);
+	}
+}
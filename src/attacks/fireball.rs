@@ -0,0 +1,176 @@
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{pos_to_tile, EffectType, Floor, FloorInfo, TILE_SIZE};
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+const RADIUS: f32 = 6.0;
+const SPEED: f32 = 4.0;
+/// Frames before a fireball that hasn't hit anything goes off anyway
+const LIFETIME: u16 = 45;
+const DAMAGE: u16 = 6;
+/// How far the explosion reaches, in tiles
+const BLAST_RADIUS: f32 = 1.5;
+/// How long the explosion's drawn for, in frames
+const BLAST_TIME: u16 = 15;
+/// How long the ground it hits keeps burning, in frames
+const BURN_TIME: u16 = 180;
+
+/// Flies straight until it hits a monster or a wall, then explodes, hurting
+/// every monster nearby and setting the floor around it alight
+#[derive(Clone, Serialize)]
+pub struct Fireball {
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	/// Frames since it went off, once it has
+	exploded: Option<u16>,
+	player_index: usize,
+}
+
+impl Fireball {
+	fn explode(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) {
+		self.exploded = Some(0);
+
+		let blast_radius = BLAST_RADIUS * TILE_SIZE as f32;
+
+		let hit: Vec<usize> = floor_info
+			.monsters
+			.iter()
+			.enumerate()
+			.filter(|(_, m)| {
+				m.targetable() &&
+					m.center().distance(self.pos) <= blast_radius &&
+					floor_info.floor.line_of_sight(self.pos, m.center())
+			})
+			.map(|(i, _)| i)
+			.collect();
+
+		hit.into_iter().for_each(|i| {
+			let damage_info = DamageInfo {
+				damage: DAMAGE,
+				damage_type: DamageType::Fire,
+				direction: get_angle(floor_info.monsters[i].center(), self.pos),
+				player: self.player_index,
+			};
+
+			hit_monster(i, damage_info, floor_info, players, events, rng);
+			floor_info.monsters[i].apply_enchantment(Enchantment {
+				kind: EnchantmentKind::Burning,
+				strength: 1,
+			});
+		});
+
+		let center_tile = pos_to_tile(&*self);
+		let tiles = BLAST_RADIUS.ceil() as i32;
+
+		(-tiles..=tiles).for_each(|x| {
+			(-tiles..=tiles).for_each(|y| {
+				let offset = IVec2::new(x, y);
+
+				if offset.as_vec2().length() <= BLAST_RADIUS {
+					floor_info
+						.floor
+						.add_effect(center_tile + offset, EffectType::Fire, BURN_TIME);
+				}
+			});
+		});
+	}
+}
+
+impl Attack for Fireball {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			angle,
+			time: 0,
+			exploded: None,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		if let Some(time) = &mut self.exploded {
+			*time += 1;
+			return *time >= BLAST_TIME;
+		}
+
+		let movement = Vec2::new(self.angle.cos(), self.angle.sin()) * SPEED;
+		self.time += 1;
+
+		if floor_info
+			.floor
+			.projectile_hit(self, movement, Some(self.player_index), events) ||
+			self.time >= LIFETIME
+		{
+			self.explode(floor_info, players, events, rng);
+			return false;
+		}
+
+		self.pos += movement;
+
+		let poly = self.as_polygon();
+
+		if floor_info
+			.monsters
+			.iter()
+			.any(|m| m.targetable() && aabb_collision(&poly, &m.as_polygon(), Vec2::ZERO))
+		{
+			self.explode(floor_info, players, events, rng);
+		}
+
+		false
+	}
+
+	fn cooldown(&self) -> u16 { 60 }
+
+	fn mana_cost(&self) -> u16 { 2 }
+
+	fn as_polygon_optional(&self) -> Option<Polygon> { Some(self.as_polygon()) }
+}
+
+impl AsPolygon for Fireball {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, Vec2::splat(RADIUS), self.angle) }
+}
+
+impl Drawable for Fireball {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(RADIUS) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(RADIUS * 2.0) }
+
+	fn rotation(&self) -> f32 { self.angle }
+
+	fn draw(&self) {
+		match self.exploded {
+			Some(time) => {
+				let progress = time as f32 / BLAST_TIME as f32;
+
+				draw_circle(
+					self.pos.x,
+					self.pos.y,
+					BLAST_RADIUS * TILE_SIZE as f32 * progress.max(0.3),
+					Color::new(1.0, 0.5, 0.1, 0.6 * (1.0 - progress)),
+				);
+			},
+			None => {
+				draw_circle(self.pos.x, self.pos.y, RADIUS, ORANGE);
+				draw_circle(self.pos.x, self.pos.y, RADIUS * 0.5, YELLOW);
+			},
+		}
+	}
+}
@@ -0,0 +1,114 @@
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+/// How far the cold reaches, in tiles
+const RADIUS: f32 = 2.0;
+/// How long the ring takes to spread out, in frames
+const LIFETIME: u16 = 20;
+const DAMAGE: u16 = 2;
+
+/// A ring of cold bursting out from the wizard, nipping and chilling every
+/// monster it reaches. Monsters that are already chilled freeze solid
+#[derive(Clone, Serialize)]
+pub struct FrostNova {
+	pos: Vec2,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for FrostNova {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, _angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			let radius = RADIUS * TILE_SIZE as f32;
+
+			let hit: Vec<usize> = floor_info
+				.monsters
+				.iter()
+				.enumerate()
+				.filter(|(_, m)| {
+					m.targetable() &&
+						m.center().distance(self.pos) <= radius &&
+						floor_info.floor.line_of_sight(self.pos, m.center())
+				})
+				.map(|(i, _)| i)
+				.collect();
+
+			hit.into_iter().for_each(|i| {
+				let damage_info = DamageInfo {
+					damage: DAMAGE,
+					damage_type: DamageType::Ice,
+					direction: get_angle(floor_info.monsters[i].center(), self.pos),
+					player: self.player_index,
+				};
+
+				hit_monster(i, damage_info, floor_info, players, events, rng);
+				floor_info.monsters[i].apply_enchantment(Enchantment {
+					kind: EnchantmentKind::Chilled,
+					strength: 1,
+				});
+			});
+		}
+
+		self.time += 1;
+
+		self.time >= LIFETIME
+	}
+
+	fn cooldown(&self) -> u16 { 90 }
+
+	fn mana_cost(&self) -> u16 { 3 }
+}
+
+impl AsPolygon for FrostNova {
+	fn as_polygon(&self) -> Polygon {
+		easy_polygon(self.pos, Vec2::splat(RADIUS * TILE_SIZE as f32), 0.0)
+	}
+}
+
+impl Drawable for FrostNova {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(RADIUS * TILE_SIZE as f32) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(RADIUS * TILE_SIZE as f32 * 2.0) }
+
+	fn draw(&self) {
+		let progress = self.time as f32 / LIFETIME as f32;
+		let radius = RADIUS * TILE_SIZE as f32 * progress.max(0.1);
+
+		draw_circle(
+			self.pos.x,
+			self.pos.y,
+			radius,
+			Color::new(0.6, 0.85, 1.0, 0.25 * (1.0 - progress)),
+		);
+		draw_circle_lines(
+			self.pos.x,
+			self.pos.y,
+			radius,
+			2.0,
+			Color::new(0.8, 0.95, 1.0, 1.0 - progress),
+		);
+	}
+}
@@ -0,0 +1,121 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo, TILE_SIZE};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::player::Player;
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+/// How far away an ally can be and still be healed, in tiles
+const RANGE: f32 = 4.0;
+const HEAL_AMOUNT: u16 = 6;
+/// How long the beam of light between the two lingers for
+const LIFETIME: u16 = 20;
+
+/// Mends the nearest ally the cleric can see, as long as they're still on
+/// their feet and hurt. Downed allies need reviving first
+#[derive(Clone, Serialize)]
+pub struct HealOther {
+	/// The cleric's center
+	pos: Vec2,
+	/// Who's being healed, once someone's been picked
+	target: Option<usize>,
+	target_pos: Vec2,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for HealOther {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, _angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			target: None,
+			target_pos: aabb.center(),
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player],
+		_events: &mut Vec<GameEvent>, _rng: &mut Rng,
+	) -> bool {
+		self.pos = players[self.player_index].center();
+
+		if self.time == 0 {
+			let range = RANGE * TILE_SIZE as f32;
+
+			self.target = players
+				.iter()
+				.enumerate()
+				.filter(|(i, ally)| {
+					*i != self.player_index &&
+						ally.hp() > 0 && ally.hp() < ally.max_hp() &&
+						ally.center().distance(self.pos) <= range &&
+						floor_info.floor.line_of_sight(self.pos, ally.center())
+				})
+				.min_by(|(_, a), (_, b)| {
+					a.center()
+						.distance(self.pos)
+						.total_cmp(&b.center().distance(self.pos))
+				})
+				.map(|(i, _)| i);
+
+			if let Some(target) = self.target {
+				players[target].heal(HEAL_AMOUNT);
+			}
+		}
+
+		if let Some(target) = self.target {
+			self.target_pos = players[target].center();
+		}
+
+		self.time += 1;
+
+		// Nothing to show if there was no one to heal
+		self.target.is_none() || self.time >= LIFETIME
+	}
+
+	fn cooldown(&self) -> u16 { 90 }
+
+	fn mana_cost(&self) -> u16 { 2 }
+}
+
+impl AsPolygon for HealOther {
+	fn as_polygon(&self) -> Polygon {
+		let center = (self.pos + self.target_pos) * 0.5;
+		let half_size = (self.pos - self.target_pos).abs() * 0.5;
+
+		easy_polygon(center, half_size, 0.0)
+	}
+}
+
+impl Drawable for HealOther {
+	fn pos(&self) -> Vec2 { self.pos.min(self.target_pos) }
+
+	fn size(&self) -> Vec2 { (self.pos - self.target_pos).abs() }
+
+	fn draw(&self) {
+		let alpha = 1.0 - self.time as f32 / LIFETIME as f32;
+
+		draw_line(
+			self.pos.x,
+			self.pos.y,
+			self.target_pos.x,
+			self.target_pos.y,
+			3.0,
+			Color::new(1.0, 0.95, 0.6, alpha),
+		);
+		draw_circle(
+			self.target_pos.x,
+			self.target_pos.y,
+			TILE_SIZE as f32 * 0.4,
+			Color::new(0.6, 1.0, 0.6, alpha * 0.4),
+		);
+	}
+}
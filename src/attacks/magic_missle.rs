@@ -1,11 +1,15 @@
+use std::f32::consts::PI;
+
 use crate::draw::{load_my_image, Drawable};
+use crate::events::GameEvent;
 use crate::map::{Floor, FloorInfo};
 use crate::math::{aabb_collision_dir, easy_polygon, get_angle, AsPolygon, Polygon};
-use crate::player::{DamageInfo, Player};
+use crate::player::{DamageType, Player};
+use crate::rng::Rng;
 use macroquad::prelude::*;
 use serde::Serialize;
 
-use super::Attack;
+use super::{Attack, FriendlyFire, Team};
 
 const HALF_SIZE: Vec2 = Vec2::new(7.5, 7.5);
 const SIZE: Vec2 = Vec2::new(15.0, 15.0);
@@ -16,7 +20,22 @@ pub struct MagicMissile {
 	angle: f32,
 	time: u16,
 	bounces: u16,
-	player_index: usize,
+	team: Team,
+	/// How much harder than usual it hits, from being wound up
+	power: u16,
+}
+
+impl MagicMissile {
+	pub fn with_power(mut self, power: u16) -> Self {
+		self.power = power;
+		self
+	}
+
+	fn damage(&self) -> u16 {
+		const BASE_DAMAGE: u16 = 1;
+		// The damage increases the more the projectile bounces
+		BASE_DAMAGE.pow((1 + self.bounces).into()) * self.power
+	}
 }
 
 impl Attack for MagicMissile {
@@ -28,20 +47,15 @@ impl Attack for MagicMissile {
 			angle,
 			time: 0,
 			bounces: 0,
-			player_index: index.unwrap(),
+			team: Team::Player(index.unwrap()),
+			power: 1,
 		}
 	}
 
-	fn side_effects(&self, player: &mut Player, floor: &Floor) {
-		// "Knocback" the player a bit
-		let change = -Vec2::new(self.angle.cos(), self.angle.sin()) * 1.5;
-
-		if !floor.collision(player, change) {
-			player.pos += change;
-		}
-	}
-
-	fn update(&mut self, floor_info: &mut FloorInfo, _players: &mut [Player]) -> bool {
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
 		let mut movement = Vec2::new(self.angle.cos(), self.angle.sin()) * 5.0;
 
 		let collision_info = floor_info.floor.collision_dir(self, movement);
@@ -54,38 +68,25 @@ impl Attack for MagicMissile {
 			movement.y = -movement.y;
 		}
 
-		if collision_info.any() {
-			if self.bounces < 3 {
-				self.bounces += 1;
-			}
+		if collision_info.any() && self.bounces < 3 {
+			self.bounces += 1;
 		}
 
 		self.angle = get_angle(movement, Vec2::ZERO);
 
-		// Check to see if it's collided with a monster
-		if let Some((monster, collision_info)) = floor_info.monsters.iter_mut().find_map(|m| {
-			let collision_info = aabb_collision_dir(self, &m.as_polygon(), Vec2::ZERO);
-
-			if collision_info.any() {
-				Some((m, collision_info))
-			} else {
-				None
-			}
-		}) {
-			const BASE_DAMAGE: u16 = 1;
-			// The damage increases the more the projectile bounces
-			let damage = BASE_DAMAGE.pow((1 + self.bounces).into());
-
-			let direction = get_angle(monster.pos(), self.pos);
-
-			let damage_info = DamageInfo {
-				damage,
-				direction,
-				player: self.player_index,
-			};
-			monster.take_damage(damage_info, &floor_info.floor);
-
+		if let Some(target) = self.team.hit_first(
+			self,
+			self.damage(),
+			DamageType::Magic,
+			floor_info,
+			players,
+			events,
+			rng,
+		) {
 			if self.bounces > 0 {
+				let collision_info =
+					aabb_collision_dir(self, &target.as_polygon(floor_info, players), Vec2::ZERO);
+
 				if collision_info.x {
 					movement.x = -movement.x;
 				}
@@ -119,6 +120,22 @@ impl Attack for MagicMissile {
 
 	fn mana_cost(&self) -> u16 { 1 }
 
+	fn team(&self) -> Option<Team> { Some(self.team) }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.team.player()?,
+			damage: self.damage(),
+			damage_type: DamageType::Magic,
+			spent_on_hit: true,
+		})
+	}
+
+	fn turn_against(&mut self, team: Team) {
+		self.team = team;
+		self.angle += PI;
+	}
+
 	fn as_polygon_optional(&self) -> Option<Polygon> { Some(self.as_polygon()) }
 }
 
@@ -1,78 +1,261 @@
+mod arrow;
+mod bite;
 mod blinding_light;
+mod blink;
+mod fireball;
+mod frost_nova;
+mod heal_other;
 mod magic_missle;
+mod radiant_burst;
+mod shield_block;
+mod shot_arrow;
 mod slash;
 mod slimeball;
+mod smash;
+mod spear_thrust;
 mod stab;
+mod summon_skeleton;
+mod team;
 mod throwing_knife;
+mod thrown_item;
+mod whirlwind;
 
+use crate::animation::Timeline;
 use crate::draw::Drawable;
+use crate::events::GameEvent;
 use crate::map::{Floor, FloorInfo};
 
 use crate::math::{AsPolygon, Polygon};
 use crate::player::Player;
+use crate::rng::Rng;
 
+pub use arrow::*;
+pub use bite::*;
 pub use blinding_light::*;
+pub use blink::*;
+pub use fireball::*;
+pub use frost_nova::*;
+pub use heal_other::*;
 pub use magic_missle::*;
+pub use radiant_burst::*;
 use serde::Serialize;
+pub use shield_block::*;
+pub use shot_arrow::*;
 pub use slash::*;
 pub use slimeball::*;
+pub use smash::*;
+pub use spear_thrust::*;
 pub use stab::*;
+pub use summon_skeleton::*;
+pub use team::*;
 pub use throwing_knife::*;
+pub use thrown_item::*;
+pub use whirlwind::*;
 
 use macroquad::prelude::*;
 
 #[derive(Clone, Serialize)]
 pub enum AttackObj {
+	Arrow(Arrow),
+	Bite(Bite),
 	BlindingLight(BlindingLight),
+	Blink(Blink),
+	Fireball(Fireball),
+	FrostNova(FrostNova),
+	HealOther(HealOther),
 	MagicMissile(MagicMissile),
+	RadiantBurst(RadiantBurst),
+	ShieldBlock(ShieldBlock),
+	ShotArrow(ShotArrow),
 	Slash(Slash),
 	Slimeball(Slimeball),
+	Smash(Smash),
+	SpearThrust(SpearThrust),
 	Stab(Stab),
+	SummonSkeleton(SummonSkeleton),
 	ThrowingKnife(ThrownKnife),
+	ThrownItem(ThrownItem),
+	Whirlwind(Whirlwind),
 }
 
 impl AttackObj {
-	pub fn side_effects(&self, player: &mut Player, floor: &Floor) {
-		match self {
-			AttackObj::BlindingLight(obj) => obj.side_effects(player, floor),
-			AttackObj::MagicMissile(obj) => obj.side_effects(player, floor),
-			AttackObj::Slash(obj) => obj.side_effects(player, floor),
-			AttackObj::Slimeball(obj) => obj.side_effects(player, floor),
-			AttackObj::Stab(obj) => obj.side_effects(player, floor),
-			AttackObj::ThrowingKnife(obj) => obj.side_effects(player, floor),
-		}
-	}
-
 	pub fn mana_cost(&self) -> u16 {
 		match self {
+			AttackObj::Arrow(obj) => obj.mana_cost(),
+			AttackObj::Bite(obj) => obj.mana_cost(),
 			AttackObj::BlindingLight(obj) => obj.mana_cost(),
+			AttackObj::Blink(obj) => obj.mana_cost(),
+			AttackObj::Fireball(obj) => obj.mana_cost(),
+			AttackObj::FrostNova(obj) => obj.mana_cost(),
+			AttackObj::HealOther(obj) => obj.mana_cost(),
 			AttackObj::MagicMissile(obj) => obj.mana_cost(),
+			AttackObj::RadiantBurst(obj) => obj.mana_cost(),
+			AttackObj::ShieldBlock(obj) => obj.mana_cost(),
+			AttackObj::ShotArrow(obj) => obj.mana_cost(),
 			AttackObj::Slash(obj) => obj.mana_cost(),
 			AttackObj::Slimeball(obj) => obj.mana_cost(),
+			AttackObj::Smash(obj) => obj.mana_cost(),
+			AttackObj::SpearThrust(obj) => obj.mana_cost(),
 			AttackObj::Stab(obj) => obj.mana_cost(),
+			AttackObj::SummonSkeleton(obj) => obj.mana_cost(),
 			AttackObj::ThrowingKnife(obj) => obj.mana_cost(),
+			AttackObj::ThrownItem(obj) => obj.mana_cost(),
+			AttackObj::Whirlwind(obj) => obj.mana_cost(),
+		}
+	}
+
+	pub fn update(
+		&mut self, floor: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		match self {
+			AttackObj::Arrow(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Bite(obj) => obj.update(floor, players, events, rng),
+			AttackObj::BlindingLight(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Blink(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Fireball(obj) => obj.update(floor, players, events, rng),
+			AttackObj::FrostNova(obj) => obj.update(floor, players, events, rng),
+			AttackObj::HealOther(obj) => obj.update(floor, players, events, rng),
+			AttackObj::MagicMissile(obj) => obj.update(floor, players, events, rng),
+			AttackObj::RadiantBurst(obj) => obj.update(floor, players, events, rng),
+			AttackObj::ShieldBlock(obj) => obj.update(floor, players, events, rng),
+			AttackObj::ShotArrow(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Slash(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Slimeball(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Smash(obj) => obj.update(floor, players, events, rng),
+			AttackObj::SpearThrust(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Stab(obj) => obj.update(floor, players, events, rng),
+			AttackObj::SummonSkeleton(obj) => obj.update(floor, players, events, rng),
+			AttackObj::ThrowingKnife(obj) => obj.update(floor, players, events, rng),
+			AttackObj::ThrownItem(obj) => obj.update(floor, players, events, rng),
+			AttackObj::Whirlwind(obj) => obj.update(floor, players, events, rng),
+		}
+	}
+
+	pub fn team(&self) -> Option<Team> {
+		match self {
+			AttackObj::Arrow(obj) => obj.team(),
+			AttackObj::Bite(obj) => obj.team(),
+			AttackObj::BlindingLight(obj) => obj.team(),
+			AttackObj::Blink(obj) => obj.team(),
+			AttackObj::Fireball(obj) => obj.team(),
+			AttackObj::FrostNova(obj) => obj.team(),
+			AttackObj::HealOther(obj) => obj.team(),
+			AttackObj::MagicMissile(obj) => obj.team(),
+			AttackObj::RadiantBurst(obj) => obj.team(),
+			AttackObj::ShieldBlock(obj) => obj.team(),
+			AttackObj::ShotArrow(obj) => obj.team(),
+			AttackObj::Slash(obj) => obj.team(),
+			AttackObj::Slimeball(obj) => obj.team(),
+			AttackObj::Smash(obj) => obj.team(),
+			AttackObj::SpearThrust(obj) => obj.team(),
+			AttackObj::Stab(obj) => obj.team(),
+			AttackObj::SummonSkeleton(obj) => obj.team(),
+			AttackObj::ThrowingKnife(obj) => obj.team(),
+			AttackObj::ThrownItem(obj) => obj.team(),
+			AttackObj::Whirlwind(obj) => obj.team(),
+		}
+	}
+
+	pub fn friendly_fire(&self) -> Option<FriendlyFire> {
+		match self {
+			AttackObj::Arrow(obj) => obj.friendly_fire(),
+			AttackObj::Bite(obj) => obj.friendly_fire(),
+			AttackObj::BlindingLight(obj) => obj.friendly_fire(),
+			AttackObj::Blink(obj) => obj.friendly_fire(),
+			AttackObj::Fireball(obj) => obj.friendly_fire(),
+			AttackObj::FrostNova(obj) => obj.friendly_fire(),
+			AttackObj::HealOther(obj) => obj.friendly_fire(),
+			AttackObj::MagicMissile(obj) => obj.friendly_fire(),
+			AttackObj::RadiantBurst(obj) => obj.friendly_fire(),
+			AttackObj::ShieldBlock(obj) => obj.friendly_fire(),
+			AttackObj::ShotArrow(obj) => obj.friendly_fire(),
+			AttackObj::Slash(obj) => obj.friendly_fire(),
+			AttackObj::Slimeball(obj) => obj.friendly_fire(),
+			AttackObj::Smash(obj) => obj.friendly_fire(),
+			AttackObj::SpearThrust(obj) => obj.friendly_fire(),
+			AttackObj::Stab(obj) => obj.friendly_fire(),
+			AttackObj::SummonSkeleton(obj) => obj.friendly_fire(),
+			AttackObj::ThrowingKnife(obj) => obj.friendly_fire(),
+			AttackObj::ThrownItem(obj) => obj.friendly_fire(),
+			AttackObj::Whirlwind(obj) => obj.friendly_fire(),
 		}
 	}
 
-	pub fn update(&mut self, floor: &mut FloorInfo, players: &mut [Player]) -> bool {
+	pub fn turn_against(&mut self, team: Team) {
 		match self {
-			AttackObj::BlindingLight(obj) => obj.update(floor, players),
-			AttackObj::MagicMissile(obj) => obj.update(floor, players),
-			AttackObj::Slash(obj) => obj.update(floor, players),
-			AttackObj::Slimeball(obj) => obj.update(floor, players),
-			AttackObj::Stab(obj) => obj.update(floor, players),
-			AttackObj::ThrowingKnife(obj) => obj.update(floor, players),
+			AttackObj::Arrow(obj) => obj.turn_against(team),
+			AttackObj::Bite(obj) => obj.turn_against(team),
+			AttackObj::BlindingLight(obj) => obj.turn_against(team),
+			AttackObj::Blink(obj) => obj.turn_against(team),
+			AttackObj::Fireball(obj) => obj.turn_against(team),
+			AttackObj::FrostNova(obj) => obj.turn_against(team),
+			AttackObj::HealOther(obj) => obj.turn_against(team),
+			AttackObj::MagicMissile(obj) => obj.turn_against(team),
+			AttackObj::RadiantBurst(obj) => obj.turn_against(team),
+			AttackObj::ShieldBlock(obj) => obj.turn_against(team),
+			AttackObj::ShotArrow(obj) => obj.turn_against(team),
+			AttackObj::Slash(obj) => obj.turn_against(team),
+			AttackObj::Slimeball(obj) => obj.turn_against(team),
+			AttackObj::Smash(obj) => obj.turn_against(team),
+			AttackObj::SpearThrust(obj) => obj.turn_against(team),
+			AttackObj::Stab(obj) => obj.turn_against(team),
+			AttackObj::SummonSkeleton(obj) => obj.turn_against(team),
+			AttackObj::ThrowingKnife(obj) => obj.turn_against(team),
+			AttackObj::ThrownItem(obj) => obj.turn_against(team),
+			AttackObj::Whirlwind(obj) => obj.turn_against(team),
 		}
 	}
 
 	pub fn cooldown(&self) -> u16 {
 		match self {
+			AttackObj::Arrow(obj) => obj.cooldown(),
+			AttackObj::Bite(obj) => obj.cooldown(),
 			AttackObj::BlindingLight(obj) => obj.cooldown(),
+			AttackObj::Blink(obj) => obj.cooldown(),
+			AttackObj::Fireball(obj) => obj.cooldown(),
+			AttackObj::FrostNova(obj) => obj.cooldown(),
+			AttackObj::HealOther(obj) => obj.cooldown(),
 			AttackObj::MagicMissile(obj) => obj.cooldown(),
+			AttackObj::RadiantBurst(obj) => obj.cooldown(),
+			AttackObj::ShieldBlock(obj) => obj.cooldown(),
+			AttackObj::ShotArrow(obj) => obj.cooldown(),
 			AttackObj::Slash(obj) => obj.cooldown(),
 			AttackObj::Slimeball(obj) => obj.cooldown(),
+			AttackObj::Smash(obj) => obj.cooldown(),
+			AttackObj::SpearThrust(obj) => obj.cooldown(),
 			AttackObj::Stab(obj) => obj.cooldown(),
+			AttackObj::SummonSkeleton(obj) => obj.cooldown(),
 			AttackObj::ThrowingKnife(obj) => obj.cooldown(),
+			AttackObj::ThrownItem(obj) => obj.cooldown(),
+			AttackObj::Whirlwind(obj) => obj.cooldown(),
+		}
+	}
+}
+
+impl AsPolygon for AttackObj {
+	fn as_polygon(&self) -> Polygon {
+		match self {
+			AttackObj::Arrow(obj) => obj.as_polygon(),
+			AttackObj::Bite(obj) => obj.as_polygon(),
+			AttackObj::BlindingLight(obj) => obj.as_polygon(),
+			AttackObj::Blink(obj) => obj.as_polygon(),
+			AttackObj::Fireball(obj) => obj.as_polygon(),
+			AttackObj::FrostNova(obj) => obj.as_polygon(),
+			AttackObj::HealOther(obj) => obj.as_polygon(),
+			AttackObj::MagicMissile(obj) => obj.as_polygon(),
+			AttackObj::RadiantBurst(obj) => obj.as_polygon(),
+			AttackObj::ShieldBlock(obj) => obj.as_polygon(),
+			AttackObj::ShotArrow(obj) => obj.as_polygon(),
+			AttackObj::Slash(obj) => obj.as_polygon(),
+			AttackObj::Slimeball(obj) => obj.as_polygon(),
+			AttackObj::Smash(obj) => obj.as_polygon(),
+			AttackObj::SpearThrust(obj) => obj.as_polygon(),
+			AttackObj::Stab(obj) => obj.as_polygon(),
+			AttackObj::SummonSkeleton(obj) => obj.as_polygon(),
+			AttackObj::ThrowingKnife(obj) => obj.as_polygon(),
+			AttackObj::ThrownItem(obj) => obj.as_polygon(),
+			AttackObj::Whirlwind(obj) => obj.as_polygon(),
 		}
 	}
 }
@@ -80,56 +263,176 @@ impl AttackObj {
 impl Drawable for AttackObj {
 	fn size(&self) -> Vec2 {
 		match self {
+			AttackObj::Arrow(obj) => obj.size(),
+			AttackObj::Bite(obj) => obj.size(),
 			AttackObj::BlindingLight(obj) => obj.size(),
+			AttackObj::Blink(obj) => obj.size(),
+			AttackObj::Fireball(obj) => obj.size(),
+			AttackObj::FrostNova(obj) => obj.size(),
+			AttackObj::HealOther(obj) => obj.size(),
 			AttackObj::MagicMissile(obj) => obj.size(),
+			AttackObj::RadiantBurst(obj) => obj.size(),
+			AttackObj::ShieldBlock(obj) => obj.size(),
+			AttackObj::ShotArrow(obj) => obj.size(),
 			AttackObj::Slash(obj) => obj.size(),
 			AttackObj::Slimeball(obj) => obj.size(),
+			AttackObj::Smash(obj) => obj.size(),
+			AttackObj::SpearThrust(obj) => obj.size(),
 			AttackObj::Stab(obj) => obj.size(),
+			AttackObj::SummonSkeleton(obj) => obj.size(),
 			AttackObj::ThrowingKnife(obj) => obj.size(),
+			AttackObj::ThrownItem(obj) => obj.size(),
+			AttackObj::Whirlwind(obj) => obj.size(),
 		}
 	}
 
 	fn pos(&self) -> Vec2 {
 		match self {
+			AttackObj::Arrow(obj) => obj.pos(),
+			AttackObj::Bite(obj) => obj.pos(),
 			AttackObj::BlindingLight(obj) => obj.pos(),
+			AttackObj::Blink(obj) => obj.pos(),
+			AttackObj::Fireball(obj) => obj.pos(),
+			AttackObj::FrostNova(obj) => obj.pos(),
+			AttackObj::HealOther(obj) => obj.pos(),
 			AttackObj::MagicMissile(obj) => obj.pos(),
+			AttackObj::RadiantBurst(obj) => obj.pos(),
+			AttackObj::ShieldBlock(obj) => obj.pos(),
+			AttackObj::ShotArrow(obj) => obj.pos(),
 			AttackObj::Slash(obj) => obj.pos(),
 			AttackObj::Slimeball(obj) => obj.pos(),
+			AttackObj::Smash(obj) => obj.pos(),
+			AttackObj::SpearThrust(obj) => obj.pos(),
 			AttackObj::Stab(obj) => obj.pos(),
+			AttackObj::SummonSkeleton(obj) => obj.pos(),
 			AttackObj::ThrowingKnife(obj) => obj.pos(),
+			AttackObj::ThrownItem(obj) => obj.pos(),
+			AttackObj::Whirlwind(obj) => obj.pos(),
 		}
 	}
 
 	fn texture(&self) -> Option<Texture2D> {
 		match self {
+			AttackObj::Arrow(obj) => obj.texture(),
+			AttackObj::Bite(obj) => obj.texture(),
 			AttackObj::BlindingLight(obj) => obj.texture(),
+			AttackObj::Blink(obj) => obj.texture(),
+			AttackObj::Fireball(obj) => obj.texture(),
+			AttackObj::FrostNova(obj) => obj.texture(),
+			AttackObj::HealOther(obj) => obj.texture(),
 			AttackObj::MagicMissile(obj) => obj.texture(),
+			AttackObj::RadiantBurst(obj) => obj.texture(),
+			AttackObj::ShieldBlock(obj) => obj.texture(),
+			AttackObj::ShotArrow(obj) => obj.texture(),
 			AttackObj::Slash(obj) => obj.texture(),
 			AttackObj::Slimeball(obj) => obj.texture(),
+			AttackObj::Smash(obj) => obj.texture(),
+			AttackObj::SpearThrust(obj) => obj.texture(),
 			AttackObj::Stab(obj) => obj.texture(),
+			AttackObj::SummonSkeleton(obj) => obj.texture(),
 			AttackObj::ThrowingKnife(obj) => obj.texture(),
+			AttackObj::ThrownItem(obj) => obj.texture(),
+			AttackObj::Whirlwind(obj) => obj.texture(),
 		}
 	}
 
 	fn rotation(&self) -> f32 {
 		match self {
+			AttackObj::Arrow(obj) => obj.rotation(),
+			AttackObj::Bite(obj) => obj.rotation(),
 			AttackObj::BlindingLight(obj) => obj.rotation(),
+			AttackObj::Blink(obj) => obj.rotation(),
+			AttackObj::Fireball(obj) => obj.rotation(),
+			AttackObj::FrostNova(obj) => obj.rotation(),
+			AttackObj::HealOther(obj) => obj.rotation(),
 			AttackObj::MagicMissile(obj) => obj.rotation(),
+			AttackObj::RadiantBurst(obj) => obj.rotation(),
+			AttackObj::ShieldBlock(obj) => obj.rotation(),
+			AttackObj::ShotArrow(obj) => obj.rotation(),
 			AttackObj::Slash(obj) => obj.rotation(),
 			AttackObj::Slimeball(obj) => obj.rotation(),
+			AttackObj::Smash(obj) => obj.rotation(),
+			AttackObj::SpearThrust(obj) => obj.rotation(),
 			AttackObj::Stab(obj) => obj.rotation(),
+			AttackObj::SummonSkeleton(obj) => obj.rotation(),
 			AttackObj::ThrowingKnife(obj) => obj.rotation(),
+			AttackObj::ThrownItem(obj) => obj.rotation(),
+			AttackObj::Whirlwind(obj) => obj.rotation(),
+		}
+	}
+
+	fn draw(&self) {
+		match self {
+			AttackObj::Arrow(obj) => obj.draw(),
+			AttackObj::Bite(obj) => obj.draw(),
+			AttackObj::BlindingLight(obj) => obj.draw(),
+			AttackObj::Blink(obj) => obj.draw(),
+			AttackObj::Fireball(obj) => obj.draw(),
+			AttackObj::FrostNova(obj) => obj.draw(),
+			AttackObj::HealOther(obj) => obj.draw(),
+			AttackObj::MagicMissile(obj) => obj.draw(),
+			AttackObj::RadiantBurst(obj) => obj.draw(),
+			AttackObj::ShieldBlock(obj) => obj.draw(),
+			AttackObj::ShotArrow(obj) => obj.draw(),
+			AttackObj::Slash(obj) => obj.draw(),
+			AttackObj::Slimeball(obj) => obj.draw(),
+			AttackObj::Smash(obj) => obj.draw(),
+			AttackObj::SpearThrust(obj) => obj.draw(),
+			AttackObj::Stab(obj) => obj.draw(),
+			AttackObj::SummonSkeleton(obj) => obj.draw(),
+			AttackObj::ThrowingKnife(obj) => obj.draw(),
+			AttackObj::ThrownItem(obj) => obj.draw(),
+			AttackObj::Whirlwind(obj) => obj.draw(),
 		}
 	}
 
 	fn flip_x(&self) -> bool {
 		match self {
+			AttackObj::Arrow(obj) => obj.flip_x(),
+			AttackObj::Bite(obj) => obj.flip_x(),
 			AttackObj::BlindingLight(obj) => obj.flip_x(),
+			AttackObj::Blink(obj) => obj.flip_x(),
+			AttackObj::Fireball(obj) => obj.flip_x(),
+			AttackObj::FrostNova(obj) => obj.flip_x(),
+			AttackObj::HealOther(obj) => obj.flip_x(),
 			AttackObj::MagicMissile(obj) => obj.flip_x(),
+			AttackObj::RadiantBurst(obj) => obj.flip_x(),
+			AttackObj::ShieldBlock(obj) => obj.flip_x(),
+			AttackObj::ShotArrow(obj) => obj.flip_x(),
 			AttackObj::Slash(obj) => obj.flip_x(),
 			AttackObj::Slimeball(obj) => obj.flip_x(),
+			AttackObj::Smash(obj) => obj.flip_x(),
+			AttackObj::SpearThrust(obj) => obj.flip_x(),
 			AttackObj::Stab(obj) => obj.flip_x(),
+			AttackObj::SummonSkeleton(obj) => obj.flip_x(),
 			AttackObj::ThrowingKnife(obj) => obj.flip_x(),
+			AttackObj::ThrownItem(obj) => obj.flip_x(),
+			AttackObj::Whirlwind(obj) => obj.flip_x(),
+		}
+	}
+
+	fn animation(&self) -> Option<(&'static Timeline, u16)> {
+		match self {
+			AttackObj::Arrow(obj) => obj.animation(),
+			AttackObj::Bite(obj) => obj.animation(),
+			AttackObj::BlindingLight(obj) => obj.animation(),
+			AttackObj::Blink(obj) => obj.animation(),
+			AttackObj::Fireball(obj) => obj.animation(),
+			AttackObj::FrostNova(obj) => obj.animation(),
+			AttackObj::HealOther(obj) => obj.animation(),
+			AttackObj::MagicMissile(obj) => obj.animation(),
+			AttackObj::RadiantBurst(obj) => obj.animation(),
+			AttackObj::ShieldBlock(obj) => obj.animation(),
+			AttackObj::ShotArrow(obj) => obj.animation(),
+			AttackObj::Slash(obj) => obj.animation(),
+			AttackObj::Slimeball(obj) => obj.animation(),
+			AttackObj::Smash(obj) => obj.animation(),
+			AttackObj::SpearThrust(obj) => obj.animation(),
+			AttackObj::Stab(obj) => obj.animation(),
+			AttackObj::SummonSkeleton(obj) => obj.animation(),
+			AttackObj::ThrowingKnife(obj) => obj.animation(),
+			AttackObj::ThrownItem(obj) => obj.animation(),
+			AttackObj::Whirlwind(obj) => obj.animation(),
 		}
 	}
 }
@@ -140,15 +443,34 @@ pub trait Attack: Drawable + Send + Sync + Clone + Serialize {
 		player: &dyn AsPolygon, player_index: Option<usize>, angle: f32, floor: &Floor,
 		is_primary: bool,
 	) -> Self;
-	/// If the attack has any side effects on the user, do them here
-	fn side_effects(&self, player: &mut Player, floor: &Floor);
 	fn mana_cost(&self) -> u16;
 	// Returns whether or not the attack should be destroyed
-	fn update(&mut self, floor: &mut FloorInfo, players: &mut [Player]) -> bool;
+	fn update(
+		&mut self, floor: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool;
 	fn cooldown(&self) -> u16;
 	fn as_polygon_optional(&self) -> Option<Polygon> { None }
+	/// Whose side the attack's on, for attacks that can be turned against
+	/// whoever made them
+	fn team(&self) -> Option<Team> { None }
+	/// How it hurts other players, if it can, when friendly fire's on
+	fn friendly_fire(&self) -> Option<FriendlyFire> { None }
+	/// Puts the attack on another side, sending it back the way it came
+	fn turn_against(&mut self, _team: Team) {}
 }
 
-pub fn update_attacks(players: &mut [Player], floor: &mut FloorInfo, attacks: &mut Vec<AttackObj>) {
-	attacks.retain_mut(|attack| !attack.update(floor, players));
+pub fn update_attacks(
+	players: &mut [Player], floor: &mut FloorInfo, attacks: &mut Vec<AttackObj>,
+	events: &mut Vec<GameEvent>, rng: &mut Rng, friendly_fire: bool,
+) {
+	crate::profile_scope!("update_attacks");
+
+	attacks.retain_mut(|attack| {
+		if attack.update(floor, players, events, rng) {
+			return false;
+		}
+
+		!(friendly_fire && hit_other_players(attack, players, &floor.floor, events))
+	});
 }
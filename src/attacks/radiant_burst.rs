@@ -0,0 +1,118 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+/// How far the light reaches, in tiles
+const RADIUS: f32 = 3.0;
+/// How long the light takes to spread out, in frames
+const LIFETIME: u16 = 24;
+/// What it does to the living, who only get a little singed
+const DAMAGE: u16 = 2;
+/// What it does to the undead, which is a lot worse
+const UNDEAD_DAMAGE: u16 = 20;
+
+/// Holy light flaring out from the caster. Barely scratches the living, but
+/// tears through skeletons, zombies and anything else that should've stayed
+/// buried
+#[derive(Clone, Serialize)]
+pub struct RadiantBurst {
+	pos: Vec2,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for RadiantBurst {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, _angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			let radius = RADIUS * TILE_SIZE as f32;
+
+			let hit: Vec<usize> = floor_info
+				.monsters
+				.iter()
+				.enumerate()
+				.filter(|(_, m)| {
+					m.targetable() &&
+						m.center().distance(self.pos) <= radius &&
+						floor_info.floor.line_of_sight(self.pos, m.center())
+				})
+				.map(|(i, _)| i)
+				.collect();
+
+			hit.into_iter().for_each(|i| {
+				let damage = match floor_info.monsters[i].kind().is_undead() {
+					true => UNDEAD_DAMAGE,
+					false => DAMAGE,
+				};
+
+				let damage_info = DamageInfo {
+					damage,
+					damage_type: DamageType::Magic,
+					direction: get_angle(floor_info.monsters[i].center(), self.pos),
+					player: self.player_index,
+				};
+
+				hit_monster(i, damage_info, floor_info, players, events, rng);
+			});
+		}
+
+		self.time += 1;
+
+		self.time >= LIFETIME
+	}
+
+	fn cooldown(&self) -> u16 { 120 }
+
+	fn mana_cost(&self) -> u16 { 3 }
+}
+
+impl AsPolygon for RadiantBurst {
+	fn as_polygon(&self) -> Polygon {
+		easy_polygon(self.pos, Vec2::splat(RADIUS * TILE_SIZE as f32), 0.0)
+	}
+}
+
+impl Drawable for RadiantBurst {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(RADIUS * TILE_SIZE as f32) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(RADIUS * TILE_SIZE as f32 * 2.0) }
+
+	fn draw(&self) {
+		let progress = self.time as f32 / LIFETIME as f32;
+		let radius = RADIUS * TILE_SIZE as f32 * progress.max(0.1);
+
+		draw_circle(
+			self.pos.x,
+			self.pos.y,
+			radius,
+			Color::new(1.0, 0.95, 0.7, 0.3 * (1.0 - progress)),
+		);
+		draw_circle_lines(
+			self.pos.x,
+			self.pos.y,
+			radius,
+			3.0,
+			Color::new(1.0, 0.85, 0.3, 1.0 - progress),
+		);
+	}
+}
@@ -0,0 +1,88 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::player::{Player, BLOCK_ARC, PLAYER_SIZE};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+/// How long the shield stays raised, in frames
+const BLOCK_TIME: u16 = 30;
+/// How far in front of the player the shield's held
+const DISTANCE: f32 = PLAYER_SIZE * 0.75;
+
+/// Raises the warrior's shield for a moment, softening any hit that comes
+/// from in front and keeping them from being knocked back by it
+#[derive(Clone, Serialize)]
+pub struct ShieldBlock {
+	/// The center of the player holding the shield
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for ShieldBlock {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			angle,
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, _floor_info: &mut FloorInfo, players: &mut [Player],
+		_events: &mut Vec<GameEvent>, _rng: &mut Rng,
+	) -> bool {
+		let player = &mut players[self.player_index];
+
+		if self.time == 0 {
+			player.blocking = BLOCK_TIME;
+		}
+
+		// The shield moves and turns with whoever's holding it
+		self.pos = player.center();
+		self.angle = player.angle;
+		self.time += 1;
+
+		self.time >= BLOCK_TIME || player.hp() == 0
+	}
+
+	fn cooldown(&self) -> u16 { 90 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+}
+
+impl AsPolygon for ShieldBlock {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, Vec2::splat(DISTANCE), self.angle) }
+}
+
+impl Drawable for ShieldBlock {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(DISTANCE) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(DISTANCE * 2.0) }
+
+	fn draw(&self) {
+		const SEGMENTS: u8 = 6;
+
+		let alpha = 1.0 - self.time as f32 / BLOCK_TIME as f32 * 0.5;
+		let color = Color::new(0.8, 0.8, 0.9, alpha);
+
+		let point = |i: u8| {
+			let angle = self.angle - BLOCK_ARC * 0.5 + BLOCK_ARC * i as f32 / SEGMENTS as f32;
+			self.pos + Vec2::new(angle.cos(), angle.sin()) * DISTANCE
+		};
+
+		(0..SEGMENTS).for_each(|i| {
+			let (start, end) = (point(i), point(i + 1));
+			draw_line(start.x, start.y, end.x, end.y, 3.0, color);
+		});
+	}
+}
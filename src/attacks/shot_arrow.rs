@@ -0,0 +1,120 @@
+use std::f32::consts::PI;
+
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::items::ItemType;
+use crate::map::{pos_to_tile, Floor, FloorInfo};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::player::{DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, FriendlyFire, Team};
+
+const HALF_SIZE: Vec2 = Vec2::new(8.0, 1.5);
+const SPEED: f32 = 10.0;
+const DAMAGE: u16 = 14;
+/// How long an arrow flies before it drops to the floor
+const MAX_TIME: u16 = 45;
+
+/// Shot from a player's bow. Arrows that hit something are lost, but ones that
+/// hit a wall or fall short can be picked up again
+#[derive(Clone, Serialize)]
+pub struct ShotArrow {
+	// The middle of the arrow
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	team: Team,
+}
+
+impl Attack for ShotArrow {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			angle,
+			time: 0,
+			team: Team::Player(index.unwrap()),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		let movement = Vec2::new(self.angle.cos(), self.angle.sin()) * SPEED;
+
+		if floor_info
+			.floor
+			.projectile_hit(self, movement, self.team.player(), events) ||
+			self.time >= MAX_TIME
+		{
+			floor_info
+				.floor
+				.drop_item(pos_to_tile(self), ItemType::from_key("arrow"));
+
+			return true;
+		}
+
+		self.pos += movement;
+		self.time += 1;
+
+		self.team
+			.hit_first(
+				self,
+				DAMAGE,
+				DamageType::Pierce,
+				floor_info,
+				players,
+				events,
+				rng,
+			)
+			.is_some()
+	}
+
+	fn cooldown(&self) -> u16 { 30 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+
+	fn team(&self) -> Option<Team> { Some(self.team) }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.team.player()?,
+			damage: DAMAGE,
+			damage_type: DamageType::Pierce,
+			spent_on_hit: true,
+		})
+	}
+
+	fn turn_against(&mut self, team: Team) {
+		self.team = team;
+		self.angle += PI;
+	}
+
+	fn as_polygon_optional(&self) -> Option<Polygon> { Some(self.as_polygon()) }
+}
+
+impl AsPolygon for ShotArrow {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, HALF_SIZE, self.angle) }
+}
+
+impl Drawable for ShotArrow {
+	fn pos(&self) -> Vec2 { self.pos - HALF_SIZE }
+
+	fn size(&self) -> Vec2 { HALF_SIZE * 2.0 }
+
+	fn rotation(&self) -> f32 { self.angle }
+
+	fn draw(&self) {
+		let direction = Vec2::new(self.angle.cos(), self.angle.sin()) * HALF_SIZE.x;
+		let tip = self.pos + direction;
+		let tail = self.pos - direction;
+
+		draw_line(tail.x, tail.y, tip.x, tip.y, 2.0, BROWN);
+		draw_circle(tip.x, tip.y, 2.0, LIGHTGRAY);
+	}
+}
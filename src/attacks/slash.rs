@@ -1,17 +1,56 @@
 use std::f32::consts::PI;
 
+use crate::animation::{Keyframe, Pose, Timeline};
 use crate::draw::{load_my_image, Drawable};
+use crate::events::GameEvent;
 use crate::map::{Floor, FloorInfo};
 use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon};
-use crate::player::{DamageInfo, Player, PLAYER_SIZE};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player, PLAYER_SIZE};
+use crate::rng::Rng;
 use macroquad::prelude::*;
 use serde::Serialize;
 
-use super::Attack;
+use super::{Attack, FriendlyFire};
 
 const HALF_SIZE: Vec2 = Vec2::new(15.0 * 0.5, 20.0 * 0.5);
 const SIZE: Vec2 = Vec2::new(15.0, 20.0);
 const SWING_TIME: u16 = 10;
+// Damage is low bc of hitting enemies multiple times
+const DAMAGE: u16 = 4;
+
+/// The blade grows in trailing behind the swing, then follows through past it
+/// as it fades
+static SWING: Timeline = Timeline {
+	keyframes: &[
+		Keyframe {
+			frame: 0,
+			pose: Pose {
+				scale: 0.5,
+				rotation: 0.4,
+				..Pose::IDENTITY
+			},
+		},
+		Keyframe {
+			frame: 3,
+			pose: Pose::IDENTITY,
+		},
+		Keyframe {
+			frame: 7,
+			pose: Pose::IDENTITY,
+		},
+		Keyframe {
+			frame: SWING_TIME,
+			pose: Pose {
+				scale: 0.6,
+				rotation: -0.3,
+				..Pose::IDENTITY
+			},
+		},
+	],
+	texture_frames: 1,
+	looping: false,
+};
 
 #[derive(Clone, Serialize)]
 pub struct Slash {
@@ -20,6 +59,16 @@ pub struct Slash {
 	time: u16,
 	player_index: usize,
 	num_piercings: u8,
+	/// How much harder than usual it hits, from being wound up or striking
+	/// from hiding
+	power: u16,
+}
+
+impl Slash {
+	pub fn with_power(mut self, power: u16) -> Self {
+		self.power = power;
+		self
+	}
 }
 
 impl Attack for Slash {
@@ -33,12 +82,14 @@ impl Attack for Slash {
 			time: 0,
 			player_index: index.unwrap(),
 			num_piercings: 0,
+			power: 1,
 		}
 	}
 
-	fn side_effects(&self, _player: &mut Player, _floor: &Floor) {}
-
-	fn update(&mut self, floor_info: &mut FloorInfo, players: &mut [Player]) -> bool {
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
 		self.time += 1;
 
 		if self.time >= SWING_TIME {
@@ -51,27 +102,34 @@ impl Attack for Slash {
 		self.pos = players[self.player_index].center() + movement;
 
 		let poly = self.as_polygon();
+		let origin = players[self.player_index].center();
 
-		// Check to see if it's collided with a monster
-		floor_info
+		// Check to see if it's collided with a monster the player can reach
+		let hit: Vec<usize> = floor_info
 			.monsters
-			.iter_mut()
-			.filter(|m| aabb_collision(&poly, &m.as_polygon(), Vec2::ZERO))
-			.for_each(|monster| {
-				// Damage is low bc of hitting enemies multiple times
-				const DAMAGE: u16 = 4;
-
-				let direction = get_angle(monster.pos(), self.pos);
-				let damage_info = DamageInfo {
-					damage: DAMAGE,
-					direction,
-					player: self.player_index,
-				};
-
-				monster.take_damage(damage_info, &floor_info.floor);
-
-				self.num_piercings += 1;
-			});
+			.iter()
+			.enumerate()
+			.filter(|(_, m)| {
+				m.targetable() &&
+					aabb_collision(&poly, &m.as_polygon(), Vec2::ZERO) &&
+					floor_info.floor.line_of_sight(origin, m.center())
+			})
+			.map(|(i, _)| i)
+			.collect();
+
+		hit.into_iter().for_each(|i| {
+			let direction = get_angle(floor_info.monsters[i].pos(), self.pos);
+			let damage_info = DamageInfo {
+				damage: DAMAGE * self.power,
+				damage_type: DamageType::Slash,
+				direction,
+				player: self.player_index,
+			};
+
+			hit_monster(i, damage_info, floor_info, players, events, rng);
+
+			self.num_piercings += 1;
+		});
 
 		false
 	}
@@ -79,6 +137,15 @@ impl Attack for Slash {
 	fn cooldown(&self) -> u16 { SWING_TIME * 3 }
 
 	fn mana_cost(&self) -> u16 { 0 }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.player_index,
+			damage: DAMAGE * self.power,
+			damage_type: DamageType::Slash,
+			spent_on_hit: false,
+		})
+	}
 }
 
 impl AsPolygon for Slash {
@@ -96,5 +163,7 @@ impl Drawable for Slash {
 
 	fn flip_x(&self) -> bool { false }
 
+	fn animation(&self) -> Option<(&'static Timeline, u16)> { Some((&SWING, self.time)) }
+
 	fn texture(&self) -> Option<Texture2D> { Some(load_my_image("sword.webp")) }
 }
@@ -1,21 +1,47 @@
+use std::f32::consts::PI;
+
 use crate::draw::{load_my_image, Drawable};
-use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
-use crate::map::{Floor, FloorInfo};
-use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
-use crate::player::{damage_player, Player};
+use crate::enchantments::{Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{pos_to_tile, EffectType, Floor, FloorInfo};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::monsters::MonsterKind;
+use crate::player::{DamageType, Player};
+use crate::rng::Rng;
 use macroquad::prelude::*;
 use serde::Serialize;
 
-use super::Attack;
+use super::{Attack, Team};
 
 const HALF_SIZE: Vec2 = Vec2::new(7.5, 2.5);
 const SIZE: Vec2 = Vec2::new(15.0, 5.0);
+const SPEED: f32 = 2.2;
+const LIFETIME: u16 = 30;
+/// How far a slimeball flies before it splats, in pixels
+pub const SLIMEBALL_RANGE: f32 = SPEED * LIFETIME as f32;
+/// How long the slime it leaves where it splats lasts, in frames
+const PUDDLE_TIME: u16 = 240;
 
 #[derive(Clone, Serialize)]
 pub struct Slimeball {
 	pos: Vec2,
 	angle: f32,
 	time: u16,
+	team: Team,
+}
+
+impl Slimeball {
+	pub fn shot_by(mut self, shooter: MonsterKind) -> Self {
+		self.team = Team::Monsters(Some(shooter));
+		self
+	}
+}
+
+impl Slimeball {
+	/// Leaves a puddle of slime wherever it ended up
+	fn splat(&self, floor: &mut Floor) {
+		floor.add_effect(pos_to_tile(self), EffectType::Slimed, PUDDLE_TIME);
+	}
 }
 
 impl Attack for Slimeball {
@@ -26,51 +52,69 @@ impl Attack for Slimeball {
 			pos: aabb.center(),
 			angle,
 			time: 0,
+			team: Team::Monsters(None),
 		}
 	}
 
-	fn side_effects(&self, _player: &mut Player, _floor_info: &Floor) {}
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		let movement = Vec2::new(self.angle.cos(), self.angle.sin()) * SPEED;
 
-	fn update(&mut self, floor_info: &mut FloorInfo, players: &mut [Player]) -> bool {
-		let movement = Vec2::new(self.angle.cos(), self.angle.sin()) * 2.2;
+		let hit_wall = floor_info
+			.floor
+			.projectile_hit(self, movement, self.team.player(), events);
 
-		if !floor_info.floor.collision(self, movement) {
+		if !hit_wall {
 			self.pos += movement;
 			self.time += 1;
-		} else {
-			return true;
 		}
 
-		if self.time >= 30 {
+		if hit_wall || self.time >= LIFETIME {
+			self.splat(&mut floor_info.floor);
 			return true;
 		}
 
-		let poly = self.as_polygon();
-
-		// Check to see if it's collided with a player
-		if let Some(player) = players
-			.iter_mut()
-			.find(|p| aabb_collision(&poly, &p.as_polygon(), Vec2::ZERO))
-		{
-			const DAMAGE: u16 = 6;
-
-			let direction = get_angle(player.pos(), self.pos);
-
-			damage_player(player, DAMAGE, direction, &floor_info.floor);
-			player.apply_enchantment(Enchantment {
-				kind: EnchantmentKind::Sticky,
-				strength: 2,
-			});
-
-			return true;
+		const DAMAGE: u16 = 6;
+
+		// Whatever it hits gets stuck
+		match self.team.hit_first(
+			self,
+			DAMAGE,
+			DamageType::Blunt,
+			floor_info,
+			players,
+			events,
+			rng,
+		) {
+			Some(target) => {
+				target.apply_enchantment(
+					Enchantment {
+						kind: EnchantmentKind::Sticky,
+						strength: 2,
+					},
+					floor_info,
+					players,
+				);
+
+				self.splat(&mut floor_info.floor);
+				true
+			},
+			None => false,
 		}
-
-		false
 	}
 
 	fn cooldown(&self) -> u16 { 80 }
 
 	fn mana_cost(&self) -> u16 { 0 }
+
+	fn team(&self) -> Option<Team> { Some(self.team) }
+
+	fn turn_against(&mut self, team: Team) {
+		self.team = team;
+		self.angle += PI;
+	}
 }
 
 impl AsPolygon for Slimeball {
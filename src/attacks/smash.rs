@@ -0,0 +1,141 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player, PLAYER_SIZE};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, FriendlyFire};
+
+/// How far in front of the player the mace comes down
+const REACH: f32 = PLAYER_SIZE * 1.5;
+/// How wide the blow lands
+const RADIUS: f32 = PLAYER_SIZE;
+/// Frames between raising the mace and it landing
+const WINDUP_TIME: u16 = 8;
+/// How long the blow lingers on screen once it's landed
+const SMASH_TIME: u16 = 16;
+const DAMAGE: u16 = 7;
+
+/// A cleric's mace, brought down on everything in a small circle in front of
+/// them. Slow, but it hits everything it lands on
+#[derive(Clone, Serialize)]
+pub struct Smash {
+	/// Where the mace lands
+	pos: Vec2,
+	time: u16,
+	player_index: usize,
+	/// How much harder than usual it hits, from being wound up
+	power: u16,
+}
+
+impl Smash {
+	pub fn with_power(mut self, power: u16) -> Self {
+		self.power = power;
+		self
+	}
+}
+
+impl Attack for Smash {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center() + Vec2::new(angle.cos(), angle.sin()) * REACH,
+			time: 0,
+			player_index: index.unwrap(),
+			power: 1,
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		self.time += 1;
+
+		if self.time == WINDUP_TIME {
+			let origin = players[self.player_index].center();
+
+			let hit: Vec<usize> = floor_info
+				.monsters
+				.iter()
+				.enumerate()
+				.filter(|(_, m)| {
+					m.targetable() &&
+						m.center().distance(self.pos) <= RADIUS + m.size().x * 0.5 &&
+						floor_info.floor.line_of_sight(origin, m.center())
+				})
+				.map(|(i, _)| i)
+				.collect();
+
+			hit.into_iter().for_each(|i| {
+				let damage_info = DamageInfo {
+					damage: DAMAGE * self.power,
+					damage_type: DamageType::Blunt,
+					direction: get_angle(floor_info.monsters[i].center(), self.pos),
+					player: self.player_index,
+				};
+
+				hit_monster(i, damage_info, floor_info, players, events, rng);
+			});
+		}
+
+		self.time >= SMASH_TIME || players[self.player_index].hp() == 0
+	}
+
+	fn cooldown(&self) -> u16 { 40 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		// Only the moment it lands can hurt anyone
+		(self.time == WINDUP_TIME).then_some(FriendlyFire {
+			player: self.player_index,
+			damage: DAMAGE * self.power,
+			damage_type: DamageType::Blunt,
+			spent_on_hit: false,
+		})
+	}
+}
+
+impl AsPolygon for Smash {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, Vec2::splat(RADIUS), 0.0) }
+}
+
+impl Drawable for Smash {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(RADIUS) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(RADIUS * 2.0) }
+
+	fn draw(&self) {
+		match self.time < WINDUP_TIME {
+			// The mace's head, coming down
+			true => {
+				let progress = self.time as f32 / WINDUP_TIME as f32;
+
+				draw_circle(
+					self.pos.x,
+					self.pos.y - RADIUS * (1.0 - progress),
+					RADIUS * 0.4,
+					GRAY,
+				);
+			},
+			// Dust and cracks spreading out from where it landed
+			false => {
+				let progress = (self.time - WINDUP_TIME) as f32 / (SMASH_TIME - WINDUP_TIME) as f32;
+
+				draw_circle_lines(
+					self.pos.x,
+					self.pos.y,
+					RADIUS * (0.5 + progress * 0.5),
+					2.0,
+					Color::new(0.8, 0.75, 0.6, 1.0 - progress),
+				);
+			},
+		}
+	}
+}
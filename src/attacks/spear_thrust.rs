@@ -0,0 +1,143 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{pos_to_tile, Floor, FloorInfo, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, points_on_line, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, FriendlyFire};
+
+/// How far the spear reaches, in tiles
+const REACH: f32 = 2.0;
+/// How long the spear stays out, in frames
+const THRUST_TIME: u16 = 10;
+const DAMAGE: u16 = 20;
+
+/// Runs through every monster in a line in front of the player, shoving the
+/// closest one back a tile
+#[derive(Clone, Serialize)]
+pub struct SpearThrust {
+	/// The center of the player holding the spear
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	player_index: usize,
+	/// How much harder than usual it hits, from striking from hiding
+	power: u16,
+}
+
+impl SpearThrust {
+	pub fn with_power(mut self, power: u16) -> Self {
+		self.power = power;
+		self
+	}
+
+	fn tip(&self) -> Vec2 {
+		self.pos + Vec2::new(self.angle.cos(), self.angle.sin()) * REACH * TILE_SIZE as f32
+	}
+}
+
+impl Attack for SpearThrust {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			angle,
+			time: 0,
+			player_index: index.unwrap(),
+			power: 1,
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			let start_tile = pos_to_tile(&players[self.player_index]);
+			let end_tile = (self.tip() / Vec2::splat(TILE_SIZE as f32))
+				.floor()
+				.as_ivec2();
+
+			// The spear stops at the first wall or closed door
+			let tiles: Vec<IVec2> = points_on_line(start_tile, end_tile)
+				.into_iter()
+				.take_while(|tile_pos| {
+					floor_info
+						.floor
+						.get_object_from_pos(*tile_pos)
+						.is_some_and(|obj| !obj.is_collidable())
+				})
+				.collect();
+
+			let mut hit: Vec<(usize, f32)> = floor_info
+				.monsters
+				.iter()
+				.enumerate()
+				.filter(|(_, m)| m.targetable() && tiles.contains(&pos_to_tile(*m)))
+				.map(|(i, m)| (i, m.center().distance(self.pos)))
+				.collect();
+
+			hit.sort_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+
+			hit.iter().for_each(|(i, _)| {
+				let damage_info = DamageInfo {
+					damage: DAMAGE * self.power,
+					damage_type: DamageType::Pierce,
+					direction: get_angle(floor_info.monsters[*i].center(), self.pos),
+					player: self.player_index,
+				};
+
+				hit_monster(*i, damage_info, floor_info, players, events, rng);
+			});
+
+			if let Some((first, _)) = hit.first() {
+				let push = Vec2::new(self.angle.cos(), self.angle.sin()) * TILE_SIZE as f32;
+				floor_info.monsters[*first].knock_back(push, &floor_info.floor);
+			}
+		}
+
+		self.pos = players[self.player_index].center();
+		self.time += 1;
+
+		self.time >= THRUST_TIME
+	}
+
+	fn cooldown(&self) -> u16 { 60 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.player_index,
+			damage: DAMAGE * self.power,
+			damage_type: DamageType::Pierce,
+			spent_on_hit: false,
+		})
+	}
+}
+
+impl AsPolygon for SpearThrust {
+	fn as_polygon(&self) -> Polygon {
+		let half_size = Vec2::new(REACH * TILE_SIZE as f32 * 0.5, 2.0);
+		easy_polygon((self.pos + self.tip()) * 0.5, half_size, self.angle)
+	}
+}
+
+impl Drawable for SpearThrust {
+	fn pos(&self) -> Vec2 { self.pos.min(self.tip()) }
+
+	fn size(&self) -> Vec2 { (self.tip() - self.pos).abs() }
+
+	fn draw(&self) {
+		let tip = self.tip();
+		let head = tip - Vec2::new(self.angle.cos(), self.angle.sin()) * 6.0;
+
+		draw_line(self.pos.x, self.pos.y, head.x, head.y, 2.0, BROWN);
+		draw_line(head.x, head.y, tip.x, tip.y, 3.0, LIGHTGRAY);
+	}
+}
@@ -1,14 +1,18 @@
 use crate::draw::{load_my_image, Drawable};
+use crate::events::GameEvent;
 use crate::map::{Floor, FloorInfo};
 use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
-use crate::player::{DamageInfo, Player, PLAYER_SIZE};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
 use macroquad::prelude::*;
 use serde::Serialize;
 
-use super::Attack;
+use super::{Attack, FriendlyFire};
 
 const HALF_SIZE: Vec2 = Vec2::new(7.5, 2.5);
 const SIZE: Vec2 = Vec2::new(15.0, 5.0);
+const DAMAGE: u16 = 25;
 
 #[derive(Clone, Serialize)]
 pub struct Stab {
@@ -17,6 +21,15 @@ pub struct Stab {
 	time: u16,
 	player_index: usize,
 	num_piercings: u8,
+	/// How much harder than usual it hits, from striking from hiding
+	power: u16,
+}
+
+impl Stab {
+	pub fn with_power(mut self, power: u16) -> Self {
+		self.power = power;
+		self
+	}
 }
 
 impl Attack for Stab {
@@ -29,18 +42,14 @@ impl Attack for Stab {
 			time: 0,
 			player_index: index.unwrap(),
 			num_piercings: 0,
+			power: 1,
 		}
 	}
 
-	fn side_effects(&self, player: &mut Player, floor: &Floor) {
-		let change = Vec2::new(self.angle.cos(), self.angle.sin()) * PLAYER_SIZE;
-
-		if !floor.collision(player, change) {
-			player.pos += change;
-		}
-	}
-
-	fn update(&mut self, floor_info: &mut FloorInfo, _players: &mut [Player]) -> bool {
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
 		let movement = Vec2::new(self.angle.cos(), self.angle.sin()) * 6.0;
 
 		self.pos += movement;
@@ -53,22 +62,20 @@ impl Attack for Stab {
 		let aabb = self.as_polygon();
 
 		// Check to see if it's collided with a monster
-		if let Some(monster) = floor_info
+		if let Some(i) = floor_info
 			.monsters
-			.iter_mut()
-			.find(|m| aabb_collision(&aabb, &m.as_polygon(), Vec2::ZERO))
+			.iter()
+			.position(|m| m.targetable() && aabb_collision(&aabb, &m.as_polygon(), Vec2::ZERO))
 		{
-			// Damage is low bc of hitting enemies multiple times
-			const DAMAGE: u16 = 25;
-
-			let direction = get_angle(monster.pos(), self.pos);
+			let direction = get_angle(floor_info.monsters[i].pos(), self.pos);
 			let damage_info = DamageInfo {
-				damage: DAMAGE,
+				damage: DAMAGE * self.power,
+				damage_type: DamageType::Pierce,
 				direction,
 				player: self.player_index,
 			};
 
-			monster.take_damage(damage_info, &floor_info.floor);
+			hit_monster(i, damage_info, floor_info, players, events, rng);
 
 			return true;
 		}
@@ -79,6 +86,15 @@ impl Attack for Stab {
 	fn cooldown(&self) -> u16 { 50 }
 
 	fn mana_cost(&self) -> u16 { 0 }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.player_index,
+			damage: DAMAGE * self.power,
+			damage_type: DamageType::Pierce,
+			spent_on_hit: true,
+		})
+	}
 }
 
 impl AsPolygon for Stab {
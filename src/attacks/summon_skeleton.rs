@@ -0,0 +1,79 @@
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterObj, SkeletonArcher};
+use crate::player::Player;
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::Attack;
+
+const RADIUS: f32 = 12.0;
+/// How long the circle the skeleton rises from stays around, in frames
+const LIFETIME: u16 = 30;
+
+/// Raises a skeleton archer at the wizard's feet to fight for them for a while
+#[derive(Clone, Serialize)]
+pub struct SummonSkeleton {
+	pos: Vec2,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for SummonSkeleton {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, _angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, _players: &mut [Player],
+		_events: &mut Vec<GameEvent>, _rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			let mut skeleton = SkeletonArcher::new(self.pos).summoned_by(self.player_index);
+			skeleton.set_pos(self.pos - skeleton.size() * 0.5);
+
+			floor_info
+				.monsters
+				.push(MonsterObj::SkeletonArcher(skeleton).scaled(floor_info.floor.scaling()));
+		}
+
+		self.time += 1;
+
+		self.time >= LIFETIME
+	}
+
+	fn cooldown(&self) -> u16 { 120 }
+
+	fn mana_cost(&self) -> u16 { 4 }
+}
+
+impl AsPolygon for SummonSkeleton {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, Vec2::splat(RADIUS), 0.0) }
+}
+
+impl Drawable for SummonSkeleton {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(RADIUS) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(RADIUS * 2.0) }
+
+	fn draw(&self) {
+		let alpha = 1.0 - self.time as f32 / LIFETIME as f32;
+
+		draw_circle_lines(
+			self.pos.x,
+			self.pos.y,
+			RADIUS,
+			2.0,
+			Color::new(0.6, 0.8, 1.0, alpha),
+		);
+	}
+}
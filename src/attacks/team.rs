@@ -0,0 +1,166 @@
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment};
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo};
+use crate::math::{aabb_collision, get_angle, AsPolygon, Polygon};
+use crate::monsters::MonsterKind;
+use crate::on_hit::hit_monster;
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::AttackObj;
+
+/// Whose side an attack's on, which decides what it can hit and who gets the
+/// credit for it. Attacks can change sides, like when they're knocked back
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub enum Team {
+	/// Hurts monsters, on behalf of the player with this index
+	Player(usize),
+	/// Hurts players. None for traps
+	Monsters(Option<MonsterKind>),
+}
+
+/// How an attack hurts players other than whoever made it, when friendly fire's
+/// on
+#[derive(Copy, Clone, Debug)]
+pub struct FriendlyFire {
+	pub player: usize,
+	pub damage: u16,
+	pub damage_type: DamageType,
+	/// Projectiles are used up by the first player they hit
+	pub spent_on_hit: bool,
+}
+
+/// Hurts every other player the attack's touching, if it can hurt players at
+/// all. Returns whether the attack's used up
+pub fn hit_other_players(
+	attack: &AttackObj, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+) -> bool {
+	let Some(friendly_fire) = attack.friendly_fire() else {
+		return false;
+	};
+
+	let poly = attack.as_polygon();
+	let center = attack.center();
+	let mut hit = false;
+
+	players
+		.iter_mut()
+		.enumerate()
+		.filter(|(i, player)| {
+			*i != friendly_fire.player &&
+				player.hp() > 0 &&
+				aabb_collision(&poly, &player.as_polygon(), Vec2::ZERO)
+		})
+		.for_each(|(_, player)| {
+			let direction = get_angle(player.pos(), center);
+
+			damage_player(
+				player,
+				friendly_fire.damage,
+				friendly_fire.damage_type,
+				direction,
+				None,
+				floor,
+				events,
+			);
+
+			hit = true;
+		});
+
+	hit && friendly_fire.spent_on_hit
+}
+
+/// Something an attack's run into, by its index
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+	Monster(usize),
+	Player(usize),
+}
+
+impl Target {
+	pub fn as_polygon(&self, floor_info: &FloorInfo, players: &[Player]) -> Polygon {
+		match self {
+			Target::Monster(i) => floor_info.monsters[*i].as_polygon(),
+			Target::Player(i) => players[*i].as_polygon(),
+		}
+	}
+
+	pub fn apply_enchantment(
+		&self, enchantment: Enchantment, floor_info: &mut FloorInfo, players: &mut [Player],
+	) {
+		match self {
+			Target::Monster(i) => floor_info.monsters[*i].apply_enchantment(enchantment),
+			Target::Player(i) => players[*i].apply_enchantment(enchantment),
+		}
+	}
+}
+
+impl Team {
+	/// Which player it's on the side of, if any
+	pub fn player(&self) -> Option<usize> {
+		match self {
+			Team::Player(player) => Some(*player),
+			Team::Monsters(_) => None,
+		}
+	}
+
+	/// The first thing on the other side touching `aabb`
+	pub fn find_target<A: AsPolygon>(
+		&self, aabb: &A, floor_info: &FloorInfo, players: &[Player],
+	) -> Option<Target> {
+		match self {
+			Team::Player(_) => floor_info
+				.monsters
+				.iter()
+				.position(|m| m.targetable() && aabb_collision(aabb, &m.as_polygon(), Vec2::ZERO))
+				.map(Target::Monster),
+			Team::Monsters(_) => players
+				.iter()
+				.position(|p| aabb_collision(aabb, &p.as_polygon(), Vec2::ZERO))
+				.map(Target::Player),
+		}
+	}
+
+	/// Hurts the first thing on the other side touching `aabb`, knocking it
+	/// away from the attack, and returns what it was
+	pub fn hit_first<A: AsPolygon>(
+		&self, aabb: &A, damage: u16, damage_type: DamageType, floor_info: &mut FloorInfo,
+		players: &mut [Player], events: &mut Vec<GameEvent>, rng: &mut Rng,
+	) -> Option<Target> {
+		let target = self.find_target(aabb, floor_info, players)?;
+
+		match (*self, target) {
+			(Team::Player(player), Target::Monster(i)) => {
+				let damage_info = DamageInfo {
+					damage,
+					damage_type,
+					direction: get_angle(floor_info.monsters[i].pos(), aabb.center()),
+					player,
+				};
+
+				hit_monster(i, damage_info, floor_info, players, events, rng);
+			},
+			(Team::Monsters(shooter), Target::Player(i)) => {
+				let player = &mut players[i];
+				let direction = get_angle(player.pos(), aabb.center());
+
+				damage_player(
+					player,
+					damage,
+					damage_type,
+					direction,
+					shooter,
+					&floor_info.floor,
+					events,
+				);
+			},
+			// Nothing finds a target on its own side
+			_ => return None,
+		}
+
+		Some(target)
+	}
+}
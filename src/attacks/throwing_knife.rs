@@ -1,14 +1,47 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::animation::{Keyframe, Pose, Timeline};
 use crate::draw::{load_my_image, Drawable};
-use crate::items::{ItemInfo, ItemType};
+use crate::enchantments::{Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::ItemType;
 use crate::map::{pos_to_tile, Floor, FloorInfo};
-use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
-use crate::player::{DamageInfo, Player, PLAYER_SIZE};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::player::{DamageType, Player};
+use crate::rng::Rng;
 use macroquad::prelude::*;
 use serde::Serialize;
 
-use super::Attack;
+use super::{Attack, FriendlyFire, Team};
 
 const SIZE: Vec2 = Vec2::new(10.0, 20.0);
+const DAMAGE: u16 = 18;
+
+/// One full turn every 12 frames, about as fast as its hitbox spins
+static SPIN: Timeline = Timeline {
+	keyframes: &[
+		Keyframe {
+			frame: 0,
+			pose: Pose::IDENTITY,
+		},
+		Keyframe {
+			frame: 12,
+			pose: Pose {
+				rotation: TAU,
+				..Pose::IDENTITY
+			},
+		},
+	],
+	texture_frames: 1,
+	looping: true,
+};
+
+/// How far either side of straight ahead the outer knives of a fan fly
+pub const FAN_SPREAD: f32 = PI / 12.0;
+const VENOM: Enchantment = Enchantment {
+	kind: EnchantmentKind::Poisoned,
+	strength: 1,
+};
 
 #[derive(Clone, Serialize)]
 pub struct ThrownKnife {
@@ -16,7 +49,30 @@ pub struct ThrownKnife {
 	movement_angle: f32,
 	rotation_angle: f32,
 	time: u16,
-	player_index: usize,
+	team: Team,
+	/// How much harder than usual it hits, from being thrown from hiding
+	power: u16,
+	/// Coated in venom, poisoning whatever it hits
+	poisoned: bool,
+}
+
+impl ThrownKnife {
+	/// Sends the knife off this many radians away from where it was aimed
+	pub fn with_angle_offset(mut self, offset: f32) -> Self {
+		self.movement_angle += offset;
+		self.rotation_angle += offset;
+		self
+	}
+
+	pub fn with_power(mut self, power: u16) -> Self {
+		self.power = power;
+		self
+	}
+
+	pub fn with_poison(mut self, poisoned: bool) -> Self {
+		self.poisoned = poisoned;
+		self
+	}
 }
 
 impl Attack for ThrownKnife {
@@ -28,23 +84,23 @@ impl Attack for ThrownKnife {
 			movement_angle: angle,
 			rotation_angle: angle,
 			time: 0,
-			player_index: index.unwrap(),
-		}
-	}
-
-	fn side_effects(&self, player: &mut Player, floor: &Floor) {
-		let change = Vec2::new(self.movement_angle.cos(), self.movement_angle.sin()) * PLAYER_SIZE;
-
-		if !floor.collision(player, change) {
-			player.pos += change;
+			team: Team::Player(index.unwrap()),
+			power: 1,
+			poisoned: false,
 		}
 	}
 
-	fn update(&mut self, floor_info: &mut FloorInfo, _players: &mut [Player]) -> bool {
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
 		let movement = Vec2::new(self.movement_angle.cos(), self.movement_angle.sin()) * 8.0;
 		let mut should_drop = false;
 
-		if !floor_info.floor.collision(self, movement) {
+		if !floor_info
+			.floor
+			.projectile_hit(self, movement, self.team.player(), events)
+		{
 			self.pos += movement;
 			self.time += 1;
 		} else {
@@ -53,68 +109,31 @@ impl Attack for ThrownKnife {
 
 		self.rotation_angle += 0.5;
 
-		let poly = self.as_polygon();
-
-		// Check to see if it's collided with a monster
-		if let Some(monster) = floor_info
-			.monsters
-			.iter_mut()
-			.find(|m| aabb_collision(&poly, &m.as_polygon(), Vec2::ZERO))
-		{
-			const DAMAGE: u16 = 18;
-
-			let direction = get_angle(monster.pos(), self.pos);
-			let damage_info = DamageInfo {
-				damage: DAMAGE,
-				direction,
-				player: self.player_index,
-			};
-
-			monster.take_damage(damage_info, &floor_info.floor);
+		if let Some(target) = self.team.hit_first(
+			self,
+			DAMAGE * self.power,
+			DamageType::Pierce,
+			floor_info,
+			players,
+			events,
+			rng,
+		) {
+			if self.poisoned {
+				target.apply_enchantment(VENOM, floor_info, players);
+			}
 
 			should_drop = true;
 		}
 
 		if should_drop {
-			let should_break = rand::gen_range(0, 9) == 9;
+			// 1 in 10 chance of breaking
+			let should_break = rng.gen_range(0, 10) == 9;
 
 			// Don't drop anything if the item should break
 			if !should_break {
-				// Find the nearest tile that is open, or just break
-				let tile_pos = pos_to_tile(self);
-				let tile_pos_vec2 = tile_pos.as_vec2();
-
-				if let Some(item_pos) = [
-					IVec2::ZERO,
-					IVec2::new(-1, 0),
-					IVec2::new(0, -1),
-					IVec2::new(-1, -1),
-					IVec2::new(1, 0),
-					IVec2::new(0, 1),
-					IVec2::new(1, 1),
-					IVec2::new(-1, 1),
-					IVec2::new(1, -1),
-				]
-				.into_iter()
-				.map(|change| tile_pos + change)
-				.filter(
-					|tile_pos| match floor_info.floor.get_object_from_pos(*tile_pos) {
-						Some(object) => !object.is_collidable(),
-						None => false,
-					},
-				)
-				.reduce(|tile_pos1, tile_pos2| {
-					let distance1 = tile_pos1.as_vec2().distance_squared(tile_pos_vec2);
-					let distance2 = tile_pos2.as_vec2().distance_squared(tile_pos_vec2);
-
-					match distance1 < distance2 {
-						true => tile_pos1,
-						false => tile_pos2,
-					}
-				}) {
-					let item = ItemInfo::new(ItemType::ThrowingKnife, Some(item_pos));
-					floor_info.floor.add_item_to_object(item);
-				}
+				floor_info
+					.floor
+					.drop_item(pos_to_tile(self), ItemType::from_key("throwing_knife"));
 			}
 		}
 
@@ -125,6 +144,22 @@ impl Attack for ThrownKnife {
 
 	fn mana_cost(&self) -> u16 { 0 }
 
+	fn team(&self) -> Option<Team> { Some(self.team) }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.team.player()?,
+			damage: DAMAGE * self.power,
+			damage_type: DamageType::Pierce,
+			spent_on_hit: true,
+		})
+	}
+
+	fn turn_against(&mut self, team: Team) {
+		self.team = team;
+		self.movement_angle += PI;
+	}
+
 	fn as_polygon_optional(&self) -> Option<Polygon> { Some(self.as_polygon()) }
 }
 
@@ -140,7 +175,11 @@ impl Drawable for ThrownKnife {
 
 	fn size(&self) -> Vec2 { SIZE }
 
-	fn rotation(&self) -> f32 { self.rotation_angle }
+	// The spin comes from the animation, so it turns smoothly rather than
+	// jumping a step every frame
+	fn rotation(&self) -> f32 { self.movement_angle }
+
+	fn animation(&self) -> Option<(&'static Timeline, u16)> { Some((&SPIN, self.time)) }
 
 	fn texture(&self) -> Option<Texture2D> { Some(load_my_image("throwing_knife.webp")) }
 }
@@ -0,0 +1,168 @@
+use crate::draw::Drawable;
+use crate::enchantments::Enchantable;
+use crate::events::GameEvent;
+use crate::items::{ItemEffect, ItemInfo};
+use crate::map::{pos_to_tile, Floor, FloorInfo, TILE_SIZE};
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, FriendlyFire};
+
+const SIZE: Vec2 = Vec2::splat(16.0);
+// Weapons hurt a bit when they hit, anything else barely does
+const WEAPON_DAMAGE: u16 = 10;
+const ITEM_DAMAGE: u16 = 3;
+
+/// Any item a player throws. Potions shatter, enchanting every monster near
+/// where they land, and everything else drops on the floor
+#[derive(Clone, Serialize)]
+pub struct ThrownItem {
+	pos: Vec2,
+	movement_angle: f32,
+	rotation_angle: f32,
+	player_index: usize,
+	item: Option<ItemInfo>,
+}
+
+impl ThrownItem {
+	pub fn with_item(mut self, item: ItemInfo) -> Self {
+		self.item = Some(item);
+		self
+	}
+
+	fn effect(&self) -> Option<ItemEffect> {
+		self.item.as_ref()?.item_type.definition()?.on_use.clone()
+	}
+
+	fn damage(&self) -> u16 {
+		match self
+			.item
+			.as_ref()
+			.and_then(|item| item.item_type.definition())
+			.is_some_and(|item| item.attack.is_some())
+		{
+			true => WEAPON_DAMAGE,
+			false => ITEM_DAMAGE,
+		}
+	}
+
+	/// Shatters or drops the item wherever it landed
+	fn land(&self, floor_info: &mut FloorInfo) {
+		let Some(item) = &self.item else {
+			return;
+		};
+
+		match self.effect() {
+			Some(ItemEffect::Enchant(enchantment)) => {
+				let center = self.center();
+
+				floor_info
+					.monsters
+					.iter_mut()
+					.filter(|m| {
+						m.center().distance(center) <= TILE_SIZE as f32 &&
+							floor_info.floor.line_of_sight(center, m.center())
+					})
+					.for_each(|m| m.apply_enchantment(enchantment.clone()));
+			},
+			// Monsters don't have any mana to restore, so it's just wasted
+			Some(ItemEffect::RestoreMana(_)) => (),
+			None => floor_info
+				.floor
+				.drop_item(pos_to_tile(self), item.item_type),
+		}
+	}
+}
+
+impl Attack for ThrownItem {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center() - SIZE * 0.5,
+			movement_angle: angle,
+			rotation_angle: angle,
+			player_index: index.unwrap(),
+			item: None,
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		let movement = Vec2::new(self.movement_angle.cos(), self.movement_angle.sin()) * 7.0;
+		let mut landed = false;
+
+		match floor_info
+			.floor
+			.projectile_hit(self, movement, Some(self.player_index), events)
+		{
+			false => self.pos += movement,
+			true => landed = true,
+		};
+
+		self.rotation_angle += 0.3;
+
+		let poly = self.as_polygon();
+
+		if let Some(i) = floor_info
+			.monsters
+			.iter()
+			.position(|m| m.targetable() && aabb_collision(&poly, &m.as_polygon(), Vec2::ZERO))
+		{
+			let damage_info = DamageInfo {
+				damage: self.damage(),
+				damage_type: DamageType::Blunt,
+				direction: get_angle(floor_info.monsters[i].pos(), self.pos),
+				player: self.player_index,
+			};
+
+			hit_monster(i, damage_info, floor_info, players, events, rng);
+
+			landed = true;
+		}
+
+		if landed {
+			self.land(floor_info);
+		}
+
+		landed
+	}
+
+	fn cooldown(&self) -> u16 { 20 }
+
+	fn mana_cost(&self) -> u16 { 0 }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.player_index,
+			damage: self.damage(),
+			damage_type: DamageType::Blunt,
+			spent_on_hit: true,
+		})
+	}
+
+	fn as_polygon_optional(&self) -> Option<Polygon> { Some(self.as_polygon()) }
+}
+
+impl AsPolygon for ThrownItem {
+	fn as_polygon(&self) -> Polygon {
+		let half_size: Vec2 = SIZE * Vec2::splat(0.5);
+		easy_polygon(self.pos + half_size, half_size, self.rotation_angle)
+	}
+}
+
+impl Drawable for ThrownItem {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { SIZE }
+
+	fn rotation(&self) -> f32 { self.rotation_angle }
+
+	fn texture(&self) -> Option<Texture2D> { self.item.as_ref().and_then(|item| item.texture()) }
+}
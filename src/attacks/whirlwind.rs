@@ -0,0 +1,126 @@
+use std::f32::consts::TAU;
+
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{Floor, FloorInfo, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::on_hit::hit_monster;
+use crate::player::{DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Attack, FriendlyFire};
+
+/// How far the spin reaches from the player's center
+const RADIUS: f32 = TILE_SIZE as f32 * 1.5;
+/// How long the spin lasts, in frames
+const SPIN_TIME: u16 = 30;
+/// Frames between each time the spin hurts everything around it
+const HIT_INTERVAL: u16 = 10;
+const DAMAGE: u16 = 6;
+
+/// Spins the warrior around on the spot, cutting into every monster close by a
+/// few times over. They can't move or attack until it's done
+#[derive(Clone, Serialize)]
+pub struct Whirlwind {
+	/// The center of the spinning player
+	pos: Vec2,
+	angle: f32,
+	time: u16,
+	player_index: usize,
+}
+
+impl Attack for Whirlwind {
+	fn new(
+		aabb: &dyn AsPolygon, index: Option<usize>, angle: f32, _floor: &Floor, _is_primary: bool,
+	) -> Self {
+		Self {
+			pos: aabb.center(),
+			angle,
+			time: 0,
+			player_index: index.unwrap(),
+		}
+	}
+
+	fn update(
+		&mut self, floor_info: &mut FloorInfo, players: &mut [Player], events: &mut Vec<GameEvent>,
+		rng: &mut Rng,
+	) -> bool {
+		if self.time == 0 {
+			players[self.player_index].rooted = SPIN_TIME;
+		}
+
+		self.pos = players[self.player_index].center();
+
+		if self.time % HIT_INTERVAL == 0 {
+			let hit: Vec<usize> = floor_info
+				.monsters
+				.iter()
+				.enumerate()
+				.filter(|(_, m)| m.targetable() && m.center().distance(self.pos) <= RADIUS)
+				.map(|(i, _)| i)
+				.collect();
+
+			hit.into_iter().for_each(|i| {
+				let damage_info = DamageInfo {
+					damage: DAMAGE,
+					damage_type: DamageType::Slash,
+					direction: get_angle(floor_info.monsters[i].center(), self.pos),
+					player: self.player_index,
+				};
+
+				hit_monster(i, damage_info, floor_info, players, events, rng);
+			});
+		}
+
+		self.angle += TAU / HIT_INTERVAL as f32;
+		self.time += 1;
+
+		self.time >= SPIN_TIME || players[self.player_index].hp() == 0
+	}
+
+	fn cooldown(&self) -> u16 { 120 }
+
+	fn mana_cost(&self) -> u16 { 2 }
+
+	fn friendly_fire(&self) -> Option<FriendlyFire> {
+		Some(FriendlyFire {
+			player: self.player_index,
+			damage: DAMAGE,
+			damage_type: DamageType::Slash,
+			spent_on_hit: false,
+		})
+	}
+}
+
+impl AsPolygon for Whirlwind {
+	fn as_polygon(&self) -> Polygon { easy_polygon(self.pos, Vec2::splat(RADIUS), 0.0) }
+}
+
+impl Drawable for Whirlwind {
+	fn pos(&self) -> Vec2 { self.pos - Vec2::splat(RADIUS) }
+
+	fn size(&self) -> Vec2 { Vec2::splat(RADIUS * 2.0) }
+
+	fn draw(&self) {
+		let alpha = 1.0 - self.time as f32 / SPIN_TIME as f32 * 0.5;
+		let blade = self.pos + Vec2::new(self.angle.cos(), self.angle.sin()) * RADIUS;
+
+		draw_circle_lines(
+			self.pos.x,
+			self.pos.y,
+			RADIUS,
+			1.0,
+			Color::new(0.8, 0.8, 0.9, alpha * 0.5),
+		);
+		draw_line(
+			self.pos.x,
+			self.pos.y,
+			blade.x,
+			blade.y,
+			3.0,
+			Color::new(0.8, 0.8, 0.9, alpha),
+		);
+	}
+}
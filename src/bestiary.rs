@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use egui::{FontId, RichText};
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::localization::{tr, tr_args};
+use crate::monsters::{MonsterKind, MONSTERS};
+
+/// What's been learned about a kind of monster, over every run
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BestiaryEntry {
+	pub kills: u32,
+	/// How much damage players have taken from them
+	pub damage_taken: u32,
+}
+
+/// Every monster that's been killed. Only kept locally, like the rest of the
+/// progression
+#[derive(Default, Serialize, Deserialize)]
+pub struct Bestiary {
+	entries: HashMap<MonsterKind, BestiaryEntry>,
+}
+
+impl Bestiary {
+	/// Returns whether the event changed anything
+	pub fn record(&mut self, event: &GameEvent) -> bool {
+		match event {
+			GameEvent::MonsterDied { kind, .. } => {
+				self.entries.entry(*kind).or_default().kills += 1;
+			},
+			GameEvent::DamageDealt {
+				damage,
+				to_player: true,
+				from: Some(kind),
+				..
+			} => {
+				self.entries.entry(*kind).or_default().damage_taken += *damage as u32;
+			},
+			_ => return false,
+		};
+
+		true
+	}
+
+	pub fn entry(&self, kind: MonsterKind) -> Option<&BestiaryEntry> { self.entries.get(&kind) }
+
+	/// Lists every monster. Ones that haven't been killed yet are hidden, and
+	/// lore only shows up after enough kills
+	pub fn draw(&self, ui: &mut egui::Ui) {
+		MONSTERS.iter().for_each(|monster| {
+			let entry = self.entry(monster.kind).filter(|entry| entry.kills > 0);
+
+			let Some(entry) = entry else {
+				ui.label(
+					RichText::new(tr("bestiary-unknown"))
+						.strong()
+						.font(FontId::proportional(25.0)),
+				);
+				ui.add_space(15.0);
+				return;
+			};
+
+			ui.label(
+				RichText::new(monster.kind.name(1))
+					.strong()
+					.font(FontId::proportional(25.0)),
+			);
+			ui.label(tr_args("bestiary-kills", [("kills", entry.kills.into())]));
+			ui.label(tr_args(
+				"bestiary-damage-taken",
+				[("damage", entry.damage_taken.into())],
+			));
+
+			let resistances = match monster.resistances.is_empty() {
+				true => tr("bestiary-no-resistances"),
+				false => monster.resistances.describe().join(", "),
+			};
+			ui.label(tr_args(
+				"bestiary-resistances",
+				[("resistances", resistances.into())],
+			));
+
+			match entry.kills >= monster.kills_for_lore {
+				true => ui.label(RichText::new(monster.lore()).italics()),
+				false => ui.label(tr_args(
+					"bestiary-lore-locked",
+					[("kills", (monster.kills_for_lore - entry.kills).into())],
+				)),
+			};
+
+			ui.add_space(15.0);
+		});
+	}
+}
@@ -0,0 +1,251 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{env, process, thread};
+
+use ggrs::{GGRSError, GGRSRequest, SessionBuilder, SessionState, UdpNonBlockingSocket};
+
+use roguelite::difficulty::Difficulty;
+use roguelite::init_game::{GameMode, GameState, LobbyPlayer};
+use roguelite::input::PlayerInput;
+use roguelite::logging::init_logging;
+use roguelite::net::{advance_frame, GGRSConfig};
+use roguelite::player::PlayerClass;
+use roguelite::{FPS, SEED};
+
+const USAGE: &str = "Runs the game's simulation without a window
+
+Usage:
+	headless soak [--seed N] [--frames N] [--players N] [--class CLASS]
+		Simulates a game with players wandering around randomly, printing a
+		checksum of the game state every second. Two runs with the same
+		arguments should always print the same checksums
+	headless synctest [--seed N] [--frames N] [--players N] [--class CLASS] [--check-distance N]
+		Like soak, but rolls back and simulates the last few frames again every
+		frame, exiting with an error as soon as they come out differently.
+		Anything nondeterministic in the simulation will desync real games
+	headless spectate HOST_ADDRESS [--seed N] [--port N] [--players N] [--class CLASS]
+		Spectates a running game, logging a checksum every second. The host
+		needs spectator_port set in its config, and the seed has to be the
+		one the host picked";
+
+// How often to print the game state's checksum, in frames
+const CHECKSUM_INTERVAL: u64 = FPS as u64;
+
+fn main() {
+	init_logging("headless.log");
+
+	let args: Vec<String> = env::args().skip(1).collect();
+
+	match args.first().map(String::as_str) {
+		Some("soak") => soak(&args[1..]),
+		Some("synctest") => synctest(&args[1..]),
+		Some("spectate") => spectate(&args[1..]),
+		_ => println!("{USAGE}"),
+	}
+}
+
+/// Returns the value after `--name`, or the default if it's missing or invalid
+fn flag<T: FromStr>(args: &[String], name: &str, default: T) -> T {
+	args.iter()
+		.position(|arg| arg == name)
+		.and_then(|i| args.get(i + 1))
+		.and_then(|value| value.parse().ok())
+		.unwrap_or(default)
+}
+
+fn class_flag(args: &[String]) -> PlayerClass {
+	let class: String = flag(args, "--class", "Warrior".to_string());
+	PlayerClass::try_from(class.as_str()).unwrap_or(PlayerClass::Warrior)
+}
+
+// Player inputs get their own RNG, so that they don't change the sequence the
+// game itself sees
+fn next_random(state: &mut u64) -> u64 {
+	*state ^= *state << 13;
+	*state ^= *state >> 7;
+	*state ^= *state << 17;
+	*state
+}
+
+fn random_input(rng: &mut u64) -> PlayerInput {
+	let angle = (next_random(rng) % 360) as f32;
+	let mut input = PlayerInput::new(angle.to_radians(), angle.to_radians());
+
+	input.set_moving();
+
+	if next_random(rng).is_multiple_of(4) {
+		input.set_primary_attacking();
+	}
+
+	input
+}
+
+fn soak(args: &[String]) {
+	let seed = flag(args, "--seed", SEED);
+	let frames: u64 = flag(args, "--frames", 60 * 60 * 5);
+	let num_players = flag(args, "--players", 1);
+
+	let mut game_state = GameState::new(
+		class_flag(args),
+		num_players,
+		GameMode::Dungeon,
+		seed,
+		Difficulty::default(),
+	);
+	let mut inputs = vec![PlayerInput::default(); num_players];
+	let mut input_rng = seed | 1;
+
+	let start = Instant::now();
+
+	(0..frames).for_each(|_| {
+		// Every second, each player picks a new direction to wander in
+		if game_state.frame.is_multiple_of(FPS as u64) {
+			inputs
+				.iter_mut()
+				.for_each(|input| *input = random_input(&mut input_rng));
+		}
+
+		advance_frame(&mut game_state, &inputs);
+
+		if game_state.frame.is_multiple_of(CHECKSUM_INTERVAL) {
+			println!("frame {}: {:04x}", game_state.frame, game_state.checksum());
+		}
+	});
+
+	let elapsed = start.elapsed();
+
+	println!(
+		"Simulated {frames} frames in {elapsed:.2?} ({:.0} frames/s)",
+		frames as f64 / elapsed.as_secs_f64()
+	);
+	println!(
+		"Monsters left: {}, player HP: {:?}, final checksum: {:04x}",
+		game_state.map.current_floor().monsters.len(),
+		game_state
+			.players
+			.iter()
+			.map(|p| p.hp())
+			.collect::<Vec<_>>(),
+		game_state.checksum()
+	);
+}
+
+fn synctest(args: &[String]) {
+	let seed = flag(args, "--seed", SEED);
+	let frames: u64 = flag(args, "--frames", 60 * 60);
+	let num_players = flag(args, "--players", 1);
+	// Has to be less than the prediction window
+	let check_distance = flag(args, "--check-distance", 7);
+
+	let mut game_state = GameState::new(
+		class_flag(args),
+		num_players,
+		GameMode::Dungeon,
+		seed,
+		Difficulty::default(),
+	);
+	let mut inputs = vec![PlayerInput::default(); num_players];
+	let mut input_rng = seed | 1;
+
+	let mut session = SessionBuilder::<GGRSConfig>::new()
+		.with_num_players(num_players)
+		.with_check_distance(check_distance)
+		.start_synctest_session()
+		.unwrap();
+
+	(0..frames).for_each(|frame| {
+		// Not the game state's frame, since that gets rolled back
+		if frame.is_multiple_of(FPS as u64) {
+			inputs
+				.iter_mut()
+				.for_each(|input| *input = random_input(&mut input_rng));
+		}
+
+		inputs.iter().enumerate().for_each(|(handle, input)| {
+			session.add_local_input(handle, *input).unwrap();
+		});
+
+		match session.advance_frame() {
+			Ok(requests) => requests.into_iter().for_each(|request| match request {
+				GGRSRequest::SaveGameState { cell, frame } => cell.save(
+					frame,
+					Some(game_state.clone()),
+					Some(game_state.checksum() as u128),
+				),
+				GGRSRequest::LoadGameState { cell, .. } => game_state = cell.load().unwrap(),
+				GGRSRequest::AdvanceFrame { inputs } => {
+					let inputs: Vec<PlayerInput> =
+						inputs.iter().map(|(input, _status)| *input).collect();
+
+					advance_frame(&mut game_state, &inputs);
+				},
+			}),
+			Err(GGRSError::MismatchedChecksum { frame }) => {
+				println!("Frame {frame} came out differently after rolling back");
+				process::exit(1);
+			},
+			Err(e) => log::warn!("{e:?}"),
+		}
+	});
+
+	println!(
+		"Simulated {frames} frames, rolling back {check_distance} each time, without desyncing. \
+		 Final checksum: {:04x}",
+		game_state.checksum()
+	);
+}
+
+fn spectate(args: &[String]) {
+	let Some(host) = args.first().and_then(|arg| arg.parse::<SocketAddr>().ok()) else {
+		println!("{USAGE}");
+		return;
+	};
+
+	let seed = flag(args, "--seed", SEED);
+	let port = flag(args, "--port", 3333);
+	let num_players = flag(args, "--players", 2);
+
+	// Has to start from exactly the same state as the host
+	let mut game_state = GameState::new(
+		class_flag(args),
+		num_players,
+		GameMode::Dungeon,
+		seed,
+		Difficulty::default(),
+	);
+	// The host waits in the lobby for everyone to be ready
+	game_state.lobby = Some(vec![LobbyPlayer::new(&game_state); num_players]);
+
+	let socket = UdpNonBlockingSocket::bind_to_port(port).unwrap();
+	let mut session = SessionBuilder::<GGRSConfig>::new()
+		.with_num_players(num_players)
+		.start_spectator_session(host, socket);
+
+	let frame_time = Duration::from_secs_f64(1.0 / FPS);
+
+	loop {
+		session.poll_remote_clients();
+		session.events().for_each(|ev| log::info!("{ev:?}"));
+
+		if session.current_state() == SessionState::Running {
+			match session.advance_frame() {
+				Ok(requests) => requests.into_iter().for_each(|request| {
+					if let GGRSRequest::AdvanceFrame { inputs } = request {
+						let inputs: Vec<PlayerInput> =
+							inputs.iter().map(|(input, _status)| *input).collect();
+
+						advance_frame(&mut game_state, &inputs);
+
+						if game_state.frame.is_multiple_of(CHECKSUM_INTERVAL) {
+							log::info!("frame {}: {:04x}", game_state.frame, game_state.checksum());
+						}
+					}
+				}),
+				Err(e) => log::warn!("{e:?}"),
+			}
+		}
+
+		thread::sleep(frame_time);
+	}
+}
@@ -0,0 +1,148 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::items::ItemType;
+use crate::map::EffectType;
+use crate::monsters::MonsterKind;
+use crate::props::{Decoration, PropKind};
+
+/// What a floor looks like and what lives there. Deeper floors get nastier
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Biome {
+	Sewer,
+	Cave,
+	Crypt,
+	Library,
+}
+
+impl Biome {
+	pub fn for_floor(floor_num: usize) -> Self {
+		match floor_num {
+			0 | 1 => Biome::Sewer,
+			2 => Biome::Cave,
+			3 => Biome::Crypt,
+			_ => Biome::Library,
+		}
+	}
+
+	pub fn name_id(&self) -> &'static str {
+		match self {
+			Biome::Sewer => "biome-sewer",
+			Biome::Cave => "biome-cave",
+			Biome::Crypt => "biome-crypt",
+			Biome::Library => "biome-library",
+		}
+	}
+
+	/// What gets spawned in rooms, ambushes and traps
+	pub fn monster_kinds(&self) -> &'static [MonsterKind] {
+		match self {
+			Biome::Sewer => &[MonsterKind::SmallRat],
+			Biome::Cave => &[
+				MonsterKind::SmallRat,
+				MonsterKind::GreenSlime,
+				MonsterKind::Bat,
+				MonsterKind::GelatinousCube,
+				MonsterKind::Spider,
+			],
+			Biome::Crypt => &[
+				MonsterKind::GreenSlime,
+				MonsterKind::SkeletonArcher,
+				MonsterKind::Bat,
+				MonsterKind::Necromancer,
+				MonsterKind::GelatinousCube,
+				MonsterKind::Spider,
+			],
+			Biome::Library => &[
+				MonsterKind::SmallRat,
+				MonsterKind::GreenSlime,
+				MonsterKind::SkeletonArcher,
+				MonsterKind::Necromancer,
+			],
+		}
+	}
+
+	/// 1 in every this many floor tiles is trapped
+	pub fn trap_chance(&self) -> u32 {
+		match self {
+			Biome::Sewer => 250,
+			Biome::Cave => 150,
+			Biome::Crypt => 100,
+			Biome::Library => 200,
+		}
+	}
+
+	/// What can be found lying around
+	pub fn item_pool(&self) -> Vec<ItemType> {
+		match self {
+			Biome::Sewer => vec![
+				ItemType::Gold(20),
+				ItemType::from_key("potion_regeneration"),
+			],
+			Biome::Cave => vec![
+				ItemType::Gold(30),
+				ItemType::from_key("potion_regeneration"),
+				ItemType::from_key("throwing_knife"),
+				ItemType::from_key("arrow"),
+			],
+			Biome::Crypt => vec![
+				ItemType::Gold(50),
+				ItemType::from_key("throwing_knife"),
+				ItemType::from_key("arrow"),
+			],
+			Biome::Library => vec![
+				ItemType::Gold(40),
+				ItemType::from_key("potion_regeneration"),
+				ItemType::from_key("wizards_dagger"),
+			],
+		}
+	}
+
+	/// What some of the hallways and room corners are filled with
+	pub fn hazards(&self) -> &'static [EffectType] {
+		match self {
+			Biome::Sewer => &[EffectType::Water],
+			Biome::Cave => &[EffectType::Lava, EffectType::Water],
+			Biome::Crypt => &[EffectType::Spikes],
+			Biome::Library => &[EffectType::Spikes],
+		}
+	}
+
+	/// What's scattered around rooms. Each room picks one
+	pub fn decorations(&self) -> &'static [Decoration] {
+		match self {
+			Biome::Sewer => &[Decoration::Moss, Decoration::Bones],
+			Biome::Cave => &[Decoration::Crack, Decoration::Moss],
+			Biome::Crypt => &[Decoration::Bones, Decoration::Crack],
+			Biome::Library => &[Decoration::Crack],
+		}
+	}
+
+	/// What rooms are furnished with. Each room picks one
+	pub fn props(&self) -> &'static [PropKind] {
+		match self {
+			Biome::Sewer => &[PropKind::Crate],
+			Biome::Cave => &[PropKind::Pillar, PropKind::Crate],
+			Biome::Crypt => &[PropKind::Pillar],
+			Biome::Library => &[PropKind::Bookshelf, PropKind::Crate],
+		}
+	}
+
+	pub fn floor_tint(&self) -> Color {
+		match self {
+			Biome::Sewer => Color::new(0.75, 0.9, 0.75, 1.0),
+			Biome::Cave => Color::new(0.85, 0.7, 0.55, 1.0),
+			Biome::Crypt => Color::new(0.7, 0.75, 0.9, 1.0),
+			Biome::Library => Color::new(0.95, 0.85, 0.65, 1.0),
+		}
+	}
+
+	pub fn wall_tint(&self) -> Color {
+		match self {
+			Biome::Sewer => Color::new(0.1, 0.18, 0.1, 1.0),
+			Biome::Cave => Color::new(0.2, 0.13, 0.08, 1.0),
+			Biome::Crypt => Color::new(0.12, 0.12, 0.18, 1.0),
+			Biome::Library => Color::new(0.25, 0.15, 0.08, 1.0),
+		}
+	}
+}
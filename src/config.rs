@@ -1,39 +1,39 @@
-use std::io::Write;
-use std::path::Path;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+#[cfg(feature = "native")]
 use std::{fs, io};
 
+use macroquad::time::get_time;
 use ron::error::SpannedError;
 use serde::{Deserialize, Serialize};
 
-use crate::init_game::{init_players, GameInfo};
-use crate::net::{init_net, GGRSConfig};
+use crate::difficulty::Difficulty;
+use crate::init_game::{init_players, GameInfo, LobbyPlayer};
+use crate::localization::{self, DEFAULT_LANGUAGE};
+#[cfg(not(feature = "web"))]
+use crate::net::Chat;
+use crate::net::{init_net, DesyncDetector, GGRSConfig, NetError, PeerStatuses};
 use crate::player::PlayerClass;
-use crate::NET_SESSION;
+use crate::{MAX_PLAYERS, SEED};
 
-#[derive(Clone, Serialize, Deserialize)]
+const CONFIG_KEY: &str = ".game_config";
+
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigInfo {
 	player_config_info: PlayerConfigInfo,
 	net_config_info: GGRSConfig,
 }
 
-impl Default for ConfigInfo {
-	fn default() -> Self {
-		Self {
-			player_config_info: PlayerConfigInfo::default(),
-			net_config_info: GGRSConfig::default(),
-		}
-	}
-}
-
 #[derive(Debug)]
 pub enum ConfigError {
 	#[cfg(feature = "native")]
 	Io(io::Error),
+	/// There's nowhere to keep the config, or the browser wouldn't allow it
+	Storage,
 	DeRonErr(SpannedError),
 	SeRonErr(ron::Error),
 }
 
+#[cfg(feature = "native")]
 impl From<io::Error> for ConfigError {
 	fn from(value: io::Error) -> Self { ConfigError::Io(value) }
 }
@@ -47,11 +47,7 @@ impl From<ron::Error> for ConfigError {
 }
 
 impl ConfigInfo {
-	#[cfg(feature = "native")]
-	pub fn new(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
-		let config: String = fs::read_to_string(path)?;
-		Ok(ron::from_str(&config)?)
-	}
+	pub fn load() -> Result<Self, ConfigError> { Ok(ron::from_str(&read_storage(CONFIG_KEY)?)?) }
 
 	pub fn set_class(&mut self, class: PlayerClass) {
 		self.player_config_info.class = class;
@@ -60,6 +56,35 @@ impl ConfigInfo {
 
 	pub fn class(&self) -> PlayerClass { self.player_config_info.class }
 
+	pub fn language(&self) -> &str { &self.player_config_info.language }
+
+	pub fn set_language(&mut self, language: &str) {
+		localization::set_language(language);
+		self.player_config_info.language = language.to_string();
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn seed(&self) -> u64 { self.player_config_info.seed }
+
+	pub fn set_seed(&mut self, seed: u64) {
+		self.player_config_info.seed = seed;
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn difficulty(&self) -> Difficulty { self.player_config_info.difficulty }
+
+	pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+		self.player_config_info.difficulty = difficulty;
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn friendly_fire(&self) -> bool { self.player_config_info.friendly_fire }
+
+	pub fn set_friendly_fire(&mut self, friendly_fire: bool) {
+		self.player_config_info.friendly_fire = friendly_fire;
+		self.save_to_disk().unwrap();
+	}
+
 	pub fn local_port(&self) -> u16 { self.net_config_info.local_port }
 
 	pub fn multiplayer(&self) -> bool { self.net_config_info.multiplayer }
@@ -74,53 +99,187 @@ impl ConfigInfo {
 		self.save_to_disk().unwrap();
 	}
 
-	pub fn remote_port(&self) -> u16 { self.net_config_info.remote_port }
+	pub fn num_players(&self) -> usize { self.net_config_info.num_players() }
+
+	/// Adds or removes remote players, up to MAX_PLAYERS in total
+	pub fn set_num_players(&mut self, num_players: usize) {
+		let local_players = self.local_players();
+		let num_remotes = num_players.clamp(local_players + 1, MAX_PLAYERS) - local_players;
 
-	pub fn set_remote_port(&mut self, remote_port: u16) {
-		self.net_config_info.remote_port = remote_port;
+		self.net_config_info
+			.remote_addrs
+			.resize_with(num_remotes, String::new);
 		self.save_to_disk().unwrap();
 	}
 
-	pub fn set_config(&self, game_info: &mut GameInfo) {
-		game_info.accumulator = Duration::ZERO;
-		game_info.last_update = Instant::now();
+	pub fn local_players(&self) -> usize { self.net_config_info.local_players() }
+
+	/// Drops remote players if there'd be too many players in total otherwise
+	pub fn set_local_players(&mut self, local_players: usize) {
+		self.net_config_info.local_players = local_players;
+		self.net_config_info
+			.remote_addrs
+			.truncate(MAX_PLAYERS - self.local_players());
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn local_handle(&self) -> usize { self.net_config_info.local_player() }
+
+	pub fn set_local_handle(&mut self, local_handle: usize) {
+		self.net_config_info.local_handle = local_handle;
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn remote_addrs(&self) -> &[String] { &self.net_config_info.remote_addrs }
+
+	pub fn set_remote_addr(&mut self, i: usize, remote_addr: String) {
+		self.net_config_info.remote_addrs[i] = remote_addr;
+		self.save_to_disk().unwrap();
+	}
 
-		let num_players = match self.multiplayer() {
-			true => 2,
-			false => 1,
-		};
+	pub fn room_url(&self) -> &str { &self.net_config_info.room_url }
+
+	pub fn set_room_url(&mut self, room_url: String) {
+		self.net_config_info.room_url = room_url;
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn input_delay(&self) -> usize { self.net_config_info.input_delay }
+
+	pub fn set_input_delay(&mut self, input_delay: usize) {
+		self.net_config_info.input_delay = input_delay;
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn max_prediction(&self) -> usize { self.net_config_info.max_prediction }
+
+	pub fn set_max_prediction(&mut self, max_prediction: usize) {
+		self.net_config_info.max_prediction = max_prediction;
+		self.save_to_disk().unwrap();
+	}
+
+	pub fn sparse_saving(&self) -> bool { self.net_config_info.sparse_saving }
+
+	pub fn set_sparse_saving(&mut self, sparse_saving: bool) {
+		self.net_config_info.sparse_saving = sparse_saving;
+		self.save_to_disk().unwrap();
+	}
+
+	/// Sets up the players and starts the session. Fails if the session can't
+	/// be started, like when the other player's address is wrong
+	pub fn set_config(&self, game_info: &mut GameInfo) -> Result<(), NetError> {
+		game_info.accumulator = Duration::ZERO;
+		game_info.last_update = get_time();
 
 		game_info.game_state.players = init_players(
 			self.player_config_info.class,
 			&game_info.game_state.map,
-			num_players,
+			self.num_players(),
 		);
-		unsafe { NET_SESSION = Some(init_net(&game_info.config_info.net_config_info)) };
-	}
+		game_info.game_state.friendly_fire = self.player_config_info.friendly_fire;
+		game_info.local_players = self.net_config_info.local_handles().collect();
+		game_info.desync = DesyncDetector::default();
+		game_info.peers = PeerStatuses::default();
+		game_info.ready = false;
 
-	#[cfg(feature = "native")]
-	fn save_to_disk(&self) -> Result<(), ConfigError> {
-		let mut file = fs::File::create(".game_config").unwrap();
-		let serialized_config = ron::to_string(self)?;
+		// Singleplayer games don't need a lobby
+		let lobby_player = LobbyPlayer::new(&game_info.game_state);
+		game_info.game_state.lobby = self
+			.multiplayer()
+			.then(|| vec![lobby_player; self.num_players()]);
+
+		#[cfg(not(feature = "web"))]
+		{
+			game_info.chat = self
+				.multiplayer()
+				.then(|| Chat::new(&self.net_config_info))
+				.transpose()?;
+		}
 
-		file.write_all(serialized_config.as_bytes())?;
+		// Whatever was left of the last session has to be closed before its port
+		// can be reused
+		game_info.session = None;
+		game_info.lobby = Some(init_net(&self.net_config_info)?);
 
 		Ok(())
 	}
 
-	#[cfg(not(feature = "native"))]
-	fn save_to_disk(&self) -> Result<(), ConfigError> { Ok(()) }
+	fn save_to_disk(&self) -> Result<(), ConfigError> {
+		write_storage(CONFIG_KEY, &ron::to_string(self)?)
+	}
+}
+
+/// Reads whatever was saved under `key`, which is a file path on native and a
+/// local storage key in browsers
+#[cfg(feature = "native")]
+pub(crate) fn read_storage(key: &str) -> Result<String, ConfigError> {
+	Ok(fs::read_to_string(key)?)
 }
 
+#[cfg(feature = "native")]
+pub(crate) fn write_storage(key: &str, data: &str) -> Result<(), ConfigError> {
+	Ok(fs::write(key, data)?)
+}
+
+#[cfg(feature = "web")]
+fn local_storage() -> Result<web_sys::Storage, ConfigError> {
+	web_sys::window()
+		.and_then(|window| window.local_storage().ok().flatten())
+		.ok_or(ConfigError::Storage)
+}
+
+#[cfg(feature = "web")]
+pub(crate) fn read_storage(key: &str) -> Result<String, ConfigError> {
+	local_storage()?
+		.get_item(key)
+		.ok()
+		.flatten()
+		.ok_or(ConfigError::Storage)
+}
+
+#[cfg(feature = "web")]
+pub(crate) fn write_storage(key: &str, data: &str) -> Result<(), ConfigError> {
+	local_storage()?
+		.set_item(key, data)
+		.map_err(|_| ConfigError::Storage)
+}
+
+#[cfg(not(any(feature = "native", feature = "web")))]
+pub(crate) fn read_storage(_key: &str) -> Result<String, ConfigError> { Err(ConfigError::Storage) }
+
+#[cfg(not(any(feature = "native", feature = "web")))]
+pub(crate) fn write_storage(_key: &str, _data: &str) -> Result<(), ConfigError> { Ok(()) }
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PlayerConfigInfo {
 	pub class: PlayerClass,
+	#[serde(default = "default_language")]
+	pub language: String,
+	/// What the dungeon's generated from. Everyone in a multiplayer game needs
+	/// the same one
+	#[serde(default = "default_seed")]
+	pub seed: u64,
+	/// Everyone in a multiplayer game needs the same one of these too
+	#[serde(default)]
+	pub difficulty: Difficulty,
+	/// Whether players' attacks can hurt each other. Everyone needs the same
+	/// setting for this as well
+	#[serde(default)]
+	pub friendly_fire: bool,
 }
 
+fn default_language() -> String { DEFAULT_LANGUAGE.to_string() }
+
+fn default_seed() -> u64 { SEED }
+
 impl Default for PlayerConfigInfo {
 	fn default() -> Self {
 		Self {
 			class: PlayerClass::Warrior,
+			language: default_language(),
+			seed: default_seed(),
+			difficulty: Difficulty::default(),
+			friendly_fire: false,
 		}
 	}
 }
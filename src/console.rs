@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::init_game::GameState;
+use crate::items::{ItemId, ItemInfo, ItemType};
+use crate::map::TILE_SIZE;
+use crate::monsters::MonsterKind;
+#[cfg(feature = "scripting")]
+use crate::scripting;
+
+/// Every monster that can be spawned, in the order they're encoded in
+const SPAWNABLE: [MonsterKind; 14] = [
+	MonsterKind::SmallRat,
+	MonsterKind::GreenSlime,
+	MonsterKind::RatKing,
+	MonsterKind::SlimeQueen,
+	MonsterKind::RatNest,
+	MonsterKind::SlimePool,
+	MonsterKind::SkeletonArcher,
+	MonsterKind::Bat,
+	MonsterKind::Mimic,
+	MonsterKind::Necromancer,
+	MonsterKind::Zombie,
+	MonsterKind::GelatinousCube,
+	MonsterKind::Spider,
+	MonsterKind::Wolf,
+];
+
+/// A command typed into the debug console. They're sent along with the
+/// player's input, like everything else that changes the game state, so
+/// rollbacks and replays run them too
+#[derive(Clone)]
+pub enum ConsoleCommand {
+	Spawn(MonsterKind, u16),
+	Give(ItemId, u8),
+	Teleport(IVec2),
+	RevealMap,
+	SetHp(u16),
+	Descend,
+	Ascend,
+}
+
+impl ConsoleCommand {
+	fn parse(line: &str) -> Result<Self, String> {
+		let mut args = line.split_whitespace();
+		let command = args.next().ok_or("no command given")?;
+
+		let mut next_arg = |name: &str| args.next().ok_or(format!("missing {name}"));
+
+		Ok(match command {
+			"spawn" => {
+				let key = next_arg("monster")?;
+				let monster = SPAWNABLE
+					.into_iter()
+					.find(|monster| monster.key() == key)
+					.ok_or(format!("unknown monster: {key}"))?;
+				let amount = next_arg("amount").unwrap_or("1");
+
+				ConsoleCommand::Spawn(monster, amount.parse().map_err(|_| "bad amount")?)
+			},
+			"give" => {
+				let key = next_arg("item")?;
+				let item = ItemId::find(key).ok_or(format!("unknown item: {key}"))?;
+				let amount = next_arg("amount").unwrap_or("1");
+
+				ConsoleCommand::Give(item, amount.parse().map_err(|_| "bad amount")?)
+			},
+			"teleport" => {
+				let x = next_arg("x")?.parse().map_err(|_| "bad x")?;
+				let y = next_arg("y")?.parse().map_err(|_| "bad y")?;
+
+				ConsoleCommand::Teleport(IVec2::new(x, y))
+			},
+			"reveal" => ConsoleCommand::RevealMap,
+			"hp" => ConsoleCommand::SetHp(next_arg("hp")?.parse().map_err(|_| "bad hp")?),
+			"descend" => ConsoleCommand::Descend,
+			"ascend" => ConsoleCommand::Ascend,
+			_ => return Err(format!("unknown command: {command}")),
+		})
+	}
+
+	/// Packs the command into a player's input. The first number says which
+	/// command it is, starting from 1, and the rest are its arguments
+	pub fn encode(&self) -> [u32; 3] {
+		match self {
+			ConsoleCommand::Spawn(monster, amount) => {
+				let index = SPAWNABLE.iter().position(|kind| kind == monster).unwrap();
+
+				[1, index as u32, *amount as u32]
+			},
+			ConsoleCommand::Give(item, amount) => [2, item.raw(), *amount as u32],
+			ConsoleCommand::Teleport(tile_pos) => [3, tile_pos.x as u32, tile_pos.y as u32],
+			ConsoleCommand::RevealMap => [4, 0, 0],
+			ConsoleCommand::SetHp(hp) => [5, *hp as u32, 0],
+			ConsoleCommand::Descend => [6, 0, 0],
+			ConsoleCommand::Ascend => [7, 0, 0],
+		}
+	}
+
+	/// None if the input didn't have a command in it, or it's not one this
+	/// build knows about
+	pub fn decode(command: [u32; 3]) -> Option<Self> {
+		let [kind, a, b] = command;
+
+		Some(match kind {
+			1 => ConsoleCommand::Spawn(*SPAWNABLE.get(a as usize)?, b as u16),
+			2 => ConsoleCommand::Give(ItemId::from_raw(a)?, b as u8),
+			3 => ConsoleCommand::Teleport(IVec2::new(a as i32, b as i32)),
+			4 => ConsoleCommand::RevealMap,
+			5 => ConsoleCommand::SetHp(a as u16),
+			6 => ConsoleCommand::Descend,
+			7 => ConsoleCommand::Ascend,
+			_ => return None,
+		})
+	}
+
+	/// Runs the command for whichever player typed it
+	pub fn apply(self, game_state: &mut GameState, player_index: usize) {
+		let player = &mut game_state.players[player_index];
+
+		match self {
+			ConsoleCommand::Spawn(monster, amount) => {
+				let pos = player.pos();
+				let floor_info = game_state.map.current_floor_mut();
+				let scaling = floor_info.floor.scaling();
+
+				floor_info
+					.monsters
+					.extend((0..amount).map(|_| monster.new_monster(pos).scaled(scaling)));
+			},
+			ConsoleCommand::Give(item, amount) => (0..amount).for_each(|_| {
+				player.give_item(ItemInfo::new(ItemType::Item(item), None));
+			}),
+			ConsoleCommand::Teleport(tile_pos) => {
+				player.pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+			},
+			ConsoleCommand::RevealMap => game_state.map.current_floor_mut().floor.reveal(),
+			ConsoleCommand::SetHp(hp) => player.set_hp(hp),
+			ConsoleCommand::Descend => game_state.descend(),
+			ConsoleCommand::Ascend => game_state.ascend(),
+		}
+	}
+}
+
+#[derive(Default)]
+pub struct Console {
+	pub open: bool,
+	input: String,
+	history: Vec<String>,
+	/// Commands waiting to be sent with the next frame's input, one a frame
+	pub pending: VecDeque<ConsoleCommand>,
+}
+
+impl Console {
+	pub fn toggle(&mut self) { self.open = !self.open; }
+
+	/// Draws the console, and queues up whatever command gets entered into it.
+	/// Commands are refused in multiplayer, since builds without the console
+	/// wouldn't run them
+	pub fn update(&mut self, multiplayer: bool) {
+		if !self.open {
+			return;
+		}
+
+		let mut submitted = false;
+
+		egui_macroquad::ui(|egui_ctx| {
+			egui::Window::new("Console").show(egui_ctx, |ui| {
+				egui::ScrollArea::vertical()
+					.max_height(200.0)
+					.stick_to_bottom(true)
+					.show(ui, |ui| {
+						self.history.iter().for_each(|line| {
+							ui.label(line);
+						});
+					});
+
+				let response = ui.text_edit_singleline(&mut self.input);
+				submitted = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+				response.request_focus();
+			});
+		});
+
+		egui_macroquad::draw();
+
+		if !submitted || self.input.trim().is_empty() {
+			return;
+		}
+
+		let line = std::mem::take(&mut self.input);
+		self.history.push(format!("> {line}"));
+
+		match (multiplayer, line.trim()) {
+			(true, _) => self
+				.history
+				.push("Console commands only work in singleplayer".to_string()),
+			// Scripts aren't part of the game state, so they're reloaded
+			// straight away rather than on the next frame
+			#[cfg(feature = "scripting")]
+			(false, "reload") => {
+				scripting::reload_scripts();
+				self.history.push("Reloaded scripts".to_string());
+			},
+			(false, line) => match ConsoleCommand::parse(line) {
+				Ok(command) => self.pending.push_back(command),
+				Err(e) => self.history.push(e),
+			},
+		}
+	}
+}
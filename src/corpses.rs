@@ -0,0 +1,97 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::map::TILE_SIZE;
+use crate::monsters::MonsterKind;
+
+/// How long a corpse lies around before there's nothing left to raise, in
+/// frames
+const CORPSE_TIME: u16 = 1800;
+/// Only this many of the most recent deaths are kept track of on each floor
+const MAX_CORPSES: usize = 16;
+
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct Corpse {
+	pub kind: MonsterKind,
+	/// Where the monster's center was when it died
+	pub pos: Vec2,
+	time_left: u16,
+}
+
+impl Corpse {
+	pub fn tile_pos(&self) -> IVec2 { (self.pos / TILE_SIZE as f32).floor().as_ivec2() }
+
+	pub fn draw(&self) {
+		let fade = self.time_left as f32 / CORPSE_TIME as f32;
+		let color = Color::new(0.4, 0.05, 0.05, 0.3 + fade * 0.4);
+
+		draw_circle(self.pos.x, self.pos.y, 7.0, color);
+		draw_line(
+			self.pos.x - 4.0,
+			self.pos.y - 4.0,
+			self.pos.x + 4.0,
+			self.pos.y + 4.0,
+			2.0,
+			BEIGE,
+		);
+		draw_line(
+			self.pos.x + 4.0,
+			self.pos.y - 4.0,
+			self.pos.x - 4.0,
+			self.pos.y + 4.0,
+			2.0,
+			BEIGE,
+		);
+	}
+}
+
+/// Where monsters have recently died on a floor, so necromancers have
+/// something to raise
+#[derive(Clone, Default, Serialize)]
+pub struct Corpses {
+	/// Oldest first
+	corpses: Vec<Corpse>,
+}
+
+impl Corpses {
+	/// Bosses, spawners and the undead don't leave anything behind worth
+	/// raising
+	pub fn record(&mut self, kind: MonsterKind, pos: Vec2) {
+		if !kind.leaves_corpse() {
+			return;
+		}
+
+		if self.corpses.len() >= MAX_CORPSES {
+			self.corpses.remove(0);
+		}
+
+		self.corpses.push(Corpse {
+			kind,
+			pos,
+			time_left: CORPSE_TIME,
+		});
+	}
+
+	/// Rots every corpse a little more, until there's nothing left of it
+	pub fn update(&mut self) {
+		self.corpses.retain_mut(|corpse| {
+			corpse.time_left = corpse.time_left.saturating_sub(1);
+			corpse.time_left > 0
+		});
+	}
+
+	/// Takes the closest corpse within `range` pixels of `pos`, if there is one
+	pub fn take_nearest(&mut self, pos: Vec2, range: f32) -> Option<Corpse> {
+		let i = self
+			.corpses
+			.iter()
+			.enumerate()
+			.filter(|(_, corpse)| corpse.pos.distance(pos) <= range)
+			.min_by(|(_, c1), (_, c2)| c1.pos.distance(pos).total_cmp(&c2.pos.distance(pos)))
+			.map(|(i, _)| i)?;
+
+		Some(self.corpses.remove(i))
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &Corpse> { self.corpses.iter() }
+}
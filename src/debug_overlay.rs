@@ -0,0 +1,123 @@
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+
+use crate::attacks::AttackObj;
+use crate::init_game::GameState;
+use crate::logging::recent_logs;
+use crate::map::{pos_to_tile, Object, TILE_SIZE};
+use crate::math::{points_on_circumference, AsPolygon};
+use crate::monsters::MonsterObj;
+use crate::player::Player;
+
+// How far player visibility rays are cast, in tiles
+const VIEW_DISTANCE: i32 = 12;
+const RECENT_LOGS_SHOWN: usize = 8;
+
+/// Draws everything in world space: collision boxes, monster paths and
+/// targets, visibility rays, tile coordinates and attack hitboxes. Must be
+/// called with the game camera set
+pub fn draw_debug_world(
+	players: &[Player], monsters: &[MonsterObj], attacks: &[AttackObj], visible_objects: &[&Object],
+) {
+	let tile_size = TILE_SIZE as f32;
+
+	visible_objects.iter().for_each(|obj| {
+		let pos = obj.tile_pos().as_vec2() * tile_size;
+		let color = match obj.is_collidable() {
+			true => RED,
+			false => Color::new(1.0, 1.0, 0.0, 0.3),
+		};
+
+		draw_rectangle_lines(pos.x, pos.y, tile_size, tile_size, 1.0, color);
+		draw_text(
+			&format!("{},{}", obj.tile_pos().x, obj.tile_pos().y),
+			pos.x + 1.0,
+			pos.y + 8.0,
+			8.0,
+			DARKGRAY,
+		);
+	});
+
+	players.iter().for_each(|player| {
+		let center = player.center();
+		let center_tile = pos_to_tile(player);
+
+		points_on_circumference(center_tile, VIEW_DISTANCE)
+			.into_iter()
+			.for_each(|edge| {
+				let edge = edge.as_vec2() * tile_size + tile_size / 2.0;
+				draw_line(
+					center.x,
+					center.y,
+					edge.x,
+					edge.y,
+					0.5,
+					Color::new(1.0, 1.0, 1.0, 0.15),
+				);
+			});
+
+		player.as_polygon().draw_outline(GREEN);
+	});
+
+	monsters.iter().for_each(|monster| {
+		monster.as_polygon().draw_outline(ORANGE);
+
+		let mut last_point = monster.center();
+
+		monster.current_path().iter().for_each(|point| {
+			draw_line(last_point.x, last_point.y, point.x, point.y, 1.0, SKYBLUE);
+			last_point = *point;
+		});
+
+		if let Some(target) = monster.target(players) {
+			draw_circle_lines(target.x, target.y, 4.0, 1.0, MAGENTA);
+		}
+	});
+
+	attacks
+		.iter()
+		.for_each(|attack| attack.as_polygon().draw_outline(PINK));
+}
+
+/// Draws the frame time, entity counts and the latest logs in screen space
+pub fn draw_debug_stats(game_state: &GameState) {
+	let floor_info = game_state.map.current_floor();
+
+	let num_items: usize = floor_info
+		.floor
+		.objects()
+		.iter()
+		.map(|obj| obj.items().len())
+		.sum();
+
+	[
+		format!(
+			"Frame time: {:.2}ms ({} FPS)",
+			get_frame_time() * 1000.0,
+			get_fps()
+		),
+		format!("Frame: {}", game_state.frame),
+		format!("Players: {}", game_state.players.len()),
+		format!("Monsters: {}", floor_info.monsters.len()),
+		format!("Attacks: {}", game_state.attacks.len()),
+		format!("Items on floor: {num_items}"),
+	]
+	.iter()
+	.enumerate()
+	.for_each(|(i, line)| {
+		root_ui().label(
+			Vec2::new(10.0, screen_height() - 70.0 + i as f32 * 10.0),
+			line,
+		);
+	});
+
+	recent_logs(RECENT_LOGS_SHOWN)
+		.iter()
+		.enumerate()
+		.for_each(|(i, line)| {
+			root_ui().label(
+				Vec2::new(10.0, screen_height() - 160.0 + i as f32 * 10.0),
+				line,
+			);
+		});
+}
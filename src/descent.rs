@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+use crate::rng::Rng;
+
+/// 1 in every this many floors lets anyone straight down
+const OPEN_CHANCE: u32 = 2;
+
+const LOCKED_CONDITIONS: [DescentCondition; 3] = [
+	DescentCondition::SlayGuardian,
+	DescentCondition::Sigil,
+	DescentCondition::Gathered,
+];
+
+/// What has to happen before touching a floor's exit takes everyone down to
+/// the next one. Rolled when the floor's generated
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum DescentCondition {
+	/// Anyone touching the exit is enough
+	Open,
+	/// The exit stays locked until the floor's guardian or boss is dead
+	SlayGuardian,
+	/// The exit stays locked until someone picks up the floor's sigil
+	Sigil,
+	/// Every living player has to be standing on the exit at once
+	Gathered,
+}
+
+impl DescentCondition {
+	/// The first floor's always open
+	pub fn roll(floor_num: usize, rng: &mut Rng) -> Self {
+		if floor_num == 0 || rng.gen_range(0, OPEN_CHANCE) == 0 {
+			return DescentCondition::Open;
+		}
+
+		*rng.choose(&LOCKED_CONDITIONS).unwrap()
+	}
+
+	/// Whether the exit starts out locked
+	pub fn locks_exit(&self) -> bool {
+		matches!(
+			self,
+			DescentCondition::SlayGuardian | DescentCondition::Sigil
+		)
+	}
+
+	/// Tells players what they need to do, shown in the HUD
+	pub fn hint_id(&self) -> Option<&'static str> {
+		match self {
+			DescentCondition::Open => None,
+			DescentCondition::SlayGuardian => Some("descent-slay-guardian"),
+			DescentCondition::Sigil => Some("descent-sigil"),
+			DescentCondition::Gathered => Some("descent-gathered"),
+		}
+	}
+}
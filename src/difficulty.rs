@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// How much tougher monsters get on each floor down
+const HEALTH_PER_FLOOR: f32 = 0.15;
+const DAMAGE_PER_FLOOR: f32 = 0.1;
+/// and how many more of them there are
+const COUNT_PER_FLOOR: f32 = 0.1;
+
+/// Picked before a run starts, and scales everything about the monsters on
+/// top of how deep the players are
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+	Easy,
+	#[default]
+	Normal,
+	Hard,
+}
+
+impl Difficulty {
+	pub fn name_id(&self) -> &'static str {
+		match self {
+			Difficulty::Easy => "difficulty-easy",
+			Difficulty::Normal => "difficulty-normal",
+			Difficulty::Hard => "difficulty-hard",
+		}
+	}
+
+	/// Multiplies monsters' health and damage
+	fn strength(&self) -> f32 {
+		match self {
+			Difficulty::Easy => 0.75,
+			Difficulty::Normal => 1.0,
+			Difficulty::Hard => 1.5,
+		}
+	}
+
+	/// Multiplies how many monsters there are
+	fn count(&self) -> f32 {
+		match self {
+			Difficulty::Easy => 0.75,
+			Difficulty::Normal => 1.0,
+			Difficulty::Hard => 1.25,
+		}
+	}
+}
+
+/// How much stronger and more numerous a floor's monsters are than their base
+/// stats, worked out once when the floor's generated
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct MonsterScaling {
+	health: f32,
+	damage: f32,
+	count: f32,
+}
+
+impl Default for MonsterScaling {
+	fn default() -> Self {
+		Self {
+			health: 1.0,
+			damage: 1.0,
+			count: 1.0,
+		}
+	}
+}
+
+impl MonsterScaling {
+	pub fn new(floor_num: usize, difficulty: Difficulty) -> Self {
+		let floor_num = floor_num as f32;
+
+		Self {
+			health: (1.0 + HEALTH_PER_FLOOR * floor_num) * difficulty.strength(),
+			damage: (1.0 + DAMAGE_PER_FLOOR * floor_num) * difficulty.strength(),
+			count: (1.0 + COUNT_PER_FLOOR * floor_num) * difficulty.count(),
+		}
+	}
+
+	/// Never scales anything down to nothing, unless it was nothing already
+	pub fn health(&self, base: u16) -> u16 { scale(base, self.health) }
+
+	pub fn damage(&self, base: u16) -> u16 { scale(base, self.damage) }
+
+	pub fn count(&self, base: usize) -> usize { (base as f32 * self.count).round() as usize }
+}
+
+fn scale(base: u16, multiplier: f32) -> u16 {
+	match base {
+		0 => 0,
+		_ => ((base as f32 * multiplier).round() as u16).max(1),
+	}
+}
@@ -0,0 +1,167 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::map::{pos_to_tile, FloorInfo, TILE_SIZE};
+use crate::player::Player;
+use crate::rng::Rng;
+
+/// How much pressure each point of damage to a player adds
+const DAMAGE_PRESSURE: f32 = 1.0;
+/// and each kill, since a fight's still going on
+const KILL_PRESSURE: f32 = 3.0;
+/// How much of the pressure is left after each frame. Halves about every ten
+/// seconds
+const PRESSURE_DECAY: f32 = 0.9988;
+/// The director only sends anything in once things have calmed down to this
+const CALM_PRESSURE: f32 = 8.0;
+/// Frames between each time the director thinks about sending something in
+const SPAWN_RATE: u16 = 20 * 60;
+/// Nothing's sent in while there are already this many monsters on the floor
+const MAX_HOSTILES: usize = 24;
+/// or while any player's below this much of their health
+const MIN_HEALTH: f32 = 0.5;
+/// Reinforcements turn up in rooms at least this many tiles from every player
+const HIDDEN_DISTANCE: f32 = 12.0;
+
+/// Keeps an eye on how hard the players are being pushed on a floor, and sends
+/// more monsters in from rooms nobody can see once things have been quiet for
+/// a while. There's only so many it'll send per floor, so clearing one still
+/// means something
+#[derive(Clone, Debug, Serialize)]
+pub struct Director {
+	/// How much the players have been hurt and fighting lately
+	pressure: f32,
+	time_til_spawn: u16,
+	/// Monsters left to send in on this floor
+	spawns_left: u32,
+	group_size: u32,
+}
+
+impl Director {
+	pub fn new(floor_num: usize) -> Self {
+		let scale = floor_num as u32 + 1;
+
+		Self {
+			pressure: 0.0,
+			time_til_spawn: SPAWN_RATE,
+			spawns_left: 4 + scale * 2,
+			group_size: 1 + scale / 2,
+		}
+	}
+
+	/// Builds up pressure from everything that happened this frame, and lets
+	/// it drain away over time
+	fn track(&mut self, events: &[GameEvent]) {
+		self.pressure *= PRESSURE_DECAY;
+
+		events.iter().for_each(|event| match event {
+			GameEvent::DamageDealt {
+				damage,
+				to_player: true,
+				..
+			} => self.pressure += *damage as f32 * DAMAGE_PRESSURE,
+			GameEvent::MonsterDied { .. } => self.pressure += KILL_PRESSURE,
+			_ => (),
+		});
+	}
+
+	/// Whether the players have had long enough to catch their breath
+	fn ready(&self, players: &[Player], floor_info: &FloorInfo) -> bool {
+		let hostiles = floor_info.monsters.iter().filter(|m| m.hostile()).count();
+
+		let hurt = players.iter().any(|player| {
+			player.hp() > 0 && (player.hp() as f32) < player.max_hp() as f32 * MIN_HEALTH
+		});
+
+		self.time_til_spawn == 0 &&
+			self.spawns_left > 0 &&
+			self.pressure <= CALM_PRESSURE &&
+			hostiles < MAX_HOSTILES &&
+			!hurt
+	}
+}
+
+/// The index of a room far from, and out of sight of, every living player.
+/// Ambush rooms and rooms with NPCs in them are left alone
+fn hidden_room(players: &[Player], floor_info: &FloorInfo, rng: &mut Rng) -> Option<usize> {
+	let ambush_rooms: Vec<usize> = floor_info
+		.ambushes
+		.iter()
+		.map(|ambush| ambush.room())
+		.collect();
+
+	let hidden: Vec<usize> = floor_info
+		.rooms()
+		.iter()
+		.enumerate()
+		.filter(|(i, room)| !ambush_rooms.contains(i) && !room.has_npc(&floor_info.npcs))
+		.filter(|(_, room)| {
+			let center = (room.center() * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+			players
+				.iter()
+				.filter(|player| player.hp() > 0)
+				.all(|player| {
+					!room.inside_room(pos_to_tile(player)) &&
+						player.center().distance(center) >= HIDDEN_DISTANCE * TILE_SIZE as f32 &&
+						!floor_info.floor.line_of_sight(player.center(), center)
+				})
+		})
+		.map(|(i, _)| i)
+		.collect();
+
+	rng.choose(&hidden).copied()
+}
+
+/// Sends a group of monsters in from somewhere out of sight once things have
+/// been quiet for long enough. They set off after the closest player
+pub fn update_director(
+	players: &[Player], floor_info: &mut FloorInfo, events: &[GameEvent], rng: &mut Rng,
+) {
+	let Some(mut director) = floor_info.director.take() else {
+		return;
+	};
+
+	director.track(events);
+	director.time_til_spawn = director.time_til_spawn.saturating_sub(1);
+
+	if director.ready(players, floor_info) {
+		director.time_til_spawn = SPAWN_RATE;
+
+		if let Some(room) = hidden_room(players, floor_info, rng) {
+			let room_center = floor_info.rooms()[room].center();
+			let group_size = director.group_size.min(director.spawns_left);
+
+			let group: Vec<_> = (0..group_size)
+				.map(|_| {
+					let pos = floor_info.random_room_pos(Some(room_center), rng);
+					let mut monster = floor_info.new_monster(pos, rng);
+
+					let closest = players
+						.iter()
+						.enumerate()
+						.filter(|(_, player)| player.hp() > 0)
+						.min_by(|(_, p1), (_, p2)| {
+							let p1_distance = p1.center().distance(pos);
+							let p2_distance = p2.center().distance(pos);
+
+							p1_distance.total_cmp(&p2_distance)
+						});
+
+					if let Some((i, player)) = closest {
+						monster.alert(i, player.center());
+					}
+
+					monster
+				})
+				.collect();
+
+			director.spawns_left -= group_size;
+			floor_info.monsters.extend(group);
+		}
+	}
+
+	floor_info.director = Some(director);
+}
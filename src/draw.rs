@@ -5,6 +5,11 @@ use std::sync::{Arc, Mutex};
 use macroquad::miniquad::fs;
 use macroquad::prelude::*;
 
+use crate::animation::{Pose, Timeline};
+use crate::map::{FloorInfo, Object, MAP_HEIGHT_TILES, MAP_WIDTH_TILES, TILE_SIZE};
+use crate::math::AsPolygon;
+use crate::player::Player;
+
 include!(concat!(env!("OUT_DIR"), "/assets.rs"));
 
 pub type Textures = Lazy<HashMap<String, Texture2D>>;
@@ -26,6 +31,18 @@ pub static TEXTURES: Textures = Lazy::new(|| {
 		});
 	}
 
+	// Mods can add new textures or replace the game's own
+	#[cfg(feature = "native")]
+	crate::mods::mod_files("textures", "webp")
+		.into_iter()
+		.for_each(|(name, path)| match std::fs::read(&path) {
+			Ok(bytes) => {
+				let texture = Texture2D::from_file_with_format(&bytes, Some(ImageFormat::WebP));
+				textures.lock().unwrap().insert(name, texture);
+			},
+			Err(e) => log::warn!("{}: {e}", path.display()),
+		});
+
 	let textures = textures.lock().unwrap();
 
 	textures.clone()
@@ -60,22 +77,155 @@ pub trait Drawable {
 	fn rotation(&self) -> f32 { 0.0 }
 	fn texture(&self) -> Option<Texture2D> { None }
 	fn flip_x(&self) -> bool { true }
-	fn draw(&self) {
-		let size = self.size();
-		let pos = self.pos();
+	/// What it's animated by, and how many frames it's been playing for
+	fn animation(&self) -> Option<(&'static Timeline, u16)> { None }
+	fn draw(&self) { self.draw_tinted(WHITE) }
+	fn draw_tinted(&self, tint: Color) {
+		let (pose, texture_frames) = match self.animation() {
+			Some((timeline, time)) => (timeline.sample(time), timeline.texture_frames.max(1)),
+			None => (Pose::IDENTITY, 1),
+		};
+
+		let size = self.size() * pose.scale;
+		// Scaling happens around the center, not the top left
+		let pos = self.pos() + (self.size() - size) * 0.5 + pose.offset;
 
 		match self.texture() {
 			Some(texture) => {
+				let frame_width = texture.width() / texture_frames as f32;
+				let source = (texture_frames > 1).then(|| {
+					Rect::new(
+						frame_width * pose.texture_frame as f32,
+						0.0,
+						frame_width,
+						texture.height(),
+					)
+				});
+
 				let texture_params = DrawTextureParams {
-					rotation: self.rotation(),
+					rotation: self.rotation() + pose.rotation,
 					flip_x: self.flip_x(),
 					dest_size: Some(size),
+					source,
 					..Default::default()
 				};
 
-				draw_texture_ex(texture, pos.x, pos.y, WHITE, texture_params);
+				draw_texture_ex(texture, pos.x, pos.y, tint, texture_params);
 			},
 			None => draw_rectangle(pos.x, pos.y, size.x, size.y, RED),
 		};
 	}
 }
+
+/// How many pixels each tile takes up on the minimap
+const MINIMAP_SCALE: f32 = 3.0;
+
+/// An overview of the current floor, showing everything that's been seen so
+/// far. Tiles are only drawn again when they change, like when a door opens
+pub struct Minimap {
+	image: Image,
+	texture: Texture2D,
+	/// What each tile was last drawn as, or None if it hasn't been seen
+	colors: Vec<Option<Color>>,
+	floor_index: usize,
+}
+
+impl Minimap {
+	pub fn new() -> Self {
+		let image = Image::gen_image_color(MAP_WIDTH_TILES as u16, MAP_HEIGHT_TILES as u16, BLANK);
+		let texture = Texture2D::from_image(&image);
+		texture.set_filter(FilterMode::Nearest);
+
+		Self {
+			image,
+			texture,
+			colors: vec![None; MAP_WIDTH_TILES * MAP_HEIGHT_TILES],
+			floor_index: 0,
+		}
+	}
+
+	/// Draws any tiles that have been seen or have changed since the last
+	/// update
+	pub fn update(&mut self, floor_info: &FloorInfo, floor_index: usize) {
+		// Starts over on a new floor
+		if floor_index != self.floor_index {
+			self.floor_index = floor_index;
+			self.colors.fill(None);
+			self.image =
+				Image::gen_image_color(MAP_WIDTH_TILES as u16, MAP_HEIGHT_TILES as u16, BLANK);
+			self.texture.update(&self.image);
+		}
+
+		let exit_tile = floor_info.exit().map(|exit| exit.tile_pos());
+		let upstairs_tile = floor_info.upstairs().map(|upstairs| upstairs.tile_pos());
+		let mut changed = false;
+
+		floor_info.floor.objects().iter().for_each(|object| {
+			let tile_pos = object.tile_pos();
+			let i = tile_pos.x as usize + tile_pos.y as usize * MAP_WIDTH_TILES;
+			let color = minimap_color(object, exit_tile, upstairs_tile);
+
+			if self.colors[i] != color {
+				self.colors[i] = color;
+				self.image
+					.set_pixel(tile_pos.x as u32, tile_pos.y as u32, color.unwrap_or(BLANK));
+				changed = true;
+			}
+		});
+
+		if changed {
+			self.texture.update(&self.image);
+		}
+	}
+
+	/// How big the minimap is on screen
+	pub fn size() -> Vec2 {
+		Vec2::new(MAP_WIDTH_TILES as f32, MAP_HEIGHT_TILES as f32) * MINIMAP_SCALE
+	}
+
+	/// Draws the minimap with its top left corner at `pos`, in screen space,
+	/// with a dot for each player
+	pub fn draw(&self, pos: Vec2, players: &[Player]) {
+		let size = Self::size();
+
+		draw_rectangle(pos.x, pos.y, size.x, size.y, Color::new(0.0, 0.0, 0.0, 0.5));
+		draw_texture_ex(
+			self.texture,
+			pos.x,
+			pos.y,
+			WHITE,
+			DrawTextureParams {
+				dest_size: Some(size),
+				..Default::default()
+			},
+		);
+
+		players.iter().for_each(|player| {
+			let player_pos = pos + player.center() / TILE_SIZE as f32 * MINIMAP_SCALE;
+			draw_circle(player_pos.x, player_pos.y, MINIMAP_SCALE, RED);
+		});
+	}
+}
+
+fn minimap_color(
+	object: &Object, exit_tile: Option<IVec2>, upstairs_tile: Option<IVec2>,
+) -> Option<Color> {
+	if !object.has_been_seen() {
+		return None;
+	}
+
+	if exit_tile == Some(object.tile_pos()) {
+		return Some(GOLD);
+	}
+
+	if upstairs_tile == Some(object.tile_pos()) {
+		return Some(SKYBLUE);
+	}
+
+	Some(match (object.is_floor(), object.door()) {
+		(_, Some(door)) if door.is_open => BEIGE,
+		(_, Some(_)) => BROWN,
+		(true, None) => LIGHTGRAY,
+		(false, None) => DARKGRAY,
+	})
+}
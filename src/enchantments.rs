@@ -1,13 +1,42 @@
-use serde::Serialize;
+use std::collections::HashMap;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+use serde::{Deserialize, Serialize};
+
+use crate::localization::tr;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum EnchantmentKind {
 	Blinded,
 	Sticky,
 	Regenerating,
+	/// Can't move or attack
+	Stunned,
+	/// Takes damage over time
+	Burning,
+	/// Takes damage over time, slower than burning but for longer
+	Poisoned,
+	/// Moves and attacks at half speed. Being chilled again freezes
+	Chilled,
+	/// Frozen solid, so it can't move or attack
+	Frozen,
+}
+
+impl EnchantmentKind {
+	pub fn name(&self) -> String {
+		tr(match self {
+			EnchantmentKind::Blinded => "enchantment-blinded",
+			EnchantmentKind::Sticky => "enchantment-sticky",
+			EnchantmentKind::Regenerating => "enchantment-regenerating",
+			EnchantmentKind::Stunned => "enchantment-stunned",
+			EnchantmentKind::Burning => "enchantment-burning",
+			EnchantmentKind::Poisoned => "enchantment-poisoned",
+			EnchantmentKind::Chilled => "enchantment-chilled",
+			EnchantmentKind::Frozen => "enchantment-frozen",
+		})
+	}
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Enchantment {
 	pub kind: EnchantmentKind,
 	pub strength: u8,
@@ -17,3 +46,22 @@ pub trait Enchantable {
 	fn apply_enchantment(&mut self, enchantment: Enchantment);
 	fn update_enchantments(&mut self);
 }
+
+/// Ticks each enchantment in the same order on every peer, keeping the ones
+/// `tick` returns true for. HashMaps iterate in a different order in each
+/// process, which would let healing and damage land in a different order too
+pub fn retain_in_order<T>(
+	enchantments: &mut HashMap<EnchantmentKind, T>,
+	mut tick: impl FnMut(EnchantmentKind, &mut T) -> bool,
+) {
+	let mut kinds: Vec<EnchantmentKind> = enchantments.keys().copied().collect();
+	kinds.sort_unstable();
+
+	for kind in kinds {
+		if let Some(effect) = enchantments.get_mut(&kind) {
+			if !tick(kind, effect) {
+				enchantments.remove(&kind);
+			}
+		}
+	}
+}
@@ -0,0 +1,137 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::biome::Biome;
+use crate::floor_modifier::FloorModifier;
+use crate::items::ItemType;
+use crate::monsters::MonsterKind;
+use crate::shrines::ShrineOutcome;
+
+/// Something that happened during a frame of the simulation. Systems push these
+/// as they go, and anything reacting to them (quests, stats, floating text)
+/// reads them afterwards instead of being called from inside the simulation.
+/// Cleared at the start of every frame
+#[derive(Clone, Debug, Serialize)]
+pub enum GameEvent {
+	DamageDealt {
+		/// Where whatever got hurt is
+		pos: Vec2,
+		damage: u16,
+		to_player: bool,
+		/// The player who dealt the damage, if it was one
+		by: Option<usize>,
+		/// The monster who dealt the damage, if it was one
+		from: Option<MonsterKind>,
+	},
+	MonsterDied {
+		kind: MonsterKind,
+		pos: Vec2,
+	},
+	ItemPickedUp {
+		player: usize,
+		item: ItemType,
+	},
+	DoorOpened {
+		tile_pos: IVec2,
+	},
+	DoorBroken {
+		tile_pos: IVec2,
+	},
+	/// Something on the other side wants to get through
+	DoorScratched {
+		tile_pos: IVec2,
+	},
+	ChestOpened {
+		player: usize,
+		tile_pos: IVec2,
+	},
+	CrateSmashed {
+		player: usize,
+		tile_pos: IVec2,
+	},
+	/// A hidden passage into a secret room
+	SecretFound {
+		player: usize,
+		tile_pos: IVec2,
+	},
+	LevelUp {
+		player: usize,
+		level: u32,
+	},
+	/// Ran out of HP, and is waiting on a teammate to get them back up
+	PlayerDowned {
+		player: usize,
+	},
+	PlayerRevived {
+		player: usize,
+	},
+	/// Bled out before anyone could get to them
+	PlayerDied {
+		player: usize,
+	},
+	TrapTriggered {
+		player: usize,
+		tile_pos: IVec2,
+	},
+	ShrineUsed {
+		player: usize,
+		outcome: ShrineOutcome,
+	},
+	AmbushStarted {
+		/// The center of the room
+		pos: Vec2,
+	},
+	AmbushCleared {
+		pos: Vec2,
+	},
+	WaveStarted {
+		wave: u32,
+		/// The center of the arena
+		pos: Vec2,
+	},
+	WaveCleared {
+		wave: u32,
+		pos: Vec2,
+	},
+	/// A boss floor's boss died
+	ExitUnlocked {
+		pos: Vec2,
+	},
+	FloorReached {
+		floor: usize,
+		biome: Biome,
+		/// Where everyone spawned
+		pos: Vec2,
+	},
+	/// Reached a floor that has something special about it
+	FloorModified {
+		modifier: FloorModifier,
+		pos: Vec2,
+	},
+	/// Something loud enough for monsters up to `radius` tiles away to hear
+	Noise {
+		pos: Vec2,
+		radius: f32,
+	},
+}
+
+/// Events waiting to be shown to the player. Kept outside of the game state, so
+/// frames that are simulated again after a rollback don't show everything twice
+#[derive(Default)]
+pub struct EventQueue {
+	events: Vec<GameEvent>,
+	last_frame: u64,
+}
+
+impl EventQueue {
+	/// Queues up everything that happened on a frame, unless it's already been
+	/// queued
+	pub fn push_frame(&mut self, frame: u64, events: &[GameEvent]) {
+		if frame > self.last_frame {
+			self.last_frame = frame;
+			self.events.extend_from_slice(events);
+		}
+	}
+
+	pub fn drain(&mut self) -> impl Iterator<Item = GameEvent> + '_ { self.events.drain(..) }
+}
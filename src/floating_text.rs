@@ -0,0 +1,151 @@
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+use crate::items::ItemInfo;
+use crate::localization::{tr, tr_args};
+use crate::math::AsPolygon;
+use crate::noise::tile_center;
+use crate::player::Player;
+
+// How long text floats for, in seconds
+const LIFETIME: f32 = 0.8;
+// How far text floats up, in pixels per second
+const RISE_SPEED: f32 = 30.0;
+const FONT_SIZE: f32 = 14.0;
+
+struct FloatingText {
+	text: String,
+	pos: Vec2,
+	color: Color,
+	time_left: f32,
+}
+
+/// Damage numbers and other short lived text that floats up from where
+/// something happened. Purely cosmetic, so it's driven by the event queue
+#[derive(Default)]
+pub struct FloatingTexts {
+	texts: Vec<FloatingText>,
+}
+
+impl FloatingTexts {
+	pub fn handle_event(&mut self, event: &GameEvent, players: &[Player]) {
+		let (text, pos, color) = match event {
+			GameEvent::DamageDealt {
+				pos,
+				damage,
+				to_player,
+				..
+			} => {
+				let color = match to_player {
+					true => RED,
+					false => WHITE,
+				};
+
+				(damage.to_string(), *pos, color)
+			},
+			GameEvent::LevelUp { player, level } => (
+				tr_args("hud-level-up", [("level", (*level).into())]),
+				players[*player].center(),
+				GOLD,
+			),
+			GameEvent::PlayerDowned { player } => {
+				(tr("player-downed"), players[*player].center(), RED)
+			},
+			GameEvent::PlayerRevived { player } => {
+				(tr("player-revived"), players[*player].center(), GREEN)
+			},
+			GameEvent::PlayerDied { player } => {
+				(tr("player-died"), players[*player].center(), DARKGRAY)
+			},
+			GameEvent::ItemPickedUp { player, item } => (
+				ItemInfo::new(*item, None).to_string(),
+				players[*player].center(),
+				SKYBLUE,
+			),
+			GameEvent::ShrineUsed { player, outcome } => {
+				let color = match outcome.is_blessing() {
+					true => GOLD,
+					false => PURPLE,
+				};
+
+				(outcome.description(), players[*player].center(), color)
+			},
+			GameEvent::SecretFound { player, .. } => {
+				(tr("secret-found"), players[*player].center(), PURPLE)
+			},
+			// Heard, not seen, so it shows up even through the door
+			GameEvent::DoorScratched { tile_pos } => {
+				(tr("door-scratched"), tile_center(*tile_pos), LIGHTGRAY)
+			},
+			GameEvent::AmbushStarted { pos } => (tr("ambush-started"), *pos, RED),
+			GameEvent::AmbushCleared { pos } => (tr("ambush-cleared"), *pos, GOLD),
+			GameEvent::WaveStarted { wave, pos } => (
+				tr_args("arena-wave-started", [("wave", (*wave).into())]),
+				*pos,
+				RED,
+			),
+			GameEvent::WaveCleared { wave, pos } => (
+				tr_args("arena-wave-cleared", [("wave", (*wave).into())]),
+				*pos,
+				GOLD,
+			),
+			GameEvent::ExitUnlocked { pos } => (tr("exit-unlocked"), *pos, GOLD),
+			GameEvent::FloorReached { floor, biome, pos } => (
+				tr_args(
+					"floor-reached",
+					[
+						("floor", (*floor + 1).into()),
+						("biome", tr(biome.name_id()).into()),
+					],
+				),
+				*pos,
+				GOLD,
+			),
+			// Underneath the floor's name
+			GameEvent::FloorModified { modifier, pos } => (
+				tr(modifier.name_id()),
+				*pos + Vec2::new(0.0, FONT_SIZE),
+				ORANGE,
+			),
+			_ => return,
+		};
+
+		self.texts.push(FloatingText {
+			text,
+			pos,
+			color,
+			time_left: LIFETIME,
+		});
+	}
+
+	/// Floats every text up, fading it out as it goes
+	pub fn update(&mut self) {
+		let delta = get_frame_time();
+
+		self.texts.retain_mut(|text| {
+			text.time_left -= delta;
+			text.pos.y -= RISE_SPEED * delta;
+
+			text.time_left > 0.0
+		});
+	}
+
+	/// Drawn in world space, so once for every camera
+	pub fn draw(&self) {
+		self.texts.iter().for_each(|text| {
+			let size = measure_text(&text.text, None, FONT_SIZE as u16, 1.0);
+			let color = Color {
+				a: (text.time_left / LIFETIME).clamp(0.0, 1.0),
+				..text.color
+			};
+
+			draw_text(
+				&text.text,
+				text.pos.x - size.width / 2.0,
+				text.pos.y,
+				FONT_SIZE,
+				color,
+			);
+		});
+	}
+}
@@ -0,0 +1,67 @@
+use serde::Serialize;
+
+use crate::rng::Rng;
+
+/// 1 in every this many floors has a modifier
+const MODIFIER_CHANCE: u32 = 4;
+
+const MODIFIERS: [FloorModifier; 3] = [
+	FloorModifier::Darkness,
+	FloorModifier::Infestation,
+	FloorModifier::Treasure,
+];
+
+/// Something special about a floor, rolled when it's generated and announced
+/// when it's reached
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum FloorModifier {
+	/// Nobody can see as far, and it's darker
+	Darkness,
+	/// Twice as many monsters
+	Infestation,
+	/// Gold's left lying around every room
+	Treasure,
+}
+
+impl FloorModifier {
+	/// The first floor's always left alone
+	pub fn roll(floor_num: usize, rng: &mut Rng) -> Option<Self> {
+		if floor_num == 0 || rng.gen_range(0, MODIFIER_CHANCE) != 0 {
+			return None;
+		}
+
+		rng.choose(&MODIFIERS).copied()
+	}
+
+	pub fn name_id(&self) -> &'static str {
+		match self {
+			FloorModifier::Darkness => "floor-modifier-darkness",
+			FloorModifier::Infestation => "floor-modifier-infestation",
+			FloorModifier::Treasure => "floor-modifier-treasure",
+		}
+	}
+
+	/// How many tiles away players can see. None for the usual distance
+	pub fn vision_radius(&self) -> Option<i32> {
+		match self {
+			FloorModifier::Darkness => Some(6),
+			_ => None,
+		}
+	}
+
+	/// Multiplies how lit up everything is
+	pub fn light_level(&self) -> f32 {
+		match self {
+			FloorModifier::Darkness => 0.5,
+			_ => 1.0,
+		}
+	}
+
+	/// Multiplies how many monsters each room spawns
+	pub fn monster_multiplier(&self) -> usize {
+		match self {
+			FloorModifier::Infestation => 2,
+			_ => 1,
+		}
+	}
+}
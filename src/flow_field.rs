@@ -0,0 +1,128 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use macroquad::prelude::*;
+
+use crate::map::{pos_to_tile, Object, MAP_HEIGHT_TILES, MAP_WIDTH_TILES, TILE_SIZE};
+use crate::player::Player;
+
+/// Going through a closed door costs this many steps, so open ways round are
+/// preferred, but a player who's shut a door behind them can still be tracked
+const CLOSED_DOOR_COST: u16 = 4;
+
+const NEIGHBORS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// How far every tile on the floor is from the closest living player. It's
+/// worked out once whenever a player moves onto another tile, and any number
+/// of monsters can then find their way to a player by heading downhill
+#[derive(Clone, Default)]
+pub struct FlowField {
+	/// The player tiles the field was worked out from
+	sources: Vec<IVec2>,
+	/// u16::MAX where no player can be reached
+	distances: Vec<u16>,
+}
+
+impl FlowField {
+	/// Works the field out again if any of the players have moved tiles
+	pub fn update(&mut self, players: &[Player], objects: &[Object]) {
+		crate::profile_scope!("flow_field");
+
+		let sources = players
+			.iter()
+			.filter(|player| player.hp() > 0)
+			.map(|player| pos_to_tile(player));
+
+		if self.sources.iter().copied().eq(sources.clone()) && !self.distances.is_empty() {
+			return;
+		}
+
+		self.sources.clear();
+		self.sources.extend(sources);
+
+		self.distances.clear();
+		self.distances.resize(objects.len(), u16::MAX);
+
+		let mut queue = BinaryHeap::new();
+
+		self.sources.iter().for_each(|tile_pos| {
+			if let Some(i) = tile_index(*tile_pos) {
+				self.distances[i] = 0;
+				queue.push(Reverse((0, i)));
+			}
+		});
+
+		while let Some(Reverse((distance, i))) = queue.pop() {
+			if distance > self.distances[i] {
+				continue;
+			}
+
+			let tile_pos = objects[i].tile_pos();
+
+			NEIGHBORS.iter().for_each(|offset| {
+				let Some(j) = tile_index(tile_pos + *offset) else {
+					return;
+				};
+
+				let Some(cost) = step_cost(&objects[j]) else {
+					return;
+				};
+
+				let new_distance = distance.saturating_add(cost);
+
+				if new_distance < self.distances[j] {
+					self.distances[j] = new_distance;
+					queue.push(Reverse((new_distance, j)));
+				}
+			});
+		}
+	}
+
+	/// How many steps the tile is from the closest player, if one can be
+	/// reached
+	pub fn distance(&self, tile_pos: IVec2) -> Option<u16> {
+		tile_index(tile_pos)
+			.and_then(|i| self.distances.get(i).copied())
+			.filter(|distance| *distance != u16::MAX)
+	}
+
+	/// The neighboring tile that's closest to a player. None once there, or if
+	/// no player can be reached
+	pub fn downhill(&self, tile_pos: IVec2) -> Option<IVec2> {
+		let current = self.distance(tile_pos)?;
+
+		NEIGHBORS
+			.iter()
+			.map(|offset| tile_pos + *offset)
+			.filter_map(|neighbor| self.distance(neighbor).map(|distance| (neighbor, distance)))
+			.filter(|(_, distance)| *distance < current)
+			.min_by_key(|(_, distance)| *distance)
+			.map(|(neighbor, _)| neighbor)
+	}
+
+	/// The middle of a tile, in world coordinates
+	pub fn tile_center(tile_pos: IVec2) -> Vec2 {
+		(tile_pos.as_vec2() + Vec2::splat(0.5)) * Vec2::splat(TILE_SIZE as f32)
+	}
+}
+
+fn tile_index(tile_pos: IVec2) -> Option<usize> {
+	let in_bounds = tile_pos.x >= 0 &&
+		tile_pos.y >= 0 &&
+		tile_pos.x < MAP_WIDTH_TILES as i32 &&
+		tile_pos.y < MAP_HEIGHT_TILES as i32;
+
+	in_bounds.then(|| (tile_pos.x + tile_pos.y * MAP_WIDTH_TILES as i32) as usize)
+}
+
+/// What it costs to step onto the object, or None if it can't be walked through
+fn step_cost(object: &Object) -> Option<u16> {
+	if !object.is_collidable() {
+		return Some(1);
+	}
+
+	match object.door() {
+		Some(door) if !door.locked() => Some(CLOSED_DOOR_COST),
+		_ => None,
+	}
+}
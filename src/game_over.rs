@@ -0,0 +1,67 @@
+use macroquad::prelude::*;
+
+use crate::init_game::GameState;
+use crate::localization::tr;
+use crate::victory::{draw_centered_text, RunSummary};
+
+// How long the game over banner stays up for, in seconds
+const BANNER_TIME: f64 = 3.0;
+
+/// Everything shown once every player's died: a game over banner, then a
+/// summary of how far they got
+pub struct GameOverScreen {
+	showing_summary: bool,
+	started: f64,
+	summary: RunSummary,
+}
+
+impl GameOverScreen {
+	pub fn new(game_state: &GameState) -> Self {
+		Self {
+			showing_summary: false,
+			started: get_time(),
+			// The floor everyone died on doesn't count as cleared
+			summary: RunSummary::new(
+				game_state,
+				game_state.frame,
+				game_state.map.current_floor_index(),
+				Vec::new(),
+			),
+		}
+	}
+
+	/// Draws the banner or the summary, returning true once the player is done
+	/// looking at the summary
+	pub fn update(&mut self) -> bool {
+		clear_background(BLACK);
+
+		if self.showing_summary {
+			return self.summary.draw();
+		}
+
+		let elapsed = get_time() - self.started;
+		let alpha = (elapsed / BANNER_TIME * 2.0).min(1.0) as f32;
+
+		draw_centered_text(
+			&tr("game-over-title"),
+			screen_height() / 2.0,
+			80.0,
+			Color::new(0.8, 0.1, 0.1, alpha),
+		);
+		draw_centered_text(
+			&tr("game-over-subtitle"),
+			screen_height() / 2.0 + 50.0,
+			30.0,
+			Color::new(1.0, 1.0, 1.0, alpha),
+		);
+
+		let skipping =
+			get_last_key_pressed().is_some() || is_mouse_button_pressed(MouseButton::Left);
+
+		if elapsed > BANNER_TIME || skipping {
+			self.showing_summary = true;
+		}
+
+		false
+	}
+}
@@ -1,67 +1,308 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
+use ggrs::P2PSession;
 #[cfg(feature = "native")]
-use gilrs::Gilrs;
+use gilrs::{Button, EventType, Gamepad, Gilrs};
 use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
 use macroquad::prelude::*;
 use macroquad::ui::{root_ui, Skin};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::attacks::{Attack, AttackObj};
+use crate::arena::Arena;
+use crate::attacks::AttackObj;
 use crate::config::ConfigInfo;
+#[cfg(feature = "native")]
+use crate::console::Console;
+use crate::difficulty::Difficulty;
+use crate::draw::Minimap;
+use crate::events::{EventQueue, GameEvent};
+use crate::floating_text::FloatingTexts;
+use crate::game_over::GameOverScreen;
+#[cfg(not(feature = "web"))]
+use crate::net::Chat;
+use crate::net::{DesyncDetector, GGRSConfig, Lobby, PeerStatuses};
 
-use crate::map::Map;
-use crate::math::AsPolygon;
+use crate::localization;
+use crate::map::{Map, Scratch, VisibilityCache};
+use crate::math::{fletcher16, AsPolygon};
 
+use crate::photo_mode::PhotoMode;
 use crate::player::{Player, PlayerClass};
-use crate::{CAMERA_ZOOM, DEFAULT_FRAGMENT_SHADER, DEFAULT_VERTEX_SHADER, NUM_PLAYERS};
+use crate::progression::Progression;
+use crate::quests::Quest;
+use crate::replay::Replay;
+use crate::rng::Rng;
+use crate::telegraphs::Telegraphs;
+use crate::victory::VictoryScreen;
+use crate::{CAMERA_ZOOM, DEFAULT_FRAGMENT_SHADER, DEFAULT_VERTEX_SHADER};
 
 #[cfg(feature = "native")]
 pub struct GamepadInfo {
 	pub gilrs: Gilrs,
 	pub active_gamepad: Option<gilrs::GamepadId>,
+	/// Buttons that were pressed on the active gamepad since the last update
+	pub pressed: Vec<Button>,
+}
+
+#[cfg(feature = "native")]
+impl GamepadInfo {
+	/// The second local player uses whichever gamepad was touched last
+	pub fn update(&mut self) {
+		self.pressed.clear();
+
+		while let Some(gilrs::Event { id, event, .. }) = self.gilrs.next_event() {
+			if self.active_gamepad != Some(id) {
+				self.active_gamepad = Some(id);
+				self.pressed.clear();
+			}
+
+			if let EventType::ButtonPressed(button, _) = event {
+				self.pressed.push(button);
+			}
+		}
+	}
+
+	pub fn active_gamepad(&self) -> Option<Gamepad> {
+		self.active_gamepad.map(|id| self.gilrs.gamepad(id))
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+	/// Fight down through the floors to the rat king
+	Dungeon,
+	/// Survive as many waves as possible in a single arena
+	Arena,
+}
+
+impl GameMode {
+	pub fn name_id(&self) -> &'static str {
+		match self {
+			GameMode::Dungeon => "mode-dungeon",
+			GameMode::Arena => "mode-arena",
+		}
+	}
+}
+
+/// What a player's said in the lobby so far
+#[derive(Copy, Clone, Serialize)]
+pub struct LobbyPlayer {
+	pub ready: bool,
+	/// The seed their dungeon was generated from. Nobody counts as ready until
+	/// everyone's picked the same one, along with the same game mode,
+	/// difficulty and friendly fire setting
+	pub seed: u64,
+	pub mode: GameMode,
+	pub difficulty: Difficulty,
+	pub friendly_fire: bool,
+}
+
+impl LobbyPlayer {
+	/// Everyone's assumed to have picked the same run as `game_state` until
+	/// their inputs say otherwise
+	pub fn new(game_state: &GameState) -> Self {
+		Self {
+			ready: false,
+			seed: game_state.seed,
+			mode: game_state.mode,
+			difficulty: game_state.difficulty,
+			friendly_fire: game_state.friendly_fire,
+		}
+	}
 }
 
 #[derive(Clone, Serialize)]
 pub struct GameState {
+	pub mode: GameMode,
 	pub frame: u64,
 	pub players: Vec<Player>,
 	pub attacks: Vec<AttackObj>,
+	/// Warnings for the attacks monsters are winding up
+	pub telegraphs: Telegraphs,
 	pub map: Map,
+	pub quests: Vec<Quest>,
+	pub events: Vec<GameEvent>,
+	/// How many monsters have been killed this run
+	pub kills: u32,
+	/// Set once the rat king dies
+	pub won_on_frame: Option<u64>,
+	/// Only in the arena game mode
+	pub arena: Option<Arena>,
+	/// Where each player's at in the lobby, until they're all ready
+	pub lobby: Option<Vec<LobbyPlayer>>,
+	/// What the dungeon was generated from, so the same run can be shared
+	pub seed: u64,
+	pub difficulty: Difficulty,
+	/// Whether players' attacks hurt each other
+	pub friendly_fire: bool,
+	/// Everything random in the simulation comes from here, so it gets rolled
+	/// back along with the rest of the state
+	pub rng: Rng,
+}
+
+impl GameState {
+	pub fn new(
+		class: PlayerClass, num_players: usize, mode: GameMode, seed: u64, difficulty: Difficulty,
+	) -> Self {
+		let mut rng = Rng::new(seed);
+		let map = match mode {
+			GameMode::Dungeon => Map::new(difficulty, &mut rng),
+			GameMode::Arena => Map::arena(difficulty, &mut rng),
+		};
+
+		Self {
+			mode,
+			frame: 0,
+			players: init_players(class, &map, num_players),
+			attacks: Vec::new(),
+			telegraphs: Telegraphs::default(),
+			map,
+			quests: Vec::new(),
+			events: Vec::new(),
+			kills: 0,
+			won_on_frame: None,
+			arena: (mode == GameMode::Arena).then(Arena::new),
+			lobby: None,
+			seed,
+			difficulty,
+			friendly_fire: false,
+			rng,
+		}
+	}
+
+	/// Takes everyone down a floor. Whatever was still flying around gets left
+	/// behind, since it'd otherwise hit things on a floor it was never on
+	pub fn descend(&mut self) {
+		if !self.map.descend(&mut self.players) {
+			return;
+		}
+
+		self.floor_changed();
+	}
+
+	pub fn ascend(&mut self) {
+		if !self.map.ascend(&mut self.players) {
+			return;
+		}
+
+		self.floor_changed();
+	}
+
+	/// Attacks don't follow anyone between floors. Companions that were killed
+	/// do, good as new
+	fn floor_changed(&mut self) {
+		self.attacks.clear();
+		self.telegraphs.clear();
+		self.players
+			.iter_mut()
+			.for_each(|player| player.companion_lost = false);
+		self.events.push(GameEvent::FloorReached {
+			floor: self.map.current_floor_index(),
+			biome: self.map.current_floor().biome(),
+			pos: self.players[0].center(),
+		});
+
+		if let Some(modifier) = self.map.current_floor().modifier() {
+			self.events.push(GameEvent::FloorModified {
+				modifier,
+				pos: self.players[0].center(),
+			});
+		}
+	}
+
+	/// A checksum of the whole game state, for noticing when peers have
+	/// desynced. Every HashMap in the state is serialized in key order, or
+	/// this would be different between processes
+	pub fn checksum(&self) -> u16 { fletcher16(bincode::serialize(self).unwrap()) }
 }
 
 pub struct GameInfo {
 	pub accumulator: Duration,
-	pub last_update: Instant,
+	/// From macroquad's clock, since `Instant` doesn't work in browsers
+	pub last_update: f64,
 
 	pub frames_to_skip: u32,
 
 	pub game_state: GameState,
+	/// The handles of the players on this machine, who get a camera each. The
+	/// first one uses the mouse and keyboard
+	pub local_players: Vec<usize>,
 	pub cameras: Vec<Camera2D>,
 
 	#[cfg(feature = "native")]
 	pub gamepad_info: GamepadInfo,
 
+	#[cfg(feature = "native")]
+	pub console: Console,
+
+	/// Players waiting to connect before the session starts. Natively, the
+	/// session starts straight away
+	pub lobby: Option<Lobby>,
+	/// Once everyone's connected. A new one's started for every run
+	pub session: Option<P2PSession<GGRSConfig>>,
+
+	pub desync: DesyncDetector,
+	pub peers: PeerStatuses,
+	/// The run that's being played, so it can be watched again afterwards
+	pub recording: Option<Replay>,
+	/// Being watched instead of played
+	pub replay: Option<Replay>,
+	/// Only in multiplayer
+	#[cfg(not(feature = "web"))]
+	pub chat: Option<Chat>,
+	/// Whether the local player is ready to start, in the lobby
+	pub ready: bool,
+
+	/// What's happened in the simulation that hasn't been shown yet
+	pub event_queue: EventQueue,
+	pub floating_texts: FloatingTexts,
+
+	/// Reusable buffers for visibility queries while rendering
+	pub scratch: Scratch,
+	pub visibility: VisibilityCache,
+
+	pub show_quest_log: bool,
+	pub show_debug_overlay: bool,
+	pub show_minimap: bool,
+	pub minimap: Minimap,
+	#[cfg(feature = "profiling")]
+	pub show_profiler: bool,
+	pub photo_mode: Option<PhotoMode>,
+
 	pub viewport_screen_height: f32,
 	pub material: Material,
 	pub game_started: bool,
 	pub in_config: bool,
 	pub config_info: ConfigInfo,
+	/// Why the last session couldn't be started, shown on the main menu
+	pub net_error: Option<String>,
+
+	/// Kept between runs, and never sent to peers
+	pub progression: Progression,
+	/// Only shown once the rat king is dead
+	pub victory: Option<VictoryScreen>,
+	/// Only shown once every player's down
+	pub game_over: Option<GameOverScreen>,
 }
 
 pub fn init_players(class: PlayerClass, map: &Map, num_players: usize) -> Vec<Player> {
 	(0..num_players)
-		.into_iter()
-		.map(|_| Player::new(class, map.current_floor().current_spawn()))
+		.map(|i| Player::new(class, map.current_floor().spawn_pos(i)))
 		.collect()
 }
 
 pub fn init_game() -> GameInfo {
-	let attacks = Vec::new();
-	let map = Map::new();
+	let config_info = ConfigInfo::load().unwrap_or_default();
 
-	let players: Vec<_> = init_players(PlayerClass::Wizard, &map, 1);
+	let game_state = GameState::new(
+		PlayerClass::Wizard,
+		1,
+		GameMode::Dungeon,
+		config_info.seed(),
+		config_info.difficulty(),
+	);
+	let players = &game_state.players;
 
 	let viewport_screen_height = screen_height(); // * (1.0 / NUM_PLAYERS as f32);
 
@@ -125,29 +366,52 @@ pub fn init_game() -> GameInfo {
 
 	root_ui().push_skin(&skin);
 
-	let config_info = ConfigInfo::new("./.game_config").unwrap_or_default();
+	localization::set_language(config_info.language());
 
 	GameInfo {
 		accumulator: Duration::ZERO,
-		last_update: Instant::now(),
+		last_update: get_time(),
 		frames_to_skip: 0,
-		game_state: GameState {
-			frame: 0,
-			players,
-			attacks,
-			map,
-		},
+		game_state,
+		local_players: vec![0],
 		cameras,
 		#[cfg(feature = "native")]
 		gamepad_info: GamepadInfo {
 			active_gamepad,
 			gilrs,
+			pressed: Vec::new(),
 		},
+		#[cfg(feature = "native")]
+		console: Console::default(),
+		lobby: None,
+		session: None,
+		desync: DesyncDetector::default(),
+		peers: PeerStatuses::default(),
+		recording: None,
+		replay: None,
+		#[cfg(not(feature = "web"))]
+		chat: None,
+		ready: false,
 
+		event_queue: EventQueue::default(),
+		floating_texts: FloatingTexts::default(),
+		scratch: Scratch::default(),
+		visibility: VisibilityCache::default(),
+		show_quest_log: false,
+		show_debug_overlay: false,
+		show_minimap: true,
+		minimap: Minimap::new(),
+		#[cfg(feature = "profiling")]
+		show_profiler: false,
+		photo_mode: None,
 		viewport_screen_height,
 		material,
 		game_started: false,
 		in_config: false,
 		config_info,
+		net_error: None,
+		progression: Progression::load(),
+		victory: None,
+		game_over: None,
 	}
 }
@@ -1,12 +1,14 @@
-use crate::attacks::{Attack, AttackObj};
-
-use crate::map::FloorInfo;
+use crate::difficulty::Difficulty;
+use crate::init_game::GameMode;
+#[cfg(feature = "native")]
+use crate::init_game::GamepadInfo;
 use crate::math::{get_angle, AsPolygon};
-use crate::player::{move_player, player_attack, Player};
+use crate::player::{item_pos_from_index, Player, ITEM_INVENTORY_SIZE};
 use bytemuck::{Pod, Zeroable};
 #[cfg(feature = "native")]
-use gilrs::{Axis, Button, Gamepad};
+use gilrs::{Axis, Button};
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
 type FlagSize = u32;
 
@@ -15,29 +17,148 @@ const SECONDARY_ATTACK: FlagSize = 0b10;
 const MOVING: FlagSize = 0b100;
 const OPENING_DOOR: FlagSize = 0b1000;
 const CLOSING_DOOR: FlagSize = 0b10000;
+const INTERACTING: FlagSize = 0b100000;
+const CHARGING: FlagSize = 0b1000000;
+const THROWING: FlagSize = 0b10000000;
+/// Only used in the lobby
+const READY: FlagSize = 0b100000000;
+const SEARCHING: FlagSize = 0b1000000000;
+/// Holding down the primary attack to let it go harder
+const WINDING_UP: FlagSize = 0b10000000000;
+const DASHING: FlagSize = 0b100000000000;
+/// Holding down interact, which is how downed teammates are helped back up
+const REVIVING: FlagSize = 0b1000000000000;
+/// Which of the boons offered on leveling up was picked, plus one so that 0
+/// means none was. Takes up two bits
+const BOON_CHOICE_SHIFT: FlagSize = 13;
+const BOON_CHOICE: FlagSize = 0b11 << BOON_CHOICE_SHIFT;
+const TOGGLING_INVENTORY: FlagSize = 0b1000000000000000;
+/// Which item in the pack is being put on, plus one, the same as boon choices.
+/// Takes up seven bits
+const EQUIPPING_SHIFT: FlagSize = 16;
+const EQUIPPING: FlagSize = 0b1111111 << EQUIPPING_SHIFT;
+/// Only used in the lobby, along with the seed. Set when playing the arena
+const ARENA: FlagSize = 0b100000000000000000000000;
+/// Only used in the lobby too. Takes up two bits
+const DIFFICULTY_SHIFT: FlagSize = 24;
+const DIFFICULTY: FlagSize = 0b11 << DIFFICULTY_SHIFT;
+/// Also only used in the lobby
+const FRIENDLY_FIRE: FlagSize = 0b100000000000000000000000000;
+/// Everything that happens once per key press, rather than for as long as the
+/// key's held down
+const PRESSES: FlagSize = OPENING_DOOR |
+	CLOSING_DOOR |
+	INTERACTING |
+	CHARGING |
+	THROWING |
+	SEARCHING |
+	DASHING |
+	BOON_CHOICE |
+	TOGGLING_INVENTORY |
+	EQUIPPING;
+
+// Where the virtual joystick sits, from the bottom left of the screen
+#[cfg(feature = "web")]
+const TOUCH_STICK_OFFSET: Vec2 = Vec2::new(100.0, -100.0);
+#[cfg(feature = "web")]
+const TOUCH_STICK_RADIUS: f32 = 60.0;
 
 #[repr(C)]
-#[derive(Copy, Clone, PartialEq, Pod, Zeroable)]
+#[derive(Copy, Clone, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
 pub struct PlayerInput {
 	movement_angle: f32,
 	rotation: f32,
 	flags: FlagSize,
+	/// A debug console command, encoded so that it's run on the same frame
+	/// everywhere and ends up in replays. All zeroes when there isn't one.
+	/// Older replays don't have it at all
+	#[serde(default)]
+	command: [u32; 3],
+	/// Only sent in the lobby, so everyone can check they're about to play the
+	/// same run
+	#[serde(default)]
+	seed: u64,
 }
 
 impl PlayerInput {
+	pub fn new(movement_angle: f32, rotation: f32) -> Self {
+		Self {
+			movement_angle,
+			rotation,
+			flags: 0,
+			command: [0; 3],
+			seed: 0,
+		}
+	}
+
 	pub fn movement_angle(&self) -> f32 { self.movement_angle }
 
 	pub fn rotation(&self) -> f32 { self.rotation }
 
-	fn set_primary_attacking(&mut self) { self.flags |= PRIMARY_ATTACK; }
+	pub fn set_primary_attacking(&mut self) { self.flags |= PRIMARY_ATTACK; }
+
+	pub fn set_secondary_attacking(&mut self) { self.flags |= SECONDARY_ATTACK; }
+
+	pub fn set_moving(&mut self) { self.flags |= MOVING; }
+
+	pub fn set_opening_door(&mut self) { self.flags |= OPENING_DOOR }
+
+	pub fn set_closing_door(&mut self) { self.flags |= CLOSING_DOOR }
+
+	pub fn set_interacting(&mut self) { self.flags |= INTERACTING }
+
+	pub fn set_charging(&mut self) { self.flags |= CHARGING }
+
+	pub fn set_throwing(&mut self) { self.flags |= THROWING }
+
+	pub fn set_ready(&mut self) { self.flags |= READY }
+
+	pub fn set_searching(&mut self) { self.flags |= SEARCHING }
+
+	pub fn set_winding_up(&mut self) { self.flags |= WINDING_UP }
+
+	pub fn set_dashing(&mut self) { self.flags |= DASHING }
+
+	pub fn set_reviving(&mut self) { self.flags |= REVIVING }
+
+	pub fn set_boon_choice(&mut self, choice: usize) {
+		self.flags &= !BOON_CHOICE;
+		self.flags |= ((choice as FlagSize + 1) << BOON_CHOICE_SHIFT) & BOON_CHOICE;
+	}
+
+	pub fn set_toggling_inventory(&mut self) { self.flags |= TOGGLING_INVENTORY }
+
+	/// Items too far into the pack to fit in the bits can't be put on
+	pub fn set_equipping(&mut self, index: usize) {
+		if index + 1 > (EQUIPPING >> EQUIPPING_SHIFT) as usize {
+			return;
+		}
+
+		self.flags &= !EQUIPPING;
+		self.flags |= (index as FlagSize + 1) << EQUIPPING_SHIFT;
+	}
+
+	pub fn set_command(&mut self, command: [u32; 3]) { self.command = command }
 
-	fn set_secondary_attacking(&mut self) { self.flags |= SECONDARY_ATTACK; }
+	/// Keys stay pressed for the whole rendered frame, which can run more than
+	/// one simulated frame. Only the first one should see the press
+	pub fn clear_presses(&mut self) { self.flags &= !PRESSES }
 
-	fn set_moving(&mut self) { self.flags |= MOVING; }
+	pub fn set_seed(&mut self, seed: u64) { self.seed = seed }
 
-	fn set_opening_door(&mut self) { self.flags |= OPENING_DOOR }
+	pub fn set_mode(&mut self, mode: GameMode) {
+		match mode {
+			GameMode::Dungeon => self.flags &= !ARENA,
+			GameMode::Arena => self.flags |= ARENA,
+		}
+	}
+
+	pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+		self.flags &= !DIFFICULTY;
+		self.flags |= (difficulty as FlagSize) << DIFFICULTY_SHIFT;
+	}
 
-	fn set_closing_door(&mut self) { self.flags |= CLOSING_DOOR }
+	pub fn set_friendly_fire(&mut self) { self.flags |= FRIENDLY_FIRE }
 
 	pub fn using_primary(&self) -> bool { self.flags & PRIMARY_ATTACK == PRIMARY_ATTACK }
 
@@ -48,12 +169,100 @@ impl PlayerInput {
 	pub fn opening_door(&self) -> bool { self.flags & OPENING_DOOR == OPENING_DOOR }
 
 	pub fn closing_door(&self) -> bool { self.flags & CLOSING_DOOR == CLOSING_DOOR }
+
+	pub fn interacting(&self) -> bool { self.flags & INTERACTING == INTERACTING }
+
+	pub fn charging(&self) -> bool { self.flags & CHARGING == CHARGING }
+
+	pub fn throwing(&self) -> bool { self.flags & THROWING == THROWING }
+
+	pub fn ready(&self) -> bool { self.flags & READY == READY }
+
+	pub fn searching(&self) -> bool { self.flags & SEARCHING == SEARCHING }
+
+	pub fn winding_up(&self) -> bool { self.flags & WINDING_UP == WINDING_UP }
+
+	pub fn dashing(&self) -> bool { self.flags & DASHING == DASHING }
+
+	pub fn reviving(&self) -> bool { self.flags & REVIVING == REVIVING }
+
+	pub fn boon_choice(&self) -> Option<usize> {
+		match (self.flags & BOON_CHOICE) >> BOON_CHOICE_SHIFT {
+			0 => None,
+			choice => Some(choice as usize - 1),
+		}
+	}
+
+	pub fn toggling_inventory(&self) -> bool {
+		self.flags & TOGGLING_INVENTORY == TOGGLING_INVENTORY
+	}
+
+	pub fn command(&self) -> [u32; 3] { self.command }
+
+	pub fn seed(&self) -> u64 { self.seed }
+
+	pub fn mode(&self) -> GameMode {
+		match self.flags & ARENA == ARENA {
+			true => GameMode::Arena,
+			false => GameMode::Dungeon,
+		}
+	}
+
+	pub fn difficulty(&self) -> Difficulty {
+		match (self.flags & DIFFICULTY) >> DIFFICULTY_SHIFT {
+			0 => Difficulty::Easy,
+			1 => Difficulty::Normal,
+			_ => Difficulty::Hard,
+		}
+	}
+
+	pub fn friendly_fire(&self) -> bool { self.flags & FRIENDLY_FIRE == FRIENDLY_FIRE }
+
+	/// Whether two players in the lobby picked the same run. Peers that didn't
+	/// would desync on the first frame
+	pub fn same_run(&self, other: &PlayerInput) -> bool {
+		self.seed == other.seed &&
+			self.mode() == other.mode() &&
+			self.difficulty() == other.difficulty() &&
+			self.friendly_fire() == other.friendly_fire()
+	}
+
+	pub fn equipping(&self) -> Option<usize> {
+		match (self.flags & EQUIPPING) >> EQUIPPING_SHIFT {
+			0 => None,
+			index => Some(index as usize - 1),
+		}
+	}
 }
 
 impl Default for PlayerInput {
 	fn default() -> Self { Self::zeroed() }
 }
 
+/// Attacks that can be wound up are held while the button's down, and only go
+/// off on the first frame it isn't
+fn set_primary_input(player: &Player, input: &mut PlayerInput) {
+	match player.can_wind_up() {
+		true => input.set_winding_up(),
+		false => input.set_primary_attacking(),
+	}
+}
+
+/// Where a point in the world is on the screen. `world_to_screen` ignores the
+/// camera's viewport, which split screen uses
+pub fn world_to_viewport(camera: &Camera2D, pos: Vec2) -> Vec2 {
+	let screen_pos = camera.world_to_screen(pos);
+
+	match camera.viewport {
+		// Viewports are measured from the bottom of the screen, like OpenGL's
+		Some((x, y, width, height)) => Vec2::new(
+			x as f32 + screen_pos.x / screen_width() * width as f32,
+			screen_height() - (y + height) as f32 + screen_pos.y / screen_height() * height as f32,
+		),
+		None => screen_pos,
+	}
+}
+
 pub fn movement_input(player: &Player, _index: Option<usize>, camera: &Camera2D) -> PlayerInput {
 	let mut input = PlayerInput::default();
 
@@ -89,7 +298,7 @@ pub fn movement_input(player: &Player, _index: Option<usize>, camera: &Camera2D)
 
 	let mouse_pos: Vec2 = mouse_position().into();
 
-	let rotation = get_angle(mouse_pos, camera.world_to_screen(player.center()));
+	let rotation = get_angle(mouse_pos, world_to_viewport(camera, player.center()));
 
 	input.rotation = rotation;
 
@@ -124,7 +333,7 @@ pub fn movement_input(player: &Player, _index: Option<usize>, camera: &Camera2D)
 					.clone();
 
 				if let Some(use_item_fn) = use_item(&item.item_type) {
-					use_item_fn(&item, player, &mut floor_info.floor);
+					use_item_fn(&item, player, floor_info);
 					player.inventory.items.remove(selected_item.index);
 					possible_selected_item = None;
 				}
@@ -135,12 +344,32 @@ pub fn movement_input(player: &Player, _index: Option<usize>, camera: &Camera2D)
 	}
 	*/
 
-	if is_mouse_button_down(MouseButton::Left) {
-		input.set_primary_attacking();
-	}
+	// The mouse is for picking what to wear while the inventory's open, rather
+	// than attacking
+	match player.in_inventory() {
+		true => {
+			let clicked = (0..player.inventory().items.len()).find(|i| {
+				let pos = item_pos_from_index(*i);
 
-	if is_mouse_button_down(MouseButton::Right) {
-		input.set_secondary_attacking();
+				Rect::new(pos.x, pos.y, ITEM_INVENTORY_SIZE.x, ITEM_INVENTORY_SIZE.y)
+					.contains(mouse_pos)
+			});
+
+			if is_mouse_button_pressed(MouseButton::Left) {
+				if let Some(i) = clicked {
+					input.set_equipping(i);
+				}
+			}
+		},
+		false => {
+			if is_mouse_button_down(MouseButton::Left) {
+				set_primary_input(player, &mut input);
+			}
+
+			if is_mouse_button_down(MouseButton::Right) {
+				input.set_secondary_attacking();
+			}
+		},
 	}
 
 	if is_key_pressed(KeyCode::O) {
@@ -151,13 +380,44 @@ pub fn movement_input(player: &Player, _index: Option<usize>, camera: &Camera2D)
 		input.set_closing_door();
 	}
 
-	/*
-	if is_key_down(KeyCode::LeftShift) {
-		pickup_items(player, &mut floor_info.floor);
+	if is_key_pressed(KeyCode::E) {
+		input.set_interacting();
+	}
+
+	if is_key_down(KeyCode::E) {
+		input.set_reviving();
+	}
+
+	if is_key_pressed(KeyCode::Q) {
+		input.set_charging();
+	}
+
+	if is_key_pressed(KeyCode::T) {
+		input.set_throwing();
+	}
+
+	if is_key_pressed(KeyCode::F) {
+		input.set_searching();
+	}
+
+	if is_key_pressed(KeyCode::Space) {
+		input.set_dashing();
 	}
 
 	if is_key_pressed(KeyCode::I) {
-		toggle_inventory(player);
+		input.set_toggling_inventory();
+	}
+
+	if let Some(choice) = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3]
+		.into_iter()
+		.position(is_key_pressed)
+	{
+		input.set_boon_choice(choice);
+	}
+
+	/*
+	if is_key_down(KeyCode::LeftShift) {
+		pickup_items(player, &mut floor_info.floor);
 	}
 	*/
 
@@ -166,50 +426,163 @@ pub fn movement_input(player: &Player, _index: Option<usize>, camera: &Camera2D)
 		input.set_moving();
 	}
 
+	#[cfg(feature = "web")]
+	touch_input(player, camera, &mut input);
+
 	input
 }
 
+#[cfg(feature = "web")]
+fn touch_stick_center() -> Vec2 {
+	Vec2::new(TOUCH_STICK_OFFSET.x, screen_height() + TOUCH_STICK_OFFSET.y)
+}
+
+/// Touches on the left half of the screen move the player like a joystick,
+/// while touches on the right half aim and attack
+#[cfg(feature = "web")]
+fn touch_input(player: &Player, camera: &Camera2D, input: &mut PlayerInput) {
+	touches()
+		.iter()
+		.for_each(|touch| match touch.position.x < screen_width() / 2.0 {
+			true => {
+				input.movement_angle = get_angle(touch.position, touch_stick_center());
+				input.set_moving();
+			},
+			false => {
+				input.rotation =
+					get_angle(touch.position, world_to_viewport(camera, player.center()));
+				input.set_primary_attacking();
+			},
+		});
+}
+
+/// Draws the virtual joystick while the screen is being touched
+#[cfg(feature = "web")]
+pub fn draw_touch_controls() {
+	let touches = touches();
+
+	if touches.is_empty() {
+		return;
+	}
+
+	let center = touch_stick_center();
+
+	draw_circle_lines(center.x, center.y, TOUCH_STICK_RADIUS, 2.0, WHITE);
+
+	if let Some(touch) = touches
+		.iter()
+		.find(|touch| touch.position.x < screen_width() / 2.0)
+	{
+		let thumb = center + (touch.position - center).clamp_length_max(TOUCH_STICK_RADIUS);
+		draw_circle(thumb.x, thumb.y, TOUCH_STICK_RADIUS / 3.0, WHITE);
+	}
+}
+
+/// Sticks don't rest at exactly zero
 #[cfg(feature = "native")]
-pub fn movement_input_controller(
-	player: &mut Player, index: Option<usize>, attacks: &mut Vec<AttackObj>,
-	floor_info: &mut FloorInfo, gamepad: &Gamepad,
-) {
-	let x_movement = gamepad
-		.axis_data(Axis::LeftStickX)
-		.map(|a| a.value())
-		.unwrap_or_default();
-
-	let y_movement = -gamepad
-		.axis_data(Axis::LeftStickY)
-		.map(|a| a.value())
-		.unwrap_or_default();
-
-	if x_movement.abs() > f32::EPSILON || y_movement.abs() > f32::EPSILON {
-		let angle = y_movement.atan2(x_movement);
-		move_player(player, angle, None, &floor_info.floor);
-	}
-
-	let x_movement_r = gamepad
-		.axis_data(Axis::RightStickX)
-		.map(|a| a.value())
-		.unwrap_or_default();
-
-	let y_movement_r = -gamepad
-		.axis_data(Axis::RightStickY)
-		.map(|a| a.value())
-		.unwrap_or_default();
-
-	player.angle = y_movement_r.atan2(x_movement_r);
-
-	if let Some(button_data) = gamepad.button_data(Button::LeftTrigger2) {
-		if button_data.is_pressed() {
-			player_attack(player, index, attacks, floor_info, false);
-		}
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+/// Input for a second player on the same machine. The left stick moves, the
+/// right stick aims, and the triggers attack
+#[cfg(feature = "native")]
+pub fn gamepad_input(player: &Player, gamepad_info: &GamepadInfo) -> PlayerInput {
+	let mut input = PlayerInput::default();
+
+	let Some(gamepad) = gamepad_info.active_gamepad() else {
+		return input;
+	};
+
+	if player.hp() == 0 {
+		return input;
 	}
 
-	if let Some(button_data) = gamepad.button_data(Button::RightTrigger2) {
-		if button_data.is_pressed() {
-			player_attack(player, index, attacks, floor_info, true);
+	let stick = |x: Axis, y: Axis| {
+		let value = |axis| {
+			gamepad
+				.axis_data(axis)
+				.map(|data| data.value())
+				.unwrap_or_default()
+		};
+
+		// The sticks' y axes point up, while the world's points down
+		Vec2::new(value(x), -value(y))
+	};
+
+	let movement = stick(Axis::LeftStickX, Axis::LeftStickY);
+	let aim = stick(Axis::RightStickX, Axis::RightStickY);
+
+	if movement.length() > STICK_DEAD_ZONE {
+		input.movement_angle = get_angle(movement, Vec2::ZERO);
+		input.set_moving();
+	}
+
+	// Keeps facing the same way once the stick's let go
+	input.rotation = match aim.length() > STICK_DEAD_ZONE {
+		true => get_angle(aim, Vec2::ZERO),
+		false => player.angle,
+	};
+
+	if gamepad.is_pressed(Button::RightTrigger2) {
+		set_primary_input(player, &mut input);
+	}
+
+	if gamepad.is_pressed(Button::LeftTrigger2) {
+		input.set_secondary_attacking();
+	}
+
+	// Like keys, these only count on the frame they're pressed
+	let pressed = |button| gamepad_info.pressed.contains(&button);
+
+	if pressed(Button::South) {
+		input.set_interacting();
+	}
+
+	if gamepad.is_pressed(Button::South) {
+		input.set_reviving();
+	}
+
+	if pressed(Button::West) {
+		input.set_opening_door();
+	}
+
+	if pressed(Button::East) {
+		input.set_closing_door();
+	}
+
+	if pressed(Button::North) {
+		input.set_throwing();
+	}
+
+	if pressed(Button::RightTrigger) {
+		input.set_charging();
+	}
+
+	if pressed(Button::LeftTrigger) {
+		input.set_searching();
+	}
+
+	if pressed(Button::LeftThumb) {
+		input.set_dashing();
+	}
+
+	if let Some(choice) = [Button::DPadLeft, Button::DPadUp, Button::DPadRight]
+		.into_iter()
+		.position(pressed)
+	{
+		input.set_boon_choice(choice);
+	}
+
+	if pressed(Button::Select) {
+		input.set_toggling_inventory();
+	}
+
+	// Without a mouse to pick with, the first thing in the pack that can be
+	// worn gets put on
+	if player.in_inventory() && pressed(Button::DPadDown) {
+		if let Some(i) = player.inventory().first_wearable() {
+			input.set_equipping(i);
 		}
 	}
+
+	input
 }
@@ -1,28 +1,254 @@
 use macroquad::prelude::*;
 use once_cell::sync::Lazy;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Display;
 
-use crate::attacks::{Attack, AttackObj, BlindingLight, MagicMissile, Slash, Stab, ThrownKnife};
+use crate::attacks::{
+	Attack,
+	AttackObj,
+	BlindingLight,
+	Blink,
+	Fireball,
+	FrostNova,
+	HealOther,
+	MagicMissile,
+	RadiantBurst,
+	ShieldBlock,
+	ShotArrow,
+	Slash,
+	Smash,
+	SpearThrust,
+	Stab,
+	SummonSkeleton,
+	ThrownKnife,
+	Whirlwind,
+	FAN_SPREAD,
+};
 use crate::draw::{load_my_image, Drawable};
-use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
-use crate::map::{Floor, FloorInfo, TILE_SIZE};
+use crate::enchantments::{Enchantable, Enchantment};
+use crate::events::GameEvent;
+use crate::localization::{tr, tr_args, try_tr};
+use crate::map::{FloorInfo, TILE_SIZE};
 use crate::math::{easy_polygon, AsPolygon, Polygon};
+#[cfg(feature = "native")]
+use crate::mods;
+use crate::on_hit::OnHit;
 use crate::player::{Player, Spell};
+use crate::resistances::Resistances;
+use crate::rng::Rng;
+#[cfg(feature = "scripting")]
+use crate::scripting;
 
+const DEFAULT_ITEMS: &str = include_str!("../assets/data/items.ron");
+
+/// Every item that isn't gold. Mods can change existing items or add new ones
+/// with their own `data/items.ron`
+pub static ITEMS: Lazy<Vec<ItemDefinition>> = Lazy::new(|| {
+	#[allow(unused_mut)]
+	let mut items: Vec<ItemDefinition> = ron::from_str(DEFAULT_ITEMS).unwrap();
+
+	#[cfg(feature = "native")]
+	if let Some(mod_items) = mods::load_data::<Vec<ItemDefinition>>("items") {
+		mod_items.into_iter().for_each(|mod_item| {
+			match items.iter_mut().find(|item| item.key == mod_item.key) {
+				Some(item) => *item = mod_item,
+				None => items.push(mod_item),
+			}
+		});
+	}
+
+	items
+});
+
+/// Where each item is in ITEMS
+static ITEM_INDICES: Lazy<HashMap<ItemId, usize>> = Lazy::new(|| {
+	let indices: HashMap<ItemId, usize> = ITEMS
+		.iter()
+		.enumerate()
+		.map(|(i, item)| (ItemId::from_key(&item.key), i))
+		.collect();
+
+	if indices.len() < ITEMS.len() {
+		log::error!("Two items' keys have the same ID, so one of them can't be found");
+	}
+
+	indices
+});
+
+#[derive(Clone, Deserialize)]
+pub struct ItemDefinition {
+	/// What the game and scripts refer to the item as
+	pub key: String,
+	/// Only shown if no language pack has an `item-<key>` message, like for
+	/// items from mods that don't come with their own translations
+	pub name: String,
+	pub description: String,
+	pub texture: String,
+	#[serde(default = "default_item_size")]
+	pub size: f32,
+	/// Items with a max stack start out with a stack count of 1
+	#[serde(default)]
+	pub max_stack: Option<u8>,
+	#[serde(default)]
+	pub price: u32,
+	#[serde(default)]
+	pub attack: Option<ItemAttack>,
+	/// Each attack uses up one of the stack, like throwing knives
+	#[serde(default)]
+	pub consumed_on_attack: bool,
+	/// The key of another item each attack uses up one of, like arrows for bows
+	#[serde(default)]
+	pub ammo: Option<String>,
+	/// How many times a wand can be cast from before it costs mana like any
+	/// other spell
+	#[serde(default)]
+	pub charges: Option<u8>,
+	#[serde(default)]
+	pub on_use: Option<ItemEffect>,
+	/// Kinds of damage the item protects whoever's holding it from
+	#[serde(default)]
+	pub resistances: Resistances,
+	/// What else happens when whoever's holding the item hurts a monster
+	#[serde(default)]
+	pub on_hit: Vec<OnHit>,
+	/// Gear that's worn rather than held
+	#[serde(default)]
+	pub wearable: Option<Wearable>,
+}
+
+/// Where gear is worn. Each slot holds one thing
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EquipmentSlot {
+	Head,
+	Body,
+	Ring,
+	Feet,
+}
+
+impl EquipmentSlot {
+	pub const ALL: [EquipmentSlot; 4] = [
+		EquipmentSlot::Head,
+		EquipmentSlot::Body,
+		EquipmentSlot::Ring,
+		EquipmentSlot::Feet,
+	];
+}
+
+impl Display for EquipmentSlot {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&tr(match self {
+			EquipmentSlot::Head => "equipment-slot-head",
+			EquipmentSlot::Body => "equipment-slot-body",
+			EquipmentSlot::Ring => "equipment-slot-ring",
+			EquipmentSlot::Feet => "equipment-slot-feet",
+		}))
+	}
+}
+
+/// What a piece of gear does for whoever's wearing it
+#[derive(Copy, Clone, Deserialize)]
+pub struct Wearable {
+	pub slot: EquipmentSlot,
+	/// Taken off every hit, though a hit always does at least 1 damage
+	#[serde(default)]
+	pub armor: u16,
+	/// How much faster the wearer moves, as a fraction of their speed. Heavy
+	/// gear slows them down instead
+	#[serde(default)]
+	pub speed: f32,
+	/// How much faster the wearer's MP comes back, as a percent
+	#[serde(default)]
+	pub mp_regen: u16,
+}
+
+fn default_item_size() -> f32 { 30.0 }
+
+#[derive(Copy, Clone, Deserialize)]
+pub enum ItemAttack {
+	/// Raises a shield that blocks hits from in front
+	Block,
+	/// Heals the nearest ally in range
+	Heal,
+	/// Shoots an arrow, as long as the player has one
+	Shoot,
+	Slash,
+	/// Brings a mace down in front of the player. Wound all the way up, it
+	/// calls down a radiant burst too, for anyone who knows it
+	Smash,
+	Stab,
+	/// Casts the player's current spell
+	Spell,
+	/// Runs a spear through everything in a line
+	Thrust,
+	ThrowingKnife,
+	/// Throws three knives at once in a spread, using up a knife for each
+	KnifeFan,
+	/// Casts a spell, whether or not the player knows it
+	Wand(Spell),
+	/// Spins on the spot, hitting everything around the player
+	Whirlwind,
+}
+
+#[derive(Clone, Deserialize)]
+pub enum ItemEffect {
+	Enchant(Enchantment),
+	/// Gives back this much MP, up to the player's max
+	RestoreMana(u16),
+}
+
+/// A hash of the item's key, rather than where it is in ITEMS, since mods can
+/// add items in any order and every peer has to agree on what an ID means
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
-pub enum PotionType {
-	Regeneration,
+pub struct ItemId(u32);
+
+impl ItemId {
+	/// Used for items the game itself relies on. If a mod's gotten rid of the
+	/// item, it just won't have a definition
+	pub fn from_key(key: &str) -> Self {
+		// FNV-1a, which unlike std's hasher is the same on every machine and
+		// every version of Rust
+		Self(key.bytes().fold(0x811c9dc5, |hash, byte| {
+			(hash ^ byte as u32).wrapping_mul(0x01000193)
+		}))
+	}
+
+	/// Only for items that exist, like ones typed into the console
+	pub fn find(key: &str) -> Option<Self> {
+		let id = Self::from_key(key);
+
+		ITEM_INDICES.contains_key(&id).then_some(id)
+	}
+
+	pub fn raw(self) -> u32 { self.0 }
+
+	/// The opposite of `raw`, for IDs that have been sent somewhere and back
+	pub fn from_raw(raw: u32) -> Option<Self> {
+		let id = Self(raw);
+
+		ITEM_INDICES.contains_key(&id).then_some(id)
+	}
+
+	pub fn definition(&self) -> Option<&'static ItemDefinition> {
+		ITEM_INDICES.get(self).map(|&i| &ITEMS[i])
+	}
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum ItemType {
-	ShortSword,
-	WizardsDagger,
-	WizardGlove,
-	ThrowingKnife,
 	Gold(u32),
-	Potion(PotionType),
+	Item(ItemId),
+}
+
+impl ItemType {
+	pub fn from_key(key: &str) -> Self { ItemType::Item(ItemId::from_key(key)) }
+
+	pub fn definition(&self) -> Option<&'static ItemDefinition> {
+		match self {
+			ItemType::Gold(_) => None,
+			ItemType::Item(id) => id.definition(),
+		}
+	}
 }
 
 pub enum ItemPos {
@@ -37,6 +263,8 @@ pub struct ItemInfo {
 	// If there is no pos, it's in the player's inventory
 	tile_pos: Option<IVec2>,
 	pub stack_count: Option<u8>,
+	/// Free casts left in a wand
+	pub charges: Option<u8>,
 }
 
 impl ItemInfo {
@@ -46,28 +274,25 @@ impl ItemInfo {
 			cursed: false,
 			item_type,
 			tile_pos,
-			stack_count: match item_type {
-				ItemType::ThrowingKnife => Some(1),
-				ItemType::Potion(_) => Some(1),
-				_ => None,
-			},
+			stack_count: item_type
+				.definition()
+				.and_then(|item| item.max_stack)
+				.map(|_| 1),
+			charges: item_type.definition().and_then(|item| item.charges),
 		}
 	}
 
 	pub fn description(&self) -> String {
-		let mut description = match self.item_type {
-			ItemType::WizardGlove => "A glove wielded by mighty sorcerers. Thiey alow magic users to directly tough the energy around them and manipulate it to their will.",
-			ItemType::ShortSword => "A sturdy short sword, passed down from many generations.",
-			ItemType::WizardsDagger => "A dagger engraved with mystical runes",
-			ItemType::ThrowingKnife => "A small but very sharp knife",
-			ItemType::Gold(_) => "Gold! Currency! Can be used at shops to purchase items",
-			ItemType::Potion(potion_kind) => match potion_kind {
-				PotionType::Regeneration => "Helps the body to recover from damage",
-			},
-		}.to_string();
+		let mut description = match (self.item_type, self.item_type.definition()) {
+			(_, Some(item)) => try_tr(&format!("item-{}-description", item.key), None)
+				.unwrap_or_else(|| item.description.clone()),
+			(ItemType::Gold(_), None) => tr("item-gold-description"),
+			(ItemType::Item(_), None) => tr("item-unknown-description"),
+		};
 
 		if self.cursed {
-			description.push_str("\nMalevolant energy slithers from it.");
+			description.push('\n');
+			description.push_str(&tr("item-cursed-description"));
 		}
 
 		description
@@ -78,66 +303,171 @@ impl ItemInfo {
 
 impl Display for ItemInfo {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_str(&match self.item_type {
-			ItemType::ShortSword => "Short Sword".to_string(),
-			ItemType::WizardGlove => "Wizard's Glove".to_string(),
-			ItemType::WizardsDagger => "Wizard's Dagger".to_string(),
-			ItemType::ThrowingKnife => "Throwing Knife".to_string(),
-			ItemType::Gold(amt) => format!("{amt} gold"),
-			ItemType::Potion(potion_type) => format!(
-				"Potion of {}",
-				match potion_type {
-					PotionType::Regeneration => "Regeneration",
+		match self.item_type {
+			ItemType::Gold(amt) => f.write_str(&tr_args("item-gold", [("amount", amt.into())])),
+			ItemType::Item(id) => {
+				let Some(item) = id.definition() else {
+					return f.write_str(&tr("item-unknown"));
+				};
+
+				let name = try_tr(&format!("item-{}", item.key), None)
+					.unwrap_or_else(|| item.name.clone());
+
+				match self.charges {
+					Some(charges) => f.write_str(&tr_args(
+						"item-charges",
+						[("item", name.into()), ("charges", charges.into())],
+					)),
+					None => f.write_str(&name),
 				}
-			),
-		})
+			},
+		}
 	}
 }
 
 pub fn attack_with_item(
 	item: ItemInfo, player: &mut Player, index: Option<usize>, floor: &FloorInfo,
 	primary_attack: bool,
-) -> Option<AttackObj> {
-	match item.item_type {
-		ItemType::ShortSword => Some(AttackObj::Slash(Slash::new(
+) -> Vec<AttackObj> {
+	let Some(attack) = item.item_type.definition().and_then(|item| item.attack) else {
+		return Vec::new();
+	};
+
+	match attack {
+		ItemAttack::Block => vec![AttackObj::ShieldBlock(ShieldBlock::new(
 			player,
 			index,
 			player.angle,
 			&floor.floor,
 			primary_attack,
-		))),
-		ItemType::WizardsDagger => Some(AttackObj::Stab(Stab::new(
+		))],
+		ItemAttack::Heal => vec![AttackObj::HealOther(HealOther::new(
 			player,
 			index,
 			player.angle,
 			&floor.floor,
 			primary_attack,
-		))),
-		ItemType::WizardGlove => player.spells().get(0).copied().map(|spell| match spell {
-			Spell::BlindingLight => AttackObj::BlindingLight(BlindingLight::new(
-				player,
-				index,
-				player.angle,
-				&floor.floor,
-				primary_attack,
-			)),
-			Spell::MagicMissile => AttackObj::MagicMissile(MagicMissile::new(
-				player,
-				index,
-				player.angle,
-				&floor.floor,
-				primary_attack,
-			)),
-		}),
-		ItemType::ThrowingKnife => Some(AttackObj::ThrowingKnife(ThrownKnife::new(
+		))],
+		ItemAttack::Shoot => vec![AttackObj::ShotArrow(ShotArrow::new(
 			player,
 			index,
 			player.angle,
 			&floor.floor,
 			primary_attack,
-		))),
-		ItemType::Potion(_) => None,
-		ItemType::Gold(_) => None,
+		))],
+		ItemAttack::Slash => vec![AttackObj::Slash(
+			Slash::new(player, index, player.angle, &floor.floor, primary_attack)
+				.with_power(player.charge_power() * player.backstab_power()),
+		)],
+		ItemAttack::Smash => {
+			let smash = AttackObj::Smash(
+				Smash::new(player, index, player.angle, &floor.floor, primary_attack)
+					.with_power(player.charge_power()),
+			);
+			let burst = cast_spell(Spell::RadiantBurst, player, index, floor, primary_attack);
+
+			// Without the mana for the burst, it's still a perfectly good swing
+			match player.fully_charged() &&
+				player.spells().contains(&Spell::RadiantBurst) &&
+				player.mp() >= burst.mana_cost()
+			{
+				true => vec![smash, burst],
+				false => vec![smash],
+			}
+		},
+		ItemAttack::Stab => vec![AttackObj::Stab(
+			Stab::new(player, index, player.angle, &floor.floor, primary_attack)
+				.with_power(player.backstab_power()),
+		)],
+		ItemAttack::Spell => player
+			.spells()
+			.first()
+			.copied()
+			.map(|spell| cast_spell(spell, player, index, floor, primary_attack))
+			.into_iter()
+			.collect(),
+		ItemAttack::Thrust => vec![AttackObj::SpearThrust(
+			SpearThrust::new(player, index, player.angle, &floor.floor, primary_attack)
+				.with_power(player.backstab_power()),
+		)],
+		ItemAttack::ThrowingKnife => vec![throw_knife(0.0, player, index, floor, primary_attack)],
+		ItemAttack::KnifeFan => [-FAN_SPREAD, 0.0, FAN_SPREAD]
+			.into_iter()
+			.map(|offset| throw_knife(offset, player, index, floor, primary_attack))
+			.collect(),
+		ItemAttack::Wand(spell) => vec![cast_spell(spell, player, index, floor, primary_attack)],
+		ItemAttack::Whirlwind => vec![AttackObj::Whirlwind(Whirlwind::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		))],
+	}
+}
+
+/// A knife thrown `offset` radians off from where the player's aiming
+fn throw_knife(
+	offset: f32, player: &Player, index: Option<usize>, floor: &FloorInfo, primary_attack: bool,
+) -> AttackObj {
+	AttackObj::ThrowingKnife(
+		ThrownKnife::new(player, index, player.angle, &floor.floor, primary_attack)
+			.with_angle_offset(offset)
+			.with_power(player.backstab_power())
+			.with_poison(player.inventory.contains(ItemType::from_key("venom_vial"))),
+	)
+}
+
+fn cast_spell(
+	spell: Spell, player: &Player, index: Option<usize>, floor: &FloorInfo, primary_attack: bool,
+) -> AttackObj {
+	match spell {
+		Spell::BlindingLight => AttackObj::BlindingLight(BlindingLight::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		)),
+		Spell::Blink => AttackObj::Blink(Blink::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		)),
+		Spell::Fireball => AttackObj::Fireball(Fireball::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		)),
+		Spell::FrostNova => AttackObj::FrostNova(FrostNova::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		)),
+		Spell::MagicMissile => AttackObj::MagicMissile(
+			MagicMissile::new(player, index, player.angle, &floor.floor, primary_attack)
+				.with_power(player.charge_power()),
+		),
+		Spell::RadiantBurst => AttackObj::RadiantBurst(RadiantBurst::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		)),
+		Spell::SummonSkeleton => AttackObj::SummonSkeleton(SummonSkeleton::new(
+			player,
+			index,
+			player.angle,
+			&floor.floor,
+			primary_attack,
+		)),
 	}
 }
 
@@ -150,9 +480,9 @@ impl AsPolygon for ItemInfo {
 
 impl Drawable for ItemInfo {
 	fn size(&self) -> Vec2 {
-		match self.item_type {
-			ItemType::Potion(_) => Vec2::splat(18.0),
-			_ => Vec2::splat(30.0),
+		match self.item_type.definition() {
+			Some(item) => Vec2::splat(item.size),
+			None => Vec2::splat(30.0),
 		}
 	}
 
@@ -162,37 +492,56 @@ impl Drawable for ItemInfo {
 	}
 
 	fn texture(&self) -> Option<Texture2D> {
-		Some(load_my_image(match self.item_type {
-			ItemType::Gold(_) => "gold.webp",
-			ItemType::Potion(potion) => match potion {
-				PotionType::Regeneration => "potion_of_regeneration.webp",
-			},
-			ItemType::ThrowingKnife => "throwing_knife.webp",
-			_ => "gold.webp",
+		Some(load_my_image(match self.item_type.definition() {
+			Some(item) => &item.texture,
+			None => "gold.webp",
 		}))
 	}
 }
 
-type UseItemFn = Lazy<Box<dyn Fn(&ItemInfo, &mut Player, &mut Floor)>>;
+type UseItemFn =
+	Lazy<Box<dyn Fn(&ItemInfo, &mut Player, &mut FloorInfo, &mut Vec<GameEvent>, &mut Rng)>>;
 
 pub fn use_item(item_type: &ItemType) -> Option<UseItemFn> {
-	match item_type {
-		ItemType::Gold(_) => None,
-		ItemType::Potion(potion) => match potion {
-			PotionType::Regeneration => Some(Lazy::new(|| {
-				Box::new(
-					|_item: &ItemInfo, player: &mut Player, _floor: &mut Floor| {
-						player.apply_enchantment(Enchantment {
-							kind: EnchantmentKind::Regenerating,
-							strength: 1,
-						})
-					},
-				)
-			})),
-		},
-		ItemType::ThrowingKnife => None,
-		ItemType::WizardGlove => None,
-		ItemType::WizardsDagger => None,
-		ItemType::ShortSword => None,
+	// Scripted items replace their built in effect entirely
+	#[cfg(feature = "scripting")]
+	if scripting::item_hook(item_type).is_some_and(scripting::has_hook) {
+		return Some(Lazy::new(|| {
+			Box::new(
+				|item: &ItemInfo,
+				 player: &mut Player,
+				 floor_info: &mut FloorInfo,
+				 events: &mut Vec<GameEvent>,
+				 rng: &mut Rng| {
+					let hook = scripting::item_hook(&item.item_type).unwrap();
+
+					if let Some(commands) =
+						scripting::run_hook(hook, scripting::ScriptContext::new(player))
+					{
+						scripting::apply_commands(commands, player, floor_info, events, rng);
+					}
+				},
+			)
+		}));
 	}
+
+	item_type.definition()?.on_use.as_ref()?;
+
+	Some(Lazy::new(|| {
+		Box::new(
+			|item: &ItemInfo,
+			 player: &mut Player,
+			 _floor_info: &mut FloorInfo,
+			 _events: &mut Vec<GameEvent>,
+			 _rng: &mut Rng| match item
+				.item_type
+				.definition()
+				.and_then(|item| item.on_use.clone())
+			{
+				Some(ItemEffect::Enchant(enchantment)) => player.apply_enchantment(enchantment),
+				Some(ItemEffect::RestoreMana(amount)) => player.restore_mp(amount),
+				None => (),
+			},
+		)
+	}))
 }
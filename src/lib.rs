@@ -0,0 +1,117 @@
+pub mod ambush;
+pub mod animation;
+pub mod arena;
+pub mod attacks;
+pub mod bestiary;
+pub mod biome;
+pub mod config;
+#[cfg(feature = "native")]
+pub mod console;
+pub mod corpses;
+pub mod debug_overlay;
+pub mod descent;
+pub mod difficulty;
+pub mod director;
+pub mod draw;
+pub mod enchantments;
+pub mod events;
+pub mod floating_text;
+pub mod floor_modifier;
+pub mod flow_field;
+pub mod game_over;
+pub mod init_game;
+pub mod input;
+pub mod items;
+pub mod localization;
+pub mod logging;
+pub mod loot;
+pub mod map;
+pub mod math;
+#[cfg(feature = "native")]
+pub mod mods;
+pub mod monsters;
+pub mod net;
+pub mod noise;
+pub mod on_hit;
+pub mod photo_mode;
+pub mod player;
+pub mod prefabs;
+pub mod profiling;
+pub mod progression;
+pub mod props;
+pub mod quests;
+pub mod replay;
+pub mod resistances;
+pub mod rng;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod shop;
+pub mod shrines;
+pub mod telegraphs;
+pub mod victory;
+
+#[cfg(all(feature = "native", feature = "web"))]
+compile_error!("The native and web features can't be enabled at the same time");
+
+pub const MAX_VIEW_OF_PLAYER: f32 = 200.0;
+
+pub const DEFAULT_FRAGMENT_SHADER: &str = "
+#version 100
+precision lowp float;
+varying vec2 uv;
+varying lowp vec4 color;
+uniform sampler2D Texture;
+uniform lowp float lowest_light_level;
+uniform lowp float window_height;
+const lowp float VISION_SIZE = 400.0;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+
+	float lighting = 1.0;
+	lighting *= lowest_light_level;
+	gl_FragColor.rgb *= vec3(lighting * 0.75);
+
+}
+";
+
+pub const DEFAULT_VERTEX_SHADER: &str = "
+#version 100
+precision lowp float;
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute lowp vec4 color0;
+varying vec2 uv;
+varying lowp vec4 color;
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+    color = color0 / 255.0;
+}
+";
+
+pub const CAMERA_ZOOM: f32 = 0.0045;
+
+/// The most players that can be in one networked game
+pub const MAX_PLAYERS: usize = 4;
+
+pub const FPS: f64 = 60.0;
+/// The run seed until another one is picked in the settings
+pub const SEED: u64 = 1000;
+
+/// Only used to seed HashMaps, so it doesn't need to be cryptographically
+/// secure. See .cargo/config.toml for why this exists
+#[cfg(feature = "web")]
+#[no_mangle]
+unsafe extern "Rust" fn __getrandom_v03_custom(
+	dest: *mut u8, len: usize,
+) -> Result<(), getrandom::Error> {
+	std::slice::from_raw_parts_mut(dest, len)
+		.iter_mut()
+		.for_each(|byte| *byte = (js_sys::Math::random() * 256.0) as u8);
+
+	Ok(())
+}
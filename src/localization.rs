@@ -0,0 +1,136 @@
+use std::sync::RwLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use once_cell::sync::Lazy;
+use unic_langid::LanguageIdentifier;
+
+#[cfg(feature = "native")]
+use crate::mods;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Language packs built into the game. Mods can add to these, or add whole new
+/// languages, with their own `lang/<code>.ftl`
+const BUILTIN_LANGUAGES: &[(&str, &str)] = &[
+	("en", include_str!("../assets/lang/en.ftl")),
+	("es", include_str!("../assets/lang/es.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+struct Localization {
+	language: String,
+	bundle: Bundle,
+	/// Anything missing from the current language is shown in English instead
+	fallback: Bundle,
+}
+
+static LOCALIZATION: Lazy<RwLock<Localization>> = Lazy::new(|| {
+	RwLock::new(Localization {
+		language: DEFAULT_LANGUAGE.to_string(),
+		bundle: load_bundle(DEFAULT_LANGUAGE),
+		fallback: load_bundle(DEFAULT_LANGUAGE),
+	})
+});
+
+/// The code and name of every language there's a pack for
+static LANGUAGES: Lazy<Vec<(String, String)>> = Lazy::new(|| {
+	#[allow(unused_mut)]
+	let mut codes: Vec<String> = BUILTIN_LANGUAGES
+		.iter()
+		.map(|(code, _)| code.to_string())
+		.collect();
+
+	#[cfg(feature = "native")]
+	mods::mod_files("lang", "ftl").keys().for_each(|file| {
+		let code = file.trim_end_matches(".ftl").to_string();
+
+		if !codes.contains(&code) {
+			codes.push(code);
+		}
+	});
+
+	codes
+		.into_iter()
+		.map(|code| {
+			let name = format(&load_bundle(&code), "language-name", None).unwrap_or(code.clone());
+			(code, name)
+		})
+		.collect()
+});
+
+fn load_bundle(code: &str) -> Bundle {
+	let lang_id: LanguageIdentifier = code.parse().unwrap_or_default();
+	let mut bundle = Bundle::new_concurrent(vec![lang_id]);
+
+	// The Unicode isolation marks around arguments show up as boxes in-game
+	bundle.set_use_isolating(false);
+
+	let builtin = BUILTIN_LANGUAGES
+		.iter()
+		.find(|(builtin_code, _)| *builtin_code == code)
+		.map(|(_, source)| source.to_string());
+
+	#[cfg(feature = "native")]
+	let from_mod = mods::mod_files("lang", "ftl")
+		.remove(&format!("{code}.ftl"))
+		.and_then(|path| std::fs::read_to_string(path).ok());
+	#[cfg(not(feature = "native"))]
+	let from_mod = None;
+
+	// Mods override the built in messages
+	builtin
+		.into_iter()
+		.chain(from_mod)
+		.for_each(|source| match FluentResource::try_new(source) {
+			Ok(resource) => bundle.add_resource_overriding(resource),
+			Err((resource, errors)) => {
+				log::warn!("Errors in the {code} language pack: {errors:?}");
+				bundle.add_resource_overriding(resource);
+			},
+		});
+
+	bundle
+}
+
+fn format(bundle: &Bundle, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+	let pattern = bundle.get_message(id)?.value()?;
+	let mut errors = Vec::new();
+
+	Some(
+		bundle
+			.format_pattern(pattern, args, &mut errors)
+			.into_owned(),
+	)
+}
+
+pub fn languages() -> &'static [(String, String)] { &LANGUAGES }
+
+pub fn language() -> String { LOCALIZATION.read().unwrap().language.clone() }
+
+/// Switches every string the game shows over to another language
+pub fn set_language(code: &str) {
+	let mut localization = LOCALIZATION.write().unwrap();
+
+	if localization.language != code {
+		localization.language = code.to_string();
+		localization.bundle = load_bundle(code);
+	}
+}
+
+/// Looks up a message in the current language, falling back to English
+pub fn try_tr(id: &str, args: Option<&FluentArgs>) -> Option<String> {
+	let localization = LOCALIZATION.read().unwrap();
+
+	format(&localization.bundle, id, args).or_else(|| format(&localization.fallback, id, args))
+}
+
+/// Looks up a message, showing its ID if no language has it
+pub fn tr(id: &str) -> String { try_tr(id, None).unwrap_or_else(|| id.to_string()) }
+
+pub fn tr_args<'a>(id: &str, args: impl IntoIterator<Item = (&'a str, FluentValue<'a>)>) -> String {
+	let args = FluentArgs::from_iter(args);
+
+	try_tr(id, Some(&args)).unwrap_or_else(|| id.to_string())
+}
@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+#[cfg(feature = "native")]
+use std::fs::File;
+#[cfg(feature = "native")]
+use std::io::Write;
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+
+/// Takes filters like `warn,roguelite::net=debug`, where the bare level applies
+/// to any module not given its own
+const FILTER_ENV_VAR: &str = "ROGUELITE_LOG";
+// How many lines the debug overlay's log viewer keeps
+const MAX_RECENT_LOGS: usize = 100;
+
+static LOGGER: OnceCell<Logger> = OnceCell::new();
+
+fn new_logger(_log_file: &str) -> Logger {
+	let filters = std::env::var(FILTER_ENV_VAR).unwrap_or_default();
+
+	let mut default_level = LevelFilter::Info;
+	let mut module_levels = Vec::new();
+
+	filters
+		.split(',')
+		.filter(|f| !f.is_empty())
+		.for_each(|filter| match filter.split_once('=') {
+			Some((module, level)) => {
+				if let Ok(level) = level.parse() {
+					module_levels.push((module.to_string(), level));
+				}
+			},
+			None => {
+				if let Ok(level) = filter.parse() {
+					default_level = level;
+				}
+			},
+		});
+
+	// Check the most specific modules first
+	module_levels
+		.sort_by_key(|(module, _): &(String, LevelFilter)| std::cmp::Reverse(module.len()));
+
+	Logger {
+		default_level,
+		module_levels,
+		#[cfg(feature = "native")]
+		file: File::create(_log_file).ok().map(Mutex::new),
+		recent: Mutex::new(VecDeque::with_capacity(MAX_RECENT_LOGS)),
+	}
+}
+
+struct Logger {
+	default_level: LevelFilter,
+	module_levels: Vec<(String, LevelFilter)>,
+	#[cfg(feature = "native")]
+	file: Option<Mutex<File>>,
+	recent: Mutex<VecDeque<String>>,
+}
+
+impl Logger {
+	fn level_for(&self, target: &str) -> LevelFilter {
+		self.module_levels
+			.iter()
+			.find(|(module, _)| target.starts_with(module.as_str()))
+			.map(|(_, level)| *level)
+			.unwrap_or(self.default_level)
+	}
+
+	fn max_level(&self) -> LevelFilter {
+		self.module_levels
+			.iter()
+			.map(|(_, level)| *level)
+			.fold(self.default_level, Ord::max)
+	}
+}
+
+impl Log for Logger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		metadata.level() <= self.level_for(metadata.target())
+	}
+
+	fn log(&self, record: &Record) {
+		if !self.enabled(record.metadata()) {
+			return;
+		}
+
+		let line = format!("[{} {}] {}", record.level(), record.target(), record.args());
+
+		println!("{line}");
+
+		// Logs are written to a file too, so they can be compared between peers
+		// when tracking down desyncs
+		#[cfg(feature = "native")]
+		if let Some(file) = &self.file {
+			let _ = writeln!(file.lock().unwrap(), "{line}");
+		}
+
+		let mut recent = self.recent.lock().unwrap();
+
+		if recent.len() == MAX_RECENT_LOGS {
+			recent.pop_front();
+		}
+
+		recent.push_back(line);
+	}
+
+	fn flush(&self) {
+		#[cfg(feature = "native")]
+		if let Some(file) = &self.file {
+			let _ = file.lock().unwrap().flush();
+		}
+	}
+}
+
+/// Sets up logging, writing to `log_file` as well as stdout on native builds
+pub fn init_logging(log_file: &str) {
+	let logger = LOGGER.get_or_init(|| new_logger(log_file));
+
+	if log::set_logger(logger).is_ok() {
+		log::set_max_level(logger.max_level());
+	}
+}
+
+/// The last few lines logged, oldest first
+pub fn recent_logs(amount: usize) -> Vec<String> {
+	let Some(logger) = LOGGER.get() else {
+		return Vec::new();
+	};
+
+	let recent = logger.recent.lock().unwrap();
+
+	recent
+		.iter()
+		.skip(recent.len().saturating_sub(amount))
+		.cloned()
+		.collect()
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+use crate::items::ItemType;
+use crate::rng::Rng;
+
+/// Weighted odds of what something drops. Each item's chance of dropping is
+/// its weight out of the total, which includes the weight of dropping nothing
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LootTable {
+	entries: Vec<(ItemType, u32)>,
+	nothing_weight: u32,
+}
+
+impl LootTable {
+	pub fn new(nothing_weight: u32) -> Self {
+		Self {
+			entries: Vec::new(),
+			nothing_weight,
+		}
+	}
+
+	pub fn with(mut self, item_type: ItemType, weight: u32) -> Self {
+		self.entries.push((item_type, weight));
+		self
+	}
+
+	/// What chests hold. Weapons and armor get more common deeper down
+	pub fn chest(floor_num: usize) -> Self {
+		let weapon_weight = 1 + floor_num as u32 / 2;
+
+		Self::new(0)
+			.with(ItemType::Gold(15 * (floor_num as u32 + 1)), 5)
+			.with(ItemType::from_key("potion_regeneration"), 3)
+			.with(ItemType::from_key("potion_mana"), 2)
+			.with(ItemType::from_key("throwing_knife"), 2)
+			.with(ItemType::from_key("arrow"), 2)
+			.with(ItemType::from_key("short_sword"), weapon_weight)
+			.with(ItemType::from_key("bow"), weapon_weight)
+			.with(ItemType::from_key("spear"), weapon_weight)
+			.with(ItemType::from_key("warriors_shield"), weapon_weight)
+			.with(ItemType::from_key("wizards_dagger"), weapon_weight)
+			.with(ItemType::from_key("leather_cap"), weapon_weight)
+			.with(ItemType::from_key("chainmail"), weapon_weight)
+			.with(ItemType::from_key("ring_of_focus"), 1)
+			.with(ItemType::from_key("swift_boots"), 1)
+			.with(ItemType::from_key("vampiric_dagger"), 1)
+			.with(ItemType::from_key("slime_sword"), 1)
+			.with(ItemType::from_key("wand_fireball"), 1)
+			.with(ItemType::from_key("wand_frost_nova"), 1)
+	}
+
+	/// What monsters drop when they die, which most of the time is nothing
+	pub fn monster(floor_num: usize) -> Self {
+		Self::new(12)
+			.with(ItemType::Gold(5 * (floor_num as u32 + 1)), 3)
+			.with(ItemType::from_key("potion_regeneration"), 1)
+			.with(ItemType::from_key("potion_mana"), 1)
+	}
+
+	pub fn roll(&self, rng: &mut Rng) -> Option<ItemType> {
+		let total =
+			self.nothing_weight + self.entries.iter().map(|(_, weight)| weight).sum::<u32>();
+		let mut roll = rng.gen_range(0, total);
+
+		if roll < self.nothing_weight {
+			return None;
+		}
+
+		roll -= self.nothing_weight;
+
+		self.entries
+			.iter()
+			.find_map(|(item_type, weight)| match roll < *weight {
+				true => Some(*item_type),
+				false => {
+					roll -= weight;
+					None
+				},
+			})
+	}
+}
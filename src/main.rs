@@ -1,339 +1,1000 @@
-mod attacks;
-mod config;
-mod draw;
-mod enchantments;
-mod init_game;
-mod input;
-mod items;
-mod map;
-mod math;
-mod monsters;
-mod net;
-mod player;
-
-use std::time::{Duration, Instant};
-
-use attacks::*;
-use draw::*;
-use egui::{FontId, RichText};
-use ggrs::{GGRSEvent, P2PSession, SessionState};
-use init_game::*;
-use input::*;
-use map::*;
-use monsters::*;
-use net::{handle_requests, GGRSConfig};
-use player::*;
+use std::time::Duration;
 
+use egui::{FontId, RichText};
+use ggrs::{GGRSEvent, SessionState};
 use macroquad::miniquad::conf::Platform;
 use macroquad::prelude::*;
 use macroquad::ui::root_ui;
-
+#[cfg(feature = "native")]
 use rayon::prelude::*;
 
-use crate::enchantments::EnchantmentKind;
-use crate::math::AsPolygon;
+use roguelite::arena::ArenaPhase;
+use roguelite::debug_overlay::{draw_debug_stats, draw_debug_world};
+use roguelite::difficulty::Difficulty;
+use roguelite::draw::{Drawable, Minimap};
+use roguelite::enchantments::EnchantmentKind;
+use roguelite::events::EventQueue;
+use roguelite::game_over::GameOverScreen;
+use roguelite::init_game::*;
+use roguelite::input::*;
+use roguelite::items::ItemInfo;
+use roguelite::localization::{languages, tr, tr_args};
+use roguelite::logging::init_logging;
+use roguelite::map::*;
+use roguelite::math::AsPolygon;
+#[cfg(not(feature = "web"))]
+use roguelite::net::parse_remote_addr;
+#[cfg(feature = "native")]
+use roguelite::net::MAX_LOCAL_PLAYERS;
+use roguelite::net::{
+	handle_requests,
+	init_solo_net,
+	PeerStatus,
+	PeerStatuses,
+	MAX_INPUT_DELAY,
+	MAX_PREDICTION_WINDOW,
+};
+#[cfg(feature = "native")]
+use roguelite::photo_mode::save_screenshot;
+use roguelite::photo_mode::PhotoMode;
+use roguelite::player::*;
+use roguelite::quests::draw_quest_log;
+use roguelite::replay::Replay;
+#[cfg(feature = "scripting")]
+use roguelite::scripting;
+use roguelite::victory::VictoryScreen;
+use roguelite::{profiling, CAMERA_ZOOM, FPS, MAX_PLAYERS};
+
+fn update_game(game_info: &mut GameInfo) -> Option<Screen> {
+	#[cfg(feature = "native")]
+	if cfg!(debug_assertions) && is_key_pressed(KeyCode::GraveAccent) {
+		game_info.console.toggle();
+	}
+
+	#[cfg(feature = "native")]
+	let console_open = game_info.console.open;
+	#[cfg(not(feature = "native"))]
+	let console_open = false;
+
+	#[cfg(not(feature = "web"))]
+	if let Some(chat) = &mut game_info.chat {
+		if !console_open && is_key_pressed(KeyCode::Enter) {
+			chat.open = true;
+		}
+
+		if is_key_pressed(KeyCode::Escape) {
+			chat.open = false;
+		}
+	}
 
-pub const MAX_VIEW_OF_PLAYER: f32 = 200.0;
+	#[cfg(not(feature = "web"))]
+	let chat_open = game_info.chat.as_ref().is_some_and(|chat| chat.open);
+	#[cfg(feature = "web")]
+	let chat_open = false;
 
-const DEFAULT_FRAGMENT_SHADER: &str = "
-#version 100
-precision lowp float;
-varying vec2 uv;
-uniform sampler2D Texture;
-uniform lowp float lowest_light_level;
-uniform lowp float window_height;
-const lowp float VISION_SIZE = 400.0;
+	let typing = console_open || chat_open;
 
-void main() {
-    gl_FragColor = texture2D(Texture, uv);
+	if !typing && is_key_pressed(KeyCode::J) {
+		game_info.show_quest_log = !game_info.show_quest_log;
+	}
 
-	float lighting = 1.0;
-	lighting *= lowest_light_level;
-	gl_FragColor.rgb *= vec3(lighting * 0.75);
+	if !typing && is_key_pressed(KeyCode::M) {
+		game_info.show_minimap = !game_info.show_minimap;
+	}
 
+	if is_key_pressed(KeyCode::F3) {
+		game_info.show_debug_overlay = !game_info.show_debug_overlay;
+	}
+
+	#[cfg(feature = "profiling")]
+	if is_key_pressed(KeyCode::F4) {
+		game_info.show_profiler = !game_info.show_profiler;
+		profiling::set_profiling(game_info.show_profiler);
+	}
+
+	if !typing && is_key_pressed(KeyCode::F2) {
+		game_info.photo_mode = match game_info.photo_mode {
+			Some(_) => None,
+			None => Some(PhotoMode::new(
+				game_info.game_state.players[game_info.local_players[0]].center(),
+			)),
+		};
+	}
+
+	if let Some(photo_mode) = &mut game_info.photo_mode {
+		photo_mode.update(&game_info.game_state.map.current_floor().floor);
+	}
+
+	// Photo mode can only pause the game when nobody else is playing
+	let paused = game_info.photo_mode.is_some() && !game_info.config_info.multiplayer();
+	// The camera keys in photo mode shouldn't move the player either
+	let ignore_input = typing || game_info.photo_mode.is_some();
+
+	#[cfg(feature = "native")]
+	game_info.gamepad_info.update();
+
+	let connection_lost = game_info.peers.connection_lost();
+
+	if paused || connection_lost {
+		// Otherwise the time spent paused would all be simulated at once afterwards
+		game_info.last_update = get_time();
+
+		// Still listen for whoever dropped, in case they come back
+		if connection_lost {
+			poll_session(game_info);
+		}
+	} else if !run_session(game_info, |game_info, i| {
+		let handle = game_info.local_players[i];
+		let player = &game_info.game_state.players[handle];
+
+		#[allow(unused_mut)]
+		let mut input = match (ignore_input, i) {
+			// Typing into the console or chat shouldn't move the player
+			(true, _) => PlayerInput::default(),
+			(false, 0) => movement_input(player, Some(handle), &game_info.cameras[0]),
+			#[cfg(feature = "native")]
+			(false, _) => gamepad_input(player, &game_info.gamepad_info),
+			#[cfg(not(feature = "native"))]
+			(false, _) => PlayerInput::default(),
+		};
+
+		// Whoever's on the keyboard is the one typing into the console
+		#[cfg(feature = "native")]
+		if let (0, Some(command)) = (i, game_info.console.pending.front()) {
+			input.set_command(command.encode());
+		}
+
+		input
+	}) {
+		render_game(game_info);
+		return None;
+	}
+
+	render_game(game_info);
+
+	#[cfg(feature = "profiling")]
+	if game_info.show_profiler && !profiling::draw_profiler() {
+		game_info.show_profiler = false;
+		profiling::set_profiling(false);
+	}
+
+	#[cfg(feature = "native")]
+	if game_info.photo_mode.is_some() && is_key_pressed(KeyCode::F12) {
+		let floor = game_info.game_state.map.current_floor_index();
+
+		match save_screenshot(game_info.game_state.seed, floor) {
+			Ok(path) => log::info!("Saved a screenshot to {}", path.display()),
+			Err(e) => log::error!("Couldn't save a screenshot: {e}"),
+		}
+	}
+
+	#[cfg(feature = "native")]
+	game_info
+		.console
+		.update(game_info.config_info.multiplayer());
+
+	// Peers would desync if their scripts changed partway through, so only
+	// singleplayer games pick up edits as they're saved
+	#[cfg(feature = "scripting")]
+	if !game_info.config_info.multiplayer() {
+		scripting::hot_reload();
+	}
+
+	#[cfg(not(feature = "web"))]
+	if let Some(chat) = &mut game_info.chat {
+		chat.update();
+
+		// Photo mode hides the chat along with the rest of the HUD
+		if game_info.photo_mode.is_none() {
+			chat.draw();
+		}
+	}
+
+	if connection_lost {
+		if let Some(new_screen) = connection_lost_prompt(game_info) {
+			return Some(new_screen);
+		}
+	}
+
+	if game_info.game_state.won_on_frame.is_some() {
+		game_info.victory = Some(VictoryScreen::new(
+			&game_info.game_state,
+			&mut game_info.progression,
+		));
+
+		return Some(Screen::Victory);
+	}
+
+	// Once nobody's left standing, there's no one to revive anyone else
+	if game_info.game_state.players.iter().all(|p| p.hp() == 0) {
+		if let Some(arena) = &game_info.game_state.arena {
+			game_info.progression.record_arena_score(arena.score());
+			reset_run(game_info);
+
+			return Some(Screen::MainMenu);
+		}
+
+		game_info.game_over = Some(GameOverScreen::new(&game_info.game_state));
+
+		return Some(Screen::GameOver);
+	}
+
+	None
 }
-";
-
-const DEFAULT_VERTEX_SHADER: &str = "
-#version 100
-precision lowp float;
-attribute vec3 position;
-attribute vec2 texcoord;
-varying vec2 uv;
-uniform mat4 Model;
-uniform mat4 Projection;
-
-void main() {
-    gl_Position = Projection * Model * vec4(position, 1);
-    uv = texcoord;
-}
-";
 
-const CAMERA_ZOOM: f32 = 0.0045;
+/// Polls the session and runs however many frames are due. Returns false if
+/// this frame is being skipped, so the other players can catch up
+fn run_session(
+	game_info: &mut GameInfo, local_input: impl Fn(&GameInfo, usize) -> PlayerInput,
+) -> bool {
+	if !poll_session(game_info) {
+		return true;
+	}
 
-pub const NUM_PLAYERS: usize = 2;
+	if game_info.frames_to_skip > 0 {
+		game_info.frames_to_skip -= 1;
+		return false;
+	}
 
-pub const FPS: f64 = 60.0;
+	// Taken out while frames run, since handling the requests needs the rest of
+	// the game info
+	let Some(mut net_session) = game_info.session.take() else {
+		return true;
+	};
 
-pub static mut NET_SESSION: Option<P2PSession<GGRSConfig>> = None;
+	let mut fps_delta = 1. / FPS;
+	if net_session.frames_ahead() > 0 {
+		fps_delta *= 1.1;
+	}
 
-fn update_game(game_info: &mut GameInfo) -> Option<Screen> {
-	if let Some(net_session) = unsafe { &mut NET_SESSION } {
-		net_session.poll_remote_clients();
+	// get delta time from last iteration and accumulate it
+	let now = get_time();
+	let delta = Duration::from_secs_f64(now - game_info.last_update);
+	game_info.accumulator = game_info.accumulator.saturating_add(delta);
+	game_info.last_update = now;
+
+	let mut presses_sent = false;
+
+	while game_info.accumulator.as_secs_f64() > fps_delta {
+		game_info.accumulator = game_info
+			.accumulator
+			.saturating_sub(Duration::from_secs_f64(fps_delta));
+
+		// Frames are only happening if sessions are synced
+		if net_session.current_state() == SessionState::Running {
+			// Add input for all local players
+			net_session
+				.local_player_handles()
+				.into_iter()
+				.for_each(|handle| {
+					// After continuing solo, the players who left are still around, but
+					// nobody's controlling them
+					let local_index = game_info.local_players.iter().position(|&h| h == handle);
+					let mut input = match local_index {
+						Some(i) => local_input(game_info, i),
+						None => PlayerInput::default(),
+					};
 
-		net_session.events().for_each(|ev| {
-			if let GGRSEvent::WaitRecommendation { skip_frames } = ev {
-				game_info.frames_to_skip = skip_frames
-			}
-		});
+					if presses_sent {
+						input.clear_presses();
+					}
 
-		if game_info.frames_to_skip > 0 {
-			game_info.frames_to_skip -= 1;
-			render_game(game_info);
-			return None;
+					net_session.add_local_input(handle, input).unwrap();
+				});
+
+			match net_session.advance_frame() {
+				Ok(requests) => {
+					handle_requests(requests, game_info);
+					presses_sent = true;
+
+					// The command's been sent, so the next frame can have the next one
+					#[cfg(feature = "native")]
+					game_info.console.pending.pop_front();
+				},
+				Err(ggrs::GGRSError::PredictionThreshold) => {
+					log::trace!("Frame {} skipped", net_session.current_frame());
+				},
+				Err(e) => log::error!("{e:?}"),
+			}
 		}
+	}
+
+	game_info.session = Some(net_session);
+
+	true
+}
+
+/// Starts the session once everyone's connected, and handles whatever it's
+/// reported since the last frame. Returns false if it hasn't started yet
+fn poll_session(game_info: &mut GameInfo) -> bool {
+	if let Some(session) = game_info.lobby.as_mut().and_then(|lobby| lobby.try_start()) {
+		game_info.lobby = None;
+		game_info.local_players = session.local_player_handles();
+		game_info.session = Some(session);
+	}
+
+	let Some(net_session) = &mut game_info.session else {
+		return false;
+	};
+
+	net_session.poll_remote_clients();
+
+	net_session.events().for_each(|ev| {
+		log::debug!("{ev:?}");
 
-		let mut fps_delta = 1. / FPS;
-		if net_session.frames_ahead() > 0 {
-			fps_delta *= 1.1;
+		game_info.peers.handle_event(&ev);
+
+		match ev {
+			GGRSEvent::WaitRecommendation { skip_frames } => game_info.frames_to_skip = skip_frames,
+			GGRSEvent::DesyncDetected {
+				frame,
+				local_checksum,
+				remote_checksum,
+				..
+			} => game_info
+				.desync
+				.desync_detected(frame, local_checksum, remote_checksum),
+			_ => (),
 		}
+	});
 
-		// get delta time from last iteration and accumulate it
-		let delta = Instant::now().duration_since(game_info.last_update);
-		game_info.accumulator = game_info.accumulator.saturating_add(delta);
-		game_info.last_update = Instant::now();
-
-		while game_info.accumulator.as_secs_f64() > fps_delta {
-			game_info.accumulator = game_info
-				.accumulator
-				.saturating_sub(Duration::from_secs_f64(fps_delta));
-
-			// Frames are only happening if sessions are synced
-			if net_session.current_state() == SessionState::Running {
-				// Add input for all local players
-				let local_input = movement_input(
-					&game_info.game_state.players[0],
-					Some(0),
-					&game_info.cameras[0],
-				);
+	true
+}
 
-				net_session
-					.local_player_handles()
-					.into_iter()
-					.for_each(|handle| {
-						net_session.add_local_input(handle, local_input).unwrap();
+/// Swaps the session for one with every player on this machine, so the run can
+/// carry on without whoever left
+fn continue_solo(game_info: &mut GameInfo) {
+	// The old session's socket has to be closed first
+	game_info.session = None;
+
+	match init_solo_net(game_info.game_state.players.len()) {
+		Ok(session) => {
+			game_info.session = Some(session);
+			game_info.peers = PeerStatuses::default();
+			game_info.desync = Default::default();
+			#[cfg(not(feature = "web"))]
+			{
+				game_info.chat = None;
+			}
+		},
+		Err(e) => log::error!("Couldn't continue solo: {e:?}"),
+	}
+}
+
+/// Shown while someone's stopped responding, or after they've been dropped
+/// from the session
+fn connection_lost_prompt(game_info: &mut GameInfo) -> Option<Screen> {
+	let mut new_screen = None;
+
+	egui_macroquad::ui(|egui_ctx| {
+		egui::Window::new(tr("disconnect-title"))
+			.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+			.collapsible(false)
+			.resizable(false)
+			.show(egui_ctx, |ui| {
+				let mut waiting = false;
+
+				game_info
+					.peers
+					.iter()
+					.filter(|(_, status)| *status != PeerStatus::Synchronized)
+					.for_each(|(addr, status)| {
+						waiting |= *status == PeerStatus::Interrupted;
+
+						ui.label(tr_args(
+							"lobby-peer",
+							[
+								("addr", addr.clone().into()),
+								("status", status.description().into()),
+							],
+						));
 					});
 
-				match net_session.advance_frame() {
-					Ok(requests) => {
-						handle_requests(requests, game_info);
-					},
-					Err(ggrs::GGRSError::PredictionThreshold) => {
-						// println!("Frame {} skipped",
-						// net_session.current_frame());
-					},
-					Err(e) => println!("{e:?}"),
+				if waiting {
+					ui.label(tr("disconnect-waiting"));
 				}
-			}
+
+				if ui.button(tr("disconnect-continue-solo")).clicked() {
+					continue_solo(game_info);
+				}
+
+				if ui.button(tr("menu-leave")).clicked() {
+					reset_run(game_info);
+					new_screen = Some(Screen::MainMenu);
+				}
+			});
+	});
+
+	egui_macroquad::draw();
+
+	new_screen
+}
+
+fn update_game_over(game_info: &mut GameInfo) -> Option<Screen> {
+	let done = game_info
+		.game_over
+		.as_mut()
+		.is_none_or(|game_over| game_over.update());
+
+	if !done {
+		return None;
+	}
+
+	reset_run(game_info);
+
+	Some(Screen::MainMenu)
+}
+
+fn update_victory(game_info: &mut GameInfo) -> Option<Screen> {
+	let done = game_info
+		.victory
+		.as_mut()
+		.is_none_or(|victory| victory.update());
+
+	if !done {
+		return None;
+	}
+
+	reset_run(game_info);
+
+	Some(Screen::MainMenu)
+}
+
+/// Starts the next run from scratch, so peers are in sync again
+fn reset_run(game_info: &mut GameInfo) {
+	// Leaving the lobby before anything happened shouldn't replace the last replay
+	if let Some(recording) = game_info.recording.take().filter(|r| !r.is_empty()) {
+		if let Err(e) = recording.save() {
+			log::error!("Couldn't save the replay: {e:?}");
 		}
 	}
 
-	render_game(game_info);
+	game_info.replay = None;
+	game_info.victory = None;
+	game_info.game_over = None;
+	game_info.photo_mode = None;
+	game_info.session = None;
+	#[cfg(not(feature = "web"))]
+	{
+		game_info.chat = None;
+	}
+	game_info.lobby = None;
+	game_info.game_state = GameState::new(
+		game_info.config_info.class(),
+		1,
+		GameMode::Dungeon,
+		game_info.config_info.seed(),
+		game_info.config_info.difficulty(),
+	);
+	game_info.event_queue = EventQueue::default();
+}
 
-	/*
-	#[cfg(feature = "native")]
-	while let Some(gilrs::Event {
-		id,
-		event: _,
-		time: _,
-	}) = game_info.gamepad_info.gilrs.next_event()
+/// Starts the session, generating a new game first if it's for a different
+/// game mode
+fn start_game(game_info: &mut GameInfo, mode: GameMode) -> Option<Screen> {
+	let seed = game_info.config_info.seed();
+	let difficulty = game_info.config_info.difficulty();
+
+	if game_info.game_state.mode != mode ||
+		game_info.game_state.seed != seed ||
+		game_info.game_state.difficulty != difficulty
 	{
-		game_info.gamepad_info.active_gamepad = Some(id);
+		game_info.game_state =
+			GameState::new(game_info.config_info.class(), 1, mode, seed, difficulty);
+	}
+
+	let config_info = game_info.config_info.clone();
+
+	match config_info.set_config(game_info) {
+		Ok(()) => {
+			game_info.net_error = None;
+			game_info.recording = Some(Replay::new(
+				seed,
+				difficulty,
+				mode,
+				config_info.class(),
+				config_info.num_players(),
+				config_info.friendly_fire(),
+			));
+
+			match game_info.game_state.lobby.is_some() {
+				true => Some(Screen::Lobby),
+				false => Some(Screen::Game),
+			}
+		},
+		Err(e) => {
+			log::error!("Couldn't start the session: {e:?}");
+			game_info.net_error = Some(e.to_string());
+			None
+		},
 	}
+}
 
+/// Plays back the last run from where it started
+fn watch_replay(game_info: &mut GameInfo, replay: Replay) -> Option<Screen> {
+	game_info.game_state = replay.initial_state();
+	game_info.local_players = vec![0];
+	game_info.event_queue = EventQueue::default();
+	game_info.accumulator = Duration::ZERO;
+	game_info.last_update = get_time();
+	game_info.replay = Some(replay);
 
-	game_info.viewport_screen_height = screen_height() * (1.0 / NUM_PLAYERS as f32);
+	Some(Screen::Replay)
+}
 
-	// Logic
-	game_info
-		.players
-		.iter_mut()
-		.for_each(|player| player.update_enchantments());
+/// Runs the replay's frames through the same requests a session would give,
+/// at the same speed
+fn update_replay(game_info: &mut GameInfo) -> Option<Screen> {
+	let fps_delta = Duration::from_secs_f64(1. / FPS);
 
-	#[cfg(feature = "native")]
-	if let Some(gamepad_id) = game_info.gamepad_info.active_gamepad {
-		let gamepad = game_info.gamepad_info.gilrs.gamepad(gamepad_id);
+	let now = get_time();
+	let delta = Duration::from_secs_f64(now - game_info.last_update);
+	game_info.accumulator = game_info.accumulator.saturating_add(delta);
+	game_info.last_update = now;
 
-		door_interaction_input_controller(
-			&game_info.players[1],
-			&game_info.players,
-			game_info.map.current_floor_mut(),
-			&gamepad,
-		)
+	let mut finished = false;
+
+	while game_info.accumulator > fps_delta {
+		game_info.accumulator -= fps_delta;
+
+		match game_info.replay.as_mut().and_then(Replay::next_request) {
+			Some(request) => handle_requests(vec![request], game_info),
+			None => {
+				finished = true;
+				break;
+			},
+		}
 	}
 
-	door_interaction_input(
-		&game_info.players[0],
-		&game_info.players,
-		game_info.map.current_floor_mut(),
-	);
+	render_game(game_info);
 
-	if game_info
-		.map
-		.current_floor()
-		.should_descend(&game_info.players)
-	{
-		game_info.map.descend(&mut game_info.players);
+	if finished || is_key_pressed(KeyCode::Escape) {
+		reset_run(game_info);
+		return Some(Screen::MainMenu);
 	}
-	*/
 
 	None
 }
 
 fn render_game(game_info: &mut GameInfo) {
+	roguelite::profile_scope!("render_game");
+
 	clear_background(BLACK);
 
-	game_info.material.set_uniform(
-		"window_height",
-		game_info.cameras[0].viewport.unwrap().3 as f32,
-	);
+	let num_cameras = game_info.local_players.len();
+	game_info.viewport_screen_height = screen_height() / num_cameras as f32;
+	game_info
+		.cameras
+		.resize_with(num_cameras, Camera2D::default);
+
+	game_info
+		.material
+		.set_uniform("window_height", game_info.viewport_screen_height);
 
 	let current_floor = game_info.game_state.map.current_floor_mut();
 
-	let exit = current_floor.exit().clone();
+	let exit = current_floor.exit().cloned();
+	let exit_locked = current_floor.exit_locked();
+	let descent_hint = current_floor.descent_hint();
+	let upstairs = current_floor.upstairs().cloned();
+	let biome = current_floor.biome();
+	let notice_board = current_floor.notice_board.clone();
+	let shop = current_floor.shop.clone();
+	let npcs = current_floor.npcs.clone();
+	let corpses = current_floor.corpses.clone();
+	// Only once the boss has noticed someone
+	let boss_bar = current_floor.monsters.iter().find_map(|m| {
+		m.target(&game_info.game_state.players)?;
+		Some((m.kind(), m.boss_health()?))
+	});
+	let vision_radius = current_floor.vision_radius();
+	let light_level = current_floor.light_level();
 
 	let objects = current_floor.floor.objects_mut();
 
 	let monsters = &mut current_floor.monsters;
 
-	objects
-		.par_iter_mut()
-		.for_each(|obj| obj.clear_currently_visible());
+	#[cfg(feature = "native")]
+	let objects_iter = objects.par_iter_mut();
+	#[cfg(not(feature = "native"))]
+	let objects_iter = objects.iter_mut();
 
-	game_info.game_state.players.iter().for_each(|player| {
-		Floor::set_visible_objects(player, None, objects);
-	});
+	objects_iter.for_each(|obj| obj.clear_currently_visible());
 
-	// Draw all objects that have been seen in the past but are not visible now
-	let seen_objects = objects
-		.iter()
-		.filter(|object: &&Object| object.has_been_seen() && !object.currently_visible());
+	game_info.visibility.set_visible_objects(
+		&game_info.game_state.players,
+		vision_radius,
+		objects,
+		&mut game_info.scratch,
+	);
 
 	let visible_objects: Vec<&Object> = objects
 		.iter()
 		.filter(|object| object.currently_visible())
 		.collect();
 
-	let monsters_to_draw = monsters.iter().filter(|m| {
-		let monster_tile_pos = pos_to_tile(&m.as_polygon());
-		visible_objects
-			.iter()
-			.any(|obj| obj.tile_pos() == monster_tile_pos)
+	let mut bestiary_changed = false;
+
+	game_info.event_queue.drain().for_each(|event| {
+		game_info
+			.floating_texts
+			.handle_event(&event, &game_info.game_state.players);
+
+		bestiary_changed |= game_info.progression.bestiary.record(&event);
 	});
 
-	let player = &game_info.game_state.players[0];
-	let camera = &mut game_info.cameras[0];
+	if bestiary_changed {
+		game_info.progression.save();
+	}
+	game_info.floating_texts.update();
 
-	camera.target = player.center();
+	// Every player on this machine gets a slice of the screen, from the top down
+	game_info
+		.local_players
+		.iter()
+		.zip(game_info.cameras.iter_mut())
+		.enumerate()
+		.for_each(|(i, (&handle, camera))| {
+			let player = &game_info.game_state.players[handle];
+
+			camera.target = match (&game_info.photo_mode, i) {
+				(Some(photo_mode), 0) => photo_mode.camera_pos,
+				_ => player.center(),
+			};
 
-	camera.zoom = Vec2::new(
-		CAMERA_ZOOM,
-		-CAMERA_ZOOM * (screen_width() / game_info.viewport_screen_height),
-	) * 0.7;
-	camera.viewport = Some((
-		0,
-		game_info.viewport_screen_height as i32 * 0 as i32,
-		screen_width() as i32,
-		game_info.viewport_screen_height as i32,
-	));
+			camera.zoom = Vec2::new(
+				CAMERA_ZOOM,
+				-CAMERA_ZOOM * (screen_width() / game_info.viewport_screen_height),
+			) * 0.7;
+			// Viewports are measured from the bottom of the screen, like OpenGL's
+			camera.viewport = Some((
+				0,
+				(game_info.viewport_screen_height * (num_cameras - 1 - i) as f32) as i32,
+				screen_width() as i32,
+				game_info.viewport_screen_height as i32,
+			));
+
+			set_camera(camera);
+
+			if player
+				.enchantments()
+				.get(&EnchantmentKind::Blinded)
+				.is_none()
+			{
+				// Draw all objects that have been seen in the past but are not visible now
+				let seen_objects = objects.iter().filter(|object: &&Object| {
+					object.has_been_seen() && !object.currently_visible()
+				});
 
-	set_camera(camera);
+				let monsters_to_draw = monsters.iter().filter(|m| {
+					let monster_tile_pos = pos_to_tile(&m.as_polygon());
+					visible_objects
+						.iter()
+						.any(|obj| obj.tile_pos() == monster_tile_pos)
+				});
 
-	if player
-		.enchantments()
-		.get(&EnchantmentKind::Blinded)
-		.is_none()
-	{
-		gl_use_material(game_info.material);
-		game_info
-			.material
-			.set_uniform("lowest_light_level", 0.6_f32);
+				gl_use_material(game_info.material);
+				game_info
+					.material
+					.set_uniform("lowest_light_level", 0.6 * light_level);
 
-		visible_objects.iter().for_each(|o| {
-			o.draw();
-			o.items().iter().rev().for_each(|item| {
-				item.draw();
-			});
-		});
+				visible_objects.iter().for_each(|o| {
+					o.draw_tinted(o.tint(biome));
 
-		// Draw all monsters on top of a visible object tile
-		monsters_to_draw.for_each(|m| m.draw());
+					if let Some(decoration) = o.decoration() {
+						decoration.draw(o.tile_pos());
+					}
 
-		game_info
-			.material
-			.set_uniform("lowest_light_level", 0.25_f32);
+					o.draw_effects();
+					o.draw_trap();
+
+					o.items().iter().rev().for_each(|item| {
+						item.draw();
+					});
+				});
+
+				corpses
+					.iter()
+					.filter(|corpse| {
+						visible_objects
+							.iter()
+							.any(|obj| obj.tile_pos() == corpse.tile_pos())
+					})
+					.for_each(|corpse| corpse.draw());
+
+				// Draw all monsters on top of a visible object tile
+				monsters_to_draw.for_each(|m| m.draw());
+
+				visible_objects
+					.iter()
+					.filter_map(|o| o.shrine())
+					.for_each(|shrine| shrine.draw());
+
+				visible_objects
+					.iter()
+					.filter_map(|o| o.chest())
+					.for_each(|chest| chest.draw());
+
+				visible_objects
+					.iter()
+					.filter_map(|o| o.prop())
+					.for_each(|prop| prop.draw());
+
+				if let Some(notice_board) = &notice_board {
+					if visible_objects
+						.iter()
+						.any(|obj| obj.tile_pos() == notice_board.tile_pos())
+					{
+						notice_board.draw();
+					}
+				}
+
+				shop.iter()
+					.filter(|shop_item| {
+						visible_objects
+							.iter()
+							.any(|obj| obj.tile_pos() == shop_item.tile_pos())
+					})
+					.for_each(|shop_item| shop_item.draw());
+
+				npcs.iter()
+					.filter(|npc| {
+						visible_objects
+							.iter()
+							.any(|obj| obj.tile_pos() == npc.tile_pos())
+					})
+					.for_each(|npc| npc.draw());
+
+				game_info
+					.material
+					.set_uniform("lowest_light_level", 0.25 * light_level);
+
+				seen_objects.for_each(|o| {
+					o.draw_tinted(o.tint(biome));
+				});
+
+				if let Some(exit) = &exit {
+					match exit_locked {
+						true => exit.draw_tinted(RED),
+						false => exit.draw(),
+					}
+				}
 
-		seen_objects.for_each(|o| {
-			o.draw();
+				if let Some(upstairs) = &upstairs {
+					upstairs.draw_tinted(SKYBLUE);
+				}
+
+				game_info
+					.material
+					.set_uniform("lowest_light_level", 0.6 * light_level);
+
+				visible_objects
+					.iter()
+					.flat_map(|o| o.items().iter())
+					.for_each(|i| i.draw());
+
+				game_info
+					.material
+					.set_uniform("lowest_light_level", 1.0_f32);
+
+				// Only warn about attacks from monsters that can be seen
+				game_info
+					.game_state
+					.telegraphs
+					.iter()
+					.filter(|telegraph| {
+						visible_objects
+							.iter()
+							.any(|obj| obj.tile_pos() == telegraph.tile_pos())
+					})
+					.for_each(|telegraph| telegraph.draw());
+
+				game_info.game_state.attacks.iter().for_each(|a| a.draw());
+			}
+
+			gl_use_default_material();
+			game_info.game_state.players.iter().for_each(|p| p.draw());
+
+			game_info.floating_texts.draw();
+
+			if game_info.show_debug_overlay {
+				draw_debug_world(
+					&game_info.game_state.players,
+					monsters,
+					&game_info.game_state.attacks,
+					&visible_objects,
+				);
+			}
 		});
 
-		exit.draw();
+	// Photo mode hides the HUD
+	if game_info.photo_mode.is_some() {
+		return;
+	}
 
-		game_info
-			.material
-			.set_uniform("lowest_light_level", 0.6_f32);
+	// Draw UI
+	draw_inventory(&game_info.game_state.players[game_info.local_players[0]]);
 
-		visible_objects
-			.iter()
-			.flat_map(|o| o.items().iter())
-			.for_each(|i| i.draw());
+	if game_info.show_minimap {
+		const MINIMAP_MARGIN: f32 = 10.0;
 
-		game_info
-			.material
-			.set_uniform("lowest_light_level", 1.0_f32);
+		game_info.minimap.update(
+			game_info.game_state.map.current_floor(),
+			game_info.game_state.map.current_floor_index(),
+		);
+
+		set_default_camera();
+		game_info.minimap.draw(
+			Vec2::new(screen_width(), screen_height()) -
+				Minimap::size() -
+				Vec2::splat(MINIMAP_MARGIN),
+			&game_info.game_state.players,
+		);
+	}
 
-		game_info.game_state.attacks.iter().for_each(|a| a.draw());
+	if let Some(frame) = game_info.desync.desynced_frame() {
+		root_ui().label(
+			Vec2::new(screen_width() / 2.0 - 100.0, 0.0),
+			&tr_args("hud-desynced", [("frame", frame.into())]),
+		);
 	}
 
-	gl_use_default_material();
-	game_info.game_state.players.iter().for_each(|p| p.draw());
+	if game_info.show_quest_log {
+		draw_quest_log(&game_info.game_state.quests);
+	}
 
-	// Draw UI
-	draw_inventory(player);
+	if game_info.show_debug_overlay {
+		draw_debug_stats(&game_info.game_state);
+	}
+
+	#[cfg(feature = "web")]
+	draw_touch_controls();
+
+	// Each player's stats go in the top right of their part of the screen
+	game_info
+		.local_players
+		.iter()
+		.enumerate()
+		.for_each(|(i, &handle)| {
+			let player = &game_info.game_state.players[handle];
+			let top = game_info.viewport_screen_height * i as f32;
+			let right = screen_width() - 150.0;
+
+			root_ui().label(
+				Vec2::new(right, top),
+				&tr_args("hud-hp", [("hp", player.hp().into())]),
+			);
+			root_ui().label(
+				Vec2::new(right, top + 10.0),
+				&tr_args("hud-mp", [("mp", player.mp().into())]),
+			);
+
+			if let Some(spell) = player.spells().first() {
+				root_ui().label(
+					Vec2::new(right, top + 20.0),
+					&match player.changing_spell {
+						false => tr_args("hud-spell", [("spell", spell.to_string().into())]),
+						true => tr("hud-cycling-spell"),
+					},
+				);
+			}
+
+			if let Some((ammo, count)) = player.ammo() {
+				root_ui().label(
+					Vec2::new(right, top + 40.0),
+					&tr_args(
+						"hud-ammo",
+						[
+							("item", ItemInfo::new(ammo, None).to_string().into()),
+							("count", count.into()),
+						],
+					),
+				);
+			}
+
+			if player.class() == PlayerClass::Rogue {
+				root_ui().label(
+					Vec2::new(right, top + 30.0),
+					&match player.hidden() {
+						true => tr("hud-hidden"),
+						false => tr("hud-seen"),
+					},
+				);
+			}
+
+			match player.life() {
+				LifeState::Up => (),
+				LifeState::Downed { bleed_out, .. } => root_ui().label(
+					Vec2::new(right, top + 50.0),
+					&tr_args(
+						"hud-downed",
+						[("seconds", ((bleed_out as f64 / FPS).ceil() as u32).into())],
+					),
+				),
+				LifeState::Dead => root_ui().label(Vec2::new(right, top + 50.0), &tr("hud-dead")),
+			}
+
+			// Play carries on while the player makes up their mind
+			if !player.boons_offered().is_empty() {
+				let left = screen_width() / 2.0 - 100.0;
+				let middle = top + game_info.viewport_screen_height / 2.0;
+
+				root_ui().label(
+					Vec2::new(left, middle - 60.0),
+					&tr_args("level-up-title", [("level", player.level.into())]),
+				);
+
+				player
+					.boons_offered()
+					.iter()
+					.enumerate()
+					.for_each(|(i, boon)| {
+						root_ui().label(
+							Vec2::new(left, middle - 45.0 + i as f32 * 12.0),
+							&tr_args(
+								"level-up-boon",
+								[("key", (i + 1).into()), ("boon", boon.to_string().into())],
+							),
+						);
+					});
+			}
+		});
+
+	if let Some(hint) = descent_hint {
+		root_ui().label(Vec2::new(screen_width() / 2.0 - 150.0, 10.0), &tr(hint));
+	}
+
+	if let Some((kind, health)) = boss_bar {
+		const BOSS_BAR_SIZE: Vec2 = Vec2::new(300.0, 10.0);
+		let left = screen_width() / 2.0 - BOSS_BAR_SIZE.x / 2.0;
+
+		set_default_camera();
+		root_ui().label(Vec2::new(left, 25.0), &kind.to_string());
+		draw_rectangle(left, 40.0, BOSS_BAR_SIZE.x, BOSS_BAR_SIZE.y, DARKGRAY);
+		draw_rectangle(left, 40.0, BOSS_BAR_SIZE.x * health, BOSS_BAR_SIZE.y, RED);
+	}
 
 	root_ui().label(
-		Vec2::new(
-			(camera.viewport.unwrap().2 - 150) as f32,
-			camera.viewport.unwrap().1 as f32,
-		),
-		&format!("HP: {}", player.hp()),
-	);
-	root_ui().label(
-		Vec2::new(
-			(camera.viewport.unwrap().2 - 150) as f32,
-			(camera.viewport.unwrap().1 + 10) as f32,
+		Vec2::new(0.0, screen_height() - 15.0),
+		&tr_args(
+			"hud-seed",
+			[("seed", game_info.game_state.seed.to_string().into())],
 		),
-		&format!("MP: {}", player.mp()),
 	);
 
-	if let Some(spell) = player.spells().first() {
-		root_ui().label(
-			Vec2::new(
-				(camera.viewport.unwrap().2 - 150) as f32,
-				(camera.viewport.unwrap().1 + 20) as f32,
+	if let Some(arena) = &game_info.game_state.arena {
+		let timer = match arena.phase() {
+			ArenaPhase::Wave => tr_args(
+				"hud-wave-time-left",
+				[("seconds", arena.seconds_left().into())],
 			),
-			&match player.changing_spell {
-				false => format!("Spell: {}", spell),
-				true => "Cycling Spell...".to_string(),
+			ArenaPhase::Break | ArenaPhase::Shopping => {
+				tr_args("hud-next-wave", [("seconds", arena.seconds_left().into())])
 			},
-		);
+		};
+
+		[
+			tr_args("hud-wave", [("wave", arena.wave().into())]),
+			tr_args("hud-score", [("score", arena.score().into())]),
+			timer,
+		]
+		.iter()
+		.enumerate()
+		.for_each(|(i, text)| {
+			root_ui().label(Vec2::new(0.0, i as f32 * 10.0), text);
+		});
 	}
 }
 
 enum Screen {
 	MainMenu,
 	Config,
+	Bestiary,
+	Lobby,
 	Game,
+	Victory,
+	GameOver,
+	Replay,
 }
 
 fn update_main_menu(game_info: &mut GameInfo) -> Option<Screen> {
@@ -354,45 +1015,99 @@ fn update_main_menu(game_info: &mut GameInfo) -> Option<Screen> {
 						.font(FontId::proportional(45.0)),
 				);
 
+				game_info.progression.unlocks.iter().for_each(|unlock| {
+					ui.label(RichText::new(tr(unlock.title_id())).color(egui::Color32::GOLD));
+				});
+
 				ui.add_space(25.0);
 
 				if ui
 					.button(
-						RichText::new("Play")
+						RichText::new(tr("menu-play"))
 							.strong()
 							.font(FontId::proportional(30.0)),
 					)
 					.clicked()
 				{
-					let config_info = game_info.config_info.clone();
-					config_info.set_config(game_info);
-
-					new_screen = Some(Screen::Game);
+					new_screen = start_game(game_info, GameMode::Dungeon);
 				}
 
 				ui.add_space(25.0);
 
 				if ui
 					.button(
-						RichText::new("Settings")
+						RichText::new(tr("menu-arena"))
 							.strong()
 							.font(FontId::proportional(30.0)),
 					)
 					.clicked()
 				{
-					new_screen = Some(Screen::Config);
+					new_screen = start_game(game_info, GameMode::Arena);
 				}
-			});
-		});
-	});
 
-	egui_macroquad::draw();
+				if game_info.progression.best_arena_score > 0 {
+					ui.label(tr_args(
+						"menu-best-arena-score",
+						[("score", game_info.progression.best_arena_score.into())],
+					));
+				}
 
-	new_screen
-}
+				if let Some(net_error) = &game_info.net_error {
+					ui.label(RichText::new(net_error).color(egui::Color32::RED));
+				}
 
-fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
-	let mut new_screen = None;
+				ui.add_space(25.0);
+
+				if ui
+					.button(
+						RichText::new(tr("menu-settings"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					)
+					.clicked()
+				{
+					new_screen = Some(Screen::Config);
+				}
+
+				ui.add_space(25.0);
+
+				if ui
+					.button(
+						RichText::new(tr("menu-bestiary"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					)
+					.clicked()
+				{
+					new_screen = Some(Screen::Bestiary);
+				}
+
+				ui.add_space(25.0);
+
+				if ui
+					.button(
+						RichText::new(tr("menu-watch-replay"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					)
+					.clicked()
+				{
+					match Replay::load() {
+						Ok(replay) => new_screen = watch_replay(game_info, replay),
+						Err(e) => log::error!("Couldn't load the last replay: {e:?}"),
+					}
+				}
+			});
+		});
+	});
+
+	egui_macroquad::draw();
+
+	new_screen
+}
+
+fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
+	let mut new_screen = None;
 
 	egui_macroquad::ui(|egui_ctx| {
 		egui_ctx.set_visuals(egui::Visuals::dark());
@@ -402,7 +1117,7 @@ fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
 				ui.spacing_mut().button_padding = egui::Vec2::new(30.0, 15.5);
 
 				ui.label(
-					RichText::new("Settings")
+					RichText::new(tr("menu-settings"))
 						.strong()
 						.font(FontId::proportional(45.0)),
 				);
@@ -413,7 +1128,7 @@ fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
 					let mut class_button = |class: PlayerClass| {
 						if ui
 							.radio(
-								&game_info.config_info.class() == &class,
+								game_info.config_info.class() == class,
 								RichText::new(class.to_string())
 									.strong()
 									.font(FontId::proportional(30.0)),
@@ -427,12 +1142,78 @@ fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
 					class_button(PlayerClass::Warrior);
 					class_button(PlayerClass::Wizard);
 					class_button(PlayerClass::Rogue);
+					class_button(PlayerClass::Cleric);
+					class_button(PlayerClass::Ranger);
+				});
+
+				ui.horizontal_top(|ui| {
+					let mut difficulty_button = |difficulty: Difficulty| {
+						if ui
+							.radio(
+								game_info.config_info.difficulty() == difficulty,
+								RichText::new(tr(difficulty.name_id()))
+									.strong()
+									.font(FontId::proportional(30.0)),
+							)
+							.clicked()
+						{
+							game_info.config_info.set_difficulty(difficulty);
+						}
+					};
+
+					difficulty_button(Difficulty::Easy);
+					difficulty_button(Difficulty::Normal);
+					difficulty_button(Difficulty::Hard);
+				});
+
+				ui.horizontal_top(|ui| {
+					languages().iter().for_each(|(code, name)| {
+						if ui
+							.radio(
+								game_info.config_info.language() == code,
+								RichText::new(name)
+									.strong()
+									.font(FontId::proportional(30.0)),
+							)
+							.clicked()
+						{
+							game_info.config_info.set_language(code);
+						}
+					});
+				});
+
+				ui.horizontal(|ui| {
+					ui.label(
+						RichText::new(tr("menu-seed"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					);
+
+					let mut seed_str = game_info.config_info.seed().to_string();
+
+					if ui.text_edit_singleline(&mut seed_str).changed() {
+						if let Ok(seed) = seed_str.parse() {
+							game_info.config_info.set_seed(seed);
+						}
+					}
+
+					if ui
+						.button(
+							RichText::new(tr("menu-random-seed"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						)
+						.clicked()
+					{
+						let millis = (macroquad::miniquad::date::now() * 1000.0) as u64;
+						game_info.config_info.set_seed(millis % 1_000_000);
+					}
 				});
 
 				ui.horizontal(|ui| {
 					let button_text = match game_info.config_info.multiplayer() {
-						false => "Singleplayer",
-						true => "Multiplayer",
+						false => tr("menu-singleplayer"),
+						true => tr("menu-multiplayer"),
 					};
 
 					if ui
@@ -447,52 +1228,398 @@ fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
 					}
 				});
 
+				// Browsers can't read gamepads
+				#[cfg(feature = "native")]
 				ui.horizontal(|ui| {
 					ui.label(
-						RichText::new("Local Port: ")
+						RichText::new(tr("menu-local-players"))
 							.strong()
 							.font(FontId::proportional(30.0)),
 					);
 
-					let mut local_port_str = game_info.config_info.local_port().to_string();
+					(1..=MAX_LOCAL_PLAYERS).for_each(|local_players| {
+						if ui
+							.radio(
+								game_info.config_info.local_players() == local_players,
+								RichText::new(local_players.to_string())
+									.strong()
+									.font(FontId::proportional(30.0)),
+							)
+							.clicked()
+						{
+							game_info.config_info.set_local_players(local_players);
+						}
+					});
+				});
 
-					ui.text_edit_singleline(&mut local_port_str);
+				if game_info.config_info.multiplayer() {
+					ui.horizontal(|ui| {
+						ui.label(
+							RichText::new(tr("menu-players"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						);
+
+						let local_players = game_info.config_info.local_players();
+
+						(local_players + 1..=MAX_PLAYERS).for_each(|num_players| {
+							if ui
+								.radio(
+									game_info.config_info.num_players() == num_players,
+									RichText::new(num_players.to_string())
+										.strong()
+										.font(FontId::proportional(30.0)),
+								)
+								.clicked()
+							{
+								game_info.config_info.set_num_players(num_players);
+							}
+						});
+					});
+				}
 
-					let new_local_port: u16 = match local_port_str.parse() {
-						Ok(port) => port,
-						Err(_) => 0,
-					};
+				// There's no one else to hit on your own
+				if game_info.config_info.num_players() > 1 {
+					let mut friendly_fire = game_info.config_info.friendly_fire();
 
-					game_info.config_info.set_local_port(new_local_port);
+					if ui
+						.checkbox(
+							&mut friendly_fire,
+							RichText::new(tr("menu-friendly-fire"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						)
+						.changed()
+					{
+						game_info.config_info.set_friendly_fire(friendly_fire);
+					}
+				}
+
+				#[cfg(not(feature = "web"))]
+				if game_info.config_info.multiplayer() {
+					ui.horizontal(|ui| {
+						ui.label(
+							RichText::new(tr("menu-player-number"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						);
+
+						let num_players = game_info.config_info.num_players();
+						let local_players = game_info.config_info.local_players();
+
+						(0..=num_players - local_players).for_each(|handle| {
+							if ui
+								.radio(
+									game_info.config_info.local_handle() == handle,
+									RichText::new((handle + 1).to_string())
+										.strong()
+										.font(FontId::proportional(30.0)),
+								)
+								.clicked()
+							{
+								game_info.config_info.set_local_handle(handle);
+							}
+						});
+					});
+				}
+
+				#[cfg(feature = "web")]
+				ui.horizontal(|ui| {
+					ui.label(
+						RichText::new(tr("menu-room"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					);
+
+					let mut room_url = game_info.config_info.room_url().to_string();
+
+					if ui.text_edit_singleline(&mut room_url).changed() {
+						game_info.config_info.set_room_url(room_url);
+					}
 				});
 
+				#[cfg(not(feature = "web"))]
 				ui.horizontal(|ui| {
 					ui.label(
-						RichText::new("Remote Port: ")
+						RichText::new(tr("menu-local-port"))
 							.strong()
 							.font(FontId::proportional(30.0)),
 					);
 
-					let mut remote_port_str = game_info.config_info.remote_port().to_string();
+					let mut local_port_str = game_info.config_info.local_port().to_string();
+
+					ui.text_edit_singleline(&mut local_port_str);
+
+					let new_local_port: u16 = local_port_str.parse().unwrap_or_default();
+
+					game_info.config_info.set_local_port(new_local_port);
+				});
+
+				// Remote players take up every handle besides the local ones, in order
+				#[cfg(not(feature = "web"))]
+				(0..game_info.config_info.remote_addrs().len()).for_each(|i| {
+					let handle = match i < game_info.config_info.local_handle() {
+						true => i,
+						false => i + game_info.config_info.local_players(),
+					};
+
+					ui.horizontal(|ui| {
+						ui.label(
+							RichText::new(tr_args(
+								"menu-remote-address",
+								[("player", (handle + 1).into())],
+							))
+							.strong()
+							.font(FontId::proportional(30.0)),
+						);
+
+						let mut remote_addr = game_info.config_info.remote_addrs()[i].clone();
+
+						if ui.text_edit_singleline(&mut remote_addr).changed() {
+							game_info.config_info.set_remote_addr(i, remote_addr);
+						}
+
+						if parse_remote_addr(&game_info.config_info.remote_addrs()[i]).is_err() {
+							ui.label(
+								RichText::new(tr("menu-invalid-address")).color(egui::Color32::RED),
+							);
+						}
+					});
+				});
+
+				// Only matters when there's someone else's inputs to wait for
+				if game_info.config_info.multiplayer() {
+					ui.horizontal(|ui| {
+						ui.label(
+							RichText::new(tr("menu-input-delay"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						);
+
+						let mut input_delay = game_info.config_info.input_delay();
+
+						if ui
+							.add(egui::Slider::new(&mut input_delay, 0..=MAX_INPUT_DELAY))
+							.changed()
+						{
+							game_info.config_info.set_input_delay(input_delay);
+						}
+					});
+
+					ui.horizontal(|ui| {
+						ui.label(
+							RichText::new(tr("menu-prediction-window"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						);
+
+						let mut max_prediction = game_info.config_info.max_prediction();
+
+						if ui
+							.add(egui::Slider::new(
+								&mut max_prediction,
+								1..=MAX_PREDICTION_WINDOW,
+							))
+							.changed()
+						{
+							game_info.config_info.set_max_prediction(max_prediction);
+						}
+					});
+
+					let mut sparse_saving = game_info.config_info.sparse_saving();
+
+					if ui
+						.checkbox(
+							&mut sparse_saving,
+							RichText::new(tr("menu-sparse-saving"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						)
+						.changed()
+					{
+						game_info.config_info.set_sparse_saving(sparse_saving);
+					}
+				}
+
+				if ui
+					.button(
+						RichText::new(tr("menu-back"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					)
+					.clicked()
+				{
+					new_screen = Some(Screen::MainMenu);
+				}
+			});
+		});
+	});
+
+	egui_macroquad::draw();
 
-					ui.text_edit_singleline(&mut remote_port_str);
+	new_screen
+}
 
-					let new_remote_port: u16 = match remote_port_str.parse() {
-						Ok(port) => port,
-						Err(_) => 0,
+fn update_bestiary(game_info: &mut GameInfo) -> Option<Screen> {
+	let mut new_screen = None;
+
+	egui_macroquad::ui(|egui_ctx| {
+		egui_ctx.set_visuals(egui::Visuals::dark());
+
+		egui::CentralPanel::default().show(egui_ctx, |ui| {
+			ui.vertical_centered(|ui| {
+				ui.spacing_mut().button_padding = egui::Vec2::new(30.0, 15.5);
+
+				ui.label(
+					RichText::new(tr("menu-bestiary"))
+						.strong()
+						.font(FontId::proportional(45.0)),
+				);
+
+				ui.add_space(25.0);
+
+				egui::ScrollArea::vertical()
+					.max_height(ui.available_height() - 100.0)
+					.show(ui, |ui| game_info.progression.bestiary.draw(ui));
+
+				ui.add_space(25.0);
+
+				if ui
+					.button(
+						RichText::new(tr("menu-back"))
+							.strong()
+							.font(FontId::proportional(30.0)),
+					)
+					.clicked()
+				{
+					new_screen = Some(Screen::MainMenu);
+				}
+			});
+		});
+	});
+
+	egui_macroquad::draw();
+
+	new_screen
+}
+
+fn update_lobby(game_info: &mut GameInfo) -> Option<Screen> {
+	run_session(game_info, |game_info, _| {
+		let mut input = PlayerInput::default();
+
+		if game_info.ready {
+			input.set_ready();
+		}
+
+		input.set_seed(game_info.game_state.seed);
+		input.set_mode(game_info.game_state.mode);
+		input.set_difficulty(game_info.game_state.difficulty);
+
+		if game_info.game_state.friendly_fire {
+			input.set_friendly_fire();
+		}
+
+		input
+	});
+
+	let Some(lobby) = game_info.game_state.lobby.clone() else {
+		return Some(Screen::Game);
+	};
+
+	let mut new_screen = None;
+
+	clear_background(WHITE);
+
+	egui_macroquad::ui(|egui_ctx| {
+		egui_ctx.set_visuals(egui::Visuals::dark());
+
+		egui::CentralPanel::default().show(egui_ctx, |ui| {
+			ui.vertical_centered(|ui| {
+				ui.spacing_mut().button_padding = egui::Vec2::new(30.0, 15.5);
+
+				ui.label(
+					RichText::new(tr("menu-lobby"))
+						.strong()
+						.font(FontId::proportional(45.0)),
+				);
+
+				ui.add_space(25.0);
+
+				// In browsers, the session only exists once everyone's joined the room
+				if game_info.lobby.is_some() {
+					ui.label(tr("lobby-waiting-for-players"));
+				}
+
+				game_info.peers.iter().for_each(|(addr, status)| {
+					ui.label(tr_args(
+						"lobby-peer",
+						[
+							("addr", addr.clone().into()),
+							("status", status.description().into()),
+						],
+					));
+				});
+
+				ui.add_space(25.0);
+
+				let game_state = &game_info.game_state;
+
+				lobby.iter().enumerate().for_each(|(handle, lobby_player)| {
+					let status = if lobby_player.seed != game_state.seed {
+						tr_args(
+							"lobby-different-seed",
+							[("seed", lobby_player.seed.to_string().into())],
+						)
+					} else if lobby_player.mode != game_state.mode {
+						tr_args(
+							"lobby-different-mode",
+							[("mode", tr(lobby_player.mode.name_id()).into())],
+						)
+					} else if lobby_player.difficulty != game_state.difficulty {
+						tr_args(
+							"lobby-different-difficulty",
+							[("difficulty", tr(lobby_player.difficulty.name_id()).into())],
+						)
+					} else if lobby_player.friendly_fire != game_state.friendly_fire {
+						match lobby_player.friendly_fire {
+							true => tr("lobby-friendly-fire-on"),
+							false => tr("lobby-friendly-fire-off"),
+						}
+					} else {
+						match lobby_player.ready {
+							true => tr("lobby-ready"),
+							false => tr("lobby-not-ready"),
+						}
+					};
+					let player = match game_info.local_players.contains(&handle) {
+						true => tr_args("lobby-you", [("player", (handle + 1).into())]),
+						false => tr_args("lobby-player", [("player", (handle + 1).into())]),
 					};
 
-					game_info.config_info.set_remote_port(new_remote_port);
+					ui.label(
+						RichText::new(format!("{player}: {status}"))
+							.font(FontId::proportional(25.0)),
+					);
 				});
 
+				ui.add_space(25.0);
+
+				ui.checkbox(
+					&mut game_info.ready,
+					RichText::new(tr("lobby-ready-toggle")).font(FontId::proportional(30.0)),
+				);
+
+				ui.add_space(25.0);
+
 				if ui
 					.button(
-						RichText::new("Back")
+						RichText::new(tr("menu-leave"))
 							.strong()
 							.font(FontId::proportional(30.0)),
 					)
 					.clicked()
 				{
+					reset_run(game_info);
 					new_screen = Some(Screen::MainMenu);
 				}
 			});
@@ -506,18 +1633,29 @@ fn config_game_update(game_info: &mut GameInfo) -> Option<Screen> {
 
 #[macroquad::main(window_conf)]
 async fn main() {
-	rand::srand(1000);
+	init_logging("roguelite.log");
+
+	// Touches are read directly, so they shouldn't also click the mouse
+	#[cfg(feature = "web")]
+	simulate_mouse_with_touch(false);
 
 	let mut game_info = init_game();
 
 	let mut update_fn: fn(&mut GameInfo) -> Option<Screen> = update_main_menu;
 
 	loop {
+		profiling::new_frame();
+
 		if let Some(new_screen) = update_fn(&mut game_info) {
 			let new_update_fn: fn(&mut GameInfo) -> Option<Screen> = match new_screen {
 				Screen::MainMenu => update_main_menu,
 				Screen::Game => update_game,
 				Screen::Config => config_game_update,
+				Screen::Bestiary => update_bestiary,
+				Screen::Lobby => update_lobby,
+				Screen::Victory => update_victory,
+				Screen::GameOver => update_game_over,
+				Screen::Replay => update_replay,
 			};
 
 			update_fn = new_update_fn;
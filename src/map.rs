@@ -1,27 +1,55 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
 use macroquad::prelude::*;
-use macroquad::rand;
-use macroquad::rand::*;
 use pathfinding::prelude::*;
-#[cfg(feature = "native")]
-use rayon::prelude::*;
 use serde::Serialize;
 
+use crate::ambush::Ambush;
+use crate::attacks::{Arrow, Attack, AttackObj};
+use crate::biome::Biome;
+use crate::corpses::Corpses;
+use crate::descent::DescentCondition;
+use crate::difficulty::{Difficulty, MonsterScaling};
+use crate::director::Director;
 use crate::draw::{load_my_image, Drawable};
 use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
-use crate::items::{ItemInfo, ItemType, PotionType};
+use crate::events::GameEvent;
+use crate::floor_modifier::FloorModifier;
+use crate::flow_field::FlowField;
+use crate::items::{ItemInfo, ItemType};
+use crate::localization::tr;
+use crate::loot::LootTable;
 use crate::math::{
 	aabb_collision,
 	aabb_collision_dir,
 	easy_polygon,
-	points_on_circumference,
-	points_on_line,
+	get_angle,
+	points_on_circumference_into,
+	points_on_line_into,
+	serialize_sorted,
 	AsPolygon,
 	Polygon,
 };
-use crate::monsters::{GreenSlime, Monster, MonsterObj, SmallRat};
-use crate::player::Player;
+use crate::monsters::{
+	Allegiance,
+	Disguise,
+	Mimic,
+	Monster,
+	MonsterKind,
+	MonsterObj,
+	RatKing,
+	SlimeQueen,
+};
+use crate::player::{damage_player, DamageType, Player, PLAYER_SIZE};
+use crate::prefabs::{Prefab, PrefabTile, PREFABS};
+use crate::props::{Decoration, Prop, PropKind};
+use crate::quests::NoticeBoard;
+use crate::rng::Rng;
+#[cfg(feature = "scripting")]
+use crate::scripting;
+use crate::shop::{ShopItem, SHOP_STOCK};
+use crate::shrines::Shrine;
 
 pub const TILE_SIZE: usize = 30;
 
@@ -30,21 +58,195 @@ pub const MAP_HEIGHT_TILES: usize = 50;
 
 pub const MAP_SIZE_TILES: IVec2 = IVec2::new(MAP_WIDTH_TILES as i32, MAP_HEIGHT_TILES as i32);
 
+/// The last floor, where the rat king waits instead of an exit
+pub const FINAL_FLOOR: usize = 5;
+
+/// Every this many floors is one big room with a boss guarding the way down
+const BOSS_FLOOR_INTERVAL: usize = 5;
+/// How many tiles across the boss room is
+const BOSS_ROOM_SIZE: i32 = 30;
+
+/// The item that opens a floor's exit when its descent condition is a sigil
+const SIGIL_KEY: &str = "floor_sigil";
+
+/// 1 in every this many rooms gets a shrine
+const SHRINE_CHANCE: u32 = 4;
+
+/// 1 in every this many rooms with monsters in it also has a rat nest or slime
+/// pool making more of them
+const SPAWNER_CHANCE: u32 = 6;
+/// 1 in every this many groups of monsters spawn asleep
+const SLEEP_CHANCE: u32 = 3;
+
+/// 1 in every this many dead end rooms has its door walled up, leaving a
+/// hidden passage to be found by searching
+const SECRET_ROOM_CHANCE: u32 = 5;
+
+/// 1 in every this many rooms is an ambush
+const AMBUSH_CHANCE: u32 = 6;
+
+/// 1 in every this many rooms gets a chest
+const CHEST_CHANCE: u32 = 3;
+/// 1 in every this many chests is a mimic instead, pretending to be either a
+/// chest or a pile of gold
+const MIMIC_CHANCE: u32 = 5;
+/// How many times a chest's loot table is rolled when it's opened
+const CHEST_ROLLS: usize = 2;
+
+/// There's an extra hallway, on top of the ones every room needs, for every
+/// this many rooms
+const EXTRA_HALLWAY_DIVISOR: usize = 3;
+
+/// The most prefabs that get stamped into a floor
+const MAX_PREFABS_PER_FLOOR: usize = 2;
+
+/// How many items each floor's vendor has for sale
+const VENDOR_STOCK_SIZE: usize = 3;
+/// How many tiles across, walls included, a room needs to be to fit a shop
+const SHOP_ROOM_MIN_SIZE: i32 = 6;
+
+/// 1 in every this many hallway tiles is a hazard
+const HALLWAY_HAZARD_CHANCE: u32 = 30;
+/// 1 in every this many room corners is a hazard
+const CORNER_HAZARD_CHANCE: u32 = 3;
+
+const SPIKE_DAMAGE: u16 = 5;
+
+/// 1 in every this many room tiles is decorated
+const DECORATION_CHANCE: u32 = 10;
+/// 1 in every this many tiles along a room's walls gets a prop
+const PROP_CHANCE: u32 = 4;
+/// Rooms need to be at least this big inside for a ring of pillars
+const PILLAR_ROOM_MIN_SIZE: i32 = 7;
+
+/// How much gold each pile on a treasure floor has, times the floor number
+const TREASURE_GOLD: u32 = 10;
+
+/// How many tiles down a corridor an arrow trap can shoot from
+const ARROW_TRAP_RANGE: i32 = 8;
+/// How many tiles out from a gas trap the gas spreads
+const POISON_GAS_RADIUS: i32 = 2;
+/// How long poison gas hangs around, in frames
+const POISON_GAS_FRAMES: u16 = 240;
+/// How far an alarm trap's heard when it's not in a room, in tiles
+const ALARM_RANGE: f32 = 10.0;
+/// How long effects that wear off take to fade out, in frames
+const EFFECT_FADE_FRAMES: u16 = 60;
+/// How close players need to get to a trap to spot it, in tiles
+const TRAP_SPOT_RANGE: f32 = 2.0;
+/// How many projectiles a door can take before it breaks
+const DOOR_HITS: u8 = 3;
+
 #[derive(Copy, Clone, Debug, Serialize)]
 enum TrapType {
 	Teleport,
 	SpawnMonster,
+	/// Shoots an arrow from down the corridor
+	Arrow,
+	/// Fills the tiles around it with poison gas
+	PoisonGas,
+	/// Wakes up every monster in the room
+	Alarm,
+}
+
+const TRAP_TYPES: [TrapType; 5] = [
+	TrapType::Teleport,
+	TrapType::SpawnMonster,
+	TrapType::Arrow,
+	TrapType::PoisonGas,
+	TrapType::Alarm,
+];
+
+#[cfg(feature = "scripting")]
+impl TrapType {
+	fn hook_name(&self) -> &'static str {
+		match self {
+			TrapType::Teleport => "trap_teleport",
+			TrapType::SpawnMonster => "trap_spawn_monster",
+			TrapType::Arrow => "trap_arrow",
+			TrapType::PoisonGas => "trap_poison_gas",
+			TrapType::Alarm => "trap_alarm",
+		}
+	}
 }
 
 #[derive(Copy, Clone, Debug, Serialize)]
 struct Trap {
 	triggered: bool,
 	trap_type: TrapType,
+	/// Someone's been close enough to see it. Only spotted traps are drawn, or
+	/// can be set off from range
+	spotted: bool,
+	/// Set off from range by this player's projectile, waiting to be sprung
+	tripped_by: Option<usize>,
 }
 
-#[derive(Copy, Clone, Debug, Serialize)]
-enum EffectType {
+impl Trap {
+	fn random(rng: &mut Rng) -> Self {
+		Trap {
+			triggered: false,
+			trap_type: *rng.choose(&TRAP_TYPES).unwrap(),
+			spotted: false,
+			tripped_by: None,
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum EffectType {
 	Slimed,
+	/// Burns whoever stands in it
+	Lava,
+	/// Hurts whoever steps on it, knocking them back off
+	Spikes,
+	/// Slows whoever wades through it
+	Water,
+	/// Left behind by gas traps, poisoning whoever breathes it in
+	PoisonGas,
+	/// Left behind by fireballs, setting whoever walks through it alight
+	Fire,
+}
+
+impl EffectType {
+	/// Flying monsters pass over these without being slowed down
+	fn slows(&self) -> bool { matches!(self, EffectType::Slimed | EffectType::Water) }
+
+	/// Hazards can't all go in hallways, or they'd block the way through
+	fn fits_in_hallway(&self) -> bool { !matches!(self, EffectType::Spikes) }
+
+	/// What standing on the tile does, if anything
+	fn enchantment(&self) -> Option<Enchantment> {
+		let (kind, strength) = match self {
+			EffectType::Slimed => (EnchantmentKind::Sticky, 1),
+			EffectType::Lava => (EnchantmentKind::Burning, 1),
+			EffectType::Spikes => return None,
+			EffectType::Water => (EnchantmentKind::Sticky, 2),
+			EffectType::PoisonGas => (EnchantmentKind::Poisoned, 1),
+			EffectType::Fire => (EnchantmentKind::Burning, 1),
+		};
+
+		Some(Enchantment { kind, strength })
+	}
+
+	/// Drawn over tiles the effect's only on for a while, like the ones attacks
+	/// leave behind
+	fn overlay_texture(&self) -> &'static str {
+		match self {
+			EffectType::Slimed => "slimeball.webp",
+			_ => "light_gray.webp",
+		}
+	}
+
+	fn tint(&self) -> Color {
+		match self {
+			EffectType::Slimed => Color::new(0.45, 0.85, 0.4, 1.0),
+			EffectType::Lava => Color::new(1.0, 0.35, 0.1, 1.0),
+			EffectType::Spikes => Color::new(0.55, 0.55, 0.6, 1.0),
+			EffectType::Water => Color::new(0.3, 0.5, 0.95, 1.0),
+			EffectType::PoisonGas => Color::new(0.6, 0.8, 0.2, 1.0),
+			EffectType::Fire => Color::new(1.0, 0.55, 0.15, 1.0),
+		}
+	}
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -53,15 +255,6 @@ struct Effect {
 	effect_type: EffectType,
 }
 
-impl Into<Enchantment> for EffectType {
-	fn into(self) -> Enchantment {
-		Enchantment {
-			strength: 1,
-			kind: EnchantmentKind::Sticky,
-		}
-	}
-}
-
 #[derive(Clone, Debug, Serialize)]
 pub struct Object {
 	pos: IVec2,
@@ -71,6 +264,16 @@ pub struct Object {
 	items: Vec<ItemInfo>,
 	door: Option<Door>,
 	trap: Option<Trap>,
+	shrine: Option<Shrine>,
+	/// A wall hiding the way into a secret room, or the floor it's become once
+	/// it's been found
+	secret: bool,
+	/// Where a shop's items are laid out
+	pedestal: bool,
+	chest: Option<Chest>,
+	decoration: Option<Decoration>,
+	prop: Option<Prop>,
+	#[serde(serialize_with = "serialize_sorted")]
 	effects: HashMap<EffectType, Effect>,
 }
 
@@ -84,6 +287,12 @@ impl Default for Object {
 			items: Vec::new(),
 			door: None,
 			trap: None,
+			shrine: None,
+			secret: false,
+			pedestal: false,
+			chest: None,
+			decoration: None,
+			prop: None,
 			effects: HashMap::new(),
 		}
 	}
@@ -97,6 +306,10 @@ impl Object {
 	pub fn tile_pos(&self) -> IVec2 { self.pos }
 
 	pub fn is_collidable(&self) -> bool {
+		if self.prop.is_some() {
+			return true;
+		}
+
 		if self.is_floor {
 			return false;
 		}
@@ -113,6 +326,81 @@ impl Object {
 
 	pub fn has_been_seen(&self) -> bool { self.has_been_seen }
 
+	pub fn is_floor(&self) -> bool { self.is_floor }
+
+	pub fn shrine(&self) -> Option<&Shrine> { self.shrine.as_ref() }
+
+	pub fn chest(&self) -> Option<&Chest> { self.chest.as_ref() }
+
+	pub fn decoration(&self) -> Option<Decoration> { self.decoration }
+
+	/// Floor with nothing at all on it, which a prop can go on
+	fn is_empty_floor(&self) -> bool {
+		self.is_floor &&
+			!self.secret &&
+			!self.pedestal &&
+			self.items.is_empty() &&
+			self.trap.is_none() &&
+			self.shrine.is_none() &&
+			self.chest.is_none() &&
+			self.prop.is_none() &&
+			self.effects.is_empty()
+	}
+
+	/// A wall with no way through it, hidden or otherwise
+	fn is_solid_wall(&self) -> bool { !self.is_floor && self.door.is_none() && !self.secret }
+
+	pub fn prop(&self) -> Option<&Prop> { self.prop.as_ref() }
+
+	/// Returns false if there wasn't a closed chest to open
+	pub fn open_chest(&mut self) -> bool {
+		match &mut self.chest {
+			Some(chest) if !chest.opened => {
+				chest.opened = true;
+				true
+			},
+			_ => false,
+		}
+	}
+
+	/// Turns a hidden passage into floor. Returns false if there wasn't one
+	pub fn reveal_passage(&mut self) -> bool {
+		match self.secret && !self.is_floor {
+			true => {
+				self.is_floor = true;
+				true
+			},
+			false => false,
+		}
+	}
+
+	/// Floors and walls take on the biome's colors, but doors, found passages
+	/// and hazards keep their own
+	pub fn tint(&self, biome: Biome) -> Color {
+		if self.secret && self.is_floor {
+			return Color::new(0.7, 0.55, 0.9, 1.0);
+		}
+
+		// Effects that wear off are drawn over the top instead
+		if let Some(effect) = self
+			.effects
+			.values()
+			.find(|effect| effect.time_til_dissipate.is_none())
+		{
+			return effect.effect_type.tint();
+		}
+
+		if self.pedestal {
+			return Color::new(0.85, 0.8, 0.7, 1.0);
+		}
+
+		match (self.is_floor, self.door) {
+			(true, _) | (false, Some(Door { broken: true, .. })) => biome.floor_tint(),
+			(false, None) => biome.wall_tint(),
+			(false, Some(_)) => WHITE,
+		}
+	}
+
 	pub fn items_mut(&mut self) -> &mut Vec<ItemInfo> { &mut self.items }
 
 	pub fn open_door(&mut self) {
@@ -127,6 +415,108 @@ impl Object {
 		}
 	}
 
+	/// Locked doors can't be broken
+	pub fn break_door(&mut self) {
+		if let Some(door) = &mut self.door {
+			if door.locked {
+				return;
+			}
+
+			door.broken = true;
+			door.open();
+		}
+	}
+
+	/// Knocks a chunk out of a closed door, breaking it once it's taken enough.
+	/// Returns whether it broke
+	fn chip_door(&mut self) -> bool {
+		let Some(door) = &mut self.door else {
+			return false;
+		};
+
+		if door.is_open || door.locked {
+			return false;
+		}
+
+		door.hits_left = door.hits_left.saturating_sub(1);
+
+		match door.hits_left {
+			0 => {
+				self.break_door();
+				true
+			},
+			_ => false,
+		}
+	}
+
+	/// Draws whatever's been left on the tile for a while, fading out as it
+	/// wears off
+	pub fn draw_effects(&self) {
+		let mut effects: Vec<&Effect> = self
+			.effects
+			.values()
+			.filter(|effect| effect.time_til_dissipate.is_some())
+			.collect();
+
+		// Always drawn in the same order, whatever order the map's in
+		effects.sort_by_key(|effect| effect.effect_type);
+
+		effects.into_iter().for_each(|effect| {
+			let frames_left = effect.time_til_dissipate.unwrap();
+			let mut color = effect.effect_type.tint();
+			color.a = 0.6 * (frames_left as f32 / EFFECT_FADE_FRAMES as f32).min(1.0);
+
+			let pos = self.pos();
+			let texture_params = DrawTextureParams {
+				dest_size: Some(self.size()),
+				..Default::default()
+			};
+
+			draw_texture_ex(
+				load_my_image(effect.effect_type.overlay_texture()),
+				pos.x,
+				pos.y,
+				color,
+				texture_params,
+			);
+		});
+	}
+
+	/// Marks a trap no one's set off yet, if it's been spotted
+	pub fn draw_trap(&self) {
+		let Some(trap) = self.trap else {
+			return;
+		};
+
+		if !trap.spotted || trap.triggered {
+			return;
+		}
+
+		let tile_size = TILE_SIZE as f32;
+		let pos = self.pos() + Vec2::splat(tile_size * 0.25);
+		let size = tile_size * 0.5;
+		let color = Color::new(0.8, 0.2, 0.2, 0.8);
+
+		draw_rectangle_lines(pos.x, pos.y, size, size, 2.0, color);
+		draw_line(pos.x, pos.y, pos.x + size, pos.y + size, 1.0, color);
+		draw_line(pos.x + size, pos.y, pos.x, pos.y + size, 1.0, color);
+	}
+
+	/// Closes and locks a door, or unlocks it. Broken doors can't be locked
+	pub fn set_door_locked(&mut self, locked: bool) {
+		if let Some(door) = &mut self.door {
+			if door.broken {
+				return;
+			}
+
+			door.locked = locked;
+
+			if locked {
+				door.is_open = false;
+			}
+		}
+	}
+
 	pub fn clear_currently_visible(&mut self) { self.is_currently_visible = false; }
 
 	pub fn currently_visible(&self) -> bool { self.is_currently_visible }
@@ -147,16 +537,122 @@ impl AsPolygon for &Object {
 	fn as_polygon(&self) -> Polygon { (*self).as_polygon() }
 }
 
+/// Someone living in the dungeon who doesn't want to fight
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum NpcKind {
+	/// Keeps the floor's shop
+	Vendor,
+}
+
+impl NpcKind {
+	pub fn name_id(&self) -> &'static str {
+		match self {
+			NpcKind::Vendor => "npc-vendor",
+		}
+	}
+}
+
+#[derive(Clone, Serialize)]
+pub struct Npc {
+	tile_pos: IVec2,
+	kind: NpcKind,
+}
+
+impl Npc {
+	pub fn new(tile_pos: IVec2, kind: NpcKind) -> Self { Self { tile_pos, kind } }
+
+	pub fn tile_pos(&self) -> IVec2 { self.tile_pos }
+
+	pub fn kind(&self) -> NpcKind { self.kind }
+}
+
+impl Drawable for Npc {
+	fn pos(&self) -> Vec2 { (self.tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2() }
+
+	fn size(&self) -> Vec2 { Vec2::splat(TILE_SIZE as f32) }
+
+	fn texture(&self) -> Option<Texture2D> { Some(load_my_image("generic_monster.webp")) }
+
+	fn draw(&self) {
+		let pos = self.pos();
+
+		self.draw_tinted(GOLD);
+		draw_text(&tr(self.kind.name_id()), pos.x, pos.y - 2.0, 12.0, WHITE);
+	}
+}
+
+/// Opened with the same key as doors, spilling out loot from the floor's chest
+/// loot table
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct Chest {
+	tile_pos: IVec2,
+	opened: bool,
+}
+
+impl Chest {
+	/// A chest that's never been opened, like the ones mimics pretend to be
+	pub fn closed(tile_pos: IVec2) -> Self {
+		Self {
+			tile_pos,
+			opened: false,
+		}
+	}
+
+	pub fn opened(&self) -> bool { self.opened }
+}
+
+impl Drawable for Chest {
+	fn size(&self) -> Vec2 { Vec2::new(TILE_SIZE as f32 * 0.7, TILE_SIZE as f32 * 0.5) }
+
+	fn pos(&self) -> Vec2 {
+		(self.tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2() +
+			Vec2::new(TILE_SIZE as f32 * 0.15, TILE_SIZE as f32 * 0.3)
+	}
+
+	fn draw(&self) {
+		let size = self.size();
+		let pos = self.pos();
+
+		match self.opened {
+			true => draw_rectangle(pos.x, pos.y, size.x, size.y, DARKBROWN),
+			false => {
+				draw_rectangle(pos.x, pos.y, size.x, size.y, BROWN);
+				draw_rectangle(pos.x, pos.y + size.y * 0.3, size.x, 2.0, GOLD);
+			},
+		}
+	}
+}
+
 #[derive(Copy, Clone, Debug, Serialize)]
 pub struct Door {
 	pos: IVec2,
 	pub is_open: bool,
+	/// Broken doors are stuck open
+	broken: bool,
+	/// Locked doors are stuck closed
+	locked: bool,
+	/// How many more projectiles it can take before it breaks
+	hits_left: u8,
 }
 
 impl Door {
-	pub fn open(&mut self) { self.is_open = true; }
+	pub fn open(&mut self) {
+		if !self.locked {
+			self.is_open = true;
+		}
+	}
+
+	pub fn close(&mut self) {
+		if !self.broken {
+			self.is_open = false;
+		}
+	}
 
-	pub fn close(&mut self) { self.is_open = false; }
+	pub fn broken(&self) -> bool { self.broken }
+
+	pub fn locked(&self) -> bool { self.locked }
+
+	pub fn tile_pos(&self) -> IVec2 { self.pos }
 }
 
 #[derive(Clone, Serialize)]
@@ -164,14 +660,19 @@ pub struct Room {
 	top_left: IVec2,
 	bottom_right: IVec2,
 	doors: Vec<Door>,
+	/// Whether a prefab's been stamped over the room
+	prefab: bool,
+	/// Walls the prefab put inside the room
+	inner_walls: Vec<IVec2>,
 }
 
 impl Room {
 	pub fn extents(&self) -> (IVec2, IVec2) { (self.top_left, self.bottom_right) }
 
+	pub fn doors(&self) -> &[Door] { &self.doors }
+
 	fn generate_walls(&self) -> Vec<IVec2> {
 		(self.top_left.x..self.bottom_right.x)
-			.into_iter()
 			.flat_map(|x| {
 				[
 					IVec2::new(x, self.top_left.y),
@@ -179,17 +680,13 @@ impl Room {
 				]
 				.into_iter()
 			})
-			.chain(
-				(self.top_left.y..=self.bottom_right.y)
-					.into_iter()
-					.flat_map(|y| {
-						[
-							IVec2::new(self.top_left.x, y),
-							IVec2::new(self.bottom_right.x, y),
-						]
-						.into_iter()
-					}),
-			)
+			.chain((self.top_left.y..=self.bottom_right.y).flat_map(|y| {
+				[
+					IVec2::new(self.top_left.x, y),
+					IVec2::new(self.bottom_right.x, y),
+				]
+				.into_iter()
+			}))
 			.collect()
 	}
 
@@ -212,37 +709,24 @@ impl Room {
 			.collect()
 	}
 
-	fn generate_floor(&self) -> Vec<Object> {
-		let map_object = |x: i32, y: i32| -> Object {
-			// 1 in 250 chance of being a trapped tile
-			let is_trap: bool = rand::gen_range(1, 250) == 100;
+	fn generate_floor(&self, biome: Biome, rng: &mut Rng) -> Vec<Object> {
+		let item_pool = biome.item_pool();
+
+		let mut map_object = |pos: IVec2| -> Object {
+			let is_trap: bool = rng.gen_range(0, biome.trap_chance()) == 0;
 
 			let trap = match is_trap {
-				true => Some(Trap {
-					triggered: false,
-					trap_type: match rand::rand() > u32::MAX / 2 {
-						true => TrapType::Teleport,
-						false => TrapType::SpawnMonster,
-					},
-				}),
+				true => Some(Trap::random(rng)),
 				false => None,
 			};
 
-			let _texture = match is_trap {
-				true => load_my_image("trap.webp"),
-				false => load_my_image("light_gray.webp"),
-			};
-
-			// 1 in every 100 tiles have a 1 in 10 chance of having gold
+			// 1 in every 50 tiles has something from the biome's items
 			let mut items = Vec::new();
 
-			let pos = IVec2::new(x, y);
-
-			if rand::gen_range(0, 50) == 25 {
-				items.push(ItemInfo::new(
-					ItemType::Potion(PotionType::Regeneration),
-					Some(pos),
-				));
+			if rng.gen_range(0, 50) == 25 {
+				if let Some(item_type) = rng.choose(&item_pool) {
+					items.push(ItemInfo::new(*item_type, Some(pos)));
+				}
 			}
 
 			Object {
@@ -256,398 +740,771 @@ impl Room {
 			}
 		};
 
-		((self.top_left.x..self.bottom_right.x)
-			.into_iter()
-			.flat_map(|x| {
-				(self.top_left.y..self.bottom_right.y)
-					.into_iter()
-					.map(move |y| map_object(x, y))
-			}))
-		.collect()
+		(self.top_left.x..self.bottom_right.x)
+			.flat_map(|x| (self.top_left.y..self.bottom_right.y).map(move |y| IVec2::new(x, y)))
+			.map(&mut map_object)
+			.collect()
 	}
 
 	/// Returns whether or not a position is inside a room
-	fn inside_room(&self, pos: IVec2) -> bool {
+	pub fn inside_room(&self, pos: IVec2) -> bool {
 		pos.cmpgt(self.top_left).all() && pos.cmplt(self.bottom_right).all()
 	}
 
 	pub fn center(&self) -> IVec2 { (self.top_left + self.bottom_right) / 2 }
-}
 
-#[derive(Clone, Serialize)]
-pub struct FloorInfo {
-	spawn: Vec2,
-	monster_types: Vec<MonsterObj>,
-	item_types: Vec<ItemType>,
-	pub monsters: Vec<MonsterObj>,
-	pub floor: Floor,
-	rooms: Vec<Room>,
-	exit: Object,
-}
+	fn on_wall(&self, pos: IVec2) -> bool {
+		pos.cmpge(self.top_left).all() &&
+			pos.cmple(self.bottom_right).all() &&
+			!self.inside_room(pos)
+	}
 
-impl FloorInfo {
-	pub fn new(_floor_num: usize) -> Self {
-		let mut rooms = Vec::new();
+	/// Doors can't go in the corners
+	fn is_corner(&self, pos: IVec2) -> bool {
+		(pos.x == self.top_left.x || pos.x == self.bottom_right.x) &&
+			(pos.y == self.top_left.y || pos.y == self.bottom_right.y)
+	}
 
-		// First, try to flll the map with as many rooms as possible
-		for _ in 0..100_000 {
-			const MIN_SIZE: i32 = 8;
-			const MAX_SIZE: i32 = 14;
+	/// A random tile inside the room that isn't one of its inner walls
+	fn random_tile(&self, rng: &mut Rng) -> IVec2 {
+		let mut random_tile = || {
+			IVec2::new(
+				rng.gen_range(self.top_left.x + 1, self.bottom_right.x - 1),
+				rng.gen_range(self.top_left.y + 1, self.bottom_right.y - 1),
+			)
+		};
 
-			let top_left = IVec2::new(
-				rand::gen_range(0, MAP_WIDTH_TILES as i32),
-				rand::gen_range(0, MAP_HEIGHT_TILES as i32),
-			);
-			let bottom_right = top_left +
-				IVec2::new(
-					rand::gen_range(MIN_SIZE, MAX_SIZE),
-					rand::gen_range(MIN_SIZE, MAX_SIZE),
-				);
+		// A prefab could have walled off most of the room, so this gives up
+		// eventually
+		let mut tile_pos = random_tile();
 
-			// Fail if the map extends past the map border
-			if bottom_right
-				.cmpgt(IVec2::new(MAP_WIDTH_TILES as i32, MAP_HEIGHT_TILES as i32))
-				.any()
-			{
-				continue;
+		for _ in 0..20 {
+			if !self.inner_walls.contains(&tile_pos) {
+				break;
 			}
 
-			if !rooms.iter().any(|room: &Room| {
-				// Don't let rooms be just one room apart, since moving through doors fro both
-				// rooms is annoying to the player Also don't let rooms collide w each other
-				const MIN_DISTANCE_BETWEEN_ROOMS: i32 = 2;
-
-				(room.bottom_right + MIN_DISTANCE_BETWEEN_ROOMS)
-					.cmpgt(top_left - MIN_DISTANCE_BETWEEN_ROOMS)
-					.all() && (bottom_right + MIN_DISTANCE_BETWEEN_ROOMS)
-					.cmpgt(room.top_left - MIN_DISTANCE_BETWEEN_ROOMS)
-					.all()
-			}) {
-				rooms.push(Room {
-					top_left,
-					bottom_right,
-					doors: Vec::new(),
-				})
-			}
+			tile_pos = random_tile();
 		}
 
-		// Then, remove rooms until we have the number of rooms we actually wanted
-		rooms.shuffle();
-		// rooms.drain(0..(rooms.len() - MAX_NUM_ROOMS));
-		// assert!(rooms.len() == MAX_NUM_ROOMS);
+		tile_pos
+	}
 
-		let mut hallways: Vec<IVec2> = rooms //[0..(rooms.len() * 3) / 4]
+	/// Where each of the prefab's tiles would go if it were put in the middle
+	/// of the room. The tiles just inside the doors are always left open. None
+	/// if the prefab would wall any of the doors off from the others
+	fn fit_prefab(&self, prefab: &Prefab) -> Option<Vec<(IVec2, PrefabTile)>> {
+		let interior_top_left = self.top_left + IVec2::ONE;
+		let interior_bottom_right = self.bottom_right - IVec2::ONE;
+		let interior_size = interior_bottom_right - interior_top_left + IVec2::ONE;
+		let offset = interior_top_left + (interior_size - prefab.size()) / 2;
+
+		let entrances: Vec<IVec2> = self
+			.doors
 			.iter()
-			.flat_map(|room: &Room| {
-				let (top_left_room, bottom_right_room) = (room.top_left, room.bottom_right);
-
-				let other_room = rooms.choose().unwrap();
-				let (top_left_other_room, bottom_right_other_room) =
-					(other_room.top_left, other_room.bottom_right);
+			.map(|door| door.pos.clamp(interior_top_left, interior_bottom_right))
+			.collect();
 
-				let room_center = (top_left_room + bottom_right_room) / 2;
-				let other_room_center = (top_left_other_room + bottom_right_other_room) / 2;
+		let tiles: Vec<(IVec2, PrefabTile)> = prefab
+			.tiles()
+			.map(|(pos, tile)| {
+				let tile_pos = offset + pos;
 
-				let (mut left_room, mut right_room) = match room_center.x < other_room_center.x {
-					true => (room_center, other_room_center),
-					false => (other_room_center, room_center),
-				};
+				match tile == PrefabTile::Wall && entrances.contains(&tile_pos) {
+					true => (tile_pos, PrefabTile::Floor),
+					false => (tile_pos, tile),
+				}
+			})
+			.collect();
 
-				let center_touching_wall = |room: &Room, center_ivec4: IVec4| -> bool {
-					let room_extremes = IVec4::new(
-						room.top_left.x,
-						room.top_left.y,
-						room.bottom_right.x,
-						room.bottom_right.y,
-					);
-					center_ivec4.cmpeq(room_extremes).any()
-				};
+		let walls: HashSet<IVec2> = tiles
+			.iter()
+			.filter(|(_, tile)| *tile == PrefabTile::Wall)
+			.map(|(tile_pos, _)| *tile_pos)
+			.collect();
 
-				let left_room_ivec4 =
-					IVec4::new(left_room.x, left_room.y, left_room.x, left_room.y);
+		// Flood fill from one entrance, to make sure it can still reach the others
+		let mut reached = HashSet::new();
+		let mut to_visit: Vec<IVec2> = entrances.first().copied().into_iter().collect();
 
-				let right_room_ivec4 =
-					IVec4::new(right_room.x, right_room.y, right_room.x, right_room.y);
+		while let Some(pos) = to_visit.pop() {
+			if walls.contains(&pos) || !self.inside_room(pos) || !reached.insert(pos) {
+				continue;
+			}
 
-				if rooms
-					.iter()
-					.any(|room| center_touching_wall(room, left_room_ivec4))
-				{
-					let new_left_room = (-2..=2).into_iter().find_map(|change| {
-						let left_room_x_change = left_room + IVec2::new(change, 0);
-						let left_room_y_change = left_room + IVec2::new(0, change);
-
-						let left_room_x_change_ivec4 = IVec4::new(
-							left_room_x_change.x,
-							left_room_x_change.y,
-							left_room_x_change.x,
-							left_room_x_change.y,
-						);
+			to_visit.extend([IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|dir| pos + dir));
+		}
 
-						let left_room_y_change_ivec4 = IVec4::new(
-							left_room_y_change.x,
-							left_room_y_change.y,
-							left_room_y_change.x,
-							left_room_y_change.y,
-						);
+		entrances
+			.iter()
+			.all(|entrance| reached.contains(entrance))
+			.then_some(tiles)
+	}
 
-						rooms.iter().find_map(|room_extremes| {
-							if !center_touching_wall(room_extremes, left_room_x_change_ivec4) {
-								Some(left_room_x_change)
-							} else if !center_touching_wall(room_extremes, left_room_y_change_ivec4)
-							{
-								Some(left_room_y_change)
-							} else {
-								None
-							}
-						})
-					});
+	/// Whether any of the NPCs are in the room
+	pub fn has_npc(&self, npcs: &[Npc]) -> bool {
+		npcs.iter().any(|npc| self.inside_room(npc.tile_pos))
+	}
 
-					if let Some(new_left_room) = new_left_room {
-						left_room = new_left_room;
-					} else {
-						return None;
-					}
-				}
+	/// The floor tiles in each corner of the room
+	/// Every tile inside the room, row by row
+	fn interior(&self) -> impl Iterator<Item = IVec2> + '_ {
+		(self.top_left.y + 1..self.bottom_right.y).flat_map(move |y| {
+			(self.top_left.x + 1..self.bottom_right.x).map(move |x| IVec2::new(x, y))
+		})
+	}
 
-				if rooms
-					.iter()
-					.any(|room_extremes| center_touching_wall(room_extremes, right_room_ivec4))
-				{
-					let new_right_room = (-2..=2).into_iter().find_map(|change| {
-						let right_room_x_change = right_room + IVec2::new(change, 0);
-						let right_room_y_change = right_room + IVec2::new(0, change);
-
-						let right_room_x_change_ivec4 = IVec4::new(
-							right_room_x_change.x,
-							right_room_x_change.y,
-							right_room_x_change.x,
-							right_room_x_change.y,
-						);
+	/// Whether the tile's inside the room, up against one of its walls
+	fn along_wall(&self, pos: IVec2) -> bool {
+		self.inside_room(pos) &&
+			[IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+				.iter()
+				.any(|offset| self.on_wall(pos + *offset))
+	}
 
-						let right_room_y_change_ivec4 = IVec4::new(
-							right_room_y_change.x,
-							right_room_y_change.y,
-							right_room_y_change.x,
-							right_room_y_change.y,
-						);
+	/// A ring of four pillars, three tiles in from each corner. None if the
+	/// room isn't big enough to walk around them
+	fn pillar_tiles(&self) -> impl Iterator<Item = IVec2> {
+		let inside_size = self.bottom_right - self.top_left - IVec2::ONE;
+		let big_enough = inside_size.cmpge(IVec2::splat(PILLAR_ROOM_MIN_SIZE)).all();
 
-						rooms.iter().find_map(|room_extremes| {
-							if !center_touching_wall(room_extremes, right_room_x_change_ivec4) {
-								Some(right_room_x_change)
-							} else if !center_touching_wall(
-								room_extremes,
-								right_room_y_change_ivec4,
-							) {
-								Some(right_room_y_change)
-							} else {
-								None
-							}
-						})
-					});
+		let near = self.top_left + IVec2::splat(3);
+		let far = self.bottom_right - IVec2::splat(3);
 
-					if let Some(new_right_room) = new_right_room {
-						right_room = new_right_room;
-					} else {
-						return None;
-					}
-				}
-				Some(
-					(left_room.x..=right_room.x)
-						.into_iter()
-						.map(move |x| IVec2::new(x, left_room.y))
-						.chain(
-							((left_room.y.min(right_room.y) - 1)..=left_room.y.max(right_room.y))
-								.into_iter()
-								.map(move |y| IVec2::new(right_room.x, y)),
-						),
-				)
+		big_enough
+			.then(|| {
+				[
+					near,
+					IVec2::new(far.x, near.y),
+					IVec2::new(near.x, far.y),
+					far,
+				]
 			})
+			.into_iter()
 			.flatten()
-			.collect();
+	}
 
-		rooms.iter_mut().for_each(|room| {
-			let room_walls = room.generate_walls();
+	fn corners(&self) -> [IVec2; 4] {
+		[
+			self.top_left + IVec2::ONE,
+			IVec2::new(self.bottom_right.x - 1, self.top_left.y + 1),
+			IVec2::new(self.top_left.x + 1, self.bottom_right.y - 1),
+			self.bottom_right - IVec2::ONE,
+		]
+	}
+}
 
-			room_walls
-				.iter()
-				.filter(|w| hallways.iter().any(|h| h == *w))
-				.for_each(|&door_pos| {
-					// Fix a bug where doors can pop up in the corners of rooms
-					if door_pos != room.top_left &&
-						door_pos != room.bottom_right &&
-						door_pos != IVec2::new(room.bottom_right.x, room.top_left.y) &&
-						door_pos != IVec2::new(room.top_left.x, room.bottom_right.y)
-					{
-						room.doors.push(Door {
-							pos: door_pos,
-							is_open: false,
-						});
-					}
-				});
-		});
+fn is_boss_floor(floor_num: usize) -> bool {
+	// The final floor already has the rat king
+	(floor_num + 1) % BOSS_FLOOR_INTERVAL == 0 && floor_num != FINAL_FLOOR
+}
 
-		// Only keep rooms that have a door
-		rooms.retain(|room| !room.doors.is_empty());
+/// Lays out the walls, floors and doors of the rooms and the hallways between
+/// them
+fn build_floor(
+	rooms: &[Room], hallways: &[IVec2], biome: Biome, scaling: MonsterScaling, rng: &mut Rng,
+) -> Floor {
+	// Actually render all of the walls
+	let walls = (0..MAP_WIDTH_TILES as i32).flat_map(|x| {
+		{
+			[
+				Object {
+					pos: IVec2::new(x, 0),
+					door: None,
+					has_been_seen: false,
+					is_floor: false,
+					items: Vec::new(),
+					trap: None,
+					..Default::default()
+				},
+				Object {
+					pos: IVec2::new(x, MAP_HEIGHT_TILES as i32),
+					door: None,
+					has_been_seen: false,
+					is_floor: false,
+					items: Vec::new(),
+					trap: None,
 
-		// Remove all "hallway" positions inside of a room
-		hallways.retain(|h| !rooms.iter().any(|r| r.inside_room(*h)));
+					..Default::default()
+				},
+			]
+			.into_iter()
+		}
+		.chain((0..=MAP_HEIGHT_TILES as i32).flat_map(|y| {
+			[
+				Object {
+					pos: IVec2::new(0, y),
+					door: None,
+					has_been_seen: false,
+					is_floor: false,
+					items: Vec::new(),
+					trap: None,
 
-		// Actually render all of the walls
-		let walls = (0..MAP_WIDTH_TILES as i32).into_iter().flat_map(|x| {
-			{
-				[
-					Object {
-						pos: IVec2::new(x, 0),
-						door: None,
-						has_been_seen: false,
-						is_floor: false,
-						items: Vec::new(),
-						trap: None,
-						..Default::default()
-					},
-					Object {
-						pos: IVec2::new(x, MAP_HEIGHT_TILES as i32),
-						door: None,
-						has_been_seen: false,
-						is_floor: false,
-						items: Vec::new(),
-						trap: None,
-
-						..Default::default()
-					},
-				]
-				.into_iter()
+					..Default::default()
+				},
+				Object {
+					pos: IVec2::new(MAP_WIDTH_TILES as i32, y),
+					door: None,
+					has_been_seen: false,
+					is_floor: false,
+					items: Vec::new(),
+					trap: None,
+
+					..Default::default()
+				},
+			]
+			.into_iter()
+		}))
+	});
+
+	let room_walls = rooms
+		.iter()
+		.flat_map(|room: &Room| room.generate_wall_objects());
+
+	let dungeon_walls = (0..MAP_WIDTH_TILES).flat_map(|x| {
+		(0..MAP_HEIGHT_TILES).filter_map(move |y| {
+			let pos = IVec2::new(x as i32, y as i32);
+			let in_room = rooms.iter().any(|r| r.inside_room(pos));
+			let is_hallway = hallways.contains(&pos);
+
+			let is_dungeon_wall = !in_room && !is_hallway;
+
+			if is_dungeon_wall {
+				Some(Object {
+					pos,
+					is_floor: false,
+					has_been_seen: false,
+					items: Vec::new(),
+					door: None,
+					trap: None,
+
+					..Default::default()
+				})
+			} else {
+				None
 			}
-			.chain((0..=MAP_HEIGHT_TILES as i32).into_iter().flat_map(|y| {
-				[
-					Object {
-						pos: IVec2::new(0, y),
-						door: None,
-						has_been_seen: false,
-						is_floor: false,
-						items: Vec::new(),
-						trap: None,
-
-						..Default::default()
-					},
-					Object {
-						pos: IVec2::new(MAP_WIDTH_TILES as i32, y),
-						door: None,
-						has_been_seen: false,
-						is_floor: false,
-						items: Vec::new(),
-						trap: None,
-
-						..Default::default()
-					},
-				]
-				.into_iter()
-			}))
+		})
+	});
+
+	let collidable_objects: Vec<Object> = walls.chain(room_walls).chain(dungeon_walls).collect();
+
+	let background_objects: Vec<Object> = hallways
+		.iter()
+		.map(|&pos| Object {
+			pos,
+			door: None,
+			has_been_seen: false,
+			items: Vec::new(),
+			trap: None,
+			is_floor: true,
+			..Default::default()
+		})
+		.chain(rooms.iter().flat_map(|r| r.generate_floor(biome, rng)))
+		.collect();
+
+	let mut objects: Vec<_> = (0..collidable_objects.len() + background_objects.len())
+		.map(|_| None)
+		.collect();
+
+	background_objects
+		.into_iter()
+		.chain(collidable_objects)
+		.for_each(|obj| {
+			let new_obj = &mut objects[(obj.pos.x + obj.pos.y * MAP_WIDTH_TILES as i32) as usize];
+			*new_obj = Some(obj)
 		});
 
-		let room_walls = rooms
+	let objects = objects
+		.into_iter()
+		.enumerate()
+		.map(|(i, obj)| match obj {
+			Some(obj) => obj,
+			None => Object {
+				pos: IVec2::new((i % MAP_WIDTH_TILES) as i32, (i / MAP_HEIGHT_TILES) as i32),
+				..Default::default()
+			},
+		})
+		.collect();
+
+	let mut floor = Floor {
+		objects,
+		scaling,
+		flow_field: FlowField::default(),
+	};
+	place_hazards(&mut floor, rooms, hallways, biome, rng);
+
+	floor
+}
+
+/// Fills some of the hallways and room corners with the biome's hazards
+fn place_hazards(
+	floor: &mut Floor, rooms: &[Room], hallways: &[IVec2], biome: Biome, rng: &mut Rng,
+) {
+	let hazards = biome.hazards();
+	let hallway_hazards: Vec<EffectType> = hazards
+		.iter()
+		.copied()
+		.filter(EffectType::fits_in_hallway)
+		.collect();
+
+	let mut hazard_tiles = Vec::new();
+
+	hallways.iter().for_each(|&pos| {
+		if rng.gen_range(0, HALLWAY_HAZARD_CHANCE) == 0 {
+			if let Some(effect_type) = rng.choose(&hallway_hazards) {
+				hazard_tiles.push((pos, *effect_type));
+			}
+		}
+	});
+
+	rooms.iter().flat_map(Room::corners).for_each(|pos| {
+		if rng.gen_range(0, CORNER_HAZARD_CHANCE) == 0 {
+			if let Some(effect_type) = rng.choose(hazards) {
+				hazard_tiles.push((pos, *effect_type));
+			}
+		}
+	});
+
+	hazard_tiles.into_iter().for_each(|(pos, effect_type)| {
+		if let Some(object) = floor.get_object_from_pos_mut(pos) {
+			if object.is_floor && object.trap.is_none() {
+				object.effects.insert(
+					effect_type,
+					Effect {
+						time_til_dissipate: None,
+						effect_type,
+					},
+				);
+			}
+		}
+	});
+}
+
+/// Stamps one or two prefabs over some of the rooms, leaving at least two plain
+/// ones for the spawn point and exit. Rooms in `skip` are left alone
+fn stamp_prefabs(
+	floor: &mut Floor, rooms: &mut [Room], skip: &[usize], biome: Biome, floor_num: usize,
+	rng: &mut Rng,
+) {
+	let prefabs: Vec<&Prefab> = PREFABS
+		.iter()
+		.filter(|prefab| prefab.min_floor <= floor_num)
+		.collect();
+
+	let mut candidates: Vec<usize> = (0..rooms.len()).filter(|i| !skip.contains(i)).collect();
+	rng.shuffle(&mut candidates);
+
+	let num_prefabs = rng
+		.gen_range(1, MAX_PREFABS_PER_FLOOR + 1)
+		.min(candidates.len().saturating_sub(2));
+	let mut stamped = 0;
+
+	for i in candidates {
+		if stamped == num_prefabs {
+			break;
+		}
+
+		let room = &mut rooms[i];
+		let interior_size = room.bottom_right - room.top_left - IVec2::ONE;
+
+		let fitting: Vec<&Prefab> = prefabs
 			.iter()
-			.flat_map(|room: &Room| room.generate_wall_objects());
+			.copied()
+			.filter(|prefab| prefab.size().cmple(interior_size).all())
+			.collect();
 
-		let rooms_ref = &rooms;
-		let hallways_ref = &hallways;
+		let Some(tiles) = rng
+			.choose(&fitting)
+			.and_then(|prefab| room.fit_prefab(prefab))
+		else {
+			continue;
+		};
 
-		let dungeon_walls = (0..MAP_WIDTH_TILES).into_iter().flat_map(|x| {
-			(0..MAP_HEIGHT_TILES).into_iter().filter_map(move |y| {
-				let pos = IVec2::new(x as i32, y as i32);
-				let in_room = rooms_ref.iter().any(|r| r.inside_room(pos));
-				let is_hallway = hallways_ref.iter().any(|h| *h == pos);
+		tiles.into_iter().for_each(|(tile_pos, tile)| {
+			if tile == PrefabTile::Unchanged {
+				return;
+			}
 
-				let is_dungeon_wall = !in_room && !is_hallway;
+			let Some(object) = floor.get_object_from_pos_mut(tile_pos) else {
+				return;
+			};
 
-				if is_dungeon_wall {
-					Some(Object {
-						pos,
-						is_floor: false,
-						has_been_seen: false,
-						items: Vec::new(),
-						door: None,
-						trap: None,
+			*object = Object {
+				pos: tile_pos,
+				is_floor: tile != PrefabTile::Wall,
+				..Default::default()
+			};
 
-						..Default::default()
-					})
-				} else {
-					None
+			match tile {
+				PrefabTile::Wall => room.inner_walls.push(tile_pos),
+				PrefabTile::Trap => object.trap = Some(Trap::random(rng)),
+				PrefabTile::Shrine => {
+					object.shrine = Some(Shrine::generate(tile_pos, floor_num, rng));
+				},
+				PrefabTile::Chest => {
+					object.chest = Some(Chest {
+						tile_pos,
+						opened: false,
+					});
+				},
+				PrefabTile::Gold => object.items.push(ItemInfo::new(
+					ItemType::Gold(25 * (floor_num as u32 + 1)),
+					Some(tile_pos),
+				)),
+				PrefabTile::Hazard => {
+					if let Some(&effect_type) = rng.choose(biome.hazards()) {
+						object.effects.insert(
+							effect_type,
+							Effect {
+								time_til_dissipate: None,
+								effect_type,
+							},
+						);
+					}
+				},
+				PrefabTile::Floor | PrefabTile::Unchanged => (),
+			}
+		});
+
+		room.prefab = true;
+		stamped += 1;
+	}
+}
+
+/// Fills the map with as many rooms as will fit
+fn generate_rooms(rng: &mut Rng) -> Vec<Room> {
+	let mut rooms = Vec::new();
+
+	// First, try to flll the map with as many rooms as possible
+	for _ in 0..100_000 {
+		const MIN_SIZE: i32 = 8;
+		const MAX_SIZE: i32 = 14;
+
+		let top_left = IVec2::new(
+			rng.gen_range(0, MAP_WIDTH_TILES as i32),
+			rng.gen_range(0, MAP_HEIGHT_TILES as i32),
+		);
+		let bottom_right = top_left +
+			IVec2::new(
+				rng.gen_range(MIN_SIZE, MAX_SIZE),
+				rng.gen_range(MIN_SIZE, MAX_SIZE),
+			);
+
+		// Fail if the map extends past the map border
+		if bottom_right
+			.cmpgt(IVec2::new(MAP_WIDTH_TILES as i32, MAP_HEIGHT_TILES as i32))
+			.any()
+		{
+			continue;
+		}
+
+		if !rooms.iter().any(|room: &Room| {
+			// Don't let rooms be just one room apart, since moving through doors fro both
+			// rooms is annoying to the player Also don't let rooms collide w each other
+			const MIN_DISTANCE_BETWEEN_ROOMS: i32 = 2;
+
+			(room.bottom_right + MIN_DISTANCE_BETWEEN_ROOMS)
+				.cmpgt(top_left - MIN_DISTANCE_BETWEEN_ROOMS)
+				.all() && (bottom_right + MIN_DISTANCE_BETWEEN_ROOMS)
+				.cmpgt(room.top_left - MIN_DISTANCE_BETWEEN_ROOMS)
+				.all()
+		}) {
+			rooms.push(Room {
+				top_left,
+				bottom_right,
+				doors: Vec::new(),
+				prefab: false,
+				inner_walls: Vec::new(),
+			})
+		}
+	}
+
+	// Then, remove rooms until we have the number of rooms we actually wanted
+	rng.shuffle(&mut rooms);
+	// rooms.drain(0..(rooms.len() - MAX_NUM_ROOMS));
+	// assert!(rooms.len() == MAX_NUM_ROOMS);
+
+	rooms
+}
+
+/// Joins every room up with hallways along a minimum spanning tree of their
+/// centers, plus a few extra so that not every room is a dead end. Doors go
+/// wherever a hallway cuts through a room's wall
+fn connect_rooms(rooms: &mut [Room], rng: &mut Rng) -> Vec<IVec2> {
+	let centers: Vec<IVec2> = rooms.iter().map(Room::center).collect();
+	let distance = |a: usize, b: usize| {
+		let diff = centers[a] - centers[b];
+		diff.x * diff.x + diff.y * diff.y
+	};
+
+	// Prim's algorithm, joining the closest room that isn't connected yet to one
+	// that is, until they all are
+	let mut connected = vec![false; rooms.len()];
+	let mut edges: Vec<(usize, usize)> = Vec::new();
+
+	if let Some(first) = connected.first_mut() {
+		*first = true;
+	}
+
+	for _ in 1..rooms.len() {
+		let closest = (0..rooms.len())
+			.filter(|&a| connected[a])
+			.flat_map(|a| {
+				(0..rooms.len())
+					.filter(|&b| !connected[b])
+					.map(move |b| (a, b))
+			})
+			.min_by_key(|&(a, b)| distance(a, b));
+
+		if let Some((a, b)) = closest {
+			connected[b] = true;
+			edges.push((a, b));
+		}
+	}
+
+	(0..rooms.len() / EXTRA_HALLWAY_DIVISOR).for_each(|_| {
+		let a = rng.gen_range(0, rooms.len());
+
+		let closest = (0..rooms.len())
+			.filter(|&b| b != a && !edges.contains(&(a, b)) && !edges.contains(&(b, a)))
+			.min_by_key(|&b| distance(a, b));
+
+		if let Some(b) = closest {
+			edges.push((a, b));
+		}
+	});
+
+	let mut hallways: Vec<IVec2> = Vec::new();
+
+	edges.iter().for_each(|&(a, b)| {
+		if let Some(path) = hallway_path(rooms, centers[a], centers[b]) {
+			path.into_iter().for_each(|pos| {
+				if !hallways.contains(&pos) {
+					hallways.push(pos);
 				}
+			});
+		}
+	});
+
+	rooms.iter_mut().for_each(|room| {
+		let doors: Vec<Door> = room
+			.generate_walls()
+			.into_iter()
+			.filter(|wall| !room.is_corner(*wall) && hallways.contains(wall))
+			.map(|pos| Door {
+				pos,
+				is_open: false,
+				broken: false,
+				locked: false,
+				hits_left: DOOR_HITS,
+			})
+			.collect();
+
+		room.doors = doors;
+	});
+
+	// Remove all "hallway" positions inside of a room
+	hallways.retain(|h| !rooms.iter().any(|r| r.inside_room(*h)));
+
+	hallways
+}
+
+/// The cheapest way from one room's center to another's. Hallways can go
+/// through other rooms, but cutting through a wall costs a lot, so they don't
+/// make any more doors than they need to, and they can't go through corners
+fn hallway_path(rooms: &[Room], start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+	const WALL_COST: u32 = 20;
+
+	dijkstra(
+		&start,
+		|&pos| {
+			[IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+				.into_iter()
+				.map(move |dir| pos + dir)
+				// The edge of the map is always wall
+				.filter(|next| {
+					next.cmpgt(IVec2::ZERO).all() && next.cmplt(MAP_SIZE_TILES - IVec2::ONE).all()
+				})
+				.filter_map(|next| match rooms.iter().find(|room| room.on_wall(next)) {
+					Some(room) if room.is_corner(next) => None,
+					Some(_) => Some((next, WALL_COST)),
+					None => Some((next, 1)),
+				})
+				.collect::<Vec<_>>()
+		},
+		|&pos| pos == goal,
+	)
+	.map(|(path, _)| path)
+}
+
+/// Whether every room can be reached from the first one through the doors and
+/// hallways
+fn all_rooms_connected(rooms: &[Room], hallways: &[IVec2]) -> bool {
+	let Some(first) = rooms.first() else {
+		return true;
+	};
+
+	let hallways: HashSet<IVec2> = hallways.iter().copied().collect();
+	let walkable = |pos: IVec2| {
+		hallways.contains(&pos) ||
+			rooms.iter().any(|room| {
+				room.inside_room(pos) || room.doors.iter().any(|door| door.pos == pos)
 			})
+	};
+
+	let mut reached = HashSet::new();
+	let mut to_visit = vec![first.center()];
+
+	while let Some(pos) = to_visit.pop() {
+		if !walkable(pos) || !reached.insert(pos) {
+			continue;
+		}
+
+		to_visit.extend([IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|dir| pos + dir));
+	}
+
+	rooms.iter().all(|room| reached.contains(&room.center()))
+}
+
+#[derive(Clone, Serialize)]
+pub struct FloorInfo {
+	biome: Biome,
+	spawn: Vec2,
+	monster_types: Vec<MonsterKind>,
+	item_types: Vec<ItemType>,
+	pub monsters: Vec<MonsterObj>,
+	pub floor: Floor,
+	pub notice_board: Option<NoticeBoard>,
+	rooms: Vec<Room>,
+	pub ambushes: Vec<Ambush>,
+	/// Sends in more monsters once things have been quiet for a while
+	pub director: Option<Director>,
+	/// Items for sale, in the vendor's room or between arena waves
+	pub shop: Vec<ShopItem>,
+	pub npcs: Vec<Npc>,
+	pub chest_loot: LootTable,
+	pub monster_loot: LootTable,
+	/// Recent deaths, for necromancers to raise
+	pub corpses: Corpses,
+	/// The final floor has no way down
+	exit: Option<Object>,
+	/// At the spawn point, on every floor but the first
+	upstairs: Option<Object>,
+	/// Until the floor's descent condition is met
+	exit_locked: bool,
+	descent: DescentCondition,
+	modifier: Option<FloorModifier>,
+}
+
+impl FloorInfo {
+	pub fn new(floor_num: usize, difficulty: Difficulty, rng: &mut Rng) -> Self {
+		let biome = Biome::for_floor(floor_num);
+		let scaling = MonsterScaling::new(floor_num, difficulty);
+
+		if is_boss_floor(floor_num) {
+			return Self::boss(floor_num, biome, scaling, rng);
+		}
+
+		// Laying out the hallways should always reach every room, but if it ever
+		// doesn't, a whole new layout gets tried rather than leaving any cut off
+		let (mut rooms, hallways) = loop {
+			let mut rooms = generate_rooms(rng);
+			let hallways = connect_rooms(&mut rooms, rng);
+
+			if all_rooms_connected(&rooms, &hallways) {
+				break (rooms, hallways);
+			}
+
+			log::warn!("Floor {floor_num} had rooms cut off, laying it out again");
+		};
+
+		let is_final_floor = floor_num == FINAL_FLOOR;
+
+		let modifier = match is_final_floor {
+			true => None,
+			false => FloorModifier::roll(floor_num, rng),
+		};
+
+		let descent = match is_final_floor {
+			true => DescentCondition::Open,
+			false => DescentCondition::roll(floor_num, rng),
+		};
+
+		// Nothing's behind a dead end, so walling one up can't cut anywhere else
+		// off. The rat king's room can't be hidden, and there has to be
+		// somewhere left for the spawn point and exit
+		let mut secret_rooms = Vec::new();
+
+		if !is_final_floor {
+			rooms.iter().enumerate().for_each(|(i, room)| {
+				if room.doors.len() == 1 &&
+					rooms.len() - secret_rooms.len() > 2 &&
+					rng.gen_range(0, SECRET_ROOM_CHANCE) == 0
+				{
+					secret_rooms.push(i);
+				}
+			});
+		}
+
+		let secret_passages: Vec<IVec2> = secret_rooms
+			.iter()
+			.map(|&i| rooms[i].doors.remove(0).pos)
+			.collect();
+
+		let mut floor = build_floor(&rooms, &hallways, biome, scaling, rng);
+
+		secret_passages.iter().for_each(|&pos| {
+			if let Some(object) = floor.get_object_from_pos_mut(pos) {
+				object.secret = true;
+			}
+		});
+
+		// Secret rooms have something worth finding in them
+		secret_rooms.iter().for_each(|&i| {
+			let center = rooms[i].center();
+
+			if let Some(object) = floor.get_object_from_pos_mut(center) {
+				object.items.push(ItemInfo::new(
+					ItemType::Gold(50 * (floor_num as u32 + 1)),
+					Some(center),
+				));
+			}
 		});
 
-		let collidable_objects: Vec<Object> =
-			walls.chain(room_walls).chain(dungeon_walls).collect();
+		// Secret rooms don't have any doors for a prefab to line up with
+		if !is_final_floor {
+			stamp_prefabs(&mut floor, &mut rooms, &secret_rooms, biome, floor_num, rng);
+		}
 
-		let background_objects: Vec<Object> = hallways
+		let open_rooms: Vec<&Room> = rooms
 			.iter()
-			.map(|&pos| Object {
-				pos,
-				door: None,
-				has_been_seen: false,
-				items: Vec::new(),
-				trap: None,
-				is_floor: true,
-				..Default::default()
-			})
-			.chain(rooms.iter().flat_map(|r| r.generate_floor()))
+			.enumerate()
+			.filter(|(i, room)| !secret_rooms.contains(i) && !room.prefab)
+			.map(|(_, room)| room)
 			.collect();
 
-		let spawn = rooms
-			.choose()
+		let spawn = rng
+			.choose(&open_rooms)
 			.map(|r| {
 				(((r.top_left + r.bottom_right) / 2) * IVec2::splat(TILE_SIZE as i32)).as_vec2()
 			})
 			.unwrap();
 
-		let exit_pos = rooms
-			.choose()
+		let exit_pos = rng
+			.choose(&open_rooms)
 			.map(|r| (r.top_left + r.bottom_right) / 2)
 			.unwrap();
 
 		// let spawn = (exit_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2() +
 		// Vec2::splat(TILE_SIZE as f32);
 
-		let mut objects: Vec<_> = (0..collidable_objects.len() + background_objects.len())
-			.into_iter()
-			.map(|_| None)
-			.collect();
-
-		background_objects
-			.into_iter()
-			.chain(collidable_objects.into_iter())
-			.for_each(|obj| {
-				let new_obj =
-					&mut objects[(obj.pos.x + obj.pos.y * MAP_WIDTH_TILES as i32) as usize];
-				*new_obj = Some(obj)
-			});
-
-		let objects = objects
-			.into_iter()
-			.enumerate()
-			.map(|(i, obj)| match obj {
-				Some(obj) => obj,
-				None => Object {
-					pos: IVec2::new((i % MAP_WIDTH_TILES) as i32, (i / MAP_HEIGHT_TILES) as i32),
-					..Default::default()
-				},
-			})
-			.collect();
-
-		let floor = Floor { objects };
-
 		let mut floor_info = FloorInfo {
-			monster_types: vec![MonsterObj::SmallRat(SmallRat::new(Vec2::ZERO))],
-			item_types: vec![
-				ItemType::Gold(20),
-				ItemType::Potion(PotionType::Regeneration),
-			],
+			biome,
+			monster_types: biome.monster_kinds().to_vec(),
+			item_types: biome.item_pool(),
 			spawn,
 			floor,
 			rooms,
-			exit: Object {
+			exit: (!is_final_floor).then(|| Object {
 				pos: exit_pos,
 				door: None,
 				has_been_seen: false,
@@ -655,66 +1512,757 @@ impl FloorInfo {
 				items: Vec::new(),
 				is_floor: true,
 
-				..Default::default()
-			},
-			monsters: Vec::new(),
-		};
+				..Default::default()
+			}),
+			upstairs: upstairs(floor_num, spawn),
+			exit_locked: descent.locks_exit(),
+			descent,
+			monsters: Vec::new(),
+			notice_board: None,
+			ambushes: Vec::new(),
+			director: (!is_final_floor).then(|| Director::new(floor_num)),
+			shop: Vec::new(),
+			npcs: Vec::new(),
+			chest_loot: LootTable::chest(floor_num),
+			monster_loot: LootTable::monster(floor_num),
+			corpses: Corpses::default(),
+			modifier,
+		};
+
+		if !is_final_floor {
+			floor_info.place_shop(rng);
+		}
+
+		floor_info.place_shrines(floor_num, rng);
+		floor_info.place_chests(rng);
+
+		if modifier == Some(FloorModifier::Treasure) {
+			floor_info.place_treasure(floor_num, rng);
+		}
+
+		match is_final_floor {
+			true => floor_info.spawn_boss(),
+			false => {
+				floor_info.spawn_monsters(rng);
+
+				match descent {
+					DescentCondition::SlayGuardian => floor_info.spawn_guardian(),
+					DescentCondition::Sigil => floor_info.place_sigil(rng),
+					DescentCondition::Open | DescentCondition::Gathered => (),
+				}
+
+				floor_info.place_ambushes(floor_num, rng);
+				floor_info.notice_board =
+					Some(NoticeBoard::generate(&mut floor_info, floor_num, rng));
+			},
+		}
+
+		floor_info.decorate_rooms(rng);
+
+		floor_info
+	}
+
+	/// A single room taking up the whole map, with no way out. Monsters are
+	/// sent in by the arena's waves instead of being placed up front
+	pub fn arena(difficulty: Difficulty, rng: &mut Rng) -> Self {
+		let rooms = vec![Room {
+			top_left: IVec2::ONE,
+			bottom_right: MAP_SIZE_TILES - IVec2::splat(2),
+			doors: Vec::new(),
+			prefab: false,
+			inner_walls: Vec::new(),
+		}];
+
+		let biome = Biome::Cave;
+		let floor = build_floor(&rooms, &[], biome, MonsterScaling::new(0, difficulty), rng);
+		let spawn = (rooms[0].center() * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+		FloorInfo {
+			biome,
+			monster_types: vec![MonsterKind::SmallRat, MonsterKind::GreenSlime],
+			item_types: Vec::new(),
+			spawn,
+			floor,
+			rooms,
+			exit: None,
+			upstairs: None,
+			exit_locked: false,
+			descent: DescentCondition::Open,
+			monsters: Vec::new(),
+			notice_board: None,
+			ambushes: Vec::new(),
+			director: None,
+			shop: Vec::new(),
+			npcs: Vec::new(),
+			// Waves pay out in gold instead
+			chest_loot: LootTable::default(),
+			monster_loot: LootTable::default(),
+			corpses: Corpses::default(),
+			modifier: None,
+		}
+	}
+
+	/// One big open room, with the boss in the middle and the way down on the
+	/// far side from the spawn point
+	fn boss(floor_num: usize, biome: Biome, scaling: MonsterScaling, rng: &mut Rng) -> Self {
+		let top_left = (MAP_SIZE_TILES - IVec2::splat(BOSS_ROOM_SIZE)) / 2;
+		let room = Room {
+			top_left,
+			bottom_right: top_left + IVec2::splat(BOSS_ROOM_SIZE),
+			doors: Vec::new(),
+			prefab: false,
+			inner_walls: Vec::new(),
+		};
+
+		let center = room.center();
+		let spawn_tile = IVec2::new(room.top_left.x + 2, center.y);
+		let exit_pos = IVec2::new(room.bottom_right.x - 2, center.y);
+
+		let rooms = vec![room];
+		let floor = build_floor(&rooms, &[], biome, scaling, rng);
+
+		let spawn = (spawn_tile * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+		let boss_pos = (center * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+		FloorInfo {
+			biome,
+			monster_types: biome.monster_kinds().to_vec(),
+			item_types: biome.item_pool(),
+			spawn,
+			floor,
+			rooms,
+			exit: Some(Object {
+				pos: exit_pos,
+				is_floor: true,
+				..Default::default()
+			}),
+			upstairs: upstairs(floor_num, spawn),
+			exit_locked: true,
+			descent: DescentCondition::SlayGuardian,
+			monsters: vec![MonsterObj::SlimeQueen(SlimeQueen::new(boss_pos)).scaled(scaling)],
+			notice_board: None,
+			ambushes: Vec::new(),
+			director: None,
+			shop: Vec::new(),
+			npcs: Vec::new(),
+			chest_loot: LootTable::chest(floor_num),
+			monster_loot: LootTable::monster(floor_num),
+			corpses: Corpses::default(),
+			modifier: None,
+		}
+	}
+
+	pub fn rooms(&self) -> &Vec<Room> { &self.rooms }
+
+	/// Picks a random tile inside the room containing `tile_pos`, or inside any
+	/// room if there isn't one, and returns its position in pixels
+	pub fn random_room_pos(&self, tile_pos: Option<IVec2>, rng: &mut Rng) -> Vec2 {
+		random_room_pos(&self.rooms, tile_pos, rng)
+	}
+
+	fn spawn_monsters(&mut self, rng: &mut Rng) {
+		// Choose every room that doesn't contain the spawn point
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32))
+			.ceil()
+			.as_ivec2();
+
+		let npcs = &self.npcs;
+
+		let valid_rooms = self.rooms.iter().filter(|room| {
+			let (top_left, bottom_right) = room.extents();
+
+			!(spawn_tile.cmpgt(top_left).all() && spawn_tile.cmplt(bottom_right).all()) &&
+				!room.has_npc(npcs)
+		});
+
+		let monster_types = &self.monster_types;
+		let scaling = self.floor.scaling;
+		let multiplier = self
+			.modifier
+			.map_or(1, |modifier| modifier.monster_multiplier());
+
+		self.monsters.extend(valid_rooms.flat_map(|room| {
+			// Pick a random position in each room to spawn from 0 to 6 rats
+			let tile_pos = room.random_tile(rng);
+
+			let pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+			let count = scaling.count(rng.gen_range(0, 6)) * multiplier;
+
+			let mut monsters: Vec<MonsterObj> = (0..count)
+				.flat_map(|_| {
+					let kind = *rng.choose(monster_types).unwrap();
+					let asleep = rng.gen_range(0, SLEEP_CHANCE) == 0;
+
+					(0..kind.group_size()).map(move |_| {
+						let mut monster = kind.new_monster(pos).scaled(scaling);
+						monster.set_asleep(asleep);
+						monster
+					})
+				})
+				.collect();
+
+			if rng.gen_range(0, SPAWNER_CHANCE) == 0 {
+				let spawner = rng.choose(monster_types).and_then(|kind| kind.spawner());
+
+				if let Some(spawner) = spawner {
+					let tile_pos = room.random_tile(rng);
+					let pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+					monsters.push(spawner.new_monster(pos).scaled(scaling));
+				}
+			}
+
+			monsters
+		}));
+	}
+
+	/// Gives some of the rooms without the spawn point in them a shrine
+	fn place_shrines(&mut self, floor_num: usize, rng: &mut Rng) {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+
+		let shrine_tiles: Vec<IVec2> = self
+			.rooms
+			.iter()
+			.filter(|room| !room.inside_room(spawn_tile) && !room.has_npc(&self.npcs))
+			.filter_map(|room| {
+				(rng.gen_range(0, SHRINE_CHANCE) == 0).then(|| {
+					(random_room_pos(&self.rooms, Some(room.center()), rng) /
+						Vec2::splat(TILE_SIZE as f32))
+					.as_ivec2()
+				})
+			})
+			.collect();
+
+		shrine_tiles.into_iter().for_each(|tile_pos| {
+			if let Some(object) = self.floor.get_object_from_pos_mut(tile_pos) {
+				if object.is_floor && object.trap.is_none() {
+					object.shrine = Some(Shrine::generate(tile_pos, floor_num, rng));
+				}
+			}
+		});
+	}
+
+	/// Hides chests in some of the rooms without the spawn point in them
+	fn place_chests(&mut self, rng: &mut Rng) {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+
+		let chest_tiles: Vec<IVec2> = self
+			.rooms
+			.iter()
+			.filter(|room| !room.inside_room(spawn_tile) && !room.has_npc(&self.npcs))
+			.filter_map(|room| {
+				(rng.gen_range(0, CHEST_CHANCE) == 0).then(|| {
+					(random_room_pos(&self.rooms, Some(room.center()), rng) /
+						Vec2::splat(TILE_SIZE as f32))
+					.as_ivec2()
+				})
+			})
+			.collect();
+
+		let scaling = self.floor.scaling;
+
+		chest_tiles.into_iter().for_each(|tile_pos| {
+			if let Some(object) = self.floor.get_object_from_pos_mut(tile_pos) {
+				if object.is_floor && object.trap.is_none() && object.shrine.is_none() {
+					if rng.gen_range(0, MIMIC_CHANCE) == 0 {
+						let disguise = match rng.gen_range(0, 2) {
+							0 => Disguise::Chest,
+							_ => Disguise::Gold,
+						};
+						let pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+						self.monsters.push(
+							MonsterObj::Mimic(Mimic::new(pos).disguised_as(disguise))
+								.scaled(scaling),
+						);
+						return;
+					}
+
+					object.chest = Some(Chest::closed(tile_pos));
+				}
+			}
+		});
+	}
+
+	/// Gives each room its own look, with one of the biome's decorations
+	/// scattered over it and one of its props. Props only go up against the
+	/// walls, or in a ring of pillars, so they never block the way through.
+	/// Prefab rooms are left how they were drawn
+	fn decorate_rooms(&mut self, rng: &mut Rng) {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+		let exit_tile = self.exit.as_ref().map(|exit| exit.tile_pos());
+
+		let mut taken_tiles: HashSet<IVec2> = self
+			.monsters
+			.iter()
+			.map(|monster| pos_to_tile(monster))
+			.chain(self.notice_board.as_ref().map(|board| board.tile_pos()))
+			.collect();
+
+		let mut decorations = Vec::new();
+		let mut props = Vec::new();
+
+		self.rooms
+			.iter()
+			.filter(|room| !room.prefab && !room.has_npc(&self.npcs))
+			.for_each(|room| {
+				let decoration = *rng.choose(self.biome.decorations()).unwrap();
+
+				room.interior().for_each(|tile_pos| {
+					if rng.gen_range(0, DECORATION_CHANCE) == 0 {
+						decorations.push((tile_pos, decoration));
+					}
+				});
+
+				// Everyone arrives in these, so they're kept clear
+				if room.inside_room(spawn_tile) ||
+					exit_tile.is_some_and(|exit_tile| room.inside_room(exit_tile))
+				{
+					return;
+				}
+
+				let kind = *rng.choose(self.biome.props()).unwrap();
+
+				let origins: Vec<IVec2> = match kind {
+					PropKind::Pillar => room.pillar_tiles().collect(),
+					PropKind::Crate => room
+						.interior()
+						.filter(|tile_pos| room.along_wall(*tile_pos))
+						.filter(|_| rng.gen_range(0, PROP_CHANCE) == 0)
+						.collect(),
+					// Along the top wall, leaving a gap after each one
+					PropKind::Bookshelf => room
+						.interior()
+						.filter(|tile_pos| tile_pos.y == room.top_left.y + 1)
+						.step_by(3)
+						.filter(|origin| {
+							Prop::tiles(kind, *origin).all(|tile_pos| {
+								room.inside_room(tile_pos) && room.along_wall(tile_pos)
+							})
+						})
+						.collect(),
+				};
+
+				origins.into_iter().for_each(|origin| {
+					let tiles: Vec<IVec2> = Prop::tiles(kind, origin).collect();
+
+					// Props can't go in front of doors or secret passages either
+					let fits = tiles.iter().all(|tile_pos| {
+						let beside_opening = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+							.iter()
+							.map(|offset| *tile_pos + *offset)
+							.filter(|neighbor| !room.inside_room(*neighbor))
+							.any(|neighbor| {
+								self.floor
+									.get_object_from_pos(neighbor)
+									.map_or(false, |object| !object.is_solid_wall())
+							});
+
+						!beside_opening &&
+							!taken_tiles.contains(tile_pos) &&
+							self.floor
+								.get_object_from_pos(*tile_pos)
+								.map_or(false, |object| object.is_empty_floor())
+					});
+
+					if !fits {
+						return;
+					}
+
+					let contents = match kind {
+						PropKind::Crate => self.monster_loot.roll(rng),
+						_ => None,
+					};
+
+					tiles.into_iter().for_each(|tile_pos| {
+						taken_tiles.insert(tile_pos);
+						props.push(Prop::new(kind, origin, tile_pos).with_contents(contents));
+					});
+				});
+			});
+
+		decorations.into_iter().for_each(|(tile_pos, decoration)| {
+			if let Some(object) = self.floor.get_object_from_pos_mut(tile_pos) {
+				object.decoration = Some(decoration);
+			}
+		});
+
+		props.into_iter().for_each(|prop| {
+			if let Some(object) = self.floor.get_object_from_pos_mut(prop.tile_pos()) {
+				object.prop = Some(prop);
+			}
+		});
+	}
+
+	/// Leaves a pile of gold in every room but the spawn room, for treasure
+	/// floors
+	fn place_treasure(&mut self, floor_num: usize, rng: &mut Rng) {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+
+		let treasure_tiles: Vec<IVec2> = self
+			.rooms
+			.iter()
+			.filter(|room| !room.inside_room(spawn_tile) && !room.has_npc(&self.npcs))
+			.map(|room| room.random_tile(rng))
+			.collect();
+
+		treasure_tiles.into_iter().for_each(|tile_pos| {
+			self.floor.drop_item(
+				tile_pos,
+				ItemType::Gold(TREASURE_GOLD * (floor_num as u32 + 1)),
+			);
+		});
+	}
+
+	/// Sets up a vendor in one of the rooms without the spawn point or exit in
+	/// it, behind a row of pedestals with their items for sale
+	fn place_shop(&mut self, rng: &mut Rng) {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+		let exit_tile = self.exit.as_ref().map(|exit| exit.tile_pos());
+
+		// Secret rooms have had their doors taken away
+		let shop_rooms: Vec<&Room> = self
+			.rooms
+			.iter()
+			.filter(|room| {
+				!room.doors.is_empty() &&
+					!room.prefab && (room.bottom_right - room.top_left)
+					.cmpge(IVec2::splat(SHOP_ROOM_MIN_SIZE))
+					.all() && !room.inside_room(spawn_tile) &&
+					!exit_tile.is_some_and(|exit_tile| room.inside_room(exit_tile))
+			})
+			.collect();
+
+		let Some(center) = rng.choose(&shop_rooms).map(|room| room.center()) else {
+			return;
+		};
+
+		let mut stock = SHOP_STOCK;
+		rng.shuffle(&mut stock);
+
+		self.shop = stock
+			.into_iter()
+			.take(VENDOR_STOCK_SIZE)
+			.enumerate()
+			.map(|(i, key)| {
+				let tile_pos = center + IVec2::new(i as i32 * 2 - 2, 1);
+
+				if let Some(object) = self.floor.get_object_from_pos_mut(tile_pos) {
+					object.pedestal = true;
+					object.trap = None;
+					object.effects.clear();
+				}
+
+				ShopItem::new(tile_pos, ItemType::from_key(key))
+			})
+			.collect();
+
+		self.npcs.push(Npc::new(center - IVec2::Y, NpcKind::Vendor));
+	}
+
+	/// Turns some of the rooms without the spawn point or exit in them into
+	/// ambushes
+	fn place_ambushes(&mut self, floor_num: usize, rng: &mut Rng) {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+		let exit_tile = self.exit.as_ref().map(|exit| exit.tile_pos());
+
+		self.ambushes = self
+			.rooms
+			.iter()
+			.enumerate()
+			.filter(|(_, room)| {
+				!room.inside_room(spawn_tile) &&
+					!exit_tile.is_some_and(|exit_tile| room.inside_room(exit_tile)) &&
+					!room.has_npc(&self.npcs)
+			})
+			.filter_map(|(i, _)| {
+				(rng.gen_range(0, AMBUSH_CHANCE) == 0).then(|| Ambush::generate(i, floor_num, rng))
+			})
+			.collect();
+	}
+
+	/// A random monster from the ones that live on this floor
+	pub fn new_monster(&self, pos: Vec2, rng: &mut Rng) -> MonsterObj {
+		rng.choose(&self.monster_types)
+			.unwrap()
+			.new_monster(pos)
+			.scaled(self.floor.scaling)
+	}
+
+	/// The middle of the room furthest from the spawn point
+	fn furthest_room_center(&self) -> IVec2 {
+		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+
+		self.rooms
+			.iter()
+			.max_by_key(|room| {
+				let distance = room.center() - spawn_tile;
+				distance.x * distance.x + distance.y * distance.y
+			})
+			.unwrap()
+			.center()
+	}
+
+	/// Puts the rat king in the room furthest from the spawn point
+	fn spawn_boss(&mut self) {
+		let pos = (self.furthest_room_center() * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+		self.monsters
+			.push(MonsterObj::RatKing(RatKing::new(pos)).scaled(self.floor.scaling));
+	}
+
+	/// Puts a slime queen in the room furthest from the spawn point, guarding
+	/// the way down
+	fn spawn_guardian(&mut self) {
+		let pos = (self.furthest_room_center() * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+		self.monsters
+			.push(MonsterObj::SlimeQueen(SlimeQueen::new(pos)).scaled(self.floor.scaling));
+	}
+
+	/// Hides the floor's sigil in a random room
+	fn place_sigil(&mut self, rng: &mut Rng) {
+		let tile_pos = (self.random_room_pos(None, rng) / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+
+		self.floor
+			.add_item_to_object(ItemInfo::new(ItemType::from_key(SIGIL_KEY), Some(tile_pos)));
+	}
+
+	pub fn should_descend(&self, players: &[Player]) -> bool {
+		if self.exit_locked {
+			return false;
+		}
+
+		let Some(exit) = &self.exit else {
+			return false;
+		};
+
+		let on_exit = |p: &Player| aabb_collision(p, exit, Vec2::ZERO);
+
+		match self.descent {
+			// Nobody gets left behind
+			DescentCondition::Gathered => {
+				players.iter().any(on_exit) && players.iter().filter(|p| p.hp() > 0).all(on_exit)
+			},
+			// If any players are touching the exit, descend a floor
+			_ => players.iter().any(on_exit),
+		}
+	}
+
+	pub fn should_ascend(&self, players: &[Player]) -> bool {
+		self.upstairs.as_ref().is_some_and(|upstairs| {
+			players
+				.iter()
+				.any(|p| aabb_collision(p, upstairs, Vec2::ZERO))
+		})
+	}
+
+	pub fn exit(&self) -> Option<&Object> { self.exit.as_ref() }
+
+	pub fn upstairs(&self) -> Option<&Object> { self.upstairs.as_ref() }
+
+	pub fn exit_locked(&self) -> bool { self.exit_locked }
+
+	pub fn descent(&self) -> DescentCondition { self.descent }
+
+	/// What players still need to do to get down, if anything
+	pub fn descent_hint(&self) -> Option<&'static str> {
+		if self.exit.is_none() || (self.descent.locks_exit() && !self.exit_locked) {
+			return None;
+		}
+
+		self.descent.hint_id()
+	}
+
+	/// Opens the way down once every boss on the floor is dead, or someone's
+	/// found the sigil
+	pub fn unlock_exit(&mut self, players: &[Player], events: &mut Vec<GameEvent>) {
+		if !self.exit_locked {
+			return;
+		}
+
+		let unlocked = match self.descent {
+			DescentCondition::SlayGuardian => !self.monsters.iter().any(|m| m.kind().is_boss()),
+			DescentCondition::Sigil => {
+				let sigil = ItemType::from_key(SIGIL_KEY);
+				players.iter().any(|p| p.inventory().contains(sigil))
+			},
+			DescentCondition::Open | DescentCondition::Gathered => true,
+		};
+
+		if !unlocked {
+			return;
+		}
+
+		self.exit_locked = false;
+
+		if let Some(exit) = &self.exit {
+			events.push(GameEvent::ExitUnlocked { pos: exit.center() });
+		}
+	}
+
+	pub fn biome(&self) -> Biome { self.biome }
+
+	pub fn modifier(&self) -> Option<FloorModifier> { self.modifier }
+
+	/// How many tiles away players can see
+	pub fn vision_radius(&self) -> Option<i32> {
+		self.modifier.and_then(|modifier| modifier.vision_radius())
+	}
+
+	/// Multiplies how lit up the floor is
+	pub fn light_level(&self) -> f32 {
+		self.modifier.map_or(1.0, |modifier| modifier.light_level())
+	}
 
-		floor_info.spawn_monsters();
+	pub fn current_spawn(&self) -> Vec2 { self.spawn }
 
-		floor_info
+	/// Where each player starts, so they don't all start on top of each other
+	pub fn spawn_pos(&self, player: usize) -> Vec2 { spread_out(self.spawn, player) }
+
+	/// Where each player ends up when coming back up from the floor below
+	pub fn exit_pos(&self, player: usize) -> Vec2 {
+		let exit_pos = self.exit.as_ref().map_or(self.spawn, |exit| exit.pos());
+		spread_out(exit_pos, player)
 	}
+}
 
-	pub fn rooms(&self) -> &Vec<Room> { &self.rooms }
+/// Moves each player a little way from `pos`, so they don't all end up on top
+/// of each other
+fn spread_out(pos: Vec2, player: usize) -> Vec2 {
+	let offset = Vec2::new((player % 2) as f32, (player / 2) as f32);
+	pos + offset * PLAYER_SIZE * 1.5
+}
 
-	fn spawn_monsters(&mut self) {
-		// Choose every room that doesn't contain the spawn point
-		let spawn_tile = (self.spawn / Vec2::splat(TILE_SIZE as f32))
-			.ceil()
-			.as_ivec2();
+/// The way back up, for every floor but the first
+fn upstairs(floor_num: usize, spawn: Vec2) -> Option<Object> {
+	(floor_num > 0).then(|| Object {
+		pos: (spawn / Vec2::splat(TILE_SIZE as f32)).as_ivec2(),
+		is_floor: true,
+		..Default::default()
+	})
+}
 
-		let valid_rooms = self.rooms.iter().filter(|room| {
-			let (top_left, bottom_right) = room.extents();
+/// Which way a floor's stairs go
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stairs {
+	Up,
+	Down,
+}
 
-			!(spawn_tile.cmpgt(top_left).all() && spawn_tile.cmplt(bottom_right).all())
-		});
+/// Reusable buffers for the visibility and pathfinding queries, which run for
+/// every monster every frame. Reusing them means those queries don't allocate a
+/// fresh set of Vecs each call
+#[derive(Default)]
+pub struct Scratch {
+	edges: Vec<IVec2>,
+	ray: Vec<IVec2>,
+	visible: Vec<usize>,
+	visible_mask: Vec<bool>,
+}
 
-		self.monsters.extend(valid_rooms.flat_map(|room| {
-			// Pick a random position in each room to spawn from 0 to 6 rats
-			let (top_left, bottom_right) = room.extents();
-			let tile_pos = IVec2::new(
-				rand::gen_range(top_left.x + 1, bottom_right.x - 1),
-				rand::gen_range(top_left.y + 1, bottom_right.y - 1),
-			);
+thread_local! {
+	// Monsters are updated across rayon's worker threads, so each thread keeps its own buffers.
+	// Pathfinding gets a separate set, since it's called while monsters hold onto their visible
+	// objects
+	static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+	static PATH_SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+}
 
-			let pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
-			let monster_types = &self.monster_types;
+/// Runs the closure with the current thread's scratch buffers
+pub fn with_scratch<R>(f: impl FnOnce(&mut Scratch) -> R) -> R {
+	SCRATCH.with(|scratch| f(&mut scratch.borrow_mut()))
+}
 
-			(0..rand::gen_range(0, 6)).into_iter().map(move |_| {
-				let monster = monster_types.choose().unwrap();
-				match monster {
-					MonsterObj::SmallRat(_) => MonsterObj::SmallRat(SmallRat::new(pos)),
-					MonsterObj::GreenSlime(_) => MonsterObj::GreenSlime(GreenSlime::new(pos)),
-				}
-			})
-		}));
+/// The objects visible from a position, borrowed from a Scratch buffer
+pub struct VisibleObjects<'a> {
+	objects: &'a [Object],
+	indices: &'a [usize],
+}
+
+impl<'a> VisibleObjects<'a> {
+	pub fn iter(&self) -> impl Iterator<Item = &'a Object> + '_ {
+		self.indices.iter().map(|&i| &self.objects[i])
 	}
 
-	pub fn should_descend(&self, players: &[Player]) -> bool {
-		// If any players are touching the exit, descend a floor
-		players
-			.iter()
-			.any(|p| aabb_collision(p, &self.exit, Vec2::ZERO))
+	pub fn last(&self) -> Option<&'a Object> { self.indices.last().map(|&i| &self.objects[i]) }
+
+	pub fn contains_tile(&self, tile_pos: IVec2) -> bool {
+		self.iter().any(|obj| obj.tile_pos() == tile_pos)
 	}
+}
+
+/// What each player could see the last time it was worked out. Rays are only
+/// cast again once a player steps onto another tile, or something that blocks
+/// sight changes, like a door opening
+#[derive(Default)]
+pub struct VisibilityCache {
+	/// Which tiles blocked sight when the cache was filled. Changing floors
+	/// changes these too, so that's caught the same way
+	blockers: Vec<bool>,
+	radius: Option<i32>,
+	/// Each player's tile, and the objects visible from it
+	players: Vec<(IVec2, Vec<usize>)>,
+}
 
-	pub fn exit(&self) -> &Object { &self.exit }
+impl VisibilityCache {
+	/// Marks every object the players can see as visible and seen
+	pub fn set_visible_objects<A: AsPolygon>(
+		&mut self, viewers: &[A], radius: Option<i32>, objects: &mut [Object],
+		scratch: &mut Scratch,
+	) {
+		crate::profile_scope!("cached_visibility");
 
-	pub fn current_spawn(&self) -> Vec2 { self.spawn }
+		let blockers_changed = self.blockers.len() != objects.len() ||
+			self.blockers
+				.iter()
+				.zip(objects.iter())
+				.any(|(blocked, object)| *blocked != object.is_collidable());
+
+		if blockers_changed || radius != self.radius {
+			self.radius = radius;
+			self.blockers.clear();
+			self.blockers
+				.extend(objects.iter().map(|object| object.is_collidable()));
+			self.players.clear();
+		}
+
+		self.players.truncate(viewers.len());
+
+		viewers.iter().enumerate().for_each(|(i, viewer)| {
+			let tile_pos = pos_to_tile(viewer);
+
+			match self.players.get_mut(i) {
+				Some((cached_tile_pos, _)) if *cached_tile_pos == tile_pos => (),
+				cached => {
+					Floor::cast_visibility(viewer, radius, objects, scratch);
+
+					match cached {
+						Some((cached_tile_pos, visible)) => {
+							*cached_tile_pos = tile_pos;
+							visible.clear();
+							visible.extend_from_slice(&scratch.visible);
+						},
+						None => self.players.push((tile_pos, scratch.visible.clone())),
+					}
+				},
+			}
+
+			self.players[i].1.iter().copied().for_each(|i| {
+				objects[i].has_been_seen = true;
+				objects[i].is_currently_visible = true;
+			});
+		});
+	}
 }
 
 #[derive(Clone, Serialize)]
 pub struct Floor {
 	objects: Vec<Object>,
+	/// How tough the monsters on this floor are
+	scaling: MonsterScaling,
+	/// Worked out from the players each frame, so there's no need to send it
+	#[serde(skip)]
+	flow_field: FlowField,
 }
 
 impl Floor {
@@ -726,6 +2274,80 @@ impl Floor {
 		object.items.push(item);
 	}
 
+	/// Drops an item on the nearest open tile to `tile_pos`. If there isn't
+	/// one, the item is lost
+	pub fn drop_item(&mut self, tile_pos: IVec2, item_type: ItemType) {
+		let tile_pos_vec2 = tile_pos.as_vec2();
+
+		if let Some(item_pos) = [
+			IVec2::ZERO,
+			IVec2::new(-1, 0),
+			IVec2::new(0, -1),
+			IVec2::new(-1, -1),
+			IVec2::new(1, 0),
+			IVec2::new(0, 1),
+			IVec2::new(1, 1),
+			IVec2::new(-1, 1),
+			IVec2::new(1, -1),
+		]
+		.into_iter()
+		.map(|change| tile_pos + change)
+		.filter(|tile_pos| match self.get_object_from_pos(*tile_pos) {
+			Some(object) => !object.is_collidable(),
+			None => false,
+		})
+		.reduce(|tile_pos1, tile_pos2| {
+			let distance1 = tile_pos1.as_vec2().distance_squared(tile_pos_vec2);
+			let distance2 = tile_pos2.as_vec2().distance_squared(tile_pos_vec2);
+
+			match distance1 < distance2 {
+				true => tile_pos1,
+				false => tile_pos2,
+			}
+		}) {
+			self.add_item_to_object(ItemInfo::new(item_type, Some(item_pos)));
+		}
+	}
+
+	/// Leaves an effect on the floor at `tile_pos` for a while, like burning
+	/// ground. Walls don't take effects, and ones that are already there for
+	/// good aren't replaced
+	pub fn add_effect(&mut self, tile_pos: IVec2, effect_type: EffectType, frames: u16) {
+		let Some(object) = self.get_object_from_pos_mut(tile_pos) else {
+			return;
+		};
+
+		if !object.is_floor {
+			return;
+		}
+
+		let effect = object.effects.entry(effect_type).or_insert(Effect {
+			time_til_dissipate: Some(0),
+			effect_type,
+		});
+
+		if let Some(time_til_dissipate) = &mut effect.time_til_dissipate {
+			*time_til_dissipate = (*time_til_dissipate).max(frames);
+		}
+	}
+
+	/// The floor tile closest to `tile_pos` that's free of walls, traps, chests
+	/// and anything else already there, which is `tile_pos` itself if it's free
+	pub fn nearest_open_tile(&self, tile_pos: IVec2) -> Option<IVec2> {
+		self.objects
+			.iter()
+			.filter(|object| {
+				object.is_floor() &&
+					!object.is_collidable() &&
+					object.trap.is_none() &&
+					object.shrine.is_none() &&
+					object.chest.is_none() &&
+					!object.pedestal
+			})
+			.map(|object| object.tile_pos())
+			.min_by_key(|open_tile| distance_squared(*open_tile, tile_pos))
+	}
+
 	pub fn get_object_from_pos(&self, pos: IVec2) -> Option<&Object> {
 		self.objects
 			.get((pos.x + pos.y * MAP_WIDTH_TILES as i32) as usize)
@@ -795,6 +2417,123 @@ impl Floor {
 		self.objects.iter_mut().filter(|obj| obj.door.is_some())
 	}
 
+	pub fn shrines_mut(&mut self) -> impl Iterator<Item = &mut Shrine> {
+		self.objects
+			.iter_mut()
+			.filter_map(|obj| obj.shrine.as_mut())
+	}
+
+	/// Walls that are hiding the way into a secret room
+	pub fn hidden_passages(&mut self) -> impl Iterator<Item = &mut Object> {
+		self.objects
+			.iter_mut()
+			.filter(|obj| obj.secret && !obj.is_floor)
+	}
+
+	pub fn closed_chests(&mut self) -> impl Iterator<Item = &mut Object> {
+		self.objects
+			.iter_mut()
+			.filter(|obj| obj.chest.is_some_and(|chest| !chest.opened))
+	}
+
+	/// Opens a closed chest next to `tile_pos`, dropping what was inside around
+	/// it. Returns where the chest was, if there was one
+	pub fn open_chest_near(
+		&mut self, tile_pos: IVec2, loot_table: &LootTable, rng: &mut Rng,
+	) -> Option<IVec2> {
+		let chest = self
+			.closed_chests()
+			.find(|obj| (obj.tile_pos() - tile_pos).abs().cmple(IVec2::ONE).all())?;
+
+		chest.open_chest();
+		let chest_pos = chest.tile_pos();
+
+		(0..CHEST_ROLLS).for_each(|_| {
+			if let Some(item_type) = loot_table.roll(rng) {
+				self.drop_item(chest_pos, item_type);
+			}
+		});
+
+		Some(chest_pos)
+	}
+
+	/// Smashes a crate next to `tile_pos`, dropping whatever was inside it
+	pub fn smash_crate_near(&mut self, tile_pos: IVec2) -> Option<IVec2> {
+		let crate_pos = self
+			.objects
+			.iter()
+			.find(|obj| {
+				obj.prop.is_some_and(|prop| prop.kind == PropKind::Crate) &&
+					(obj.tile_pos() - tile_pos).abs().cmple(IVec2::ONE).all()
+			})?
+			.tile_pos();
+
+		self.smash_crate_at(crate_pos);
+
+		Some(crate_pos)
+	}
+
+	/// Returns false if there wasn't a crate on the tile
+	fn smash_crate_at(&mut self, tile_pos: IVec2) -> bool {
+		let Some(object) = self.get_object_from_pos_mut(tile_pos) else {
+			return false;
+		};
+
+		if !object.prop.is_some_and(|prop| prop.kind == PropKind::Crate) {
+			return false;
+		}
+
+		let contents = object.prop.take().and_then(|prop| prop.contents());
+
+		if let Some(item_type) = contents {
+			self.drop_item(tile_pos, item_type);
+		}
+
+		true
+	}
+
+	/// Checks what a projectile moving by `distance` flies into, returning
+	/// whether it hit something solid. Players' projectiles chip at doors,
+	/// smash crates, and set off spotted traps they pass over
+	pub fn projectile_hit<A: AsPolygon + Sync>(
+		&mut self, aabb: &A, distance: Vec2, player: Option<usize>, events: &mut Vec<GameEvent>,
+	) -> bool {
+		let hit = self.collision_obj(aabb, distance).map(|obj| obj.tile_pos());
+
+		let Some(player) = player else {
+			return hit.is_some();
+		};
+
+		let Some(tile_pos) = hit else {
+			self.trip_trap(pos_to_tile(aabb), player);
+			return false;
+		};
+
+		if self.smash_crate_at(tile_pos) {
+			events.push(GameEvent::CrateSmashed { player, tile_pos });
+		} else if let Some(object) = self.get_object_from_pos_mut(tile_pos) {
+			if object.chip_door() {
+				events.push(GameEvent::DoorBroken { tile_pos });
+			}
+		}
+
+		true
+	}
+
+	/// Sets off a spotted trap on `tile_pos` from range. It's sprung along
+	/// with the rest of the traps
+	fn trip_trap(&mut self, tile_pos: IVec2, player: usize) {
+		let trap = self
+			.get_object_from_pos_mut(tile_pos)
+			.and_then(|obj| obj.trap.as_mut());
+
+		if let Some(trap) = trap {
+			if trap.spotted && !trap.triggered && trap.tripped_by.is_none() {
+				trap.tripped_by = Some(player);
+			}
+		}
+	}
+
 	pub fn untriggered_traps(&mut self) -> impl Iterator<Item = &mut Object> {
 		self.objects.iter_mut().filter_map(|obj| match &obj.trap {
 			Some(trap) => match trap.triggered {
@@ -809,110 +2548,226 @@ impl Floor {
 		&self, pos: &S, goal: &G, only_visible: bool, ignore_door_collision: bool,
 		randomness: Option<i32>,
 	) -> Option<Vec<Vec2>> {
-		inner_find_path(
-			pos,
-			goal,
-			self,
-			only_visible,
-			ignore_door_collision,
-			randomness,
-		)
+		crate::profile_scope!("find_path");
+
+		PATH_SCRATCH.with(|scratch| {
+			inner_find_path(
+				pos,
+				goal,
+				self,
+				only_visible,
+				ignore_door_collision,
+				randomness,
+				&mut scratch.borrow_mut(),
+			)
+		})
 	}
 
-	pub fn set_visible_objects<A: AsPolygon>(aabb: &A, size: Option<i32>, objects: &mut [Object]) {
+	/// Casts rays out from the AABB, and fills the scratch buffer with the
+	/// indices of every object that can be seen
+	fn cast_visibility<A: AsPolygon>(
+		aabb: &A, size: Option<i32>, objects: &[Object], scratch: &mut Scratch,
+	) {
+		crate::profile_scope!("visibility");
+
 		let center_tile = pos_to_tile(aabb);
 
-		let edges = points_on_circumference(center_tile, size.unwrap_or(12));
+		let Scratch {
+			edges,
+			ray,
+			visible,
+			..
+		} = scratch;
 
-		let rays = edges
-			.into_iter()
-			.map(|edge| points_on_line(center_tile, edge));
+		points_on_circumference_into(center_tile, size.unwrap_or(12), edges);
+		visible.clear();
 
-		let mut visible_object_indices =
-			Vec::with_capacity(rays.len() * size.unwrap_or(12) as usize);
+		for edge in edges.iter().copied() {
+			points_on_line_into(center_tile, edge, ray);
 
-		for ray in rays {
-			'ray: for pos in ray.into_iter() {
+			for pos in ray.iter().copied() {
 				if let Some(index) = get_object_from_pos_mut(pos, objects) {
-					visible_object_indices.push(index);
+					visible.push(index);
 
-					let obj = &objects[index];
-					if obj.is_collidable() {
-						break 'ray;
+					if objects[index].is_collidable() {
+						break;
 					}
 				}
 			}
 		}
-
-		visible_object_indices.iter().copied().for_each(|i| {
-			objects[i].has_been_seen = true;
-			objects[i].is_currently_visible = true;
-		});
 	}
 
 	pub fn visible_objects<A: AsPolygon>(&self, aabb: &A, size: Option<i32>) -> Vec<&Object> {
-		let center_tile = pos_to_tile(aabb);
+		with_scratch(|scratch| {
+			Self::cast_visibility(aabb, size, &self.objects, scratch);
 
-		let edges = points_on_circumference(center_tile, size.unwrap_or(12));
+			scratch.visible.iter().map(|&i| &self.objects[i]).collect()
+		})
+	}
 
-		let rays = edges
-			.into_iter()
-			.map(|edge| points_on_line(center_tile, edge));
+	/// Same as visible_objects, but the results borrow the scratch buffer
+	/// instead of being collected into a new Vec
+	pub fn visible_objects_with<'a, A: AsPolygon>(
+		&'a self, aabb: &A, size: Option<i32>, scratch: &'a mut Scratch,
+	) -> VisibleObjects<'a> {
+		Self::cast_visibility(aabb, size, &self.objects, scratch);
 
-		let mut visible_objects = Vec::with_capacity(rays.len() * size.unwrap_or(12) as usize);
+		VisibleObjects {
+			objects: &self.objects,
+			indices: &scratch.visible,
+		}
+	}
 
-		for ray in rays {
-			'ray: for pos in ray.into_iter() {
-				if let Some(obj) = self.get_object_from_pos(pos) {
-					visible_objects.push(obj);
+	pub fn objects(&self) -> &[Object] { &self.objects }
 
-					if obj.is_collidable() {
-						break 'ray;
-					}
-				}
-			}
-		}
+	pub fn flow_field(&self) -> &FlowField { &self.flow_field }
 
-		visible_objects
+	pub fn scaling(&self) -> MonsterScaling { self.scaling }
+
+	pub fn update_flow_field(&mut self, players: &[Player]) {
+		self.flow_field.update(players, &self.objects);
 	}
 
-	pub fn objects(&self) -> &[Object] { &self.objects }
+	/// Walks the tiles between two points, returning the first one that blocks
+	/// the way, like a wall or a closed door
+	pub fn raycast(&self, from: Vec2, to: Vec2) -> Option<&Object> {
+		let to_tile = |pos: Vec2| (pos / TILE_SIZE as f32).floor().as_ivec2();
+
+		with_scratch(|scratch| {
+			points_on_line_into(to_tile(from), to_tile(to), &mut scratch.ray);
+
+			scratch
+				.ray
+				.iter()
+				.filter_map(|pos| self.get_object_from_pos(*pos))
+				.find(|object| object.is_collidable())
+		})
+	}
+
+	/// Whether nothing's blocking the way between two points
+	pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool { self.raycast(from, to).is_none() }
+
+	/// Marks every tile as seen
+	pub fn reveal(&mut self) { self.objects.iter_mut().for_each(|o| o.has_been_seen = true); }
 
 	pub fn objects_mut(&mut self) -> &mut [Object] { &mut self.objects }
 }
 
+/// Every floor, which are all generated up front and kept around, so going
+/// back up finds them just how they were left
 #[derive(Clone, Serialize)]
 pub struct Map {
 	current_floor_index: usize,
 	rooms: Vec<FloorInfo>,
+	/// Set after changing floors, since everyone arrives on top of the stairs,
+	/// until they've all stepped off them
+	on_arrival_stairs: bool,
 }
 
 impl Map {
-	pub fn new() -> Self {
-		let floors: Vec<FloorInfo> = (0..5)
-			.into_iter()
-			.map(|floor_num| FloorInfo::new(floor_num))
+	pub fn new(difficulty: Difficulty, rng: &mut Rng) -> Self {
+		let floors: Vec<FloorInfo> = (0..=FINAL_FLOOR)
+			.map(|floor_num| FloorInfo::new(floor_num, difficulty, rng))
 			.collect();
 
 		Self {
 			current_floor_index: 0,
 			rooms: floors,
+			on_arrival_stairs: false,
+		}
+	}
+
+	/// Just the arena, for the arena game mode
+	pub fn arena(difficulty: Difficulty, rng: &mut Rng) -> Self {
+		Self {
+			current_floor_index: 0,
+			rooms: vec![FloorInfo::arena(difficulty, rng)],
+			on_arrival_stairs: false,
 		}
 	}
 
+	pub fn current_floor_index(&self) -> usize { self.current_floor_index }
+
+	pub fn num_floors(&self) -> usize { self.rooms.len() }
+
 	pub fn current_floor(&self) -> &FloorInfo { &self.rooms[self.current_floor_index] }
 
 	pub fn current_floor_mut(&mut self) -> &mut FloorInfo {
 		&mut self.rooms[self.current_floor_index]
 	}
 
-	pub fn descend(&mut self, players: &mut [Player]) {
+	/// Moves everyone to the next floor's spawn. Returns false if this is the
+	/// last one
+	pub fn descend(&mut self, players: &mut [Player]) -> bool {
+		// The arena only has the one floor
+		if self.current_floor_index + 1 >= self.rooms.len() {
+			return false;
+		}
+
+		self.dismiss_companions();
 		self.current_floor_index += 1;
+		self.on_arrival_stairs = true;
+		let current_floor = self.current_floor_mut();
+
+		// A sigil only opens the floor it was found on
+		let sigil = ItemType::from_key(SIGIL_KEY);
+
+		players.iter_mut().enumerate().for_each(|(i, p)| {
+			p.pos = current_floor.spawn_pos(i);
+			p.inventory.items.retain(|item| item.item_type != sigil);
+		});
+
+		true
+	}
+
+	/// Moves everyone back to the previous floor's exit. Returns false if this
+	/// is the first one
+	pub fn ascend(&mut self, players: &mut [Player]) -> bool {
+		if self.current_floor_index == 0 {
+			return false;
+		}
+
+		self.dismiss_companions();
+		self.current_floor_index -= 1;
+		self.on_arrival_stairs = true;
 		let current_floor = self.current_floor_mut();
 
-		players.iter_mut().for_each(|p| {
-			p.pos = current_floor.spawn;
+		players.iter_mut().enumerate().for_each(|(i, p)| {
+			p.pos = current_floor.exit_pos(i);
 		});
+
+		true
+	}
+
+	/// Companions follow their owners between floors, so they're taken off the
+	/// one being left and called again on the next
+	fn dismiss_companions(&mut self) {
+		self.current_floor_mut()
+			.monsters
+			.retain(|m| !matches!(m.allegiance(), Allegiance::Companion { .. }));
+	}
+
+	/// Which stairs someone's standing on, if any. The stairs everyone arrived
+	/// on don't count until they've all stepped off, so they don't get sent
+	/// straight back
+	pub fn stairs_taken(&mut self, players: &[Player]) -> Option<Stairs> {
+		let current_floor = self.current_floor();
+
+		let stairs = match (
+			current_floor.should_descend(players),
+			current_floor.should_ascend(players),
+		) {
+			(true, _) => Some(Stairs::Down),
+			(false, true) => Some(Stairs::Up),
+			(false, false) => None,
+		};
+
+		if self.on_arrival_stairs {
+			self.on_arrival_stairs = stairs.is_some();
+			return None;
+		}
+
+		stairs
 	}
 }
 
@@ -925,20 +2780,22 @@ impl Drawable for Object {
 		Some(match self.is_floor {
 			true => load_my_image("light_gray.webp"),
 			false => match self.door {
-				Some(door) => match door.is_open {
-					false => load_my_image("door.webp"),
-					true => load_my_image("open_door.webp"),
+				Some(door) => match (door.is_open, door.broken) {
+					(_, true) => load_my_image("light_gray.webp"),
+					(false, false) => load_my_image("door.webp"),
+					(true, false) => load_my_image("open_door.webp"),
 				},
-				None => load_my_image("black.webp"),
+				// Tinted by the biome
+				None => load_my_image("light_gray.webp"),
 			},
 		})
 	}
 }
 
-fn find_viable_neighbors(
-	collidable_objects: &[Object], pos: IVec2, visible_objects: &Option<Vec<&Object>>,
+fn find_viable_neighbors<'a>(
+	collidable_objects: &'a [Object], pos: IVec2, visible_mask: Option<&'a [bool]>,
 	ignore_door_collision: bool, _randomness: Option<i32>,
-) -> Vec<(IVec2, i32)> {
+) -> impl Iterator<Item = (IVec2, i32)> + 'a {
 	let change = IVec4::new(-1, -1, 1, 1);
 	let new_pos = IVec4::new(pos.x, pos.y, pos.x, pos.y) + change;
 
@@ -951,22 +2808,23 @@ fn find_viable_neighbors(
 
 	potential_neighbors
 		.into_iter()
-		.filter(|new_pos| {
+		.filter(move |new_pos| {
 			let p = new_pos;
 
 			// OOB objects automatically are not eligible
 			if p.cmplt(IVec2::ZERO).any() || p.cmpgt(MAP_SIZE_TILES).any() {
 				false
-			} else if let Some(visible_objects) = visible_objects {
-				let is_visible = visible_objects.iter().any(|obj| obj.pos == *p);
+			} else if let Some(visible_mask) = visible_mask {
 				// Only return visible objects as potential neighbors
-				is_visible
+				get_object_from_pos_mut(*p, collidable_objects)
+					.map(|i| visible_mask[i])
+					.unwrap_or(false)
 			} else {
 				true
 			}
 		})
 		.filter(
-			|pos| match get_object_from_pos_list(*pos, collidable_objects) {
+			move |pos| match get_object_from_pos_list(*pos, collidable_objects) {
 				Some(obj) => match obj.is_collidable() {
 					true => ignore_door_collision && obj.door().is_some(),
 					false => true,
@@ -975,18 +2833,31 @@ fn find_viable_neighbors(
 			},
 		)
 		.map(|pos| (pos, 1))
-		.collect()
 }
 
 pub fn inner_find_path<S: AsPolygon, G: AsPolygon>(
 	start: &S, goal: &G, floor: &Floor, only_visible: bool, ignore_door_collision: bool,
-	randomness: Option<i32>,
+	randomness: Option<i32>, scratch: &mut Scratch,
 ) -> Option<Vec<Vec2>> {
 	let start_tile_pos = pos_to_tile(start);
 	let goal_tile_pos = pos_to_tile(goal);
 
-	let visible_objects = match only_visible {
-		true => Some(floor.visible_objects(start, None)),
+	let visible_mask = match only_visible {
+		true => {
+			Floor::cast_visibility(start, None, &floor.objects, scratch);
+
+			let Scratch {
+				visible,
+				visible_mask,
+				..
+			} = scratch;
+
+			visible_mask.clear();
+			visible_mask.resize(floor.objects.len(), false);
+			visible.iter().for_each(|&i| visible_mask[i] = true);
+
+			Some(visible_mask.as_slice())
+		},
 		false => None,
 	};
 
@@ -996,7 +2867,7 @@ pub fn inner_find_path<S: AsPolygon, G: AsPolygon>(
 			find_viable_neighbors(
 				&floor.objects,
 				*pos,
-				&visible_objects,
+				visible_mask,
 				ignore_door_collision,
 				randomness,
 			)
@@ -1027,82 +2898,240 @@ pub fn pos_to_tile<A: AsPolygon>(obj: &A) -> IVec2 {
 	(center / Vec2::splat(TILE_SIZE as f32)).floor().as_ivec2()
 }
 
-pub fn trigger_traps(players: &mut [Player], floor_info: &mut FloorInfo) {
+pub fn trigger_traps(
+	players: &mut [Player], floor_info: &mut FloorInfo, attacks: &mut Vec<AttackObj>,
+	events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	spot_traps(players, &mut floor_info.floor);
+
 	let trapped_objs = floor_info.floor.untriggered_traps();
 
+	// Some traps change the floor, so they're only sprung once it's done being
+	// searched
+	let mut sprung = Vec::new();
+
+	#[cfg(feature = "scripting")]
+	let mut scripted = Vec::new();
+
 	trapped_objs.for_each(|trapped_obj| {
-		players.iter_mut().for_each(|player| {
-			let player_tile_pos = pos_to_tile(player);
+		let tile_pos = trapped_obj.tile_pos();
+		let trap = trapped_obj.trap.as_mut().unwrap();
 
-			if player_tile_pos == trapped_obj.tile_pos() {
-				let trap = trapped_obj.trap.as_mut().unwrap();
+		// Whoever's standing on it, or whoever shot it
+		let triggered_by = players
+			.iter()
+			.position(|player| pos_to_tile(player) == tile_pos)
+			.or(trap.tripped_by);
 
-				trap.triggered = true;
+		let Some(i) = triggered_by else {
+			return;
+		};
 
-				match trap.trap_type {
-					TrapType::Teleport => {
-						let rand_room = floor_info.rooms.choose().unwrap();
-						let rand_pos = IVec2::new(
-							rand::gen_range(rand_room.top_left.x + 1, rand_room.bottom_right.x - 1),
-							rand::gen_range(rand_room.top_left.y + 1, rand_room.bottom_right.y - 1),
-						);
-						// Pick a random background object to teleport the player to
-						player.pos = (rand_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
-					},
-					TrapType::SpawnMonster => {
-						// Summons six rats in the room somewhere
-						floor_info.monsters.extend((0..6).into_iter().map(|_| {
-							let player_room = floor_info
-								.rooms
-								.iter()
-								.find(|room| room.inside_room(player_tile_pos))
-								.unwrap();
-
-							let tile_pos = IVec2::new(
-								rand::gen_range(
-									player_room.top_left.x + 1,
-									player_room.bottom_right.x - 1,
-								),
-								rand::gen_range(
-									player_room.top_left.y + 1,
-									player_room.bottom_right.y - 1,
-								),
-							);
-
-							let pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
-
-							match floor_info.monster_types.choose().unwrap() {
-								MonsterObj::SmallRat(_) => MonsterObj::SmallRat(SmallRat::new(pos)),
-								MonsterObj::GreenSlime(_) => {
-									MonsterObj::GreenSlime(GreenSlime::new(pos))
-								},
-							}
-						}))
-					},
-				};
-			}
+		trap.triggered = true;
+
+		events.push(GameEvent::TrapTriggered {
+			player: i,
+			tile_pos,
+		});
+
+		#[cfg(feature = "scripting")]
+		if let Some(commands) = scripting::run_hook(
+			trap.trap_type.hook_name(),
+			scripting::ScriptContext::new(&players[i]),
+		) {
+			scripted.push((i, commands));
+			return;
+		}
+
+		sprung.push((i, tile_pos, trap.trap_type));
+	});
+
+	sprung.into_iter().for_each(|(i, tile_pos, trap_type)| {
+		match trap_type {
+			TrapType::Teleport => {
+				// Pick a random background object to teleport the player to, if
+				// they're the one standing on it
+				if pos_to_tile(&players[i]) == tile_pos {
+					players[i].pos = random_room_pos(&floor_info.rooms, None, rng);
+				}
+			},
+			TrapType::SpawnMonster => {
+				// Summons six rats in the room somewhere, or more further down
+				let scaling = floor_info.floor.scaling;
+
+				floor_info.monsters.extend((0..scaling.count(6)).map(|_| {
+					let pos = random_room_pos(&floor_info.rooms, Some(tile_pos), rng);
+
+					rng.choose(&floor_info.monster_types)
+						.unwrap()
+						.new_monster(pos)
+						.scaled(scaling)
+				}))
+			},
+			TrapType::Arrow => {
+				if let Some(arrow) = fire_arrow(tile_pos, &floor_info.floor) {
+					attacks.push(AttackObj::Arrow(arrow));
+				}
+			},
+			TrapType::PoisonGas => release_poison_gas(tile_pos, &mut floor_info.floor),
+			TrapType::Alarm => {
+				let room = floor_info
+					.rooms
+					.iter()
+					.find(|room| room.inside_room(tile_pos));
+				let player_pos = players[i].center();
+
+				floor_info.monsters.iter_mut().for_each(|monster| {
+					let heard = match room {
+						Some(room) => room.inside_room(pos_to_tile(monster)),
+						None => {
+							monster.center().distance(player_pos) <= ALARM_RANGE * TILE_SIZE as f32
+						},
+					};
+
+					if heard {
+						monster.alert(i, player_pos);
+					}
+				});
+			},
+		};
+	});
+
+	#[cfg(feature = "scripting")]
+	scripted.into_iter().for_each(|(i, commands)| {
+		scripting::apply_commands(commands, &mut players[i], floor_info, events, rng);
+	});
+}
+
+/// Players notice traps close enough to them, as long as they can see them
+fn spot_traps(players: &[Player], floor: &mut Floor) {
+	let spot_range = TRAP_SPOT_RANGE * TILE_SIZE as f32;
+
+	let spotted: Vec<usize> = floor
+		.objects
+		.iter()
+		.enumerate()
+		.filter(|(_, obj)| obj.trap.is_some_and(|trap| !trap.spotted))
+		.filter(|(_, obj)| {
+			players.iter().any(|player| {
+				player.center().distance(obj.center()) <= spot_range &&
+					floor.line_of_sight(player.center(), obj.center())
+			})
+		})
+		.map(|(i, _)| i)
+		.collect();
+
+	spotted.into_iter().for_each(|i| {
+		if let Some(trap) = &mut floor.objects[i].trap {
+			trap.spotted = true;
+		}
+	});
+}
+
+/// Shoots an arrow back at the trap from as far down the corridor as it can,
+/// if the trap isn't boxed in
+fn fire_arrow(tile_pos: IVec2, floor: &Floor) -> Option<Arrow> {
+	let open_tiles = |direction: IVec2| {
+		(1..=ARROW_TRAP_RANGE)
+			.take_while(|i| {
+				get_object_from_pos_list(tile_pos + direction * *i, &floor.objects)
+					.map_or(false, |obj| !obj.is_collidable())
+			})
+			.count() as i32
+	};
+
+	let (direction, distance) = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+		.into_iter()
+		.map(|direction| (direction, open_tiles(direction)))
+		.max_by_key(|(_, distance)| *distance)?;
+
+	if distance == 0 {
+		return None;
+	}
+
+	let tile_center =
+		|tile_pos: IVec2| (tile_pos.as_vec2() + Vec2::splat(0.5)) * Vec2::splat(TILE_SIZE as f32);
+
+	let start = tile_center(tile_pos + direction * distance);
+	let angle = get_angle(tile_center(tile_pos), start);
+
+	Some(Arrow::new(
+		&easy_polygon(start, Vec2::ZERO, 0.0),
+		None,
+		angle,
+		floor,
+		false,
+	))
+}
+
+fn release_poison_gas(tile_pos: IVec2, floor: &mut Floor) {
+	(-POISON_GAS_RADIUS..=POISON_GAS_RADIUS).for_each(|x| {
+		(-POISON_GAS_RADIUS..=POISON_GAS_RADIUS).for_each(|y| {
+			floor.add_effect(
+				tile_pos + IVec2::new(x, y),
+				EffectType::PoisonGas,
+				POISON_GAS_FRAMES,
+			);
 		});
 	});
 }
 
+fn random_room_pos(rooms: &[Room], tile_pos: Option<IVec2>, rng: &mut Rng) -> Vec2 {
+	let room = tile_pos
+		.and_then(|tile_pos| rooms.iter().find(|room| room.inside_room(tile_pos)))
+		.unwrap_or_else(|| rng.choose(rooms).unwrap());
+
+	let tile_pos = room.random_tile(rng);
+
+	(tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2()
+}
+
 fn apply_effect<E: Enchantable + ?Sized>(e: &mut E, effect: EffectType) {
-	let enchantment: Enchantment = effect.into();
-	e.apply_enchantment(enchantment);
+	if let Some(enchantment) = effect.enchantment() {
+		e.apply_enchantment(enchantment);
+	}
 }
 
-pub fn set_effects(players: &mut [Player], floor_info: &mut FloorInfo) {
-	floor_info.floor.objects.iter().for_each(|obj| {
-		obj.effects.keys().copied().for_each(|effect_type| {
-			players
-				.iter_mut()
-				.for_each(|player| apply_effect(player, effect_type));
+/// Applies the effects of whatever tile everyone's standing on
+pub fn set_effects(
+	players: &mut [Player], floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>,
+) {
+	let floor = &floor_info.floor;
+
+	players.iter_mut().for_each(|player| {
+		let Some(obj) = get_object_from_pos_list(pos_to_tile(player), &floor.objects) else {
+			return;
+		};
 
-			floor_info
-				.monsters
-				.iter_mut()
-				.for_each(|monster| apply_effect(monster, effect_type));
+		obj.effects.keys().copied().for_each(|effect_type| {
+			apply_effect(player, effect_type);
+
+			if effect_type == EffectType::Spikes {
+				let damage_direction = get_angle(player.center(), obj.center());
+				damage_player(
+					player,
+					SPIKE_DAMAGE,
+					DamageType::Pierce,
+					damage_direction,
+					None,
+					floor,
+					events,
+				);
+			}
 		});
 	});
+
+	floor_info.monsters.iter_mut().for_each(|monster| {
+		if let Some(obj) = get_object_from_pos_list(pos_to_tile(monster), &floor.objects) {
+			let flying = monster.flying();
+
+			obj.effects
+				.keys()
+				.copied()
+				.filter(|effect_type| !(flying && effect_type.slows()))
+				.for_each(|effect_type| apply_effect(monster, effect_type));
+		}
+	});
 }
 
 pub fn update_effects(floor: &mut Floor) {
@@ -1,4 +1,7 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
 use macroquad::prelude::*;
+use serde::{Serialize, Serializer};
 
 use crate::draw::Drawable;
 
@@ -7,12 +10,6 @@ pub fn get_angle(c: Vec2, e: Vec2) -> f32 {
 	d.y.atan2(d.x)
 }
 
-enum Orientation {
-	Colinear,
-	Clockwise,
-	CounterClockwise,
-}
-
 #[derive(Copy, Clone)]
 struct Line {
 	point1: Vec2,
@@ -52,14 +49,19 @@ impl Polygon {
 			line.point2 += dir;
 		});
 	}
-}
 
-impl Drawable for Polygon {
-	fn size(&self) -> Vec2 { Vec2::ZERO }
-
-	fn pos(&self) -> Vec2 { Vec2::ZERO }
+	/// The top left and bottom right corners of the box around the polygon
+	fn bounds(&self) -> (Vec2, Vec2) {
+		self.lines
+			.iter()
+			.flat_map(|line| [line.point1, line.point2])
+			.fold(
+				(Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+				|(min, max), point| (min.min(point), max.max(point)),
+			)
+	}
 
-	fn draw(&self) {
+	pub fn draw_outline(&self, color: Color) {
 		self.lines.iter().for_each(|line| {
 			draw_line(
 				line.point1.x,
@@ -67,12 +69,20 @@ impl Drawable for Polygon {
 				line.point2.x,
 				line.point2.y,
 				1.0,
-				WHITE,
+				color,
 			);
 		});
 	}
 }
 
+impl Drawable for Polygon {
+	fn size(&self) -> Vec2 { Vec2::ZERO }
+
+	fn pos(&self) -> Vec2 { Vec2::ZERO }
+
+	fn draw(&self) { self.draw_outline(WHITE); }
+}
+
 impl AsPolygon for Polygon {
 	fn as_polygon(&self) -> Polygon { *self }
 }
@@ -152,8 +162,8 @@ pub fn aabb_collision_dir<A: AsPolygon, B: AsPolygon>(
 	let obj1 = aabb1.as_polygon();
 	let obj2 = aabb2.as_polygon();
 
-	let mut obj1_pos_x = obj1.clone();
-	let mut obj1_pos_y = obj1.clone();
+	let mut obj1_pos_x = obj1;
+	let mut obj1_pos_y = obj1;
 
 	obj1_pos_x.shift(Vec2::new(distance.x, 0.0));
 	obj1_pos_y.shift(Vec2::new(0.0, distance.y));
@@ -163,14 +173,44 @@ pub fn aabb_collision_dir<A: AsPolygon, B: AsPolygon>(
 	BVec2::new(check_collision(obj1_pos_x), check_collision(obj1_pos_y))
 }
 
+/// How far `poly1` has to move to stop overlapping `poly2`, along whichever
+/// axis is the shortest way out. None if they don't overlap
+pub fn push_out<A: AsPolygon, B: AsPolygon>(poly1: &A, poly2: &B) -> Option<Vec2> {
+	let (min1, max1) = poly1.as_polygon().bounds();
+	let (min2, max2) = poly2.as_polygon().bounds();
+
+	let overlap = max1.min(max2) - min1.max(min2);
+
+	if overlap.cmple(Vec2::ZERO).any() {
+		return None;
+	}
+
+	// Two polygons in exactly the same place get pushed apart the same way
+	// every time
+	let away = ((min1 + max1) - (min2 + max2)).signum();
+
+	Some(match overlap.x < overlap.y {
+		true => Vec2::new(overlap.x * away.x, 0.0),
+		false => Vec2::new(0.0, overlap.y * away.y),
+	})
+}
+
 /// Bresenhams Circle Algorithm
 pub fn points_on_circumference(center: IVec2, radius: i32) -> Vec<IVec2> {
+	let mut points = Vec::new();
+	points_on_circumference_into(center, radius, &mut points);
+
+	points
+}
+
+/// Same as points_on_circumference, but reuses the given Vec instead of
+/// allocating a new one
+pub fn points_on_circumference_into(center: IVec2, radius: i32, points: &mut Vec<IVec2>) {
 	// Distance from center
 	let mut d = IVec2::new(radius, 0);
 	let mut o2 = 1 - radius;
 
-	// TODO: Use an ArrayVec
-	let mut points = Vec::new();
+	points.clear();
 
 	while d.y <= d.x {
 		points.push(center + d);
@@ -191,11 +231,18 @@ pub fn points_on_circumference(center: IVec2, radius: i32) -> Vec<IVec2> {
 			o2 += (2 * (d.y - d.x)) + 1;
 		}
 	}
-
-	points
 }
 
 pub fn points_on_line(pos1: IVec2, pos2: IVec2) -> Vec<IVec2> {
+	let mut lines = Vec::new();
+	points_on_line_into(pos1, pos2, &mut lines);
+
+	lines
+}
+
+/// Same as points_on_line, but reuses the given Vec instead of allocating a new
+/// one
+pub fn points_on_line_into(pos1: IVec2, pos2: IVec2, lines: &mut Vec<IVec2>) {
 	let mut d = (pos2 - pos1).abs();
 
 	let mut pos = pos1;
@@ -207,7 +254,7 @@ pub fn points_on_line(pos1: IVec2, pos2: IVec2) -> Vec<IVec2> {
 
 	d *= 2;
 
-	let mut lines = Vec::new();
+	lines.clear();
 
 	while n > 0 {
 		lines.push(pos);
@@ -222,8 +269,6 @@ pub fn points_on_line(pos1: IVec2, pos2: IVec2) -> Vec<IVec2> {
 
 		n -= 1;
 	}
-
-	lines
 }
 
 pub fn fletcher16(data: Vec<u8>) -> u16 {
@@ -236,3 +281,20 @@ pub fn fletcher16(data: Vec<u8>) -> u16 {
 
 	(sum2 << 8) | sum1
 }
+
+/// Serializes a HashMap in key order, since the order its entries come out in
+/// is different between processes
+pub fn serialize_sorted<S: Serializer, K: Ord + Serialize, V: Serialize>(
+	map: &HashMap<K, V>, serializer: S,
+) -> Result<S::Ok, S::Error> {
+	map.iter()
+		.collect::<BTreeMap<&K, &V>>()
+		.serialize(serializer)
+}
+
+/// Same as serialize_sorted, but for HashSets
+pub fn serialize_sorted_set<S: Serializer, T: Ord + Serialize>(
+	set: &HashSet<T>, serializer: S,
+) -> Result<S::Ok, S::Error> {
+	set.iter().collect::<BTreeSet<&T>>().serialize(serializer)
+}
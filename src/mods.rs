@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+
+const MODS_DIR: &str = "mods";
+const LOAD_ORDER_FILE: &str = "mods/load_order.ron";
+
+/// Every mod in the mods directory, in the order they're loaded
+pub static MODS: Lazy<Vec<Mod>> = Lazy::new(load_mods);
+
+/// A directory inside `mods/`. Mods can contain any of:
+/// - `textures/*.webp`, which add to or replace the game's textures by file
+///   name
+/// - `scripts/*.rhai`, which add to or replace scripting hooks by file name
+/// - `data/*.ron`, which override the game's data files
+pub struct Mod {
+	pub name: String,
+	path: PathBuf,
+}
+
+fn load_mods() -> Vec<Mod> {
+	let Ok(dir) = fs::read_dir(MODS_DIR) else {
+		return Vec::new();
+	};
+
+	let mut mods: Vec<Mod> = dir
+		.flatten()
+		.filter(|entry| entry.path().is_dir())
+		.filter_map(|entry| {
+			Some(Mod {
+				name: entry.file_name().to_str()?.to_string(),
+				path: entry.path(),
+			})
+		})
+		.collect();
+
+	mods.sort_by(|a, b| a.name.cmp(&b.name));
+
+	// The load order file lists mods from first to last loaded. Any mods not in
+	// it get loaded afterwards, alphabetically
+	if let Ok(load_order) = fs::read_to_string(LOAD_ORDER_FILE) {
+		match ron::from_str::<Vec<String>>(&load_order) {
+			Ok(load_order) => mods.sort_by_key(|m| {
+				load_order
+					.iter()
+					.position(|name| *name == m.name)
+					.unwrap_or(load_order.len())
+			}),
+			Err(e) => log::warn!("{LOAD_ORDER_FILE}: {e}"),
+		}
+	}
+
+	mods.iter()
+		.for_each(|m| log::info!("Loading mod {}", m.name));
+
+	mods
+}
+
+/// Every file with the given extension in each mod's `dir`, keyed by file name.
+/// If more than one mod has a file with the same name, the one loaded last wins
+pub fn mod_files(dir: &str, extension: &str) -> HashMap<String, PathBuf> {
+	MODS.iter()
+		.filter_map(|m| fs::read_dir(m.path.join(dir)).ok())
+		.flat_map(|dir| dir.flatten())
+		.filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some(extension))
+		.filter_map(|entry| Some((entry.file_name().to_str()?.to_string(), entry.path())))
+		.collect()
+}
+
+/// Loads `data/<name>.ron` from the last mod that has it, if any
+pub fn load_data<T: DeserializeOwned>(name: &str) -> Option<T> {
+	let path = mod_files("data", "ron").remove(&format!("{name}.ron"))?;
+	let data = fs::read_to_string(&path).ok()?;
+
+	match ron::from_str(&data) {
+		Ok(data) => Some(data),
+		Err(e) => {
+			log::warn!("{}: {e}", path.display());
+			None
+		},
+	}
+}
@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::flow_field::FlowField;
+use crate::map::{pos_to_tile, Floor};
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::find_target;
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 10.0;
+const MAX_HEALTH: u16 = 6;
+const SPEED: f32 = 1.8;
+const DAMAGE: u16 = 4;
+/// Frames between each bite
+const BITE_COOLDOWN: u8 = 30;
+/// How many tiles away a player can be seen from
+const SIGHT_RANGE: i32 = 10;
+/// How far bats swerve from side to side as they fly, in pixels per frame
+const WOBBLE: f32 = 1.5;
+/// How quickly bats swerve from side to side, in radians per frame
+const WOBBLE_SPEED: f32 = 0.25;
+/// How long a bat stays up on the ceiling after being hit, in frames
+const CEILING_TIME: u16 = 75;
+/// Bats that spot a player bring every other bat within this many tiles with
+/// them
+pub const SWARM_RANGE: f32 = 6.0;
+/// How many bats are spawned together
+pub const SWARM_SIZE: usize = 3;
+
+/// Flies straight at players, swerving from side to side. It flies up to the
+/// ceiling out of reach whenever it's hit, and comes back down once it's
+/// recovered
+#[derive(Clone, Serialize)]
+pub struct Bat {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
+	pos: Vec2,
+	speed_mul: f32,
+	target: Option<usize>,
+	/// Counts up every frame, to keep the bat swerving
+	time: u16,
+	time_til_bite: u8,
+	time_on_ceiling: u16,
+	afflictions: Afflictions,
+}
+
+impl Bat {
+	/// The player the bat's after, if any
+	pub fn hunting(&self) -> Option<usize> { self.target }
+
+	fn on_ceiling(&self) -> bool { self.time_on_ceiling > 0 }
+
+	/// Which way to fly to get to the player. Bats don't work out paths, but
+	/// they can follow the floor's flow field round corners
+	fn heading(&self, player: &Player, floor: &Floor) -> Vec2 {
+		let goal = match floor.flow_field().downhill(pos_to_tile(self)) {
+			Some(next_tile_pos) => FlowField::tile_center(next_tile_pos),
+			None => player.center(),
+		};
+
+		(goal - self.center()).normalize_or_zero()
+	}
+
+	/// Moves as far as the walls allow, sliding along them
+	fn fly(&mut self, change: Vec2, floor: &Floor) {
+		if !floor.collision(self, change) {
+			self.pos += change;
+			return;
+		}
+
+		[Vec2::new(change.x, 0.0), Vec2::new(0.0, change.y)]
+			.into_iter()
+			.for_each(|change| {
+				if !floor.collision(self, change) {
+					self.pos += change;
+				}
+			});
+	}
+}
+
+impl Monster for Bat {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			pos,
+			speed_mul: 1.0,
+			target: None,
+			time: 0,
+			time_til_bite: 0,
+			time_on_ceiling: 0,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		self.time = self.time.wrapping_add(1);
+		self.time_til_bite = self.time_til_bite.saturating_sub(1);
+		self.time_on_ceiling = self.time_on_ceiling.saturating_sub(1);
+
+		if self
+			.target
+			.map_or(true, |i| players.get(i).map_or(true, |p| p.hp() == 0))
+		{
+			self.target = find_target(self, SIGHT_RANGE, players, floor);
+		}
+
+		let wobble = (self.time as f32 * WOBBLE_SPEED).sin() * WOBBLE;
+
+		let Some(player) = self.target.map(|i| &players[i]) else {
+			// Flutter round in circles until someone comes by
+			let angle = self.time as f32 * 0.05;
+			self.fly(Vec2::new(angle.cos(), angle.sin()) * wobble, floor);
+			return;
+		};
+
+		let heading = match self.on_ceiling() {
+			// Keeps away until it's ready to come back down
+			true => (self.center() - player.center()).normalize_or_zero(),
+			false => self.heading(player, floor),
+		};
+		let side = Vec2::new(-heading.y, heading.x);
+
+		self.fly((heading * SPEED + side * wobble) * self.speed_mul, floor);
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		if self.on_ceiling() || self.time_til_bite > 0 {
+			return;
+		}
+
+		if let Some(p) = players
+			.iter_mut()
+			.find(|p| aabb_collision(*p, self, Vec2::ZERO))
+		{
+			let damage_direction = get_angle(p.pos(), self.pos);
+
+			damage_player(
+				p,
+				DAMAGE,
+				DamageType::Pierce,
+				damage_direction,
+				Some(MonsterKind::Bat),
+				floor,
+				events,
+			);
+
+			self.time_til_bite = BITE_COOLDOWN;
+		}
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+		self.time_on_ceiling = CEILING_TIME;
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn asleep(&self) -> bool { self.asleep }
+
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 1;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> {
+		players.get(self.target?).map(|p| p.center())
+	}
+
+	fn alert(&mut self, player: usize, _pos: Vec2) { self.target = Some(player); }
+
+	fn targetable(&self) -> bool { !self.on_ceiling() }
+
+	fn flying(&self) -> bool { true }
+}
+
+impl Enchantable for Bat {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			EnchantmentKind::Blinded => self.target = None,
+			EnchantmentKind::Sticky => self.speed_mul = 0.5,
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Stunned |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Chilled |
+			EnchantmentKind::Frozen => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 60,
+			EnchantmentKind::Frozen => 45,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 300,
+			_ => 180,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| {
+				if kind == EnchantmentKind::Sticky {
+					self.speed_mul = 1.0;
+				}
+			});
+	}
+}
+
+impl AsPolygon for Bat {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for Bat {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		let center = self.center();
+
+		// Up on the ceiling, only its shadow's left on the floor
+		if self.on_ceiling() {
+			draw_circle(
+				center.x,
+				center.y,
+				SIZE * 0.4,
+				Color::new(0.0, 0.0, 0.0, 0.35),
+			);
+			return;
+		}
+
+		let body = Color::new(0.3, 0.2, 0.35, 1.0);
+		let flap = (self.time as f32 * 0.5).sin() * SIZE * 0.4;
+
+		[-1.0, 1.0].into_iter().for_each(|side: f32| {
+			draw_triangle(
+				center,
+				center + Vec2::new(side * SIZE, -flap),
+				center + Vec2::new(side * SIZE * 0.6, SIZE * 0.3),
+				body,
+			);
+		});
+
+		draw_circle(center.x, center.y, SIZE * 0.35, body);
+	}
+}
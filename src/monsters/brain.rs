@@ -0,0 +1,532 @@
+use crate::draw::Drawable;
+use crate::enchantments::EnchantmentKind;
+use crate::flow_field::FlowField;
+use crate::map::{pos_to_tile, with_scratch, Floor, Object, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::player::Player;
+use crate::rng::Rng;
+use crate::telegraphs::{TelegraphShape, Telegraphs};
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{shove, Monster};
+
+/// What a monster's brain is up to
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum BrainState {
+	/// Standing around, waiting to wander off somewhere
+	Idle,
+	/// Heading somewhere with nobody in sight
+	Wander,
+	/// Going after a player it's seen
+	Chase,
+	/// Getting away from a player that came too close
+	Flee,
+	/// Winding up a lunge at a player, then recovering from it
+	Attack,
+}
+
+/// Everything that makes one monster's brain behave differently to another's.
+/// Ranges are in tiles, speeds in pixels a frame, and times in frames
+pub struct BrainParams {
+	/// How far away players can be seen from
+	pub sight_range: i32,
+	/// How long the monster stands around between wanders
+	pub idle_time: u16,
+	/// Wanders anywhere on the floor, instead of just somewhere it can see
+	pub wanders_far: bool,
+	pub wander_speed: f32,
+	/// How long it takes to start chasing a player after spotting them
+	pub reaction_time: u16,
+	/// Monsters that don't chase hold their ground once they've seen someone
+	pub chase_speed: Option<f32>,
+	/// How close a player can get before the monster runs off, if it ever does
+	pub flee_range: Option<f32>,
+	pub flee_speed: f32,
+	/// How close the monster gets before lunging at a player, if it ever does
+	pub lunge_range: Option<f32>,
+	/// How far a lunge carries the monster, in pixels
+	pub lunge_distance: f32,
+	/// How long the monster crouches before lunging, giving players the chance
+	/// to get out of the way
+	pub lunge_windup: u16,
+	pub lunge_cooldown: u16,
+}
+
+/// Where a monster is and how big it is, which is all a brain needs to look
+/// around and find its way
+struct Body {
+	pos: Vec2,
+	size: f32,
+}
+
+impl AsPolygon for Body {
+	fn as_polygon(&self) -> Polygon {
+		let half_size = Vec2::splat(self.size * 0.5);
+		easy_polygon(self.pos + half_size, half_size, 0.0)
+	}
+}
+
+/// Wandering, chasing and fleeing, shared between monsters. Each monster
+/// passes in its own BrainParams, and only has to handle its own attacks
+#[derive(Clone, Serialize)]
+pub struct Brain {
+	state: BrainState,
+	/// The player being chased
+	prey: Option<usize>,
+	/// Where the monster's wandering or fleeing to
+	goal: Option<Vec2>,
+	path: Option<(Vec<Vec2>, usize)>,
+	/// Which way the monster's about to lunge
+	lunge: Option<f32>,
+	time_til_move: u16,
+	speed_mul: f32,
+}
+
+impl Brain {
+	pub fn new(params: &BrainParams) -> Self {
+		Self {
+			state: BrainState::Idle,
+			prey: None,
+			goal: None,
+			path: None,
+			lunge: None,
+			time_til_move: params.idle_time,
+			speed_mul: 1.0,
+		}
+	}
+
+	/// Whether the monster's seen a player and is doing something about it
+	pub fn aggressive(&self) -> bool {
+		matches!(
+			self.state,
+			BrainState::Chase | BrainState::Flee | BrainState::Attack
+		)
+	}
+
+	pub fn set_speed_mul(&mut self, speed_mul: f32) { self.speed_mul = speed_mul; }
+
+	pub fn think(
+		&mut self, params: &BrainParams, pos: &mut Vec2, size: f32, players: &[Player],
+		floor: &Floor, rng: &mut Rng,
+	) {
+		if self.time_til_move > 0 {
+			self.time_til_move -= 1;
+			return;
+		}
+
+		let mut body = Body { pos: *pos, size };
+
+		match self.state {
+			BrainState::Idle | BrainState::Wander => {
+				match spot_player(params, &body, players, floor) {
+					Some(i) => {
+						self.alert(i);
+						self.time_til_move = params.reaction_time;
+					},
+					None => self.wander(params, &mut body, floor, rng),
+				}
+			},
+			BrainState::Chase => self.chase(params, &mut body, players, floor, rng),
+			BrainState::Flee => {
+				if !self.follow_path(&mut body, params.flee_speed) {
+					self.state = BrainState::Chase;
+					self.goal = None;
+				}
+			},
+			BrainState::Attack => match self.lunge.take() {
+				Some(angle) => {
+					body.pos += Vec2::new(angle.cos(), angle.sin()) * params.lunge_distance;
+					self.time_til_move = params.lunge_cooldown;
+				},
+				None => self.state = BrainState::Chase,
+			},
+		};
+
+		*pos = body.pos;
+	}
+
+	/// Bumbles around at random while the monster can't see
+	pub fn stumble(&mut self, pos: &mut Vec2, size: f32, floor: &Floor, rng: &mut Rng) {
+		if self.time_til_move > 0 {
+			self.time_til_move -= 1;
+			return;
+		}
+
+		let mut body = Body { pos: *pos, size };
+
+		match self.goal {
+			Some(goal) => {
+				if goal.distance(body.pos) < size {
+					self.goal = None;
+				}
+
+				let angle = get_angle(goal, body.pos);
+				let change = Vec2::new(angle.cos(), angle.sin()) * 1.2 * self.speed_mul;
+
+				if !floor.collision(&body, change) {
+					body.pos += change;
+				} else {
+					// Bounce off whatever it walked into
+					let change = change * 1.5;
+					if !floor.collision(&body, -change) {
+						body.pos -= change;
+					}
+					self.goal = None;
+					self.time_til_move = 30;
+				}
+			},
+			None => {
+				let direction = Vec2::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0));
+
+				self.goal = Some(
+					direction * Vec2::splat((TILE_SIZE * 2) as f32) +
+						body.pos + Vec2::splat(size * 0.25),
+				);
+			},
+		};
+
+		*pos = body.pos;
+	}
+
+	pub fn alert(&mut self, player: usize) {
+		self.state = BrainState::Chase;
+		self.prey = Some(player);
+		self.goal = None;
+		self.path = None;
+		self.lunge = None;
+	}
+
+	/// Warns players about a lunge that's being wound up
+	pub fn telegraph(
+		&self, params: &BrainParams, center: Vec2, size: f32, telegraphs: &mut Telegraphs,
+	) {
+		if let Some(angle) = self.lunge {
+			telegraphs.warn(
+				TelegraphShape::Line {
+					from: center,
+					angle,
+					length: params.lunge_distance + size * 0.5,
+				},
+				self.time_til_move,
+				params.lunge_windup,
+			);
+		}
+	}
+
+	/// Wanders over to the tile a noise came from, and spots whoever's there on
+	/// the way
+	pub fn on_noise(&mut self, pos: Vec2) {
+		if self.aggressive() {
+			return;
+		}
+
+		let tile_pos = (pos / TILE_SIZE as f32).floor() * TILE_SIZE as f32;
+
+		// Already on its way
+		if self.state == BrainState::Wander && self.goal == Some(tile_pos) {
+			return;
+		}
+
+		self.state = BrainState::Wander;
+		self.goal = Some(tile_pos);
+		self.path = None;
+		self.time_til_move = 0;
+	}
+
+	/// Forgets everything, since the monster can't see where it's going anymore
+	pub fn blind(&mut self) {
+		self.state = BrainState::Wander;
+		self.prey = None;
+		self.goal = None;
+		self.path = None;
+		self.lunge = None;
+		self.time_til_move = 50;
+	}
+
+	/// Gets its bearings again after being blinded
+	pub fn unblind(&mut self) {
+		self.state = BrainState::Idle;
+		self.prey = None;
+		self.goal = None;
+		self.path = None;
+		self.lunge = None;
+		self.time_til_move = 10;
+	}
+
+	pub fn clear_path(&mut self) { self.path = None; }
+
+	pub fn target(&self, players: &[Player]) -> Option<Vec2> {
+		match self.state {
+			BrainState::Chase | BrainState::Attack => players.get(self.prey?).map(|p| p.center()),
+			_ => self.goal,
+		}
+	}
+
+	pub fn current_path(&self) -> &[Vec2] {
+		match &self.path {
+			Some((path, i)) => path.get(*i..).unwrap_or_default(),
+			None => &[],
+		}
+	}
+
+	fn rest(&mut self, params: &BrainParams) {
+		self.state = BrainState::Idle;
+		self.prey = None;
+		self.goal = None;
+		self.path = None;
+		self.time_til_move = params.idle_time;
+	}
+
+	fn wander(&mut self, params: &BrainParams, body: &mut Body, floor: &Floor, rng: &mut Rng) {
+		self.state = BrainState::Wander;
+
+		if self.path.is_none() {
+			let goal = self.goal.or_else(|| match params.wanders_far {
+				true => {
+					// Any room on the floor
+					let rooms = floor
+						.objects()
+						.iter()
+						.filter(|obj| !obj.is_collidable())
+						.collect::<Vec<&Object>>();
+
+					rng.choose(&rooms).map(|obj| obj.pos())
+				},
+				false => with_scratch(|scratch| {
+					floor
+						.visible_objects_with(body, Some(params.sight_range), scratch)
+						.last()
+						.map(|obj| obj.pos())
+				}),
+			});
+
+			self.path = goal.and_then(|goal| {
+				let goal_aabb = tile_polygon(goal);
+
+				match params.wanders_far {
+					true => floor.find_path(body, &goal_aabb, false, true, None),
+					false => floor.find_path(body, &goal_aabb, true, false, Some(4)),
+				}
+			});
+
+			match self.path.is_some() {
+				true => self.goal = goal,
+				false => {
+					self.rest(params);
+					return;
+				},
+			};
+		}
+
+		if !self.follow_path(body, params.wander_speed) {
+			self.rest(params);
+		}
+	}
+
+	fn chase(
+		&mut self, params: &BrainParams, body: &mut Body, players: &[Player], floor: &Floor,
+		rng: &mut Rng,
+	) {
+		let Some(player) = self
+			.prey
+			.and_then(|i| players.get(i))
+			.filter(|p| p.hp() > 0)
+		else {
+			// Whoever it was after is dead, so back to wandering
+			self.rest(params);
+			return;
+		};
+
+		if let Some(flee_range) = params.flee_range {
+			let threat = players
+				.iter()
+				.filter(|p| p.hp() > 0)
+				.map(|p| (p, p.center().distance(body.center())))
+				.filter(|(_, distance)| *distance <= flee_range * TILE_SIZE as f32)
+				.min_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+
+			if let Some((threat, _)) = threat {
+				if self.flee(threat, flee_range, body, floor, rng) {
+					return;
+				}
+			}
+		}
+
+		let distance = player.center().distance(body.center());
+
+		if params
+			.lunge_range
+			.is_some_and(|range| distance <= range * TILE_SIZE as f32)
+		{
+			// The aim's fixed once the monster starts winding up
+			self.lunge = Some(get_angle(player.pos(), body.pos));
+			self.state = BrainState::Attack;
+			self.time_til_move = params.lunge_windup;
+			self.path = None;
+			return;
+		}
+
+		if let Some(speed) = params.chase_speed {
+			self.follow_scent(body, player, floor, speed);
+		}
+	}
+
+	/// Heads for somewhere far enough away from the threat, returning whether
+	/// anywhere was found
+	fn flee(
+		&mut self, threat: &Player, flee_range: f32, body: &Body, floor: &Floor, rng: &mut Rng,
+	) -> bool {
+		let spots = floor
+			.objects()
+			.iter()
+			.filter(|obj| match obj.is_collidable() {
+				true => obj.door().is_some(),
+				false => true,
+			})
+			.filter(|obj| obj.center().distance(threat.center()) >= flee_range * TILE_SIZE as f32)
+			.collect::<Vec<&Object>>();
+
+		let Some(spot) = rng.choose(&spots).map(|obj| obj.pos()) else {
+			return false;
+		};
+
+		self.path = floor
+			.find_path(body, &tile_polygon(spot), false, true, None)
+			.map(|path| (path, 1));
+
+		if self.path.is_some() {
+			self.state = BrainState::Flee;
+			self.goal = Some(spot);
+		}
+
+		self.path.is_some()
+	}
+
+	/// Walks along the path, returning whether there was any of it left
+	fn follow_path(&mut self, body: &mut Body, speed: f32) -> bool {
+		let speed = speed * self.speed_mul;
+
+		let Some((path, i)) = &mut self.path else {
+			return false;
+		};
+
+		match path.get(*i) {
+			Some(next_pos) => {
+				match speed >= body.pos.distance(*next_pos) {
+					true => {
+						body.pos = *next_pos;
+						*i += 1;
+					},
+					false => {
+						let angle = get_angle(*next_pos, body.pos);
+						body.pos += Vec2::new(angle.cos(), angle.sin()) * speed;
+					},
+				};
+
+				true
+			},
+			None => {
+				self.path = None;
+				false
+			},
+		}
+	}
+
+	/// Heads downhill on the floor's flow field, towards the closest player
+	fn follow_scent(&mut self, body: &mut Body, player: &Player, floor: &Floor, speed: f32) {
+		let flow_field = floor.flow_field();
+		let tile_pos = pos_to_tile(body);
+
+		let goal = match flow_field.downhill(tile_pos) {
+			Some(next_tile_pos) => {
+				// A closed door still smells of the player, but there's no getting through it
+				if floor
+					.get_object_from_pos(next_tile_pos)
+					.map_or(true, |obj| obj.is_collidable())
+				{
+					return;
+				}
+
+				FlowField::tile_center(next_tile_pos) - Vec2::splat(body.size * 0.5)
+			},
+			None if flow_field.distance(tile_pos) == Some(0) => player.pos(),
+			None => return,
+		};
+
+		let angle = get_angle(goal, body.pos);
+		let change = Vec2::new(angle.cos(), angle.sin()) * speed * self.speed_mul;
+
+		body.pos += change.clamp_length_max(body.pos.distance(goal));
+	}
+}
+
+/// The first living player the monster can see
+fn spot_player(
+	params: &BrainParams, body: &Body, players: &[Player], floor: &Floor,
+) -> Option<usize> {
+	with_scratch(|scratch| {
+		let visible_objects = floor.visible_objects_with(body, Some(params.sight_range), scratch);
+
+		players.iter().position(|player| {
+			player.noticeable() && visible_objects.contains_tile(pos_to_tile(player))
+		})
+	})
+}
+
+/// The closest living player a monster can see, for monsters that pick out
+/// their own targets instead of using a brain. Blinded monsters can't see
+/// anyone
+pub fn find_target<M: Monster>(
+	monster: &M, sight_range: i32, players: &[Player], floor: &Floor,
+) -> Option<usize> {
+	if monster.afflictions().has(EnchantmentKind::Blinded) {
+		return None;
+	}
+
+	with_scratch(|scratch| {
+		let visible_objects = floor.visible_objects_with(monster, Some(sight_range), scratch);
+
+		players
+			.iter()
+			.enumerate()
+			.filter(|(_, player)| {
+				player.noticeable() && visible_objects.contains_tile(pos_to_tile(*player))
+			})
+			.min_by(|(_, p1), (_, p2)| {
+				let p1_distance = p1.center().distance(monster.center());
+				let p2_distance = p2.center().distance(monster.center());
+
+				p1_distance.total_cmp(&p2_distance)
+			})
+			.map(|(i, _)| i)
+	})
+}
+
+/// Heads towards the closest player along the floor's flow field, waiting
+/// behind anything that's in the way, like a closed door
+pub fn close_in<M: Monster>(monster: &mut M, size: f32, speed: f32, floor: &Floor) {
+	let Some(next_tile_pos) = floor.flow_field().downhill(pos_to_tile(monster)) else {
+		return;
+	};
+
+	if floor
+		.get_object_from_pos(next_tile_pos)
+		.map_or(true, |obj| obj.is_collidable())
+	{
+		return;
+	}
+
+	let pos = monster.pos();
+	let goal = FlowField::tile_center(next_tile_pos) - Vec2::splat(size * 0.5);
+	let angle = get_angle(goal, pos);
+	let change = Vec2::new(angle.cos(), angle.sin()) * speed;
+
+	shove(monster, change.clamp_length_max(pos.distance(goal)), floor);
+}
+
+fn tile_polygon(pos: Vec2) -> Polygon {
+	const HALF_TILE_SIZE: Vec2 = Vec2::splat((TILE_SIZE / 2) as f32);
+	easy_polygon(pos + HALF_TILE_SIZE, HALF_TILE_SIZE, 0.0)
+}
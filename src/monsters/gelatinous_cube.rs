@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::ItemType;
+use crate::map::Floor;
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind, MonsterObj};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::{Brain, BrainParams};
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 28.0;
+const MAX_HEALTH: u16 = 50;
+const DAMAGE: u16 = 12;
+/// How many times over a cube splits in two before it dies for good
+const SPLITS: u8 = 1;
+/// How big each half is compared to the cube it split from
+const SPLIT_SCALE: f32 = 0.6;
+
+/// Cubes slide slowly around whatever they can see, and slowly after anyone
+/// they spot
+const BRAIN: BrainParams = BrainParams {
+	sight_range: 6,
+	idle_time: 90,
+	wanders_far: false,
+	wander_speed: 0.4,
+	reaction_time: 30,
+	chase_speed: Some(0.5),
+	flee_range: None,
+	flee_speed: 0.0,
+	lunge_range: None,
+	lunge_distance: 0.0,
+	lunge_windup: 0,
+	lunge_cooldown: 0,
+};
+
+/// A big, slow block of jelly. It swallows anything left lying on the floor
+/// as it goes, and drops it all when it dies, after splitting into two smaller
+/// cubes
+#[derive(Clone, Serialize)]
+pub struct GelatinousCube {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
+	pos: Vec2,
+	size: f32,
+	splits_left: u8,
+	brain: Brain,
+	/// Everything it's swallowed, which it drops when it dies
+	engulfed: Vec<ItemType>,
+	afflictions: Afflictions,
+}
+
+impl GelatinousCube {
+	pub fn engulf(&mut self, items: impl Iterator<Item = ItemType>) { self.engulfed.extend(items); }
+
+	pub fn engulfed(&self) -> &[ItemType] { &self.engulfed }
+
+	/// The two halves a dead cube leaves behind, each with half its health.
+	/// Whatever it had swallowed is dropped instead of being split between them
+	pub fn split(&self) -> Vec<MonsterObj> {
+		if self.splits_left == 0 {
+			return Vec::new();
+		}
+
+		let size = self.size * SPLIT_SCALE;
+		let max_health = (self.max_health / 2).max(1);
+		let center = self.center();
+
+		[-1.0, 1.0]
+			.into_iter()
+			.map(|side| {
+				let mut half = Self::new(center + Vec2::new(side * size * 0.5, 0.0) - size * 0.5);
+				half.size = size;
+				half.splits_left = self.splits_left - 1;
+				half.set_max_health(max_health);
+
+				// Both halves keep going after whoever the cube was after
+				half.brain = self.brain.clone();
+				half.brain.clear_path();
+
+				MonsterObj::GelatinousCube(half)
+			})
+			.collect()
+	}
+}
+
+impl Monster for GelatinousCube {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			pos,
+			size: SIZE,
+			splits_left: SPLITS,
+			brain: Brain::new(&BRAIN),
+			engulfed: Vec::new(),
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, rng: &mut Rng) {
+		match self.afflictions.has(EnchantmentKind::Blinded) {
+			true => self.brain.stumble(&mut self.pos, self.size, floor, rng),
+			false => self
+				.brain
+				.think(&BRAIN, &mut self.pos, self.size, players, floor, rng),
+		};
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		players.iter_mut().for_each(|p| {
+			if aabb_collision(p, self, Vec2::ZERO) {
+				let damage_direction = get_angle(p.pos(), self.pos);
+
+				damage_player(
+					p,
+					DAMAGE,
+					DamageType::Blunt,
+					damage_direction,
+					Some(MonsterKind::GelatinousCube),
+					floor,
+					events,
+				);
+			}
+		});
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn asleep(&self) -> bool { self.asleep }
+
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		// The halves are worth less than the whole thing
+		let xp = match self.splits_left {
+			0 => 1,
+			_ => 4,
+		};
+
+		(self.afflictions.damaged_by(), xp)
+	}
+
+	fn current_path(&self) -> &[Vec2] { self.brain.current_path() }
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> { self.brain.target(players) }
+
+	fn alert(&mut self, player: usize, _pos: Vec2) { self.brain.alert(player); }
+
+	fn on_noise(&mut self, pos: Vec2) { self.brain.on_noise(pos); }
+}
+
+impl Enchantable for GelatinousCube {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			EnchantmentKind::Blinded => self.brain.blind(),
+			EnchantmentKind::Stunned | EnchantmentKind::Frozen => self.brain.clear_path(),
+			// Cubes are immune to being stuck, so they never get this far
+			EnchantmentKind::Sticky |
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Chilled => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 300,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| {
+				if kind == EnchantmentKind::Blinded {
+					self.brain.unblind();
+				}
+			});
+	}
+}
+
+impl AsPolygon for GelatinousCube {
+	fn as_polygon(&self) -> Polygon {
+		let half_size = self.size() * Vec2::splat(0.5);
+		easy_polygon(self.pos + half_size, half_size, 0.0)
+	}
+}
+
+impl Drawable for GelatinousCube {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(self.size) }
+
+	fn draw(&self) {
+		let size = self.size;
+
+		draw_rectangle(
+			self.pos.x,
+			self.pos.y,
+			size,
+			size,
+			Color::new(0.45, 0.85, 0.55, 0.5),
+		);
+		draw_rectangle_lines(
+			self.pos.x,
+			self.pos.y,
+			size,
+			size,
+			1.5,
+			Color::new(0.3, 0.7, 0.4, 0.9),
+		);
+
+		// Whatever it's swallowed hangs inside it, in a grid
+		const COLUMNS: usize = 3;
+		let spacing = size / (COLUMNS + 1) as f32;
+
+		self.engulfed
+			.iter()
+			.take(COLUMNS * COLUMNS)
+			.enumerate()
+			.for_each(|(i, item)| {
+				let color = match item {
+					ItemType::Gold(_) => GOLD,
+					_ => Color::new(0.6, 0.4, 0.3, 1.0),
+				};
+
+				let offset =
+					Vec2::new((i % COLUMNS + 1) as f32, (i / COLUMNS + 1) as f32) * spacing;
+				draw_rectangle(
+					self.pos.x + offset.x - 2.0,
+					self.pos.y + offset.y - 2.0,
+					4.0,
+					4.0,
+					color,
+				);
+			});
+	}
+}
@@ -0,0 +1,269 @@
+use std::collections::HashSet;
+
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::{ItemInfo, ItemType};
+use crate::map::{pos_to_tile, Chest, Floor, TILE_SIZE};
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{shove, Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::close_in;
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 28.0;
+const MAX_HEALTH: u16 = 40;
+const SPEED: f32 = 1.2;
+/// The bite it gives whoever wakes it up
+const SURPRISE_DAMAGE: u16 = 25;
+const BITE_DAMAGE: u16 = 15;
+/// Frames between each bite
+const BITE_COOLDOWN: u8 = 45;
+/// How much gold a mimic disguised as a gold pile looks like
+const FAKE_GOLD: u32 = 25;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Disguise {
+	Gold,
+	Chest,
+}
+
+/// Sits perfectly still looking like a pile of gold or a chest, drawn exactly
+/// like the real thing, until a player gets within a tile of it. Then it bites
+/// them, hard, and chases after them
+#[derive(Clone, Serialize)]
+pub struct Mimic {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	pos: Vec2,
+	disguise: Disguise,
+	revealed: bool,
+	target: Option<usize>,
+	/// Whoever woke the mimic up, and hasn't been bitten for it yet
+	surprised: Option<usize>,
+	time_til_bite: u8,
+	afflictions: Afflictions,
+}
+
+impl Mimic {
+	pub fn disguised_as(mut self, disguise: Disguise) -> Self {
+		self.disguise = disguise;
+		self
+	}
+}
+
+impl Monster for Mimic {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			// Sits in the middle of the tile, wherever the real thing would be
+			pos: pos + Vec2::splat((TILE_SIZE as f32 - SIZE) * 0.5),
+			disguise: Disguise::Gold,
+			revealed: false,
+			target: None,
+			surprised: None,
+			time_til_bite: 0,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		self.time_til_bite = self.time_til_bite.saturating_sub(1);
+
+		if !self.revealed {
+			let tile_pos = pos_to_tile(self);
+
+			// Anyone on this tile or any of the eight around it
+			self.surprised = players.iter().position(|player| {
+				let offset = (pos_to_tile(player) - tile_pos).abs();
+				player.hp() > 0 && offset.max_element() <= 1
+			});
+
+			if self.surprised.is_some() {
+				self.revealed = true;
+				self.target = self.surprised;
+			}
+
+			return;
+		}
+
+		if self.target.is_some() {
+			close_in(self, SIZE, SPEED, floor);
+		}
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		if let Some(i) = self.surprised.take() {
+			let damage_direction = get_angle(players[i].pos(), self.pos);
+
+			damage_player(
+				&mut players[i],
+				SURPRISE_DAMAGE,
+				DamageType::Pierce,
+				damage_direction,
+				Some(MonsterKind::Mimic),
+				floor,
+				events,
+			);
+
+			self.time_til_bite = BITE_COOLDOWN;
+			return;
+		}
+
+		if !self.revealed || self.time_til_bite > 0 {
+			return;
+		}
+
+		if let Some(p) = players
+			.iter_mut()
+			.find(|p| aabb_collision(*p, self, Vec2::ZERO))
+		{
+			let damage_direction = get_angle(p.pos(), self.pos);
+
+			damage_player(
+				p,
+				BITE_DAMAGE,
+				DamageType::Pierce,
+				damage_direction,
+				Some(MonsterKind::Mimic),
+				floor,
+				events,
+			);
+
+			self.time_til_bite = BITE_COOLDOWN;
+		}
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+
+		// Hitting it gives the game away, but at least nobody gets bitten
+		if !self.revealed {
+			self.revealed = true;
+			self.target = Some(damage_info.player);
+		}
+	}
+
+	fn knock_back(&mut self, change: Vec2, floor: &Floor) {
+		// Rooted to the spot while it's pretending
+		if self.revealed {
+			shove(self, change, floor);
+		}
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 6;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> {
+		players.get(self.target?).map(|p| p.center())
+	}
+
+	fn alert(&mut self, player: usize, _pos: Vec2) {
+		// An alarm's no reason to give itself away
+		if self.revealed {
+			self.target = Some(player);
+		}
+	}
+}
+
+impl Enchantable for Mimic {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 300,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |_| ());
+	}
+}
+
+impl AsPolygon for Mimic {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for Mimic {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		// Drawn with the same code as the real thing, so there's nothing to
+		// tell them apart by
+		if !self.revealed {
+			let tile_pos = pos_to_tile(self);
+
+			match self.disguise {
+				Disguise::Gold => ItemInfo::new(ItemType::Gold(FAKE_GOLD), Some(tile_pos)).draw(),
+				Disguise::Chest => Chest::closed(tile_pos).draw(),
+			}
+
+			return;
+		}
+
+		let body = match self.disguise {
+			Disguise::Gold => GOLD,
+			Disguise::Chest => BROWN,
+		};
+
+		draw_rectangle(self.pos.x, self.pos.y, SIZE, SIZE, body);
+
+		// A mouth full of teeth where the lid was
+		let mouth_y = self.pos.y + SIZE * 0.45;
+		draw_rectangle(self.pos.x, mouth_y, SIZE, SIZE * 0.2, MAROON);
+
+		(0..4).for_each(|i| {
+			let x = self.pos.x + SIZE * (i as f32 + 0.5) / 4.0;
+
+			draw_triangle(
+				Vec2::new(x - 3.0, mouth_y),
+				Vec2::new(x + 3.0, mouth_y),
+				Vec2::new(x, mouth_y + 5.0),
+				WHITE,
+			);
+		});
+	}
+}
@@ -1,187 +1,1278 @@
+mod bat;
+mod brain;
+mod gelatinous_cube;
+mod mimic;
+mod necromancer;
+mod rat_king;
+mod skeleton_archer;
 mod slime;
+mod slime_queen;
 mod small_rat;
+mod spawner;
+mod spider;
+mod wolf;
+mod zombie;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 
 use crate::attacks::AttackObj;
+use crate::corpses::Corpses;
+use crate::difficulty::MonsterScaling;
 use crate::draw::Drawable;
-use crate::enchantments::{Enchantable, Enchantment};
-use crate::map::{Floor, FloorInfo};
-use crate::math::{AsPolygon, Polygon};
-use crate::player::{DamageInfo, Player};
+use crate::enchantments::{retain_in_order, Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::{ItemId, ItemType};
+use crate::localization::{tr_args, try_tr};
+use crate::map::{pos_to_tile, Floor, FloorInfo, TILE_SIZE};
+use crate::math::{
+	aabb_collision,
+	push_out,
+	serialize_sorted,
+	serialize_sorted_set,
+	AsPolygon,
+	Polygon,
+};
+#[cfg(feature = "native")]
+use crate::mods;
+use crate::noise::Noise;
+use crate::player::{interact_with_door, DamageInfo, DoorInteraction, Player, PlayerClass};
+use crate::resistances::{Resistance, Resistances};
+use crate::rng::Rng;
+#[cfg(feature = "scripting")]
+use crate::scripting;
+use crate::telegraphs::Telegraphs;
 
 use macroquad::prelude::*;
 
+pub use bat::*;
+pub use gelatinous_cube::*;
+pub use mimic::*;
+pub use necromancer::*;
+use once_cell::sync::Lazy;
+pub use rat_king::*;
 #[cfg(feature = "native")]
 use rayon::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+pub use skeleton_archer::*;
 pub use slime::*;
+pub use slime_queen::*;
 pub use small_rat::*;
+pub use spawner::*;
+pub use spider::*;
+pub use wolf::*;
+pub use zombie::*;
 
 #[derive(Clone, PartialEq, Eq, Hash, Serialize)]
-struct Effect {
+pub struct Effect {
 	enchantment: Enchantment,
 	frames_left: u16,
 }
 
-#[derive(Clone, Serialize)]
-pub enum MonsterObj {
-	SmallRat(SmallRat),
-	GreenSlime(GreenSlime),
+/// The enchantments on a monster and the players who've hurt it. Every kind
+/// of monster holds one, so they all tick the same way
+#[derive(Clone, Default, Serialize)]
+pub struct Afflictions {
+	#[serde(serialize_with = "serialize_sorted")]
+	enchantments: HashMap<EnchantmentKind, Effect>,
+	#[serde(serialize_with = "serialize_sorted_set")]
+	damaged_by: HashSet<usize>,
 }
 
-impl MonsterObj {
-	pub fn movement(&mut self, players: &[Player], floor: &Floor) {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.movement(players, floor),
-			MonsterObj::GreenSlime(obj) => obj.movement(players, floor),
+impl Afflictions {
+	pub fn has(&self, kind: EnchantmentKind) -> bool { self.enchantments.contains_key(&kind) }
+
+	/// Can't move or attack
+	pub fn stunned(&self) -> bool {
+		self.has(EnchantmentKind::Stunned) || self.has(EnchantmentKind::Frozen)
+	}
+
+	pub fn insert(&mut self, enchantment: Enchantment, frames_left: u16) {
+		self.enchantments.insert(
+			enchantment.kind,
+			Effect {
+				enchantment,
+				frames_left,
+			},
+		);
+	}
+
+	pub fn hurt_by(&mut self, player: usize) { self.damaged_by.insert(player); }
+
+	pub fn damaged_by(&self) -> &HashSet<usize> { &self.damaged_by }
+
+	/// Heals, burns and poisons the monster, then wears each enchantment down
+	/// by a frame. `wore_off` is told about each one that runs out, so the
+	/// monster can undo whatever it did
+	pub fn update(
+		&mut self, health: &mut u16, max_health: u16, mut wore_off: impl FnMut(EnchantmentKind),
+	) {
+		retain_in_order(&mut self.enchantments, |kind, effect| {
+			let strength = effect.enchantment.strength;
+
+			match kind {
+				EnchantmentKind::Regenerating => {
+					// Heal every half second
+					if *health < max_health && effect.frames_left % (30 / strength) as u16 == 0 {
+						*health += 1;
+					}
+				},
+				EnchantmentKind::Burning => {
+					// Burn every half second
+					if effect.frames_left % (30 / strength) as u16 == 0 {
+						*health = health.saturating_sub(1);
+					}
+				},
+				EnchantmentKind::Poisoned => {
+					// Poison every second
+					if effect.frames_left % (60 / strength) as u16 == 0 {
+						*health = health.saturating_sub(1);
+					}
+				},
+				EnchantmentKind::Blinded |
+				EnchantmentKind::Sticky |
+				EnchantmentKind::Stunned |
+				EnchantmentKind::Chilled |
+				EnchantmentKind::Frozen => (),
+			};
+
+			effect.frames_left = effect.frames_left.saturating_sub(1);
+
+			if effect.frames_left == 0 {
+				wore_off(kind);
+			}
+
+			effect.frames_left > 0
+		});
+	}
+}
+
+/// How close a player can get to a sleeping monster before it wakes up, in
+/// tiles. Rogues can get a lot closer
+const WAKE_RANGE: f32 = 2.5;
+const ROGUE_WAKE_RANGE: f32 = 1.0;
+/// How much harder hitting a sleeping monster is
+const SNEAK_ATTACK_MULTIPLIER: u16 = 2;
+/// Hits that take at least this much of a monster's max health knock it back
+const HEAVY_HIT: f32 = 0.25;
+/// How far the heaviest hits knock monsters back, in tiles
+const MAX_KNOCKBACK: f32 = 0.5;
+
+/// Animals stuck behind a closed door scratch at it about once every this many
+/// frames
+const SCRATCH_RATE: u32 = 45;
+
+/// Whose side a monster's on
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Allegiance {
+	Hostile,
+	/// Summoned to fight for a player, until its time runs out
+	Ally {
+		summoner: usize,
+		frames_left: u16,
+	},
+	/// Follows a player around for good, from floor to floor
+	Companion {
+		owner: usize,
+	},
+}
+
+impl Allegiance {
+	fn update(&mut self) {
+		if let Allegiance::Ally { frames_left, .. } = self {
+			*frames_left = frames_left.saturating_sub(1);
 		}
 	}
+}
 
-	pub fn damage_players(&mut self, players: &mut [Player], floor: &Floor) {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.damage_players(players, floor),
-			MonsterObj::GreenSlime(obj) => obj.damage_players(players, floor),
+/// How long monsters flash for after being hit, in frames
+const FLASH_TIME: u8 = 8;
+
+/// Counts down the frames left of a monster's flash after it's hit. It flashes
+/// white, then red
+#[derive(Copy, Clone, Default, Serialize)]
+pub struct HitFlash(u8);
+
+impl HitFlash {
+	pub fn hit(&mut self) { self.0 = FLASH_TIME; }
+
+	pub fn update(&mut self) { self.0 = self.0.saturating_sub(1); }
+
+	fn color(self) -> Option<Color> {
+		match self.0 {
+			0 => None,
+			frames_left if frames_left > FLASH_TIME / 2 => Some(Color::new(1.0, 1.0, 1.0, 0.7)),
+			_ => Some(Color::new(1.0, 0.0, 0.0, 0.5)),
 		}
 	}
+}
 
-	pub fn take_damage(&mut self, damage_info: DamageInfo, floor: &Floor) {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.take_damage(damage_info, floor),
-			MonsterObj::GreenSlime(obj) => obj.take_damage(damage_info, floor),
+const DEFAULT_MONSTERS: &str = include_str!("../../assets/data/monsters.ron");
+
+/// What the bestiary knows about each monster. Mods can change these with
+/// their own `data/monsters.ron`
+pub static MONSTERS: Lazy<Vec<MonsterDefinition>> = Lazy::new(|| {
+	#[allow(unused_mut)]
+	let mut monsters: Vec<MonsterDefinition> = ron::from_str(DEFAULT_MONSTERS).unwrap();
+
+	#[cfg(feature = "native")]
+	if let Some(mod_monsters) = mods::load_data::<Vec<MonsterDefinition>>("monsters") {
+		mod_monsters.into_iter().for_each(|mod_monster| {
+			match monsters.iter_mut().find(|m| m.kind == mod_monster.kind) {
+				Some(monster) => *monster = mod_monster,
+				None => monsters.push(mod_monster),
+			}
+		});
+	}
+
+	monsters
+});
+
+#[derive(Clone, Deserialize)]
+pub struct MonsterDefinition {
+	pub kind: MonsterKind,
+	/// Only shown if no language pack has a `lore-<key>` message
+	pub lore: String,
+	/// Enchantments and kinds of damage that don't work as well on the
+	/// monster, don't work at all, or work better
+	#[serde(default)]
+	pub resistances: Resistances,
+	/// How many have to be killed before the bestiary shows the lore
+	pub kills_for_lore: u32,
+	/// What the monster might drop when it dies, on top of whatever the floor's
+	/// loot table gives
+	#[serde(default)]
+	pub drops: Vec<MonsterDrop>,
+	/// Skeletons, zombies and anything else that's risen from the dead, which
+	/// holy magic hits far harder
+	#[serde(default)]
+	pub undead: bool,
+}
+
+#[derive(Clone, Deserialize)]
+pub enum DropItem {
+	Gold(u32),
+	/// The item's key
+	Item(String),
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MonsterDrop {
+	pub item: DropItem,
+	/// Out of 100
+	pub chance: u32,
+}
+
+impl MonsterDrop {
+	/// Items from mods that aren't loaded never drop
+	pub fn item_type(&self) -> Option<ItemType> {
+		match &self.item {
+			DropItem::Gold(amount) => Some(ItemType::Gold(*amount)),
+			DropItem::Item(key) => ItemId::find(key).map(ItemType::Item),
 		}
 	}
+}
 
-	pub fn living(&self) -> bool {
+impl MonsterDefinition {
+	pub fn lore(&self) -> String {
+		try_tr(&format!("lore-{}", self.kind.key()), None).unwrap_or_else(|| self.lore.clone())
+	}
+}
+
+/// What a monster does when the player it's after shuts a door on it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DoorSkill {
+	/// Opens it and keeps going
+	Opens,
+	/// Scratches at it, loud enough to be heard from the other side
+	Scratches,
+	/// Never gets stuck behind one, or never gets near one in the first place
+	Ignores,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MonsterKind {
+	SmallRat,
+	GreenSlime,
+	RatKing,
+	SlimeQueen,
+	RatNest,
+	SlimePool,
+	SkeletonArcher,
+	Bat,
+	Mimic,
+	Necromancer,
+	Zombie,
+	GelatinousCube,
+	Spider,
+	Wolf,
+}
+
+impl MonsterKind {
+	pub fn key(self) -> &'static str {
 		match self {
-			MonsterObj::SmallRat(obj) => obj.living(),
-			MonsterObj::GreenSlime(obj) => obj.living(),
+			MonsterKind::SmallRat => "small_rat",
+			MonsterKind::GreenSlime => "green_slime",
+			MonsterKind::RatKing => "rat_king",
+			MonsterKind::SlimeQueen => "slime_queen",
+			MonsterKind::RatNest => "rat_nest",
+			MonsterKind::SlimePool => "slime_pool",
+			MonsterKind::SkeletonArcher => "skeleton_archer",
+			MonsterKind::Bat => "bat",
+			MonsterKind::Mimic => "mimic",
+			MonsterKind::Necromancer => "necromancer",
+			MonsterKind::Zombie => "zombie",
+			MonsterKind::GelatinousCube => "gelatinous_cube",
+			MonsterKind::Spider => "spider",
+			MonsterKind::Wolf => "wolf",
 		}
 	}
 
-	pub fn xp(&self) -> (&HashSet<usize>, u32) {
+	pub fn definition(self) -> Option<&'static MonsterDefinition> {
+		MONSTERS.iter().find(|monster| monster.kind == self)
+	}
+
+	pub fn resistances(self) -> Option<&'static Resistances> {
+		self.definition().map(|monster| &monster.resistances)
+	}
+
+	pub fn new_monster(self, pos: Vec2) -> MonsterObj {
 		match self {
-			MonsterObj::SmallRat(obj) => obj.xp(),
-			MonsterObj::GreenSlime(obj) => obj.xp(),
+			MonsterKind::SmallRat => MonsterObj::SmallRat(SmallRat::new(pos)),
+			MonsterKind::GreenSlime => MonsterObj::GreenSlime(GreenSlime::new(pos)),
+			MonsterKind::RatKing => MonsterObj::RatKing(RatKing::new(pos)),
+			MonsterKind::SlimeQueen => MonsterObj::SlimeQueen(SlimeQueen::new(pos)),
+			MonsterKind::RatNest => {
+				MonsterObj::Spawner(Spawner::new(pos).spawning(MonsterKind::SmallRat))
+			},
+			MonsterKind::SlimePool => {
+				MonsterObj::Spawner(Spawner::new(pos).spawning(MonsterKind::GreenSlime))
+			},
+			MonsterKind::SkeletonArcher => MonsterObj::SkeletonArcher(SkeletonArcher::new(pos)),
+			MonsterKind::Bat => MonsterObj::Bat(Bat::new(pos)),
+			MonsterKind::Mimic => MonsterObj::Mimic(Mimic::new(pos)),
+			MonsterKind::Necromancer => MonsterObj::Necromancer(Necromancer::new(pos)),
+			MonsterKind::Zombie => MonsterObj::Zombie(Zombie::new(pos)),
+			MonsterKind::GelatinousCube => MonsterObj::GelatinousCube(GelatinousCube::new(pos)),
+			MonsterKind::Spider => MonsterObj::Spider(Spider::new(pos)),
+			MonsterKind::Wolf => MonsterObj::Wolf(Wolf::new(pos)),
 		}
 	}
 
-	fn attack(&mut self, players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>) {
+	/// Rolls each of the monster's drops separately, so it can drop several
+	/// things at once
+	pub fn roll_drops(self, rng: &mut Rng) -> Vec<ItemType> {
+		self.definition()
+			.map(|definition| definition.drops.as_slice())
+			.unwrap_or_default()
+			.iter()
+			.filter(|drop| rng.gen_range(0, 100) < drop.chance)
+			.filter_map(|drop| drop.item_type())
+			.collect()
+	}
+
+	pub fn is_undead(self) -> bool { self.definition().is_some_and(|monster| monster.undead) }
+
+	/// Bosses guard the way down on their floors
+	pub fn is_boss(self) -> bool { matches!(self, MonsterKind::RatKing | MonsterKind::SlimeQueen) }
+
+	/// Whether a necromancer can raise the monster once it's dead. Bosses are
+	/// too big, spawners were never alive, zombies have already been raised
+	/// once, and cubes leave nothing behind but jelly
+	pub fn leaves_corpse(self) -> bool {
+		!self.is_boss() &&
+			!matches!(
+				self,
+				MonsterKind::RatNest |
+					MonsterKind::SlimePool |
+					MonsterKind::Zombie |
+					MonsterKind::GelatinousCube
+			)
+	}
+
+	/// Skeletons and necromancers know what a door handle is. Slimes ooze
+	/// under doors, and bosses and spawners stay where they are
+	pub fn door_skill(self) -> DoorSkill {
 		match self {
-			MonsterObj::SmallRat(obj) => obj.attack(players, floor, attacks),
-			MonsterObj::GreenSlime(obj) => obj.attack(players, floor, attacks),
+			MonsterKind::SkeletonArcher | MonsterKind::Necromancer => DoorSkill::Opens,
+			MonsterKind::SmallRat |
+			MonsterKind::Bat |
+			MonsterKind::Zombie |
+			MonsterKind::Spider |
+			MonsterKind::Wolf => DoorSkill::Scratches,
+			MonsterKind::GreenSlime |
+			MonsterKind::GelatinousCube |
+			MonsterKind::RatKing |
+			MonsterKind::SlimeQueen |
+			MonsterKind::RatNest |
+			MonsterKind::SlimePool |
+			MonsterKind::Mimic => DoorSkill::Ignores,
 		}
 	}
-}
 
-impl Enchantable for MonsterObj {
-	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+	/// How many of the monster get spawned together
+	pub fn group_size(self) -> usize {
 		match self {
-			MonsterObj::SmallRat(obj) => obj.apply_enchantment(enchantment),
-			MonsterObj::GreenSlime(obj) => obj.apply_enchantment(enchantment),
+			MonsterKind::Bat => SWARM_SIZE,
+			_ => 1,
 		}
 	}
 
-	fn update_enchantments(&mut self) {
+	/// The spawner that keeps making this kind of monster, if there is one
+	pub fn spawner(self) -> Option<MonsterKind> {
 		match self {
-			MonsterObj::SmallRat(obj) => obj.update_enchantments(),
-			MonsterObj::GreenSlime(obj) => obj.update_enchantments(),
+			MonsterKind::SmallRat => Some(MonsterKind::RatNest),
+			MonsterKind::GreenSlime => Some(MonsterKind::SlimePool),
+			_ => None,
 		}
 	}
+
+	/// The monster's name, pluralized for the amount
+	pub fn name(self, amount: u32) -> String {
+		let id = match self {
+			MonsterKind::SmallRat => "monster-small-rat",
+			MonsterKind::GreenSlime => "monster-green-slime",
+			MonsterKind::RatKing => "monster-rat-king",
+			MonsterKind::SlimeQueen => "monster-slime-queen",
+			MonsterKind::RatNest => "monster-rat-nest",
+			MonsterKind::SlimePool => "monster-slime-pool",
+			MonsterKind::SkeletonArcher => "monster-skeleton-archer",
+			MonsterKind::Bat => "monster-bat",
+			MonsterKind::Mimic => "monster-mimic",
+			MonsterKind::Necromancer => "monster-necromancer",
+			MonsterKind::Zombie => "monster-zombie",
+			MonsterKind::GelatinousCube => "monster-gelatinous-cube",
+			MonsterKind::Spider => "monster-spider",
+			MonsterKind::Wolf => "monster-wolf",
+		};
+
+		tr_args(id, [("count", amount.into())])
+	}
 }
 
-impl Drawable for MonsterObj {
-	fn size(&self) -> Vec2 {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.size(),
-			MonsterObj::GreenSlime(obj) => obj.size(),
-		}
+impl Display for MonsterKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.name(1))
 	}
+}
+
+#[derive(Clone, Serialize)]
+pub enum MonsterObj {
+	SmallRat(SmallRat),
+	GreenSlime(GreenSlime),
+	RatKing(RatKing),
+	SlimeQueen(SlimeQueen),
+	Spawner(Spawner),
+	SkeletonArcher(SkeletonArcher),
+	Bat(Bat),
+	Mimic(Mimic),
+	Necromancer(Necromancer),
+	Zombie(Zombie),
+	GelatinousCube(GelatinousCube),
+	Spider(Spider),
+	Wolf(Wolf),
+}
+
+/// Runs the same code on whichever monster is inside a MonsterObj, so a new
+/// monster only has to be added to the enum and here
+macro_rules! dispatch {
+	($monster:expr, $obj:ident => $body:expr) => {
+		match $monster {
+			MonsterObj::SmallRat($obj) => $body,
+			MonsterObj::GreenSlime($obj) => $body,
+			MonsterObj::RatKing($obj) => $body,
+			MonsterObj::SlimeQueen($obj) => $body,
+			MonsterObj::Bat($obj) => $body,
+			MonsterObj::Mimic($obj) => $body,
+			MonsterObj::SkeletonArcher($obj) => $body,
+			MonsterObj::Spawner($obj) => $body,
+			MonsterObj::Necromancer($obj) => $body,
+			MonsterObj::Zombie($obj) => $body,
+			MonsterObj::GelatinousCube($obj) => $body,
+			MonsterObj::Spider($obj) => $body,
+			MonsterObj::Wolf($obj) => $body,
+		}
+	};
+}
 
-	fn pos(&self) -> Vec2 {
+impl MonsterObj {
+	pub fn kind(&self) -> MonsterKind {
 		match self {
-			MonsterObj::SmallRat(obj) => obj.pos(),
-			MonsterObj::GreenSlime(obj) => obj.pos(),
+			MonsterObj::SmallRat(_) => MonsterKind::SmallRat,
+			MonsterObj::GreenSlime(_) => MonsterKind::GreenSlime,
+			MonsterObj::RatKing(_) => MonsterKind::RatKing,
+			MonsterObj::SlimeQueen(_) => MonsterKind::SlimeQueen,
+			MonsterObj::Spawner(obj) => obj.kind(),
+			MonsterObj::SkeletonArcher(_) => MonsterKind::SkeletonArcher,
+			MonsterObj::Bat(_) => MonsterKind::Bat,
+			MonsterObj::Mimic(_) => MonsterKind::Mimic,
+			MonsterObj::Necromancer(_) => MonsterKind::Necromancer,
+			MonsterObj::Zombie(_) => MonsterKind::Zombie,
+			MonsterObj::GelatinousCube(_) => MonsterKind::GelatinousCube,
+			MonsterObj::Spider(_) => MonsterKind::Spider,
+			MonsterObj::Wolf(_) => MonsterKind::Wolf,
 		}
 	}
 
-	fn rotation(&self) -> f32 {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.rotation(),
-			MonsterObj::GreenSlime(obj) => obj.rotation(),
+	pub fn movement(&mut self, players: &[Player], floor: &Floor, rng: &mut Rng) {
+		dispatch!(self, obj => obj.movement(players, floor, rng))
+	}
+
+	pub fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		dispatch!(self, obj => obj.damage_players(players, floor, events))
+	}
+
+	pub fn take_damage(
+		&mut self, mut damage_info: DamageInfo, floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		if let Some(resistance) = self
+			.kind()
+			.resistances()
+			.and_then(|resistances| resistances.to_damage(damage_info.damage_type))
+		{
+			damage_info.damage = resistance.apply(damage_info.damage);
+		}
+
+		if self.asleep() {
+			damage_info.damage *= SNEAK_ATTACK_MULTIPLIER;
+			self.set_asleep(false);
+		}
+
+		events.push(GameEvent::DamageDealt {
+			pos: self.center(),
+			damage: damage_info.damage,
+			to_player: false,
+			by: Some(damage_info.player),
+			from: None,
+		});
+
+		let damage = damage_info.damage;
+		let direction = Vec2::new(damage_info.direction.cos(), damage_info.direction.sin());
+
+		dispatch!(self, obj => obj.take_damage(damage_info, floor));
+
+		let (health, max_health) = self.health();
+		let heaviness = damage as f32 / max_health as f32;
+
+		if health > 0 && heaviness >= HEAVY_HIT {
+			self.knock_back(
+				direction * heaviness.min(MAX_KNOCKBACK) * TILE_SIZE as f32,
+				floor,
+			);
 		}
 	}
 
-	fn texture(&self) -> Option<Texture2D> {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.texture(),
-			MonsterObj::GreenSlime(obj) => obj.texture(),
+	pub fn knock_back(&mut self, change: Vec2, floor: &Floor) {
+		dispatch!(self, obj => obj.knock_back(change, floor))
+	}
+
+	/// Moves the monster as far as the walls let it, even if it's too heavy to
+	/// be knocked back. Only behavior scripts move monsters from outside
+	pub fn walk(&mut self, change: Vec2, floor: &Floor) {
+		dispatch!(self, obj => shove(obj, change, floor))
+	}
+
+	pub fn stunned(&self) -> bool { dispatch!(self, obj => obj.stunned()) }
+
+	pub fn living(&self) -> bool { dispatch!(self, obj => obj.living()) }
+
+	/// The players to give XP to, in order. Sets iterate in a different order
+	/// in every process, so anything done for each of them would desync
+	pub fn xp(&self) -> (Vec<usize>, u32) {
+		let (indices, xp) = dispatch!(self, obj => obj.xp());
+		let mut indices: Vec<usize> = indices.iter().copied().collect();
+		indices.sort_unstable();
+
+		(indices, xp)
+	}
+
+	pub fn current_path(&self) -> &[Vec2] { dispatch!(self, obj => obj.current_path()) }
+
+	pub fn target(&self, players: &[Player]) -> Option<Vec2> {
+		dispatch!(self, obj => obj.target(players))
+	}
+
+	pub fn alert(&mut self, player: usize, pos: Vec2) {
+		dispatch!(self, obj => obj.alert(player, pos))
+	}
+
+	pub fn boss_health(&self) -> Option<f32> { dispatch!(self, obj => obj.boss_health()) }
+
+	pub fn health(&self) -> (u16, u16) { dispatch!(self, obj => obj.health()) }
+
+	/// Makes a freshly spawned monster as tough as the floor it's on
+	pub fn scaled(mut self, scaling: MonsterScaling) -> Self {
+		let (_, max_health) = self.health();
+		let max_health = scaling.health(max_health);
+
+		dispatch!(&mut self, obj => obj.set_max_health(max_health));
+
+		self
+	}
+
+	pub fn hit_flash(&self) -> HitFlash { dispatch!(self, obj => obj.hit_flash()) }
+
+	fn afflictions(&self) -> &Afflictions { dispatch!(self, obj => obj.afflictions()) }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions {
+		dispatch!(self, obj => obj.afflictions_mut())
+	}
+
+	/// Chilled monsters only get to move and attack every other frame
+	fn sluggish(&self) -> bool {
+		self.afflictions()
+			.enchantments
+			.get(&EnchantmentKind::Chilled)
+			.is_some_and(|effect| effect.frames_left % 2 == 0)
+	}
+
+	pub fn hit_flash_mut(&mut self) -> &mut HitFlash { dispatch!(self, obj => obj.hit_flash_mut()) }
+
+	pub fn asleep(&self) -> bool { dispatch!(self, obj => obj.asleep()) }
+
+	pub fn set_asleep(&mut self, asleep: bool) { dispatch!(self, obj => obj.set_asleep(asleep)) }
+
+	pub fn on_noise(&mut self, pos: Vec2) { dispatch!(self, obj => obj.on_noise(pos)) }
+
+	/// Allies can't be hit by the players they're fighting for, or by each
+	/// other
+	pub fn targetable(&self) -> bool { self.hostile() && dispatch!(self, obj => obj.targetable()) }
+
+	pub fn allegiance(&self) -> Allegiance { dispatch!(self, obj => obj.allegiance()) }
+
+	pub fn hostile(&self) -> bool { self.allegiance() == Allegiance::Hostile }
+
+	fn update_allegiance(&mut self) {
+		if let Some(allegiance) = dispatch!(self, obj => obj.allegiance_mut()) {
+			allegiance.update();
 		}
 	}
 
-	fn flip_x(&self) -> bool {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.flip_x(),
-			MonsterObj::GreenSlime(obj) => obj.flip_x(),
+	pub fn flying(&self) -> bool { dispatch!(self, obj => obj.flying()) }
+
+	fn attack(
+		&mut self, players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>,
+		telegraphs: &mut Telegraphs,
+	) {
+		dispatch!(self, obj => obj.attack(players, floor, attacks, telegraphs))
+	}
+}
+
+impl Enchantable for MonsterObj {
+	fn apply_enchantment(&mut self, mut enchantment: Enchantment) {
+		// Chilling a monster that's already chilled freezes it solid
+		if enchantment.kind == EnchantmentKind::Chilled &&
+			self.afflictions().has(EnchantmentKind::Chilled)
+		{
+			enchantment.kind = EnchantmentKind::Frozen;
+		}
+
+		let kind = enchantment.kind;
+		let resistance = self
+			.kind()
+			.resistances()
+			.and_then(|resistances| resistances.to_enchantment(kind));
+
+		if resistance == Some(Resistance::Immune) {
+			return;
+		}
+
+		if kind == EnchantmentKind::Frozen {
+			self.afflictions_mut()
+				.enchantments
+				.remove(&EnchantmentKind::Chilled);
+		}
+
+		dispatch!(self, obj => obj.apply_enchantment(enchantment));
+
+		// Resisted enchantments wear off sooner, and ones the monster's
+		// vulnerable to last longer
+		if let Some(resistance) = resistance {
+			if let Some(effect) = self.afflictions_mut().enchantments.get_mut(&kind) {
+				effect.frames_left = resistance.apply(effect.frames_left);
+			}
 		}
 	}
+
+	fn update_enchantments(&mut self) { dispatch!(self, obj => obj.update_enchantments()) }
 }
 
-impl AsPolygon for MonsterObj {
-	fn as_polygon(&self) -> Polygon {
-		match self {
-			MonsterObj::SmallRat(obj) => obj.as_polygon(),
-			MonsterObj::GreenSlime(obj) => obj.as_polygon(),
+impl Drawable for MonsterObj {
+	fn size(&self) -> Vec2 { dispatch!(self, obj => obj.size()) }
+
+	fn pos(&self) -> Vec2 { dispatch!(self, obj => obj.pos()) }
+
+	fn rotation(&self) -> f32 { dispatch!(self, obj => obj.rotation()) }
+
+	fn texture(&self) -> Option<Texture2D> { dispatch!(self, obj => obj.texture()) }
+
+	fn flip_x(&self) -> bool { dispatch!(self, obj => obj.flip_x()) }
+
+	fn draw(&self) {
+		dispatch!(self, obj => obj.draw());
+
+		if self.asleep() {
+			let pos = self.pos();
+			draw_text("z", pos.x + self.size().x, pos.y, 14.0, WHITE);
+		}
+
+		if let Some(color) = self.hit_flash().color() {
+			let pos = self.pos();
+			let size = self.size();
+			draw_rectangle(pos.x, pos.y, size.x, size.y, color);
+		}
+
+		// Bosses have theirs across the top of the screen instead
+		let (health, max_health) = self.health();
+
+		if health < max_health && self.boss_health().is_none() {
+			let pos = self.pos();
+			let width = self.size().x;
+
+			draw_rectangle(pos.x, pos.y - 6.0, width, 3.0, DARKGRAY);
+			draw_rectangle(
+				pos.x,
+				pos.y - 6.0,
+				width * health as f32 / max_health as f32,
+				3.0,
+				RED,
+			);
 		}
 	}
 }
 
+impl AsPolygon for MonsterObj {
+	fn as_polygon(&self) -> Polygon { dispatch!(self, obj => obj.as_polygon()) }
+}
+
 // All monsters are required to have a drawable AABB and be drawable
 pub trait Monster: AsPolygon + Drawable + Send + Sync + Enchantable + Clone + Serialize {
 	fn new(pos: Vec2) -> Self;
 	// Movement and damaging players are seperate so that the movement part can be
 	// run in parallel
-	fn movement(&mut self, players: &[Player], floor: &Floor);
-	fn attack(&mut self, _players: &[Player], _floor: &Floor, _attacks: &mut Vec<AttackObj>) {}
-	fn damage_players(&mut self, players: &mut [Player], floor: &Floor);
+	fn movement(&mut self, players: &[Player], floor: &Floor, rng: &mut Rng);
+	/// Anything that's wound up first should warn players with a telegraph
+	fn attack(
+		&mut self, _players: &[Player], _floor: &Floor, _attacks: &mut Vec<AttackObj>,
+		_telegraphs: &mut Telegraphs,
+	) {
+	}
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	);
 	fn take_damage(&mut self, damage_info: DamageInfo, floor: &Floor);
+	/// Pushes the monster without hurting it. Some monsters are too heavy to
+	/// move
+	fn knock_back(&mut self, change: Vec2, floor: &Floor) { shove(self, change, floor); }
+	fn set_pos(&mut self, pos: Vec2);
+	fn stunned(&self) -> bool { self.afflictions().stunned() }
 	fn living(&self) -> bool;
+	/// Current and max health, for the health bar over damaged monsters
+	fn health(&self) -> (u16, u16);
+	/// Heals the monster up to its new max health. Only done when it spawns
+	fn set_max_health(&mut self, max_health: u16);
+	fn hit_flash(&self) -> HitFlash;
+	fn hit_flash_mut(&mut self) -> &mut HitFlash;
+	fn afflictions(&self) -> &Afflictions;
+	/// So resistances can change how long enchantments last
+	fn afflictions_mut(&mut self) -> &mut Afflictions;
 	/// The players to give XP to, and how much XP to give
 	fn xp(&self) -> (&HashSet<usize>, u32);
+	/// What's left of the path the monster is following, for the debug overlay
+	fn current_path(&self) -> &[Vec2] { &[] }
+	/// Where the monster is trying to get to, for the debug overlay
+	fn target(&self, _players: &[Player]) -> Option<Vec2> { None }
+	/// Makes the monster go after a player, who's at `pos`. Bosses are already
+	/// after whoever they can see
+	fn alert(&mut self, _player: usize, _pos: Vec2) {}
+	/// Sleeping monsters don't move or attack until a player gets close, makes
+	/// a noise or hits them
+	fn asleep(&self) -> bool { false }
+	/// Monsters that never sleep ignore this
+	fn set_asleep(&mut self, _asleep: bool) {}
+	/// Lets the monster know it heard something at `pos`. Only monsters that
+	/// aren't already after someone go and see what it was
+	fn on_noise(&mut self, _pos: Vec2) {}
+	/// Whether attacks can hit the monster, which bats can't be while they're
+	/// up on the ceiling
+	fn targetable(&self) -> bool { true }
+	/// Flying monsters aren't slowed down by what's on the floor beneath them
+	fn flying(&self) -> bool { false }
+	/// How much of its health a boss has left, from 0 to 1, for the health bar
+	/// across the top of the screen. Other monsters don't get one
+	fn boss_health(&self) -> Option<f32> { None }
+	/// Whose side the monster's on. Only summoned monsters and companions fight
+	/// for players
+	fn allegiance(&self) -> Allegiance { Allegiance::Hostile }
+	/// So a summoned monster's time can run out. Monsters that can't be
+	/// summoned or tamed don't have one
+	fn allegiance_mut(&mut self) -> Option<&mut Allegiance> { None }
+}
+
+/// Moves a monster as far as the walls let it, sliding along them if it can't
+/// go straight. Everything that pushes monsters around goes through here
+pub fn shove<M: Monster>(monster: &mut M, change: Vec2, floor: &Floor) {
+	let change = [change, Vec2::new(change.x, 0.0), Vec2::new(0.0, change.y)]
+		.into_iter()
+		.find(|change| !floor.collision(monster, *change));
+
+	if let Some(change) = change {
+		monster.set_pos(monster.pos() + change);
+	}
 }
 
 pub fn update_monsters(
 	players: &mut [Player], floor_info: &mut FloorInfo, attacks: &mut Vec<AttackObj>,
+	telegraphs: &mut Telegraphs, events: &mut Vec<GameEvent>, rng: &mut Rng,
 ) {
+	crate::profile_scope!("update_monsters");
+
+	telegraphs.clear();
+
+	floor_info.floor.update_flow_field(players);
+	use_doors(players, floor_info, events, rng);
+	call_companions(players, floor_info);
+	lead_allies(&mut floor_info.monsters, players, &floor_info.floor);
+
 	#[cfg(not(feature = "native"))]
-	let monsters_iter = floor_info.monsters.iter_mut();
+	let monsters_iter = floor_info.monsters.iter_mut().enumerate();
 
 	#[cfg(feature = "native")]
-	let monsters_iter = floor_info.monsters.par_chunks_mut(4);
+	let monsters_iter = floor_info
+		.monsters
+		.par_iter_mut()
+		.with_min_len(4)
+		.enumerate();
+
+	// Monsters move in parallel, so each gets its own generator, forked in the
+	// same order on every peer
+	let monster_rng = Rng::new(rng.next_u64());
+
+	// Everything loud that's happened so far this frame
+	let noises: Vec<Noise> = events.iter().filter_map(Noise::from_event).collect();
 
-	monsters_iter.flatten().for_each(|m| {
+	monsters_iter.for_each(|(i, m)| {
 		// Only move monsters that are within a certain distance of any player
 		m.update_enchantments();
-		m.movement(players, &floor_info.floor);
+		m.hit_flash_mut().update();
+		m.update_allegiance();
+
+		// The last noise is the freshest
+		let noise = noises
+			.iter()
+			.rev()
+			.find(|noise| noise.heard_from(m.center()));
+
+		if m.asleep() {
+			let woken = noise.is_some() || players.iter().any(|player| wakes(player, m));
+
+			if !woken {
+				return;
+			}
+
+			m.set_asleep(false);
+		}
+
+		if let Some(noise) = noise {
+			m.on_noise(noise.pos);
+		}
+
+		if !m.stunned() && !m.sluggish() {
+			#[cfg(feature = "scripting")]
+			if scripting::run_behavior(m, players, &floor_info.floor) {
+				return;
+			}
+
+			m.movement(players, &floor_info.floor, &mut monster_rng.fork(i as u64));
+		}
 	});
 
 	let floor = &floor_info.floor;
 	let monsters = &mut floor_info.monsters;
+	let monster_loot = &floor_info.monster_loot;
+	let corpses = &mut floor_info.corpses;
+
+	// Dropped once every monster is done, since the floor's borrowed until then
+	let mut drops = Vec::new();
+	// and the same goes for what's left of split cubes
+	let mut halves = Vec::new();
+
+	#[cfg(feature = "scripting")]
+	let mut scripted = Vec::new();
 
 	monsters.retain_mut(|m| {
-		m.attack(players, floor, attacks);
-		m.damage_players(players, &floor);
+		if !m.stunned() && !m.asleep() && !m.sluggish() {
+			m.attack(players, floor, attacks, telegraphs);
+			m.damage_players(players, floor, events);
+		}
+
 		let living = m.living();
 
+		// Summoned allies crumble away once they're killed or their time's up,
+		// leaving nothing behind
+		if let Allegiance::Ally { frames_left, .. } = m.allegiance() {
+			return living && frames_left > 0;
+		}
+
+		// Companions don't come back until the next floor
+		if let Allegiance::Companion { owner } = m.allegiance() {
+			if !living {
+				players[owner].companion_lost = true;
+			}
+
+			return living;
+		}
+
 		// If a monster dies, give all players who damaged it some XP
 		if !living {
+			events.push(GameEvent::MonsterDied {
+				kind: m.kind(),
+				pos: m.center(),
+			});
+
+			corpses.record(m.kind(), m.center());
+
+			let tile_pos = pos_to_tile(m);
+
+			if let Some(item_type) = monster_loot.roll(rng) {
+				drops.push((tile_pos, item_type));
+			}
+
+			drops.extend(
+				m.kind()
+					.roll_drops(rng)
+					.into_iter()
+					.map(|item_type| (tile_pos, item_type)),
+			);
+
+			if let MonsterObj::GelatinousCube(cube) = m {
+				drops.extend(
+					cube.engulfed()
+						.iter()
+						.map(|item_type| (tile_pos, *item_type)),
+				);
+				halves.extend(cube.split());
+			}
+
 			let (indices, xp) = m.xp();
 
-			indices.iter().copied().for_each(|i| {
-				players[i].add_xp(xp);
+			indices.into_iter().for_each(|i| {
+				if players[i].add_xp(xp) {
+					events.push(GameEvent::LevelUp {
+						player: i,
+						level: players[i].level,
+					});
+				}
+
+				#[cfg(feature = "scripting")]
+				if let Some(commands) = scripting::run_hook(
+					scripting::monster_death_hook(m),
+					scripting::ScriptContext::new(&players[i]),
+				) {
+					scripted.push((i, commands));
+				}
 			});
 		}
 
 		living
 	});
+
+	drops.into_iter().for_each(|(tile_pos, item_type)| {
+		floor_info.floor.drop_item(tile_pos, item_type);
+	});
+	floor_info.monsters.extend(halves);
+
+	let scaling = floor_info.floor.scaling();
+
+	separate_monsters(&mut floor_info.monsters, &floor_info.floor);
+	maul_companions(&mut floor_info.monsters);
+	engulf_items(&mut floor_info.monsters, &mut floor_info.floor);
+	spawn_from_spawners(&mut floor_info.monsters, scaling);
+	rouse_swarms(&mut floor_info.monsters, players);
+	raise_corpses(&mut floor_info.monsters, &mut floor_info.corpses, scaling);
+	summon_rats(&mut floor_info.monsters, scaling);
+	floor_info.corpses.update();
+
+	floor_info.unlock_exit(players, events);
+
+	// Applied once every monster is done, since scripts can spawn new ones
+	#[cfg(feature = "scripting")]
+	scripted.into_iter().for_each(|(i, commands)| {
+		scripting::apply_commands(commands, &mut players[i], floor_info, events, rng);
+	});
+}
+
+/// Monsters after a player who's shut a door on them open it if they can, or
+/// scratch at it if they can't. Players are tracked through closed doors by
+/// scent, so a door being next on the way downhill means one's in the way
+fn use_doors(
+	players: &[Player], floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	let floor = &floor_info.floor;
+
+	let blocked: Vec<(Polygon, DoorSkill, IVec2)> = floor_info
+		.monsters
+		.iter()
+		.filter(|m| m.hostile() && !m.stunned() && !m.asleep() && m.target(players).is_some())
+		.filter_map(|m| {
+			let tile_pos = floor.flow_field().downhill(pos_to_tile(m))?;
+			let door = floor.get_object_from_pos(tile_pos)?.door().as_ref()?;
+
+			(!door.is_open && !door.locked())
+				.then(|| (m.as_polygon(), m.kind().door_skill(), tile_pos))
+		})
+		.collect();
+
+	blocked
+		.into_iter()
+		.for_each(|(aabb, door_skill, tile_pos)| match door_skill {
+			DoorSkill::Opens => {
+				interact_with_door(&aabb, DoorInteraction::Opening, floor_info, events)
+			},
+			DoorSkill::Scratches => {
+				if rng.gen_range(0, SCRATCH_RATE) == 0 {
+					events.push(GameEvent::DoorScratched { tile_pos });
+				}
+			},
+			DoorSkill::Ignores => (),
+		});
+}
+
+/// Points every summoned skeleton at the closest hostile monster it can see,
+/// and every companion at whatever its owner last hit
+fn lead_allies(monsters: &mut [MonsterObj], players: &[Player], floor: &Floor) {
+	let hostiles: Vec<Vec2> = monsters
+		.iter()
+		.filter(|m| m.targetable())
+		.map(|m| m.center())
+		.collect();
+
+	monsters.iter_mut().for_each(|m| match m {
+		MonsterObj::SkeletonArcher(skeleton) => skeleton.hunt(&hostiles, floor),
+		MonsterObj::Wolf(wolf) => wolf.hunt(&hostiles, players),
+		_ => (),
+	});
+}
+
+/// Gives every living ranger on the floor their wolf, unless it's already
+/// been killed on this floor
+fn call_companions(players: &[Player], floor_info: &mut FloorInfo) {
+	let scaling = floor_info.floor.scaling();
+
+	players.iter().enumerate().for_each(|(i, player)| {
+		if player.class() != PlayerClass::Ranger || player.hp() == 0 || player.companion_lost {
+			return;
+		}
+
+		let has_companion = floor_info
+			.monsters
+			.iter()
+			.any(|m| m.allegiance() == Allegiance::Companion { owner: i });
+
+		if has_companion {
+			return;
+		}
+
+		let mut wolf = Wolf::new(player.center()).tamed_by(i);
+		wolf.set_pos(player.center() - wolf.size() * 0.5);
+
+		floor_info
+			.monsters
+			.push(MonsterObj::Wolf(wolf).scaled(scaling));
+	});
+}
+
+/// Hostile monsters hurt any companion they're touching. Nothing else fights
+/// back against companions, since monsters only ever go after players
+fn maul_companions(monsters: &mut [MonsterObj]) {
+	let hostiles: Vec<Polygon> = monsters
+		.iter()
+		.filter(|m| m.targetable())
+		.map(|m| m.as_polygon())
+		.collect();
+
+	monsters.iter_mut().for_each(|m| {
+		let MonsterObj::Wolf(wolf) = m else {
+			return;
+		};
+
+		if wolf.allegiance() == Allegiance::Hostile {
+			return;
+		}
+
+		let wolf_poly = wolf.as_polygon();
+
+		if hostiles
+			.iter()
+			.any(|hostile| aabb_collision(&wolf_poly, hostile, Vec2::ZERO))
+		{
+			wolf.mauled();
+		}
+	});
+}
+
+/// Whether the player's close enough to wake a sleeping monster up
+fn wakes(player: &Player, monster: &MonsterObj) -> bool {
+	let range = match player.class() {
+		PlayerClass::Rogue => ROGUE_WAKE_RANGE,
+		_ => WAKE_RANGE,
+	};
+
+	player.noticeable() && player.center().distance(monster.center()) <= range * TILE_SIZE as f32
+}
+
+/// Bats that have spotted a player bring every other bat nearby along with
+/// them
+fn rouse_swarms(monsters: &mut [MonsterObj], players: &[Player]) {
+	let hunting: Vec<(Vec2, usize)> = monsters
+		.iter()
+		.filter_map(|m| match m {
+			MonsterObj::Bat(bat) => bat.hunting().map(|target| (bat.center(), target)),
+			_ => None,
+		})
+		.collect();
+
+	if hunting.is_empty() {
+		return;
+	}
+
+	monsters.iter_mut().for_each(|m| {
+		let MonsterObj::Bat(bat) = m else {
+			return;
+		};
+
+		if bat.hunting().is_some() {
+			return;
+		}
+
+		if let Some((_, target)) = hunting
+			.iter()
+			.find(|(pos, _)| pos.distance(bat.center()) <= SWARM_RANGE * TILE_SIZE as f32)
+		{
+			bat.alert(*target, players[*target].center());
+		}
+	});
+}
+
+/// Pushes apart monsters that have ended up on top of each other, half the
+/// way each. Flying monsters go over everything
+fn separate_monsters(monsters: &mut [MonsterObj], floor: &Floor) {
+	(0..monsters.len()).for_each(|i| {
+		(i + 1..monsters.len()).for_each(|j| {
+			if monsters[i].flying() || monsters[j].flying() {
+				return;
+			}
+
+			if let Some(push) = push_out(&monsters[i], &monsters[j]) {
+				monsters[i].knock_back(push * 0.5, floor);
+				monsters[j].knock_back(-push * 0.5, floor);
+			}
+		});
+	});
+}
+
+/// Gelatinous cubes swallow whatever's lying on the tile they're on
+fn engulf_items(monsters: &mut [MonsterObj], floor: &mut Floor) {
+	monsters.iter_mut().for_each(|m| {
+		let MonsterObj::GelatinousCube(cube) = m else {
+			return;
+		};
+
+		if let Some(object) = floor.get_object_from_pos_mut(pos_to_tile(&*cube)) {
+			cube.engulf(object.items_mut().drain(..).map(|item| item.item_type));
+		}
+	});
+}
+
+/// Every necromancer that's ready raises the closest corpse in range as a
+/// zombie
+fn raise_corpses(monsters: &mut Vec<MonsterObj>, corpses: &mut Corpses, scaling: MonsterScaling) {
+	let mut zombies = Vec::new();
+
+	monsters.iter_mut().for_each(|m| {
+		let MonsterObj::Necromancer(necromancer) = m else {
+			return;
+		};
+
+		if !necromancer.ready_to_raise() {
+			return;
+		}
+
+		if let Some(corpse) =
+			corpses.take_nearest(necromancer.center(), RAISE_RANGE * TILE_SIZE as f32)
+		{
+			zombies.push(MonsterObj::Zombie(Zombie::rise(&corpse)).scaled(scaling));
+			necromancer.raised();
+		}
+	});
+
+	monsters.extend(zombies);
+}
+
+/// An angry rat king calls for more rats, as long as there aren't already too
+/// many around it
+fn summon_rats(monsters: &mut Vec<MonsterObj>, scaling: MonsterScaling) {
+	let mut rats = Vec::new();
+
+	let kings: Vec<usize> = monsters
+		.iter()
+		.enumerate()
+		.filter_map(|(i, m)| match m {
+			MonsterObj::RatKing(king) if king.ready_to_summon() => Some(i),
+			_ => None,
+		})
+		.collect();
+
+	kings.into_iter().for_each(|i| {
+		let center = monsters[i].center();
+		let nearby = monsters
+			.iter()
+			.filter(|m| {
+				m.kind() == MonsterKind::SmallRat &&
+					m.center().distance(center) <= NEARBY_DISTANCE * TILE_SIZE as f32
+			})
+			.count();
+
+		// Otherwise it calls again as soon as some of them have been dealt with
+		if let MonsterObj::RatKing(king) = &mut monsters[i] {
+			if nearby < MAX_SUMMONED {
+				rats.extend(king.summon().into_iter().map(|rat| rat.scaled(scaling)));
+			}
+		}
+	});
+
+	monsters.extend(rats);
+}
+
+/// Every spawner that's ready makes a monster, as long as there aren't already
+/// too many of its monsters around it
+fn spawn_from_spawners(monsters: &mut Vec<MonsterObj>, scaling: MonsterScaling) {
+	let ready: Vec<usize> = monsters
+		.iter()
+		.enumerate()
+		.filter_map(|(i, m)| match m {
+			MonsterObj::Spawner(spawner) if spawner.ready() => {
+				let nearby = monsters
+					.iter()
+					.filter(|other| {
+						other.kind() == spawner.spawns() &&
+							other.center().distance(spawner.center()) <=
+								NEARBY_DISTANCE * TILE_SIZE as f32
+					})
+					.count();
+
+				(nearby < MAX_NEARBY_SPAWNS).then_some(i)
+			},
+			_ => None,
+		})
+		.collect();
+
+	ready.into_iter().for_each(|i| {
+		if let MonsterObj::Spawner(spawner) = &mut monsters[i] {
+			let monster = spawner.spawn().scaled(scaling);
+			monsters.push(monster);
+		}
+	});
 }
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::f32::consts::FRAC_PI_4;
+
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::flow_field::FlowField;
+use crate::map::{Floor, MAP_SIZE_TILES, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{shove, Monster};
+use crate::player::{DamageInfo, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::{close_in, find_target};
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 18.0;
+const MAX_HEALTH: u16 = 30;
+const SPEED: f32 = 0.9;
+/// How many tiles away a player can be seen from
+const SIGHT_RANGE: i32 = 12;
+/// The necromancer backs away from players closer than this many tiles
+const MIN_RANGE: f32 = 6.0;
+/// and closes in on players further than this many tiles
+const MAX_RANGE: f32 = 9.0;
+// How often the necromancer looks around for players, in frames
+const RETARGET_RATE: u8 = 20;
+/// Frames between each corpse raised
+const RAISE_RATE: u16 = 240;
+/// How far away a corpse can be raised from, in tiles
+pub const RAISE_RANGE: f32 = 8.0;
+/// How far the necromancer tries to teleport away when it's hit, in tiles
+const TELEPORT_DISTANCE: i32 = 6;
+/// Frames before the necromancer can teleport again
+const TELEPORT_COOLDOWN: u16 = 120;
+
+/// Keeps well away from players, raising the monsters they've killed as
+/// zombies to fight for it. Whenever it's hit, it teleports away from
+/// whoever hit it
+#[derive(Clone, Serialize)]
+pub struct Necromancer {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
+	pos: Vec2,
+	speed_mul: f32,
+	target: Option<usize>,
+	time_til_retarget: u8,
+	time_til_raise: u16,
+	time_til_teleport: u16,
+	afflictions: Afflictions,
+}
+
+impl Necromancer {
+	/// Necromancers only raise the dead once they know someone's around
+	pub fn ready_to_raise(&self) -> bool { self.time_til_raise == 0 && self.target.is_some() }
+
+	pub fn raised(&mut self) { self.time_til_raise = RAISE_RATE; }
+
+	/// Blinks to an open tile away from the hit, trying straight away first and
+	/// then further and further round to the sides
+	fn teleport(&mut self, direction: f32, floor: &Floor) {
+		let center = self.center();
+
+		let destination = [0.0, 1.0, -1.0, 2.0, -2.0, 3.0, -3.0]
+			.into_iter()
+			.map(|turns| direction + turns * FRAC_PI_4)
+			.flat_map(|angle| {
+				(2..=TELEPORT_DISTANCE).rev().map(move |distance| {
+					center +
+						Vec2::new(angle.cos(), angle.sin()) * (distance * TILE_SIZE as i32) as f32
+				})
+			})
+			.map(|pos| (pos / Vec2::splat(TILE_SIZE as f32)).floor().as_ivec2())
+			.find(|tile_pos| {
+				tile_pos.cmpge(IVec2::ZERO).all() &&
+					tile_pos.cmplt(MAP_SIZE_TILES).all() &&
+					floor
+						.get_object_from_pos(*tile_pos)
+						.is_some_and(|obj| !obj.is_collidable())
+			});
+
+		if let Some(tile_pos) = destination {
+			self.pos = FlowField::tile_center(tile_pos) - Vec2::splat(SIZE * 0.5);
+			self.time_til_teleport = TELEPORT_COOLDOWN;
+		}
+	}
+}
+
+impl Monster for Necromancer {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			pos,
+			speed_mul: 1.0,
+			target: None,
+			time_til_retarget: 0,
+			time_til_raise: RAISE_RATE,
+			time_til_teleport: 0,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		self.time_til_retarget = self.time_til_retarget.saturating_sub(1);
+		self.time_til_raise = self.time_til_raise.saturating_sub(1);
+		self.time_til_teleport = self.time_til_teleport.saturating_sub(1);
+
+		if self.time_til_retarget == 0 {
+			self.time_til_retarget = RETARGET_RATE;
+			self.target = find_target(self, SIGHT_RANGE, players, floor);
+		}
+
+		let Some(player) = self.target.map(|i| &players[i]) else {
+			return;
+		};
+
+		let distance = player.center().distance(self.center()) / TILE_SIZE as f32;
+
+		if distance < MIN_RANGE {
+			let angle = get_angle(self.center(), player.center());
+			let change = Vec2::new(angle.cos(), angle.sin()) * SPEED * self.speed_mul;
+
+			shove(self, change, floor);
+		} else if distance > MAX_RANGE {
+			close_in(self, SIZE, SPEED * self.speed_mul, floor);
+		}
+	}
+
+	fn damage_players(
+		&mut self, _players: &mut [Player], _floor: &Floor, _events: &mut Vec<GameEvent>,
+	) {
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+
+		if self.health > 0 && self.time_til_teleport == 0 {
+			self.teleport(damage_info.direction, floor);
+		}
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn asleep(&self) -> bool { self.asleep }
+
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 5;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> {
+		players.get(self.target?).map(|p| p.center())
+	}
+
+	fn alert(&mut self, player: usize, _pos: Vec2) { self.target = Some(player); }
+}
+
+impl Enchantable for Necromancer {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			EnchantmentKind::Blinded => self.target = None,
+			EnchantmentKind::Sticky => self.speed_mul = 0.5,
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Stunned |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Chilled |
+			EnchantmentKind::Frozen => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 300,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| {
+				if kind == EnchantmentKind::Sticky {
+					self.speed_mul = 1.0;
+				}
+			});
+	}
+}
+
+impl AsPolygon for Necromancer {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for Necromancer {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		let center = self.center();
+
+		// A hooded robe, with a glow where the face should be
+		draw_triangle(
+			center + Vec2::new(0.0, -SIZE * 0.5),
+			center + Vec2::new(-SIZE * 0.5, SIZE * 0.5),
+			center + Vec2::new(SIZE * 0.5, SIZE * 0.5),
+			Color::new(0.25, 0.1, 0.35, 1.0),
+		);
+		draw_circle(center.x, center.y - 1.0, 2.5, GREEN);
+	}
+}
@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+
+use crate::draw::{load_my_image, Drawable};
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{Floor, TILE_SIZE};
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind, MonsterObj, SmallRat};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::find_target;
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 42.0;
+const MAX_HEALTH: u16 = 300;
+const SPEED: f32 = 1.2;
+const DAMAGE: u16 = 15;
+/// How many tiles away a player can be seen from
+const SIGHT_RANGE: i32 = 14;
+// How often the path to the closest player is worked out again, in frames
+const REPATH_RATE: u8 = 30;
+/// How close a player has to be before the rat king lunges at them, in tiles
+const LUNGE_RANGE: f32 = 2.5;
+/// How long the rat king catches its breath after lunging, in frames
+const LUNGE_COOLDOWN: u16 = 50;
+/// Frames between each call for more rats
+const SUMMON_RATE: u16 = 300;
+/// How many rats answer each call
+const SUMMON_COUNT: usize = 2;
+/// No more rats come while there are already this many around the rat king
+pub const MAX_SUMMONED: usize = 6;
+/// How far the tail reaches around the rat king, in tiles
+const SWEEP_RADIUS: f32 = 2.5;
+const SWEEP_DAMAGE: u16 = 20;
+/// How long the rat king winds up before its tail comes round, in frames
+const SWEEP_WINDUP: u8 = 35;
+/// Frames between each tail sweep
+const SWEEP_RATE: u16 = 150;
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize)]
+enum Phase {
+	/// Lunges at players like a giant rat
+	Lunging,
+	/// Below half health, it calls for help and sweeps its tail round
+	Summoning,
+}
+
+/// The boss of the final floor. Chases down the closest player it can see and
+/// lunges at them once it's close. Below half health it calls for more rats,
+/// and sweeps its tail round at anyone who gets near
+#[derive(Clone, Serialize)]
+pub struct RatKing {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	pos: Vec2,
+	phase: Phase,
+	current_path: Option<(Vec<Vec2>, usize)>,
+	target: Option<usize>,
+	time_til_repath: u8,
+	time_til_lunge: u16,
+	time_til_summon: u16,
+	time_til_sweep: u16,
+	/// Frames left before the tail comes round, while it's winding up
+	sweep_windup: Option<u8>,
+	afflictions: Afflictions,
+}
+
+impl RatKing {
+	fn enraged(&self) -> bool { self.phase == Phase::Summoning }
+
+	/// Rats only come once the rat king's angry, and knows who to send them
+	/// after
+	pub fn ready_to_summon(&self) -> bool {
+		self.enraged() && self.time_til_summon == 0 && self.target.is_some()
+	}
+
+	/// Calls rats out from under the rat king, already after its target
+	pub fn summon(&mut self) -> Vec<MonsterObj> {
+		self.time_til_summon = SUMMON_RATE;
+
+		let Some(target) = self.target else {
+			return Vec::new();
+		};
+
+		(0..SUMMON_COUNT)
+			.map(|i| {
+				let offset = Vec2::new(i as f32 * SIZE * 0.5, SIZE * 0.5);
+				let mut rat = SmallRat::new(self.pos + offset);
+				rat.alert(target, self.center());
+
+				MonsterObj::SmallRat(rat)
+			})
+			.collect()
+	}
+
+	/// Jumps at the player, stopping short of any walls
+	fn lunge(&mut self, player: &Player, floor: &Floor) {
+		let angle = get_angle(player.pos(), self.pos);
+		let step = Vec2::new(angle.cos(), angle.sin()) * SIZE * 0.25;
+
+		for _ in 0..4 {
+			if floor.collision(self, step) {
+				break;
+			}
+
+			self.pos += step;
+		}
+
+		self.time_til_lunge = LUNGE_COOLDOWN;
+		self.current_path = None;
+		self.time_til_repath = 0;
+	}
+}
+
+impl Monster for RatKing {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			pos,
+			phase: Phase::Lunging,
+			current_path: None,
+			target: None,
+			time_til_repath: 0,
+			time_til_lunge: 0,
+			time_til_summon: 0,
+			time_til_sweep: SWEEP_RATE,
+			sweep_windup: None,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		// Once it's angry, healing back up won't calm it down
+		if self.health < self.max_health / 2 {
+			self.phase = Phase::Summoning;
+		}
+
+		if self.enraged() {
+			self.time_til_summon = self.time_til_summon.saturating_sub(1);
+			self.time_til_sweep = self.time_til_sweep.saturating_sub(1);
+		}
+
+		// Planted while the tail comes round
+		if let Some(frames_left) = &mut self.sweep_windup {
+			*frames_left = frames_left.saturating_sub(1);
+			return;
+		}
+
+		if self.afflictions.has(EnchantmentKind::Blinded) {
+			return;
+		}
+
+		self.time_til_repath = self.time_til_repath.saturating_sub(1);
+
+		if self.time_til_repath == 0 {
+			self.time_til_repath = REPATH_RATE;
+			self.target = find_target(self, SIGHT_RANGE, players, floor);
+
+			self.current_path = self.target.and_then(|i| {
+				floor
+					.find_path(self, &players[i].as_polygon(), false, false, None)
+					.map(|path| (path, 1))
+			});
+		}
+
+		if let Some(player) = self.target.map(|i| &players[i]) {
+			let distance = player.center().distance(self.center()) / TILE_SIZE as f32;
+
+			if self.enraged() && self.time_til_sweep == 0 && distance <= SWEEP_RADIUS {
+				self.sweep_windup = Some(SWEEP_WINDUP);
+				return;
+			}
+
+			if self.time_til_lunge > 0 {
+				self.time_til_lunge -= 1;
+				return;
+			}
+
+			if distance <= LUNGE_RANGE {
+				self.lunge(player, floor);
+				return;
+			}
+		}
+
+		let speed = match self.afflictions.has(EnchantmentKind::Sticky) {
+			true => SPEED * 0.5,
+			false => SPEED,
+		};
+
+		if let Some((path, i)) = &mut self.current_path {
+			match path.get(*i) {
+				Some(pos) => match speed >= self.pos.distance(*pos) {
+					true => {
+						self.pos = *pos;
+						*i += 1;
+					},
+					false => {
+						let angle = get_angle(*pos, self.pos);
+						self.pos += Vec2::new(angle.cos(), angle.sin()) * speed;
+					},
+				},
+				None => self.current_path = None,
+			}
+		}
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		if self.sweep_windup == Some(0) {
+			self.sweep_windup = None;
+			self.time_til_sweep = SWEEP_RATE;
+
+			players
+				.iter_mut()
+				.filter(|p| p.center().distance(self.center()) <= SWEEP_RADIUS * TILE_SIZE as f32)
+				.for_each(|p| {
+					let damage_direction = get_angle(p.center(), self.center());
+
+					damage_player(
+						p,
+						SWEEP_DAMAGE,
+						DamageType::Slash,
+						damage_direction,
+						Some(MonsterKind::RatKing),
+						floor,
+						events,
+					);
+				});
+		}
+
+		players.iter_mut().for_each(|p| {
+			if aabb_collision(p, self, Vec2::ZERO) {
+				let damage_direction = get_angle(p.pos(), self.pos);
+
+				damage_player(
+					p,
+					DAMAGE,
+					DamageType::Pierce,
+					damage_direction,
+					Some(MonsterKind::RatKing),
+					floor,
+					events,
+				);
+			}
+		});
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		// Too heavy to be knocked back
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	// Far too heavy to push around
+	fn knock_back(&mut self, _change: Vec2, _floor: &Floor) {}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 10;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn current_path(&self) -> &[Vec2] {
+		match &self.current_path {
+			Some((path, i)) => path.get(*i..).unwrap_or_default(),
+			None => &[],
+		}
+	}
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> {
+		players.get(self.target?).map(|p| p.center())
+	}
+
+	fn boss_health(&self) -> Option<f32> { Some(self.health as f32 / self.max_health as f32) }
+}
+
+impl Enchantable for RatKing {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		// Effects wear off the rat king much faster than other monsters
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Blinded => 30,
+			EnchantmentKind::Sticky => 60,
+			EnchantmentKind::Regenerating => 300,
+			EnchantmentKind::Stunned => 20,
+			EnchantmentKind::Burning => 30,
+			EnchantmentKind::Poisoned => 90,
+			EnchantmentKind::Chilled => 60,
+			EnchantmentKind::Frozen => 20,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |_| ());
+	}
+}
+
+impl AsPolygon for RatKing {
+	fn as_polygon(&self) -> Polygon {
+		let half_size = self.size() * Vec2::splat(0.5);
+		easy_polygon(self.pos + half_size, half_size, 0.0)
+	}
+}
+
+impl Drawable for RatKing {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn texture(&self) -> Option<Texture2D> { Some(load_my_image("small_rat.webp")) }
+
+	fn draw(&self) {
+		// The tail's reach, filling in as it winds up
+		if let Some(frames_left) = self.sweep_windup {
+			let center = self.center();
+			let radius = SWEEP_RADIUS * TILE_SIZE as f32;
+			let windup = 1.0 - frames_left as f32 / SWEEP_WINDUP as f32;
+
+			draw_circle_lines(center.x, center.y, radius, 2.0, RED);
+			draw_circle(
+				center.x,
+				center.y,
+				radius * windup,
+				Color::new(1.0, 0.0, 0.0, 0.25),
+			);
+		}
+
+		let color = match self.enraged() {
+			true => Color::new(1.0, 0.5, 0.4, 1.0),
+			false => GOLD,
+		};
+
+		draw_texture_ex(
+			self.texture().unwrap(),
+			self.pos.x,
+			self.pos.y,
+			color,
+			DrawTextureParams {
+				dest_size: Some(self.size()),
+				flip_x: self.flip_x(),
+				..Default::default()
+			},
+		);
+	}
+}
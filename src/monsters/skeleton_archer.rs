@@ -0,0 +1,424 @@
+use std::collections::HashSet;
+
+use crate::attacks::{Arrow, Attack, AttackObj};
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{Floor, TILE_SIZE};
+use crate::math::{easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{shove, Allegiance, Monster, MonsterKind};
+use crate::player::{DamageInfo, Player};
+use crate::rng::Rng;
+use crate::telegraphs::{TelegraphShape, Telegraphs};
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::{close_in, find_target};
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 16.0;
+const MAX_HEALTH: u16 = 18;
+const SPEED: f32 = 1.1;
+/// How many tiles away a player can be seen from
+const SIGHT_RANGE: i32 = 12;
+/// The archer backs off from players closer than this many tiles
+const MIN_RANGE: f32 = 5.0;
+/// and closes in on players further than this many tiles
+const MAX_RANGE: f32 = 8.0;
+/// How far the archer will look for somewhere to back off to, in tiles
+const RETREAT_SEARCH_RANGE: f32 = 5.0;
+// How often the path to somewhere safer is worked out again, in frames
+const REPATH_RATE: u8 = 30;
+/// How long the bow's drawn before the arrow's let go, in frames
+const WINDUP: u16 = 40;
+const COOLDOWN: u16 = 90;
+/// How long a summoned skeleton fights for before crumbling away, in frames
+const SUMMON_TIME: u16 = 20 * 60;
+/// Summoned skeletons with nothing to shoot at stay within this many tiles of
+/// whoever summoned them
+const FOLLOW_RANGE: f32 = 3.0;
+
+/// Lives deep in the dungeon, keeping its distance and shooting arrows. It
+/// draws its bow for a moment before each shot, giving players the chance to
+/// get out of the way, and backs off from anyone who gets too close. Wizards
+/// can summon their own to shoot at everything else for a while
+#[derive(Clone, Serialize)]
+pub struct SkeletonArcher {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
+	pos: Vec2,
+	speed_mul: f32,
+	target: Option<usize>,
+	allegiance: Allegiance,
+	/// The closest monster a summoned skeleton can see
+	quarry: Option<Vec2>,
+	current_path: Option<(Vec<Vec2>, usize)>,
+	time_til_repath: u8,
+	time_til_attack: u16,
+	/// Frames left until the drawn arrow's let go, and where it's aimed
+	windup: Option<(u16, f32)>,
+	afflictions: Afflictions,
+}
+
+impl SkeletonArcher {
+	/// Makes the skeleton fight for a player until its time runs out
+	pub fn summoned_by(mut self, summoner: usize) -> Self {
+		self.allegiance = Allegiance::Ally {
+			summoner,
+			frames_left: SUMMON_TIME,
+		};
+		self
+	}
+
+	/// Summoned skeletons go after the closest of `hostiles` they can see
+	pub fn hunt(&mut self, hostiles: &[Vec2], floor: &Floor) {
+		if self.allegiance == Allegiance::Hostile {
+			return;
+		}
+
+		if self.afflictions.has(EnchantmentKind::Blinded) {
+			self.quarry = None;
+			return;
+		}
+
+		let center = self.center();
+
+		self.quarry = hostiles
+			.iter()
+			.copied()
+			.filter(|pos| {
+				pos.distance(center) <= (SIGHT_RANGE * TILE_SIZE as i32) as f32 &&
+					floor.line_of_sight(center, *pos)
+			})
+			.min_by(|pos1, pos2| pos1.distance(center).total_cmp(&pos2.distance(center)));
+	}
+
+	/// Whatever the archer's shooting at: a player, or a monster if it's been
+	/// summoned
+	fn aim(&self, players: &[Player]) -> Option<Vec2> {
+		match self.allegiance {
+			Allegiance::Hostile => players.get(self.target?).map(|p| p.center()),
+			Allegiance::Ally { .. } | Allegiance::Companion { .. } => self.quarry,
+		}
+	}
+
+	/// Finds a way to the closest tile that's back in range of what it's
+	/// shooting at
+	fn find_retreat(&self, aim: Vec2, floor: &Floor) -> Option<Vec<Vec2>> {
+		let tile_size = TILE_SIZE as f32;
+
+		let goal = floor
+			.objects()
+			.iter()
+			.filter(|obj| !obj.is_collidable())
+			.filter(|obj| obj.center().distance(self.center()) <= RETREAT_SEARCH_RANGE * tile_size)
+			.filter(|obj| {
+				let distance = obj.center().distance(aim);
+				distance >= MIN_RANGE * tile_size && distance <= MAX_RANGE * tile_size
+			})
+			.min_by(|obj1, obj2| {
+				let obj1_distance = obj1.center().distance(self.center());
+				let obj2_distance = obj2.center().distance(self.center());
+
+				obj1_distance.total_cmp(&obj2_distance)
+			})?;
+
+		let poly = easy_polygon(goal.center(), Vec2::splat(tile_size * 0.5), 0.0);
+
+		floor.find_path(self, &poly, false, false, None)
+	}
+
+	fn step_path(&mut self) {
+		let speed = SPEED * self.speed_mul;
+
+		if let Some((path, i)) = &mut self.current_path {
+			match path.get(*i) {
+				Some(pos) => match speed >= self.pos.distance(*pos) {
+					true => {
+						self.pos = *pos;
+						*i += 1;
+					},
+					false => {
+						let angle = get_angle(*pos, self.pos);
+						self.pos += Vec2::new(angle.cos(), angle.sin()) * speed;
+					},
+				},
+				None => self.current_path = None,
+			}
+		}
+	}
+
+	/// Walks straight at something it can see
+	fn approach(&mut self, goal: Vec2, floor: &Floor) {
+		let angle = get_angle(goal, self.center());
+		let change = Vec2::new(angle.cos(), angle.sin()) * SPEED * self.speed_mul;
+
+		shove(self, change, floor);
+	}
+}
+
+impl Monster for SkeletonArcher {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			pos,
+			speed_mul: 1.0,
+			target: None,
+			allegiance: Allegiance::Hostile,
+			quarry: None,
+			current_path: None,
+			time_til_repath: 0,
+			time_til_attack: COOLDOWN,
+			windup: None,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		self.time_til_repath = self.time_til_repath.saturating_sub(1);
+
+		if self.time_til_repath == 0 {
+			self.time_til_repath = REPATH_RATE;
+			self.current_path = None;
+
+			if self.allegiance == Allegiance::Hostile {
+				self.target = find_target(self, SIGHT_RANGE, players, floor);
+			}
+
+			if let Some(aim) = self.aim(players) {
+				let distance = aim.distance(self.center()) / TILE_SIZE as f32;
+
+				if distance < MIN_RANGE {
+					self.current_path = self.find_retreat(aim, floor).map(|path| (path, 1));
+				}
+			}
+		}
+
+		// Archers stand still while drawing their bow
+		if self.windup.is_some() {
+			return;
+		}
+
+		if self.current_path.is_some() {
+			self.step_path();
+			return;
+		}
+
+		let Some(aim) = self.aim(players) else {
+			// Summoned skeletons keep up with whoever summoned them, who's
+			// usually the closest player along the flow field
+			if let Allegiance::Ally { summoner, .. } = self.allegiance {
+				if players[summoner].center().distance(self.center()) >
+					FOLLOW_RANGE * TILE_SIZE as f32
+				{
+					close_in(self, SIZE, SPEED * self.speed_mul, floor);
+				}
+			}
+
+			return;
+		};
+
+		if aim.distance(self.center()) > MAX_RANGE * TILE_SIZE as f32 {
+			match self.allegiance {
+				Allegiance::Hostile => close_in(self, SIZE, SPEED * self.speed_mul, floor),
+				Allegiance::Ally { .. } | Allegiance::Companion { .. } => self.approach(aim, floor),
+			}
+		}
+	}
+
+	fn attack(
+		&mut self, players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>,
+		telegraphs: &mut Telegraphs,
+	) {
+		if let Some((frames_left, angle)) = self.windup {
+			let frames_left = frames_left.saturating_sub(1);
+			self.windup = Some((frames_left, angle));
+
+			// Where the arrow's about to go. Summoned skeletons' arrows can't
+			// hurt players, so there's nothing to warn them about
+			if self.allegiance == Allegiance::Hostile {
+				telegraphs.warn(
+					TelegraphShape::Line {
+						from: self.center(),
+						angle,
+						length: MAX_RANGE * TILE_SIZE as f32,
+					},
+					frames_left,
+					WINDUP,
+				);
+			}
+
+			if frames_left == 0 {
+				let arrow = Arrow::new(self, None, angle, floor, true);
+				let arrow = match self.allegiance {
+					Allegiance::Hostile => arrow.shot_by(MonsterKind::SkeletonArcher),
+					Allegiance::Ally { summoner, .. } |
+					Allegiance::Companion { owner: summoner } => arrow.fired_for(summoner),
+				};
+				attacks.push(AttackObj::Arrow(arrow));
+
+				self.windup = None;
+				self.time_til_attack = COOLDOWN;
+			}
+
+			return;
+		}
+
+		self.time_til_attack = self.time_til_attack.saturating_sub(1);
+
+		if self.time_til_attack > 0 {
+			return;
+		}
+
+		let Some(aim) = self.aim(players) else {
+			return;
+		};
+
+		// The aim's fixed once the bow's drawn, so the arrow can be dodged
+		if aim.distance(self.center()) <= (MAX_RANGE + 1.0) * TILE_SIZE as f32 {
+			self.windup = Some((WINDUP, get_angle(aim, self.center())));
+		}
+	}
+
+	fn damage_players(
+		&mut self, _players: &mut [Player], _floor: &Floor, _events: &mut Vec<GameEvent>,
+	) {
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn asleep(&self) -> bool { self.asleep }
+
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 3;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn current_path(&self) -> &[Vec2] {
+		match &self.current_path {
+			Some((path, i)) => path.get(*i..).unwrap_or_default(),
+			None => &[],
+		}
+	}
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> { self.aim(players) }
+
+	fn alert(&mut self, player: usize, _pos: Vec2) {
+		if self.allegiance != Allegiance::Hostile {
+			return;
+		}
+
+		self.target = Some(player);
+		self.time_til_repath = 0;
+	}
+
+	fn allegiance(&self) -> Allegiance { self.allegiance }
+
+	fn allegiance_mut(&mut self) -> Option<&mut Allegiance> { Some(&mut self.allegiance) }
+}
+
+impl Enchantable for SkeletonArcher {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			// Can't aim at what it can't see
+			EnchantmentKind::Blinded => {
+				self.target = None;
+				self.quarry = None;
+				self.windup = None;
+				self.current_path = None;
+			},
+			EnchantmentKind::Sticky => {
+				self.speed_mul = 0.5;
+			},
+			EnchantmentKind::Stunned | EnchantmentKind::Frozen => {
+				self.windup = None;
+				self.current_path = None;
+			},
+			// Skeletons are immune to poison, so it never gets this far
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Chilled => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| {
+				if kind == EnchantmentKind::Sticky {
+					self.speed_mul = 1.0;
+				}
+			});
+	}
+}
+
+impl AsPolygon for SkeletonArcher {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for SkeletonArcher {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		let center = self.center();
+
+		// Summoned skeletons glow, and fade away over their last couple of
+		// seconds
+		let color = match self.allegiance {
+			Allegiance::Hostile => BEIGE,
+			Allegiance::Ally { frames_left, .. } => {
+				Color::new(0.6, 0.8, 1.0, (frames_left as f32 / 120.0).min(1.0))
+			},
+			Allegiance::Companion { .. } => Color::new(0.6, 0.8, 1.0, 1.0),
+		};
+
+		draw_circle(center.x, center.y, SIZE * 0.5, color);
+		draw_circle(center.x - 3.0, center.y - 2.0, 2.0, BLACK);
+		draw_circle(center.x + 3.0, center.y - 2.0, 2.0, BLACK);
+	}
+}
@@ -1,45 +1,56 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
-use crate::attacks::{Attack, AttackObj, Slimeball};
+use crate::attacks::{Attack, AttackObj, Slimeball, SLIMEBALL_RANGE};
 use crate::draw::{load_my_image, Drawable};
 use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
-use crate::map::{pos_to_tile, Floor, Object, TILE_SIZE};
+use crate::events::GameEvent;
+use crate::map::{pos_to_tile, with_scratch, Floor};
 use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
-use crate::monsters::Monster;
-use crate::player::{damage_player, DamageInfo, Player};
+use crate::monsters::{Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use crate::telegraphs::{TelegraphShape, Telegraphs};
 
 use macroquad::prelude::*;
-use macroquad::rand::ChooseRandom;
 use serde::Serialize;
 
-use super::Effect;
-
-#[derive(PartialEq, Clone, Serialize)]
-enum AttackMode {
-	Passive,
-	Attacking,
-}
-
-#[derive(Copy, Clone, Serialize)]
-enum Target {
-	Pos(Vec2),
-}
+use super::brain::{Brain, BrainParams};
+use super::{Afflictions, HitFlash};
 
 const SIZE: f32 = 14.0;
 const MAX_HEALTH: u16 = 15;
+/// How long a slime swells up before spitting, in frames
+const WINDUP: u16 = 20;
+
+/// Slimes ooze from room to room, and once they've seen a player they stay
+/// put and throw slimeballs, running off whenever anyone gets too close
+const BRAIN: BrainParams = BrainParams {
+	sight_range: 10,
+	idle_time: 0,
+	wanders_far: true,
+	wander_speed: 1.0,
+	reaction_time: 0,
+	chase_speed: None,
+	flee_range: Some(4.0),
+	flee_speed: 1.3,
+	lunge_range: None,
+	lunge_distance: 0.0,
+	lunge_windup: 0,
+	lunge_cooldown: 0,
+};
 
 #[derive(Clone, Serialize)]
 pub struct GreenSlime {
 	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
 	pos: Vec2,
-	attack_mode: AttackMode,
-	current_path: Option<(Vec<Vec2>, usize)>,
-	enchantments: HashMap<EnchantmentKind, Effect>,
-	// All the players who have damaged me
-	damaged_by: HashSet<usize>,
-	// Gotta keep track of if the target moved, to reset the path
-	current_target: Option<Target>,
+	brain: Brain,
+	afflictions: Afflictions,
 	time_til_attack: u8,
+	/// Frames left until the slime spits
+	windup: Option<u16>,
 }
 
 impl Monster for GreenSlime {
@@ -47,223 +58,168 @@ impl Monster for GreenSlime {
 		Self {
 			pos,
 			health: MAX_HEALTH,
-			attack_mode: AttackMode::Passive,
-			current_path: None,
-			current_target: None,
-			enchantments: HashMap::new(),
-			damaged_by: HashSet::new(),
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			brain: Brain::new(&BRAIN),
+			afflictions: Afflictions::default(),
 			time_til_attack: 30,
+			windup: None,
 		}
 	}
 
-	fn movement(&mut self, players: &[Player], floor: &Floor) {
-		match self.attack_mode {
-			AttackMode::Passive => passive_mode(self, players, floor),
-			AttackMode::Attacking => attack_mode(self, players, floor),
-		};
+	fn movement(&mut self, players: &[Player], floor: &Floor, rng: &mut Rng) {
+		self.brain
+			.think(&BRAIN, &mut self.pos, SIZE, players, floor, rng);
 	}
 
-	fn attack(&mut self, players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>) {
+	fn attack(
+		&mut self, players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>,
+		telegraphs: &mut Telegraphs,
+	) {
 		self.time_til_attack = self.time_til_attack.saturating_sub(1);
 
 		if self.time_til_attack > 0 {
 			return;
 		}
 
-		let visible_objects = floor.visible_objects(self, Some(10));
+		with_scratch(|scratch| {
+			let visible_objects = floor.visible_objects_with(self, Some(10), scratch);
 
-		// Throw a slimeball at all visible players
-		let players_to_attack = players.iter().filter(|player| {
-			let player_tile_pos = pos_to_tile(&player.as_polygon());
-			visible_objects
+			let mut players_to_attack = players
 				.iter()
-				.any(|obj| obj.tile_pos() == player_tile_pos)
-		});
+				.filter(|player| {
+					player.noticeable() &&
+						visible_objects.contains_tile(pos_to_tile(&player.as_polygon()))
+				})
+				.peekable();
+
+			// Swells up before spitting, which anyone nearby can see coming
+			let frames_left = match self.windup {
+				Some(frames_left) => frames_left.saturating_sub(1),
+				None if players_to_attack.peek().is_some() => WINDUP,
+				None => return,
+			};
+
+			telegraphs.warn(
+				TelegraphShape::Circle {
+					center: self.center(),
+					radius: SLIMEBALL_RANGE,
+				},
+				frames_left,
+				WINDUP,
+			);
+
+			if frames_left > 0 {
+				self.windup = Some(frames_left);
+				return;
+			}
+
+			self.windup = None;
 
-		players_to_attack.for_each(|player| {
-			let angle = get_angle(player.center(), self.center());
-			let slimeball = Slimeball::new(self, None, angle, &floor, true);
+			// Throw a slimeball at all visible players
+			players_to_attack.for_each(|player| {
+				let angle = get_angle(player.center(), self.center());
+				let slimeball =
+					Slimeball::new(self, None, angle, floor, true).shot_by(MonsterKind::GreenSlime);
 
-			self.time_til_attack = slimeball.cooldown() as u8;
-			attacks.push(AttackObj::Slimeball(slimeball));
+				self.time_til_attack = slimeball.cooldown() as u8;
+				attacks.push(AttackObj::Slimeball(slimeball));
+			});
 		});
 	}
 
-	fn damage_players(&mut self, players: &mut [Player], floor: &Floor) {
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
 		players.iter_mut().for_each(|p| {
 			if aabb_collision(p, self, Vec2::ZERO) {
 				const DAMAGE: u16 = 10;
 				let damage_direction = get_angle(p.pos(), self.pos);
 
-				damage_player(p, DAMAGE, damage_direction, floor);
+				damage_player(
+					p,
+					DAMAGE,
+					DamageType::Blunt,
+					damage_direction,
+					Some(MonsterKind::GreenSlime),
+					floor,
+					events,
+				);
 			}
 		});
 	}
 
 	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
 		self.health = self.health.saturating_sub(damage_info.damage);
-		self.damaged_by.insert(damage_info.player);
+		self.afflictions.hurt_by(damage_info.player);
 	}
 
-	fn living(&self) -> bool { self.health > 0 }
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
 
-	fn xp(&self) -> (&HashSet<usize>, u32) {
-		const DEFAULT_XP: u32 = 2;
-		(&self.damaged_by, DEFAULT_XP)
-	}
-}
-
-fn step_pathfinding(my_monster: &mut GreenSlime, _players: &[Player], floor: &Floor, speed: f32) {
-	if let Some((path, i)) = &mut my_monster.current_path {
-		if let Some(pos) = path.get(*i) {
-			let distance_to_target = my_monster.pos.distance(*pos);
-
-			if speed >= distance_to_target {
-				my_monster.pos = *pos;
-				*i += 1;
-			} else {
-				let angle = get_angle(*pos, my_monster.pos);
-				let change = Vec2::new(angle.cos(), angle.sin()) * speed;
-
-				my_monster.pos += change;
-			}
-		} else {
-			// Finished following path
-			my_monster.current_path = None;
-			my_monster.current_target = None;
-		}
-	} else {
-		if let Some(Target::Pos(pos)) = my_monster.current_target {
-			let poly = easy_polygon(
-				pos + Vec2::splat((TILE_SIZE / 2) as f32),
-				Vec2::splat((TILE_SIZE / 2) as f32),
-				0.0,
-			);
+	fn living(&self) -> bool { self.health > 0 }
 
-			let path = floor.find_path(my_monster, &poly, false, true, None);
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
 
-			if let Some(path) = path {
-				my_monster.current_path = Some((path, 1));
-			} else {
-				my_monster.current_path = None;
-				my_monster.current_target = None;
-			}
-		}
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
 	}
-}
 
-fn attack_mode(my_monster: &mut GreenSlime, players: &[Player], floor: &Floor) {
-	// Check how far the closest player is
-	let (player, p_distance) = players
-		.iter()
-		.map(|player| (player, player.center().distance(my_monster.center())))
-		.reduce(|(p1, p1_distance), (p2, p2_distance)| {
-			if p1_distance < p2_distance {
-				(p1, p1_distance)
-			} else {
-				(p2, p2_distance)
-			}
-		})
-		.unwrap();
-
-	if p_distance <= (TILE_SIZE * 4) as f32 {
-		// If the player is within 4 tiles, flee
-		let valid_objs = floor
-			.objects()
-			.iter()
-			.filter(|obj| match obj.is_collidable() {
-				true => obj.door().is_some(),
-				false => true,
-			})
-			.filter(|obj| obj.center().distance(player.center()) >= (TILE_SIZE * 4) as f32)
-			.collect::<Vec<&Object>>();
-
-		let obj = valid_objs.choose().unwrap();
-		my_monster.current_target = Some(Target::Pos(obj.pos()));
-	}
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
 
-	step_pathfinding(my_monster, players, floor, 1.3);
-}
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
 
-fn passive_mode(my_monster: &mut GreenSlime, players: &[Player], floor: &Floor) {
-	// Check if any players are in my visible range
-	let visible_objects = floor.visible_objects(my_monster, Some(10));
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
 
-	let should_aggro = players.iter().any(|player| {
-		let player_tile_pos = pos_to_tile(player);
+	fn asleep(&self) -> bool { self.asleep }
 
-		visible_objects
-			.iter()
-			.any(|obj| obj.tile_pos() == player_tile_pos)
-	});
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
 
-	if should_aggro {
-		my_monster.attack_mode = AttackMode::Attacking;
-		return;
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 2;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
 	}
 
-	if my_monster.current_target.is_none() {
-		// Choose a random room
-		let valid_rooms = floor
-			.objects()
-			.iter()
-			.filter(|obj| !obj.is_collidable())
-			.collect::<Vec<&Object>>();
+	fn current_path(&self) -> &[Vec2] { self.brain.current_path() }
 
-		let room = valid_rooms.choose().unwrap();
+	fn target(&self, players: &[Player]) -> Option<Vec2> { self.brain.target(players) }
 
-		let room_center_pos = room.center();
-		my_monster.current_target = Some(Target::Pos(room_center_pos));
-	}
+	fn alert(&mut self, player: usize, _pos: Vec2) { self.brain.alert(player); }
 
-	step_pathfinding(my_monster, players, floor, 1.0);
+	fn on_noise(&mut self, pos: Vec2) { self.brain.on_noise(pos); }
 }
 
 impl Enchantable for GreenSlime {
 	fn apply_enchantment(&mut self, enchantment: Enchantment) {
 		match enchantment.kind {
-			// Lacking eyes, slimes can't be blinded, and will instead take 1 damage
-			EnchantmentKind::Blinded => {
-				self.health -= 1;
-			},
-			// I am a slime, lol
-			EnchantmentKind::Sticky => (),
-			EnchantmentKind::Regenerating => {
-				self.enchantments.insert(
-					enchantment.kind,
-					Effect {
-						enchantment,
-						frames_left: 300,
-					},
-				);
-			},
+			EnchantmentKind::Stunned | EnchantmentKind::Frozen => self.brain.clear_path(),
+			// Slimes are immune to blinding and sticking, so they never get this far
+			EnchantmentKind::Blinded |
+			EnchantmentKind::Sticky |
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Chilled => (),
 		};
-	}
 
-	fn update_enchantments(&mut self) {
-		self.enchantments.retain(|e_kind, effect| {
-			match e_kind {
-				EnchantmentKind::Blinded => {
-					self.attack_mode = AttackMode::Passive;
-					self.current_target = None;
-					self.current_path = None;
-				},
-				EnchantmentKind::Sticky => (),
-				EnchantmentKind::Regenerating => {
-					if self.health < MAX_HEALTH {
-						// Heal every half second
-						if effect.frames_left % (30 / effect.enchantment.strength) as u16 == 0 {
-							self.health += 1;
-						}
-					}
-				},
-			}
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Chilled => 180,
+			_ => 300,
+		};
 
-			effect.frames_left = effect.frames_left.saturating_sub(1);
-			let removing_enchantment = effect.frames_left == 0;
+		self.afflictions.insert(enchantment, frames_left);
+	}
 
-			!removing_enchantment
-		});
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |_| ());
 	}
 }
 
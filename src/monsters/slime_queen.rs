@@ -0,0 +1,283 @@
+use std::collections::HashSet;
+
+use crate::attacks::{Attack, AttackObj, Slimeball, SLIMEBALL_RANGE};
+use crate::draw::{load_my_image, Drawable};
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::Floor;
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use crate::telegraphs::{TelegraphShape, Telegraphs};
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::find_target;
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 48.0;
+const MAX_HEALTH: u16 = 200;
+const SPEED: f32 = 0.8;
+/// How many tiles away a player can be seen from
+const SIGHT_RANGE: i32 = 14;
+// How often the path to the closest player is worked out again, in frames
+const REPATH_RATE: u8 = 45;
+// The angle between each slimeball in a volley, in radians
+const VOLLEY_SPREAD: f32 = 0.25;
+/// How long the queen winds up before each volley, in frames
+const WINDUP: u16 = 30;
+
+/// The boss of the boss floors. Slowly follows the closest player it can see,
+/// lobbing fans of slimeballs at them. Below half health it gets angry, and
+/// throws wider fans more often
+#[derive(Clone, Serialize)]
+pub struct SlimeQueen {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	pos: Vec2,
+	current_path: Option<(Vec<Vec2>, usize)>,
+	target: Option<usize>,
+	time_til_repath: u8,
+	time_til_attack: u16,
+	/// Frames left until the volley's thrown, and where it's aimed
+	windup: Option<(u16, f32)>,
+	afflictions: Afflictions,
+}
+
+impl SlimeQueen {
+	fn enraged(&self) -> bool { self.health < self.max_health / 2 }
+}
+
+impl Monster for SlimeQueen {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			pos,
+			current_path: None,
+			target: None,
+			time_til_repath: 0,
+			time_til_attack: 90,
+			windup: None,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		self.time_til_repath = self.time_til_repath.saturating_sub(1);
+
+		if self.time_til_repath == 0 {
+			self.time_til_repath = REPATH_RATE;
+			self.target = find_target(self, SIGHT_RANGE, players, floor);
+
+			self.current_path = self.target.and_then(|i| {
+				floor
+					.find_path(self, &players[i].as_polygon(), false, false, None)
+					.map(|path| (path, 1))
+			});
+		}
+
+		if let Some((path, i)) = &mut self.current_path {
+			match path.get(*i) {
+				Some(pos) => match SPEED >= self.pos.distance(*pos) {
+					true => {
+						self.pos = *pos;
+						*i += 1;
+					},
+					false => {
+						let angle = get_angle(*pos, self.pos);
+						self.pos += Vec2::new(angle.cos(), angle.sin()) * SPEED;
+					},
+				},
+				None => self.current_path = None,
+			}
+		}
+	}
+
+	fn attack(
+		&mut self, players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>,
+		telegraphs: &mut Telegraphs,
+	) {
+		let (num_slimeballs, cooldown) = match self.enraged() {
+			true => (7, 60),
+			false => (5, 90),
+		};
+
+		if let Some((frames_left, aim)) = self.windup {
+			let frames_left = frames_left.saturating_sub(1);
+			self.windup = Some((frames_left, aim));
+
+			let spread = VOLLEY_SPREAD * (num_slimeballs - 1) as f32;
+
+			telegraphs.warn(
+				TelegraphShape::Cone {
+					from: self.center(),
+					angle: aim,
+					spread,
+					length: SLIMEBALL_RANGE,
+				},
+				frames_left,
+				WINDUP,
+			);
+
+			if frames_left == 0 {
+				let first_angle = aim - spread / 2.0;
+
+				attacks.extend((0..num_slimeballs).map(|i| {
+					let angle = first_angle + i as f32 * VOLLEY_SPREAD;
+					AttackObj::Slimeball(
+						Slimeball::new(self, None, angle, floor, true)
+							.shot_by(MonsterKind::SlimeQueen),
+					)
+				}));
+
+				self.windup = None;
+				self.time_til_attack = cooldown;
+			}
+
+			return;
+		}
+
+		self.time_til_attack = self.time_til_attack.saturating_sub(1);
+
+		if self.time_til_attack > 0 {
+			return;
+		}
+
+		let Some(target) = self.target.and_then(|i| players.get(i)) else {
+			return;
+		};
+
+		// The aim's fixed once the queen starts winding up, so the volley can be
+		// dodged
+		self.windup = Some((WINDUP, get_angle(target.center(), self.center())));
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		players.iter_mut().for_each(|p| {
+			if aabb_collision(p, self, Vec2::ZERO) {
+				const DAMAGE: u16 = 10;
+				let damage_direction = get_angle(p.pos(), self.pos);
+
+				damage_player(
+					p,
+					DAMAGE,
+					DamageType::Blunt,
+					damage_direction,
+					Some(MonsterKind::SlimeQueen),
+					floor,
+					events,
+				);
+			}
+		});
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		// Too heavy to be knocked back
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	// Far too heavy to push around
+	fn knock_back(&mut self, _change: Vec2, _floor: &Floor) {}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 8;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn current_path(&self) -> &[Vec2] {
+		match &self.current_path {
+			Some((path, i)) => path.get(*i..).unwrap_or_default(),
+			None => &[],
+		}
+	}
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> {
+		players.get(self.target?).map(|p| p.center())
+	}
+
+	fn boss_health(&self) -> Option<f32> { Some(self.health as f32 / self.max_health as f32) }
+}
+
+impl Enchantable for SlimeQueen {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		let frames_left = match enchantment.kind {
+			// Like other slimes she's immune to these, so they never get this far
+			EnchantmentKind::Blinded | EnchantmentKind::Sticky => 240,
+			EnchantmentKind::Regenerating => 300,
+			EnchantmentKind::Stunned => 30,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 180,
+			EnchantmentKind::Chilled => 90,
+			EnchantmentKind::Frozen => 30,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |_| ());
+	}
+}
+
+impl AsPolygon for SlimeQueen {
+	fn as_polygon(&self) -> Polygon {
+		let half_size = self.size() * Vec2::splat(0.5);
+		easy_polygon(self.pos + half_size, half_size, 0.0)
+	}
+}
+
+impl Drawable for SlimeQueen {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn texture(&self) -> Option<Texture2D> { Some(load_my_image("green_slime.webp")) }
+
+	fn draw(&self) {
+		let color = match self.enraged() {
+			true => Color::new(1.0, 0.6, 0.6, 1.0),
+			false => WHITE,
+		};
+
+		draw_texture_ex(
+			self.texture().unwrap(),
+			self.pos.x,
+			self.pos.y,
+			color,
+			DrawTextureParams {
+				dest_size: Some(self.size()),
+				flip_x: self.flip_x(),
+				..Default::default()
+			},
+		);
+	}
+}
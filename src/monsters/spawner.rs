@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{Floor, TILE_SIZE};
+use crate::math::{easy_polygon, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind, MonsterObj};
+use crate::player::{DamageInfo, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 26.0;
+const MAX_HEALTH: u16 = 40;
+/// Frames between each new monster
+const SPAWN_RATE: u16 = 300;
+/// Spawners lie dormant until a player gets this close, in tiles
+const WAKE_DISTANCE: f32 = 10.0;
+/// How many of a spawner's monsters can be around it before it stops making
+/// more
+pub const MAX_NEARBY_SPAWNS: usize = 4;
+/// How close a monster has to be to count as being around a spawner, in tiles
+pub const NEARBY_DISTANCE: f32 = 6.0;
+
+/// A rat nest or slime pool. It never moves, but keeps producing monsters
+/// while players are near until it's destroyed
+#[derive(Clone, Serialize)]
+pub struct Spawner {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	pos: Vec2,
+	spawns: MonsterKind,
+	time_til_spawn: u16,
+	afflictions: Afflictions,
+}
+
+impl Spawner {
+	pub fn spawning(mut self, spawns: MonsterKind) -> Self {
+		self.spawns = spawns;
+		self
+	}
+
+	pub fn spawns(&self) -> MonsterKind { self.spawns }
+
+	/// Rat nests make rats, and slime pools make slimes
+	pub fn kind(&self) -> MonsterKind {
+		match self.spawns {
+			MonsterKind::SmallRat => MonsterKind::RatNest,
+			_ => MonsterKind::SlimePool,
+		}
+	}
+
+	pub fn ready(&self) -> bool { self.time_til_spawn == 0 }
+
+	/// Makes a new monster on top of the spawner, and starts waiting for the
+	/// next one
+	pub fn spawn(&mut self) -> MonsterObj {
+		self.time_til_spawn = SPAWN_RATE;
+		self.spawns.new_monster(self.pos)
+	}
+}
+
+impl Monster for Spawner {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			pos,
+			spawns: MonsterKind::SmallRat,
+			time_til_spawn: SPAWN_RATE,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], _floor: &Floor, _rng: &mut Rng) {
+		let awake = players.iter().any(|player| {
+			player.noticeable() &&
+				player.center().distance(self.center()) <= WAKE_DISTANCE * TILE_SIZE as f32
+		});
+
+		if awake {
+			self.time_til_spawn = self.time_til_spawn.saturating_sub(1);
+		}
+	}
+
+	fn damage_players(
+		&mut self, _players: &mut [Player], _floor: &Floor, _events: &mut Vec<GameEvent>,
+	) {
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	// Never moves
+	fn knock_back(&mut self, _change: Vec2, _floor: &Floor) {}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 4;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+}
+
+impl Enchantable for Spawner {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		let frames_left = match enchantment.kind {
+			// Nests are immune to these, so they never get this far
+			EnchantmentKind::Blinded | EnchantmentKind::Sticky => 240,
+			EnchantmentKind::Regenerating => 300,
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 300,
+			EnchantmentKind::Chilled => 180,
+			EnchantmentKind::Frozen => 60,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |_| ());
+	}
+}
+
+impl AsPolygon for Spawner {
+	fn as_polygon(&self) -> Polygon {
+		let half_size = self.size() * Vec2::splat(0.5);
+		easy_polygon(self.pos + half_size, half_size, 0.0)
+	}
+}
+
+impl Drawable for Spawner {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		let center = self.center();
+		let radius = SIZE * 0.5;
+
+		match self.kind() {
+			MonsterKind::RatNest => {
+				draw_circle(
+					center.x,
+					center.y,
+					radius,
+					Color::new(0.45, 0.32, 0.18, 1.0),
+				);
+				draw_circle_lines(center.x, center.y, radius, 2.0, DARKBROWN);
+				draw_circle(center.x - 4.0, center.y, 3.0, BLACK);
+				draw_circle(center.x + 5.0, center.y + 3.0, 2.5, BLACK);
+			},
+			_ => {
+				draw_circle(center.x, center.y, radius, Color::new(0.35, 0.8, 0.3, 0.8));
+				draw_circle(
+					center.x + 3.0,
+					center.y - 3.0,
+					radius * 0.35,
+					Color::new(0.6, 0.95, 0.55, 0.8),
+				);
+			},
+		}
+	}
+}
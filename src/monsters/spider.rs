@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+
+use crate::attacks::AttackObj;
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::Floor;
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use crate::telegraphs::Telegraphs;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::{Brain, BrainParams};
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 16.0;
+const MAX_HEALTH: u16 = 16;
+const DAMAGE: u16 = 4;
+const VENOM: Enchantment = Enchantment {
+	kind: EnchantmentKind::Poisoned,
+	strength: 1,
+};
+
+/// Spiders wait in the dark for something to come close, then jump it from
+/// further away than anything else can
+const BRAIN: BrainParams = BrainParams {
+	sight_range: 6,
+	idle_time: 120,
+	wanders_far: false,
+	wander_speed: 0.5,
+	reaction_time: 15,
+	chase_speed: Some(1.2),
+	flee_range: None,
+	flee_speed: 0.0,
+	lunge_range: Some(2.0),
+	lunge_distance: SIZE * 2.0,
+	lunge_windup: 20,
+	lunge_cooldown: 60,
+};
+
+/// Its bite's weak, but the venom keeps hurting long after it's let go
+#[derive(Clone, Serialize)]
+pub struct Spider {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
+	pos: Vec2,
+	brain: Brain,
+	afflictions: Afflictions,
+}
+
+impl Monster for Spider {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			pos,
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			brain: Brain::new(&BRAIN),
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, rng: &mut Rng) {
+		match self.afflictions.has(EnchantmentKind::Blinded) {
+			true => self.brain.stumble(&mut self.pos, SIZE, floor, rng),
+			false => self
+				.brain
+				.think(&BRAIN, &mut self.pos, SIZE, players, floor, rng),
+		};
+	}
+
+	fn attack(
+		&mut self, _players: &[Player], _floor: &Floor, _attacks: &mut Vec<AttackObj>,
+		telegraphs: &mut Telegraphs,
+	) {
+		self.brain
+			.telegraph(&BRAIN, self.center(), SIZE, telegraphs);
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		players.iter_mut().for_each(|p| {
+			if aabb_collision(p, self, Vec2::ZERO) {
+				let damage_direction = get_angle(p.pos(), self.pos);
+				let hp = p.hp();
+
+				damage_player(
+					p,
+					DAMAGE,
+					DamageType::Pierce,
+					damage_direction,
+					Some(MonsterKind::Spider),
+					floor,
+					events,
+				);
+
+				// Only bites that get through poison
+				if p.hp() < hp {
+					p.apply_enchantment(VENOM);
+				}
+			}
+		});
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn asleep(&self) -> bool { self.asleep }
+
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 2;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn current_path(&self) -> &[Vec2] { self.brain.current_path() }
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> { self.brain.target(players) }
+
+	fn alert(&mut self, player: usize, _pos: Vec2) { self.brain.alert(player); }
+
+	fn on_noise(&mut self, pos: Vec2) { self.brain.on_noise(pos); }
+}
+
+impl Enchantable for Spider {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			EnchantmentKind::Blinded => self.brain.blind(),
+			EnchantmentKind::Sticky => self.brain.set_speed_mul(0.5),
+			EnchantmentKind::Stunned | EnchantmentKind::Frozen => self.brain.clear_path(),
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Chilled => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			EnchantmentKind::Poisoned => 300,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| match kind {
+				EnchantmentKind::Blinded => self.brain.unblind(),
+				EnchantmentKind::Sticky => self.brain.set_speed_mul(1.0),
+				_ => (),
+			});
+	}
+}
+
+impl AsPolygon for Spider {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for Spider {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 {
+		match self.brain.aggressive() {
+			true => Vec2::splat(SIZE * 1.1),
+			false => Vec2::splat(SIZE),
+		}
+	}
+
+	fn draw(&self) {
+		let center = self.center();
+		let leg_color = Color::new(0.15, 0.1, 0.1, 1.0);
+
+		// Four legs on each side
+		(0..4).for_each(|i| {
+			let y = center.y - 4.5 + i as f32 * 3.0;
+
+			draw_line(center.x, y, center.x - SIZE * 0.5, y - 2.0, 1.0, leg_color);
+			draw_line(center.x, y, center.x + SIZE * 0.5, y - 2.0, 1.0, leg_color);
+		});
+
+		draw_circle(
+			center.x,
+			center.y,
+			SIZE * 0.3,
+			Color::new(0.2, 0.15, 0.15, 1.0),
+		);
+		draw_circle(center.x - 1.5, center.y - 2.0, 1.0, RED);
+		draw_circle(center.x + 1.5, center.y - 2.0, 1.0, RED);
+	}
+}
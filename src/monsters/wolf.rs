@@ -0,0 +1,384 @@
+use std::collections::HashSet;
+
+use crate::attacks::{Attack, AttackObj, Bite};
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::{Floor, TILE_SIZE};
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{shove, Allegiance, Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+use crate::telegraphs::Telegraphs;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::{Brain, BrainParams};
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 16.0;
+const MAX_HEALTH: u16 = 20;
+const DAMAGE: u16 = 3;
+/// How fast a companion runs, which is faster than the players it follows
+const SPEED: f32 = 2.4;
+/// Companions with nothing to hunt stay within this many tiles of their owner
+const FOLLOW_RANGE: f32 = 2.0;
+/// and are at their owner's side again if they're ever left this many behind
+const LEASH_RANGE: f32 = 10.0;
+/// How close a companion has to be to bite what it's hunting, in tiles
+const BITE_RANGE: f32 = 1.0;
+const BITE_COOLDOWN: u16 = 40;
+/// How far the thing a companion's hunting can get from where it was last
+/// frame before the companion loses track of it
+const TRACKING_RANGE: f32 = TILE_SIZE as f32 * 0.5;
+/// How much monsters hurt a companion that's touching them
+const MAULED_DAMAGE: u16 = 2;
+/// How long before a companion can be mauled again, in frames
+const MAULED_COOLDOWN: u16 = 30;
+
+/// Wild wolves hunt in the open, running players down from a long way off
+const BRAIN: BrainParams = BrainParams {
+	sight_range: 8,
+	idle_time: 90,
+	wanders_far: true,
+	wander_speed: 0.8,
+	reaction_time: 10,
+	chase_speed: Some(1.6),
+	flee_range: None,
+	flee_speed: 0.0,
+	lunge_range: Some(1.5),
+	lunge_distance: SIZE * 1.5,
+	lunge_windup: 15,
+	lunge_cooldown: 45,
+};
+
+/// Rangers bring a tame one along, which follows them from floor to floor and
+/// goes after whatever they last hit
+#[derive(Clone, Serialize)]
+pub struct Wolf {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	asleep: bool,
+	pos: Vec2,
+	speed_mul: f32,
+	brain: Brain,
+	allegiance: Allegiance,
+	/// Where whatever a companion's hunting is
+	quarry: Option<Vec2>,
+	/// Where the owner last hit something, so a new hit can be noticed
+	owners_last_hit: Option<Vec2>,
+	time_til_bite: u16,
+	/// Frames left until monsters can hurt a companion again
+	invincibility_frames: u16,
+	afflictions: Afflictions,
+}
+
+impl Wolf {
+	/// Makes the wolf a player's companion for good
+	pub fn tamed_by(mut self, owner: usize) -> Self {
+		self.allegiance = Allegiance::Companion { owner };
+		self
+	}
+
+	/// Companions go after whatever their owner last hit, keeping track of it
+	/// among `hostiles` as it moves
+	pub fn hunt(&mut self, hostiles: &[Vec2], players: &[Player]) {
+		let Allegiance::Companion { owner } = self.allegiance else {
+			return;
+		};
+
+		if self.afflictions.has(EnchantmentKind::Blinded) {
+			self.quarry = None;
+			return;
+		}
+
+		let last_hit = players[owner].last_hit;
+
+		if last_hit != self.owners_last_hit {
+			self.owners_last_hit = last_hit;
+			self.quarry = last_hit;
+		}
+
+		// Lost once it's dead, or out of reach on the ceiling
+		self.quarry = self.quarry.and_then(|quarry| {
+			hostiles
+				.iter()
+				.copied()
+				.filter(|pos| pos.distance(quarry) <= TRACKING_RANGE)
+				.min_by(|pos1, pos2| pos1.distance(quarry).total_cmp(&pos2.distance(quarry)))
+		});
+	}
+
+	/// Hostile monsters hurt a companion that gets too close, every so often
+	pub fn mauled(&mut self) {
+		if self.invincibility_frames > 0 {
+			return;
+		}
+
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(MAULED_DAMAGE);
+		self.invincibility_frames = MAULED_COOLDOWN;
+	}
+
+	/// Runs at what it's hunting, or back to its owner
+	fn follow(&mut self, owner: &Player, floor: &Floor) {
+		let owner_pos = owner.center();
+		let distance = owner_pos.distance(self.center());
+
+		if distance > LEASH_RANGE * TILE_SIZE as f32 {
+			self.pos = owner_pos - Vec2::splat(SIZE * 0.5);
+			self.quarry = None;
+			return;
+		}
+
+		let goal = match self.quarry {
+			Some(quarry) => quarry,
+			None if distance > FOLLOW_RANGE * TILE_SIZE as f32 => owner_pos,
+			None => return,
+		};
+
+		// Stops once it's close enough to bite
+		if self.quarry.is_some() && goal.distance(self.center()) <= SIZE {
+			return;
+		}
+
+		let angle = get_angle(goal, self.center());
+		let change = Vec2::new(angle.cos(), angle.sin()) * SPEED * self.speed_mul;
+
+		shove(self, change, floor);
+	}
+}
+
+impl Monster for Wolf {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			asleep: false,
+			pos,
+			speed_mul: 1.0,
+			brain: Brain::new(&BRAIN),
+			allegiance: Allegiance::Hostile,
+			quarry: None,
+			owners_last_hit: None,
+			time_til_bite: 0,
+			invincibility_frames: 0,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, players: &[Player], floor: &Floor, rng: &mut Rng) {
+		self.invincibility_frames = self.invincibility_frames.saturating_sub(1);
+
+		if let Allegiance::Companion { owner } = self.allegiance {
+			self.follow(&players[owner], floor);
+			return;
+		}
+
+		match self.afflictions.has(EnchantmentKind::Blinded) {
+			true => self.brain.stumble(&mut self.pos, SIZE, floor, rng),
+			false => self
+				.brain
+				.think(&BRAIN, &mut self.pos, SIZE, players, floor, rng),
+		};
+	}
+
+	fn attack(
+		&mut self, _players: &[Player], floor: &Floor, attacks: &mut Vec<AttackObj>,
+		telegraphs: &mut Telegraphs,
+	) {
+		let Allegiance::Companion { owner } = self.allegiance else {
+			self.brain
+				.telegraph(&BRAIN, self.center(), SIZE, telegraphs);
+			return;
+		};
+
+		self.time_til_bite = self.time_til_bite.saturating_sub(1);
+
+		let Some(quarry) = self.quarry else {
+			return;
+		};
+
+		if self.time_til_bite == 0 &&
+			quarry.distance(self.center()) <= BITE_RANGE * TILE_SIZE as f32
+		{
+			let angle = get_angle(quarry, self.center());
+
+			attacks.push(AttackObj::Bite(Bite::new(
+				self,
+				Some(owner),
+				angle,
+				floor,
+				true,
+			)));
+			self.time_til_bite = BITE_COOLDOWN;
+		}
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		if self.allegiance != Allegiance::Hostile {
+			return;
+		}
+
+		players.iter_mut().for_each(|p| {
+			if aabb_collision(p, self, Vec2::ZERO) {
+				let damage_direction = get_angle(p.pos(), self.pos);
+
+				damage_player(
+					p,
+					DAMAGE,
+					DamageType::Pierce,
+					damage_direction,
+					Some(MonsterKind::Wolf),
+					floor,
+					events,
+				);
+			}
+		});
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn asleep(&self) -> bool { self.asleep }
+
+	fn set_asleep(&mut self, asleep: bool) { self.asleep = asleep; }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 3;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+
+	fn current_path(&self) -> &[Vec2] { self.brain.current_path() }
+
+	fn target(&self, players: &[Player]) -> Option<Vec2> {
+		match self.allegiance {
+			Allegiance::Companion { .. } => self.quarry,
+			_ => self.brain.target(players),
+		}
+	}
+
+	fn alert(&mut self, player: usize, _pos: Vec2) {
+		if self.allegiance == Allegiance::Hostile {
+			self.brain.alert(player);
+		}
+	}
+
+	fn on_noise(&mut self, pos: Vec2) {
+		if self.allegiance == Allegiance::Hostile {
+			self.brain.on_noise(pos);
+		}
+	}
+
+	fn allegiance(&self) -> Allegiance { self.allegiance }
+
+	fn allegiance_mut(&mut self) -> Option<&mut Allegiance> { Some(&mut self.allegiance) }
+}
+
+impl Enchantable for Wolf {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			EnchantmentKind::Blinded => {
+				self.brain.blind();
+				self.quarry = None;
+			},
+			EnchantmentKind::Sticky => {
+				self.brain.set_speed_mul(0.5);
+				self.speed_mul = 0.5;
+			},
+			EnchantmentKind::Stunned | EnchantmentKind::Frozen => self.brain.clear_path(),
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Chilled => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| match kind {
+				EnchantmentKind::Blinded => self.brain.unblind(),
+				EnchantmentKind::Sticky => {
+					self.brain.set_speed_mul(1.0);
+					self.speed_mul = 1.0;
+				},
+				_ => (),
+			});
+	}
+}
+
+impl AsPolygon for Wolf {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for Wolf {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		let center = self.center();
+
+		// Companions are a warmer brown than the grey wild ones
+		let (fur, eyes) = match self.allegiance {
+			Allegiance::Companion { .. } => (Color::new(0.55, 0.4, 0.25, 1.0), SKYBLUE),
+			_ => (GRAY, YELLOW),
+		};
+
+		draw_triangle(
+			center + Vec2::new(-SIZE * 0.45, -SIZE * 0.2),
+			center + Vec2::new(-SIZE * 0.3, -SIZE * 0.6),
+			center + Vec2::new(-SIZE * 0.1, -SIZE * 0.3),
+			fur,
+		);
+		draw_triangle(
+			center + Vec2::new(SIZE * 0.45, -SIZE * 0.2),
+			center + Vec2::new(SIZE * 0.3, -SIZE * 0.6),
+			center + Vec2::new(SIZE * 0.1, -SIZE * 0.3),
+			fur,
+		);
+		draw_circle(center.x, center.y, SIZE * 0.45, fur);
+		draw_circle(center.x, center.y + 3.0, SIZE * 0.2, LIGHTGRAY);
+		draw_circle(center.x - 3.0, center.y - 2.0, 1.2, eyes);
+		draw_circle(center.x + 3.0, center.y - 2.0, 1.2, eyes);
+	}
+}
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+
+use crate::corpses::Corpse;
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::map::Floor;
+use crate::math::{aabb_collision, easy_polygon, get_angle, AsPolygon, Polygon};
+use crate::monsters::{Monster, MonsterKind};
+use crate::player::{damage_player, DamageInfo, DamageType, Player};
+use crate::rng::Rng;
+
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use super::brain::close_in;
+use super::{Afflictions, HitFlash};
+
+const SIZE: f32 = 16.0;
+const MAX_HEALTH: u16 = 10;
+const SPEED: f32 = 0.7;
+const DAMAGE: u16 = 6;
+/// Frames between each bite
+const BITE_COOLDOWN: u8 = 60;
+
+/// A monster raised from the dead by a necromancer. It's slow and weak, but it
+/// never stops shambling towards the closest player
+#[derive(Clone, Serialize)]
+pub struct Zombie {
+	health: u16,
+	max_health: u16,
+	hit_flash: HitFlash,
+	pos: Vec2,
+	speed_mul: f32,
+	time_til_bite: u8,
+	afflictions: Afflictions,
+}
+
+impl Zombie {
+	/// Stands the corpse back up where it fell
+	pub fn rise(corpse: &Corpse) -> Self { Self::new(corpse.pos - Vec2::splat(SIZE * 0.5)) }
+}
+
+impl Monster for Zombie {
+	fn new(pos: Vec2) -> Self {
+		Self {
+			health: MAX_HEALTH,
+			max_health: MAX_HEALTH,
+			hit_flash: HitFlash::default(),
+			pos,
+			speed_mul: 1.0,
+			time_til_bite: BITE_COOLDOWN,
+			afflictions: Afflictions::default(),
+		}
+	}
+
+	fn movement(&mut self, _players: &[Player], floor: &Floor, _rng: &mut Rng) {
+		self.time_til_bite = self.time_til_bite.saturating_sub(1);
+
+		close_in(self, SIZE, SPEED * self.speed_mul, floor);
+	}
+
+	fn damage_players(
+		&mut self, players: &mut [Player], floor: &Floor, events: &mut Vec<GameEvent>,
+	) {
+		if self.time_til_bite > 0 {
+			return;
+		}
+
+		if let Some(p) = players
+			.iter_mut()
+			.find(|p| aabb_collision(*p, self, Vec2::ZERO))
+		{
+			let damage_direction = get_angle(p.pos(), self.pos);
+
+			damage_player(
+				p,
+				DAMAGE,
+				DamageType::Blunt,
+				damage_direction,
+				Some(MonsterKind::Zombie),
+				floor,
+				events,
+			);
+
+			self.time_til_bite = BITE_COOLDOWN;
+		}
+	}
+
+	fn take_damage(&mut self, damage_info: DamageInfo, _floor: &Floor) {
+		self.hit_flash.hit();
+		self.health = self.health.saturating_sub(damage_info.damage);
+		self.afflictions.hurt_by(damage_info.player);
+	}
+
+	fn set_pos(&mut self, pos: Vec2) { self.pos = pos; }
+
+	fn living(&self) -> bool { self.health > 0 }
+
+	fn health(&self) -> (u16, u16) { (self.health, self.max_health) }
+
+	fn set_max_health(&mut self, max_health: u16) {
+		self.max_health = max_health;
+		self.health = max_health;
+	}
+
+	fn hit_flash(&self) -> HitFlash { self.hit_flash }
+
+	fn hit_flash_mut(&mut self) -> &mut HitFlash { &mut self.hit_flash }
+
+	fn afflictions(&self) -> &Afflictions { &self.afflictions }
+
+	fn afflictions_mut(&mut self) -> &mut Afflictions { &mut self.afflictions }
+
+	fn xp(&self) -> (&HashSet<usize>, u32) {
+		const DEFAULT_XP: u32 = 1;
+		(self.afflictions.damaged_by(), DEFAULT_XP)
+	}
+}
+
+impl Enchantable for Zombie {
+	fn apply_enchantment(&mut self, enchantment: Enchantment) {
+		match enchantment.kind {
+			EnchantmentKind::Sticky => self.speed_mul = 0.5,
+			// Zombies are immune to blinding and poison, so those never get this far
+			EnchantmentKind::Blinded |
+			EnchantmentKind::Poisoned |
+			EnchantmentKind::Regenerating |
+			EnchantmentKind::Stunned |
+			EnchantmentKind::Burning |
+			EnchantmentKind::Chilled |
+			EnchantmentKind::Frozen => (),
+		};
+
+		let frames_left = match enchantment.kind {
+			EnchantmentKind::Stunned => 90,
+			EnchantmentKind::Frozen => 60,
+			EnchantmentKind::Burning => 60,
+			_ => 240,
+		};
+
+		self.afflictions.insert(enchantment, frames_left);
+	}
+
+	fn update_enchantments(&mut self) {
+		self.afflictions
+			.update(&mut self.health, self.max_health, |kind| {
+				if kind == EnchantmentKind::Sticky {
+					self.speed_mul = 1.0;
+				}
+			});
+	}
+}
+
+impl AsPolygon for Zombie {
+	fn as_polygon(&self) -> Polygon {
+		const HALF_SIZE: Vec2 = Vec2::splat(SIZE * 0.5);
+		easy_polygon(self.pos + HALF_SIZE, HALF_SIZE, 0.0)
+	}
+}
+
+impl Drawable for Zombie {
+	fn pos(&self) -> Vec2 { self.pos }
+
+	fn size(&self) -> Vec2 { Vec2::splat(SIZE) }
+
+	fn draw(&self) {
+		let center = self.center();
+
+		draw_circle(
+			center.x,
+			center.y,
+			SIZE * 0.5,
+			Color::new(0.45, 0.55, 0.35, 1.0),
+		);
+		draw_circle(center.x - 3.0, center.y - 2.0, 1.5, RED);
+		draw_circle(center.x + 3.0, center.y - 2.0, 1.5, RED);
+	}
+}
@@ -0,0 +1,228 @@
+use std::collections::{HashSet, VecDeque};
+use std::net::{SocketAddr, UdpSocket};
+
+use macroquad::time::get_time;
+use serde::{Deserialize, Serialize};
+
+use super::{parse_remote_addr, GGRSConfig, NetError};
+use crate::localization::tr_args;
+
+/// Chat gets its own socket, on the port after the one GGRS uses
+const CHAT_PORT_OFFSET: u16 = 1;
+/// How many messages are shown at once
+const MAX_MESSAGES: usize = 8;
+const MAX_MESSAGE_LEN: usize = 200;
+/// How long to wait for an ack before sending a message again, in seconds
+const RESEND_TIME: f64 = 0.5;
+/// Messages that still haven't been acked after this many tries are dropped
+const MAX_TRIES: u32 = 10;
+
+#[derive(Serialize, Deserialize)]
+enum Packet {
+	Message {
+		id: u32,
+		player: usize,
+		text: String,
+	},
+	Ack {
+		id: u32,
+	},
+}
+
+struct ChatMessage {
+	player: usize,
+	text: String,
+}
+
+/// A message that hasn't been acked by one of the peers yet
+struct Unacked {
+	id: u32,
+	addr: SocketAddr,
+	bytes: Vec<u8>,
+	last_sent: f64,
+	tries: u32,
+}
+
+/// Text chat between players. It's sent outside of GGRS, since it has nothing
+/// to do with the simulation, and every message is resent until it's acked
+pub struct Chat {
+	socket: UdpSocket,
+	remotes: Vec<SocketAddr>,
+	local_player: usize,
+	next_id: u32,
+	unacked: Vec<Unacked>,
+	/// So resent messages only get shown once
+	received: HashSet<(SocketAddr, u32)>,
+	messages: VecDeque<ChatMessage>,
+	/// Whether the player's typing a message
+	pub open: bool,
+	input: String,
+}
+
+impl Chat {
+	pub fn new(conf: &GGRSConfig) -> Result<Self, NetError> {
+		let chat_port = |addr: SocketAddr| {
+			SocketAddr::new(addr.ip(), addr.port().wrapping_add(CHAT_PORT_OFFSET))
+		};
+
+		let remotes = conf
+			.remote_addrs
+			.iter()
+			.map(|addr| parse_remote_addr(addr).map(chat_port))
+			.collect::<Result<Vec<SocketAddr>, NetError>>()?;
+
+		let port = conf.local_port.wrapping_add(CHAT_PORT_OFFSET);
+		let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], port)))
+			.and_then(|socket| socket.set_nonblocking(true).map(|()| socket))
+			.map_err(|e| NetError::Bind(port, e))?;
+
+		Ok(Self {
+			socket,
+			remotes,
+			local_player: conf.local_player(),
+			next_id: 0,
+			unacked: Vec::new(),
+			received: HashSet::new(),
+			messages: VecDeque::new(),
+			open: false,
+			input: String::new(),
+		})
+	}
+
+	fn push_message(&mut self, message: ChatMessage) {
+		self.messages.push_back(message);
+
+		if self.messages.len() > MAX_MESSAGES {
+			self.messages.pop_front();
+		}
+	}
+
+	pub fn send(&mut self, text: String) {
+		let text: String = text.trim().chars().take(MAX_MESSAGE_LEN).collect();
+
+		if text.is_empty() {
+			return;
+		}
+
+		let id = self.next_id;
+		self.next_id += 1;
+
+		let bytes = bincode::serialize(&Packet::Message {
+			id,
+			player: self.local_player,
+			text: text.clone(),
+		})
+		.unwrap();
+
+		self.unacked
+			.extend(self.remotes.iter().map(|&addr| Unacked {
+				id,
+				addr,
+				bytes: bytes.clone(),
+				// Sent straight away by the next update
+				last_sent: f64::NEG_INFINITY,
+				tries: 0,
+			}));
+
+		self.push_message(ChatMessage {
+			player: self.local_player,
+			text,
+		});
+	}
+
+	/// Reads whatever's arrived, and resends anything that hasn't been acked
+	pub fn update(&mut self) {
+		let mut buf = [0; 1024];
+
+		while let Ok((len, addr)) = self.socket.recv_from(&mut buf) {
+			// Anyone could send anything to this port
+			if !self.remotes.contains(&addr) {
+				continue;
+			}
+
+			match bincode::deserialize(&buf[..len]) {
+				Ok(Packet::Message { id, player, text }) => {
+					let ack = bincode::serialize(&Packet::Ack { id }).unwrap();
+					let _ = self.socket.send_to(&ack, addr);
+
+					if self.received.insert((addr, id)) {
+						self.push_message(ChatMessage {
+							player,
+							text: text.chars().take(MAX_MESSAGE_LEN).collect(),
+						});
+					}
+				},
+				Ok(Packet::Ack { id }) => self
+					.unacked
+					.retain(|unacked| unacked.id != id || unacked.addr != addr),
+				Err(e) => log::warn!("Got a bad chat packet from {addr}: {e:?}"),
+			}
+		}
+
+		let now = get_time();
+
+		self.unacked.retain_mut(|unacked| {
+			if now - unacked.last_sent < RESEND_TIME {
+				return true;
+			}
+
+			if unacked.tries == MAX_TRIES {
+				log::warn!(
+					"Gave up sending chat message {} to {}",
+					unacked.id,
+					unacked.addr
+				);
+				return false;
+			}
+
+			if let Err(e) = self.socket.send_to(&unacked.bytes, unacked.addr) {
+				log::warn!("Couldn't send a chat message to {}: {e}", unacked.addr);
+			}
+
+			unacked.last_sent = now;
+			unacked.tries += 1;
+
+			true
+		});
+	}
+
+	/// Shows the last few messages, and a box to type in while the chat is
+	/// open
+	pub fn draw(&mut self) {
+		let mut submitted = false;
+
+		egui_macroquad::ui(|egui_ctx| {
+			egui::Area::new("chat")
+				.anchor(egui::Align2::LEFT_BOTTOM, egui::Vec2::new(10.0, -10.0))
+				.show(egui_ctx, |ui| {
+					self.messages.iter().for_each(|message| {
+						ui.label(
+							egui::RichText::new(tr_args(
+								"chat-message",
+								[
+									("player", (message.player + 1).into()),
+									("text", message.text.clone().into()),
+								],
+							))
+							.color(egui::Color32::WHITE),
+						);
+					});
+
+					if self.open {
+						let response = ui.text_edit_singleline(&mut self.input);
+						submitted =
+							response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+						response.request_focus();
+					}
+				});
+		});
+
+		egui_macroquad::draw();
+
+		if submitted {
+			let text = std::mem::take(&mut self.input);
+			self.send(text);
+			self.open = false;
+		}
+	}
+}
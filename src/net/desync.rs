@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+
+use ggrs::Frame;
+
+use crate::config::{write_storage, ConfigError};
+use crate::init_game::GameState;
+
+/// How often peers compare checksums, in frames
+pub const DESYNC_INTERVAL: u32 = 10;
+/// Checksums from peers can arrive a while after the frame they're for
+const KEPT_STATES: usize = 16;
+
+/// Remembers the states that get compared with other peers, so that the first
+/// one that didn't match can be dumped for debugging
+#[derive(Default)]
+pub struct DesyncDetector {
+	states: VecDeque<(Frame, GameState)>,
+	/// The first frame that didn't match another peer's
+	desynced_frame: Option<Frame>,
+}
+
+impl DesyncDetector {
+	pub fn desynced_frame(&self) -> Option<Frame> { self.desynced_frame }
+
+	pub fn save(&mut self, frame: Frame, game_state: &GameState) {
+		if frame % DESYNC_INTERVAL as Frame != 0 {
+			return;
+		}
+
+		// Rolling back saves the same frame again
+		self.states.retain(|(saved_frame, _)| *saved_frame != frame);
+		self.states.push_back((frame, game_state.clone()));
+
+		if self.states.len() > KEPT_STATES {
+			self.states.pop_front();
+		}
+	}
+
+	pub fn desync_detected(&mut self, frame: Frame, local_checksum: u128, remote_checksum: u128) {
+		log::error!(
+			"Desynced on frame {frame}: local checksum {local_checksum:04x}, remote checksum \
+			 {remote_checksum:04x}"
+		);
+
+		if self.desynced_frame.is_some() {
+			return;
+		}
+
+		self.desynced_frame = Some(frame);
+
+		let Some((_, game_state)) = self
+			.states
+			.iter()
+			.find(|(saved_frame, _)| *saved_frame == frame)
+		else {
+			log::error!("Frame {frame} wasn't kept, so it can't be dumped");
+			return;
+		};
+
+		let key = format!("desync_frame_{frame}.ron");
+		let dumped = ron::ser::to_string_pretty(game_state, Default::default())
+			.map_err(ConfigError::from)
+			.and_then(|dump| write_storage(&key, &dump));
+
+		match dumped {
+			Ok(()) => log::info!("Dumped frame {frame} to {key}"),
+			Err(e) => log::error!("Couldn't dump frame {frame}: {e:?}"),
+		}
+	}
+}
@@ -1,159 +1,562 @@
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::fmt::Display;
+#[cfg(not(feature = "web"))]
+use std::io;
+#[cfg(not(feature = "web"))]
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::time::Duration;
 
-use ggrs::{Config, GGRSRequest, P2PSession, SessionBuilder, UdpNonBlockingSocket};
+use ggrs::{Config, DesyncDetection, GGRSError, GGRSRequest, SessionBuilder};
 use serde::{Deserialize, Serialize};
 
+use crate::ambush::update_ambushes;
+use crate::arena::update_arena;
 use crate::attacks::update_attacks;
+#[cfg(feature = "native")]
+use crate::console::ConsoleCommand;
+use crate::director::update_director;
+use crate::events::GameEvent;
 use crate::init_game::{GameInfo, GameState};
 use crate::input::PlayerInput;
+use crate::localization::tr_args;
 
-use crate::map::{set_effects, trigger_traps, update_effects};
-use crate::monsters::update_monsters;
+use crate::map::{set_effects, trigger_traps, update_effects, Stairs};
+use crate::monsters::{update_monsters, MonsterKind};
+use crate::noise::ATTACK_NOISE;
 use crate::player::{
+	breach_door,
+	dash,
 	interact_with_door,
 	move_player,
+	offer_boons,
+	open_chest,
 	player_attack,
+	search_walls,
+	smash_crate,
+	throw_item,
+	toggle_inventory,
 	update_cooldowns,
+	update_downed,
+	update_stealth,
+	wind_up_attack,
 	DoorInteraction,
 };
+use crate::quests::update_quests;
+use crate::shop::buy_item;
+use crate::shrines::use_shrine;
 use crate::FPS;
 
-#[derive(Clone, Serialize, Deserialize)]
+// Chat has its own socket, which browsers can't open
+#[cfg(not(feature = "web"))]
+mod chat;
+mod desync;
+mod peers;
+// Peers talk over UDP natively, and over WebRTC in browsers
+#[cfg(not(feature = "web"))]
+mod udp;
+#[cfg(feature = "web")]
+mod webrtc;
+
+#[cfg(not(feature = "web"))]
+pub use chat::Chat;
+pub use desync::{DesyncDetector, DESYNC_INTERVAL};
+pub use peers::{PeerStatus, PeerStatuses};
+// Whichever transport was built in is picked by init_net
+#[cfg(not(feature = "web"))]
+pub use udp::{init_net, init_solo_net, parse_remote_addr, Lobby};
+#[cfg(feature = "web")]
+pub use webrtc::{init_net, init_solo_net, Lobby};
+
+/// How long a peer can go quiet for before they're dropped from the session,
+/// which gives them a chance to reconnect
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a peer has to be quiet for before the game's paused for them
+const DISCONNECT_NOTIFY_DELAY: Duration = Duration::from_secs(1);
+/// The most input delay players can pick, in frames
+pub const MAX_INPUT_DELAY: usize = 8;
+/// The furthest ahead of the other players that players can let the game
+/// predict, in frames
+pub const MAX_PREDICTION_WINDOW: usize = 16;
+/// Players sharing a machine split the screen between them
+pub const MAX_LOCAL_PLAYERS: usize = 2;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GGRSConfig {
 	pub multiplayer: bool,
 	pub local_port: u16,
-	pub remote_port: u16,
+	/// Which player this is. Every peer has to agree on everyone's handles
+	#[serde(default)]
+	pub local_handle: usize,
+	/// How many people are playing on this machine. The first uses the mouse
+	/// and keyboard, and the second a gamepad. They get the handles starting
+	/// at `local_handle`
+	#[serde(default = "default_local_players")]
+	pub local_players: usize,
+	/// The other players' `ip:port`s, from the lowest handle to the highest.
+	/// Browsers only use how many there are
+	#[serde(default = "default_remote_addrs")]
+	pub remote_addrs: Vec<String>,
+	/// Lets a headless observer spectate the game from this port on localhost
+	#[serde(default)]
+	pub spectator_port: Option<u16>,
+	/// The matchbox signalling server browsers find each other through
+	#[serde(default = "default_room_url")]
+	pub room_url: String,
+	/// How many frames local inputs are held back for. Higher delays mean
+	/// fewer rollbacks on slow connections, but the game feels less responsive
+	#[serde(default)]
+	pub input_delay: usize,
+	/// How many frames the game can run ahead of the other players' inputs
+	/// before it waits for them
+	#[serde(default = "default_max_prediction")]
+	pub max_prediction: usize,
+	/// Only saves the state on confirmed frames, which makes saving cheaper but
+	/// rollbacks longer
+	#[serde(default = "default_sparse_saving")]
+	pub sparse_saving: bool,
 }
 
+fn default_local_players() -> usize { 1 }
+
+fn default_remote_addrs() -> Vec<String> { vec!["127.0.0.1:2222".to_string()] }
+
+fn default_room_url() -> String { "ws://127.0.0.1:3536/roguelite?next=2".to_string() }
+
+fn default_max_prediction() -> usize { 8 }
+
+fn default_sparse_saving() -> bool { true }
+
 impl Default for GGRSConfig {
 	fn default() -> Self {
 		Self {
 			multiplayer: false,
 			local_port: 1111,
-			remote_port: 2222,
+			local_handle: 0,
+			local_players: default_local_players(),
+			remote_addrs: default_remote_addrs(),
+			spectator_port: None,
+			room_url: default_room_url(),
+			input_delay: 0,
+			max_prediction: default_max_prediction(),
+			sparse_saving: default_sparse_saving(),
+		}
+	}
+}
+
+impl GGRSConfig {
+	/// Browsers can't read gamepads, so they only ever have one local player
+	pub fn local_players(&self) -> usize {
+		match cfg!(feature = "native") {
+			true => self.local_players.clamp(1, MAX_LOCAL_PLAYERS),
+			false => 1,
+		}
+	}
+
+	pub fn num_players(&self) -> usize {
+		match self.multiplayer {
+			true => self.local_players() + self.remote_addrs.len(),
+			false => self.local_players(),
 		}
 	}
+
+	/// The handle of the first player on this machine
+	pub fn local_player(&self) -> usize {
+		self.local_handle
+			.min(self.num_players() - self.local_players())
+	}
+
+	/// The handles of every player on this machine
+	pub fn local_handles(&self) -> Range<usize> {
+		self.local_player()..self.local_player() + self.local_players()
+	}
+
+	/// Everything about a session that doesn't depend on the transport
+	fn session_builder(&self) -> SessionBuilder<GGRSConfig> {
+		SessionBuilder::<GGRSConfig>::new()
+			.with_num_players(self.num_players())
+			.with_fps(FPS as usize)
+			.unwrap()
+			.with_input_delay(self.input_delay.min(MAX_INPUT_DELAY))
+			.with_max_prediction_window(self.max_prediction.clamp(1, MAX_PREDICTION_WINDOW))
+			.with_sparse_saving_mode(self.sparse_saving)
+			.with_disconnect_timeout(DISCONNECT_TIMEOUT)
+			.with_disconnect_notify_delay(DISCONNECT_NOTIFY_DELAY)
+			.with_desync_detection_mode(DesyncDetection::On {
+				interval: DESYNC_INTERVAL,
+			})
+	}
 }
 
 impl Config for GGRSConfig {
 	type Input = PlayerInput;
 	type State = GameState;
+	#[cfg(not(feature = "web"))]
 	type Address = SocketAddr;
+	/// Matchbox's peer IDs
+	#[cfg(feature = "web")]
+	type Address = String;
 }
 
-pub fn init_net(conf: &GGRSConfig) -> P2PSession<GGRSConfig> {
-	let local_sock = UdpNonBlockingSocket::bind_to_port(conf.local_port).unwrap();
-	let remote = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, conf.remote_port));
+/// Why a session couldn't be started
+#[derive(Debug)]
+pub enum NetError {
+	/// The remote address isn't an `ip:port`
+	BadAddress(String),
+	/// The local port is already taken, or can't be used
+	#[cfg(not(feature = "web"))]
+	Bind(u16, io::Error),
+	/// There's no route to the other player from this machine
+	#[cfg(not(feature = "web"))]
+	Unreachable(SocketAddr, io::Error),
+	Session(GGRSError),
+}
 
-	let mut session = SessionBuilder::<GGRSConfig>::new()
-		.with_num_players(match conf.multiplayer {
-			true => 2,
-			false => 1,
+impl From<GGRSError> for NetError {
+	fn from(value: GGRSError) -> Self { NetError::Session(value) }
+}
+
+impl Display for NetError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&match self {
+			NetError::BadAddress(addr) => {
+				tr_args("net-error-bad-address", [("addr", addr.clone().into())])
+			},
+			#[cfg(not(feature = "web"))]
+			NetError::Bind(port, e) => tr_args(
+				"net-error-bind",
+				[("port", (*port).into()), ("error", e.to_string().into())],
+			),
+			#[cfg(not(feature = "web"))]
+			NetError::Unreachable(addr, e) => tr_args(
+				"net-error-unreachable",
+				[
+					("addr", addr.to_string().into()),
+					("error", e.to_string().into()),
+				],
+			),
+			NetError::Session(e) => tr_args("net-error-session", [("error", e.to_string().into())]),
 		})
-		.with_fps(FPS as usize)
-		.unwrap()
-		// .with_input_delay(1)
-		.add_player(ggrs::PlayerType::Local, 0)
-		.unwrap();
-
-	if conf.multiplayer {
-		session = session
-			.add_player(ggrs::PlayerType::Remote(remote), 1)
-			.unwrap();
 	}
-	session
-		.with_sparse_saving_mode(true)
-		.start_p2p_session(local_sock)
-		.unwrap()
 }
 
 pub fn handle_requests(reqs: Vec<GGRSRequest<GGRSConfig>>, game_info: &mut GameInfo) {
 	reqs.iter().for_each(|req| match req {
 		GGRSRequest::SaveGameState { cell, frame } => {
-			// let bin = bincode::serialize(&game_info.game_state).unwrap();
-			// let checksum = fletcher16(bin) as u128;
-			cell.save(*frame, Some(game_info.game_state.clone()), None);
+			game_info.desync.save(*frame, &game_info.game_state);
+
+			cell.save(
+				*frame,
+				Some(game_info.game_state.clone()),
+				Some(game_info.game_state.checksum() as u128),
+			);
 		},
-		GGRSRequest::LoadGameState { cell, frame: _ } => {
+		GGRSRequest::LoadGameState { cell, frame } => {
+			log::debug!("Rolling back to frame {frame}");
 			game_info.game_state = cell.load().unwrap();
 		},
 		GGRSRequest::AdvanceFrame { inputs } => {
-			game_info.game_state.frame += 1;
-			let players = &mut game_info.game_state.players;
-
-			inputs.iter().zip(players.iter_mut().enumerate()).for_each(
-				|((input, _input_status), (i, player))| {
-					player.angle = input.rotation();
-
-					if input.is_moving() {
-						move_player(
-							player,
-							input.movement_angle(),
-							None,
-							&game_info.game_state.map.current_floor().floor,
-						);
-					}
-
-					if input.using_primary() {
-						player_attack(
-							player,
-							Some(i),
-							&mut game_info.game_state.attacks,
-							&game_info.game_state.map.current_floor(),
-							true,
-						);
-					}
-
-					if input.using_secondary() {
-						player_attack(
-							player,
-							Some(i),
-							&mut game_info.game_state.attacks,
-							&game_info.game_state.map.current_floor(),
-							false,
-						);
-					}
-
-					if input.opening_door() {
-						interact_with_door(
-							player,
-							DoorInteraction::Opening,
-							game_info.game_state.map.current_floor_mut(),
-						);
-					}
-
-					if input.closing_door() {
-						interact_with_door(
-							player,
-							DoorInteraction::Closing,
-							game_info.game_state.map.current_floor_mut(),
-						);
-					}
-				},
-			);
+			let inputs: Vec<PlayerInput> = inputs.iter().map(|(input, _status)| *input).collect();
 
-			update_attacks(
-				&mut game_info.game_state.players,
-				game_info.game_state.map.current_floor_mut(),
-				&mut game_info.game_state.attacks,
-			);
+			// Nothing that happens in the lobby needs to be replayed
+			if let Some(recording) = &mut game_info.recording {
+				if game_info.game_state.lobby.is_none() {
+					recording.record(game_info.game_state.frame, &inputs);
+				}
+			}
 
-			update_cooldowns(&mut game_info.game_state.players);
+			advance_frame(&mut game_info.game_state, &inputs);
 
-			trigger_traps(
-				&mut game_info.game_state.players,
-				game_info.game_state.map.current_floor_mut(),
-			);
-			set_effects(
-				&mut game_info.game_state.players,
-				game_info.game_state.map.current_floor_mut(),
-			);
-			update_effects(&mut game_info.game_state.map.current_floor_mut().floor);
-			update_monsters(
-				&mut game_info.game_state.players,
-				game_info.game_state.map.current_floor_mut(),
-				&mut game_info.game_state.attacks,
-			);
+			game_info
+				.event_queue
+				.push_frame(game_info.game_state.frame, &game_info.game_state.events);
 		},
 	});
 }
+
+/// Runs a single frame of the simulation. Nothing in here can depend on
+/// anything outside of the game state and inputs, or peers will desync
+pub fn advance_frame(game_state: &mut GameState, inputs: &[PlayerInput]) {
+	crate::profile_scope!("advance_frame");
+
+	// Nothing happens until everyone's ready
+	if let Some(lobby) = &mut game_state.lobby {
+		let same_run = inputs.iter().all(|input| input.same_run(&inputs[0]));
+
+		lobby.iter_mut().zip(inputs).for_each(|(player, input)| {
+			player.ready = input.ready() && same_run;
+			player.seed = input.seed();
+			player.mode = input.mode();
+			player.difficulty = input.difficulty();
+			player.friendly_fire = input.friendly_fire();
+		});
+
+		if lobby.iter().all(|player| player.ready) {
+			game_state.lobby = None;
+		}
+
+		return;
+	}
+
+	game_state.frame += 1;
+	game_state.events.clear();
+
+	// A modified peer could send commands of its own, so they're only trusted
+	// when there's no one else to cheat against
+	#[cfg(feature = "native")]
+	if let [input] = inputs {
+		if let Some(command) = ConsoleCommand::decode(input.command()) {
+			command.apply(game_state, 0);
+		}
+	}
+
+	let players = &mut game_state.players;
+
+	inputs
+		.iter()
+		.zip(players.iter_mut().enumerate())
+		.for_each(|(input, (i, player))| {
+			// Downed and dead players just watch until someone gets them back up
+			if player.stunned() || player.hp() == 0 {
+				return;
+			}
+
+			player.angle = input.rotation();
+
+			if let Some(choice) = input.boon_choice() {
+				player.choose_boon(choice);
+			}
+
+			if input.toggling_inventory() {
+				toggle_inventory(player);
+			}
+
+			if let Some(index) = input.equipping() {
+				player.inventory.equip(index);
+			}
+
+			if input.is_moving() && player.rooted == 0 {
+				move_player(
+					player,
+					input.movement_angle(),
+					None,
+					&game_state.map.current_floor().floor,
+				);
+			}
+
+			if input.dashing() {
+				// Dashes go the way the player's moving, or where they're facing
+				// if they're standing still
+				let angle = match input.is_moving() {
+					true => input.movement_angle(),
+					false => player.angle,
+				};
+
+				dash(player, angle, &game_state.map.current_floor().floor);
+			}
+
+			let attacks_before = game_state.attacks.len();
+
+			wind_up_attack(
+				player,
+				Some(i),
+				input.winding_up(),
+				&mut game_state.attacks,
+				game_state.map.current_floor(),
+			);
+
+			if input.using_primary() {
+				player_attack(
+					player,
+					Some(i),
+					&mut game_state.attacks,
+					game_state.map.current_floor(),
+					true,
+				);
+			}
+
+			if input.using_secondary() {
+				player_attack(
+					player,
+					Some(i),
+					&mut game_state.attacks,
+					game_state.map.current_floor(),
+					false,
+				);
+			}
+
+			if input.throwing() {
+				throw_item(
+					player,
+					Some(i),
+					&mut game_state.attacks,
+					game_state.map.current_floor(),
+				);
+			}
+
+			// Swinging, casting and throwing can all be heard from a way off
+			if game_state.attacks.len() > attacks_before {
+				game_state.events.push(GameEvent::Noise {
+					pos: player.center(),
+					radius: ATTACK_NOISE,
+				});
+			}
+
+			if input.opening_door() {
+				interact_with_door(
+					player,
+					DoorInteraction::Opening,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+				);
+
+				open_chest(
+					player,
+					i,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+					&mut game_state.rng,
+				);
+
+				smash_crate(
+					player,
+					i,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+				);
+			}
+
+			if input.closing_door() {
+				interact_with_door(
+					player,
+					DoorInteraction::Closing,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+				);
+			}
+
+			if input.charging() {
+				breach_door(
+					player,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+				);
+			}
+
+			if input.searching() {
+				search_walls(
+					player,
+					i,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+				);
+			}
+
+			if input.interacting() {
+				use_shrine(
+					player,
+					i,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+					&mut game_state.rng,
+				);
+
+				buy_item(
+					player,
+					i,
+					game_state.map.current_floor_mut(),
+					&mut game_state.events,
+				);
+			}
+		});
+
+	update_attacks(
+		&mut game_state.players,
+		game_state.map.current_floor_mut(),
+		&mut game_state.attacks,
+		&mut game_state.events,
+		&mut game_state.rng,
+		game_state.friendly_fire,
+	);
+
+	let reviving: Vec<bool> = inputs.iter().map(|input| input.reviving()).collect();
+
+	update_downed(&mut game_state.players, &reviving, &mut game_state.events);
+	offer_boons(&mut game_state.players, &mut game_state.rng);
+	update_cooldowns(&mut game_state.players);
+	update_stealth(&mut game_state.players, game_state.map.current_floor());
+
+	trigger_traps(
+		&mut game_state.players,
+		game_state.map.current_floor_mut(),
+		&mut game_state.attacks,
+		&mut game_state.events,
+		&mut game_state.rng,
+	);
+	set_effects(
+		&mut game_state.players,
+		game_state.map.current_floor_mut(),
+		&mut game_state.events,
+	);
+	update_effects(&mut game_state.map.current_floor_mut().floor);
+	update_monsters(
+		&mut game_state.players,
+		game_state.map.current_floor_mut(),
+		&mut game_state.attacks,
+		&mut game_state.telegraphs,
+		&mut game_state.events,
+		&mut game_state.rng,
+	);
+	update_ambushes(
+		&game_state.players,
+		game_state.map.current_floor_mut(),
+		&mut game_state.events,
+		&mut game_state.rng,
+	);
+	update_director(
+		&game_state.players,
+		game_state.map.current_floor_mut(),
+		&game_state.events,
+		&mut game_state.rng,
+	);
+
+	if let Some(arena) = &mut game_state.arena {
+		update_arena(
+			arena,
+			game_state.map.current_floor_mut(),
+			&mut game_state.events,
+			&mut game_state.rng,
+		);
+	}
+
+	let floor_index = game_state.map.current_floor_index();
+
+	update_quests(
+		&mut game_state.quests,
+		&mut game_state.events,
+		&mut game_state.players,
+		game_state.map.current_floor_mut(),
+		floor_index,
+	);
+
+	match game_state.map.stairs_taken(&game_state.players) {
+		Some(Stairs::Down) => game_state.descend(),
+		Some(Stairs::Up) => game_state.ascend(),
+		None => (),
+	}
+
+	update_stats(game_state);
+}
+
+/// Keeps track of how the run is going, from everything that happened this
+/// frame
+fn update_stats(game_state: &mut GameState) {
+	game_state.events.iter().for_each(|event| {
+		if let GameEvent::MonsterDied { kind, .. } = event {
+			game_state.kills += 1;
+
+			if *kind == MonsterKind::RatKing && game_state.won_on_frame.is_none() {
+				game_state.won_on_frame = Some(game_state.frame);
+			}
+		}
+	});
+}
@@ -0,0 +1,79 @@
+use ggrs::GGRSEvent;
+
+use super::GGRSConfig;
+use crate::localization::{tr, tr_args};
+
+/// How the connection to another player is going
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PeerStatus {
+	Synchronizing {
+		count: u32,
+		total: u32,
+	},
+	Synchronized,
+	/// Nothing's been heard from them for a while, but they haven't timed out
+	/// yet
+	Interrupted,
+	Disconnected,
+}
+
+impl PeerStatus {
+	pub fn description(&self) -> String {
+		match self {
+			PeerStatus::Synchronizing { count, total } => tr_args(
+				"lobby-synchronizing",
+				[("count", (*count).into()), ("total", (*total).into())],
+			),
+			PeerStatus::Synchronized => tr("lobby-synchronized"),
+			PeerStatus::Interrupted => tr("lobby-interrupted"),
+			PeerStatus::Disconnected => tr("lobby-disconnected"),
+		}
+	}
+}
+
+/// Every peer that the session has said anything about, by address. Only used
+/// for showing players what's going on, so it isn't part of the game state
+#[derive(Default)]
+pub struct PeerStatuses {
+	peers: Vec<(String, PeerStatus)>,
+}
+
+impl PeerStatuses {
+	pub fn handle_event(&mut self, event: &GGRSEvent<GGRSConfig>) {
+		let (addr, status) = match event {
+			GGRSEvent::Synchronizing { addr, total, count } => (
+				addr,
+				PeerStatus::Synchronizing {
+					count: *count,
+					total: *total,
+				},
+			),
+			GGRSEvent::Synchronized { addr } => (addr, PeerStatus::Synchronized),
+			GGRSEvent::NetworkInterrupted { addr, .. } => (addr, PeerStatus::Interrupted),
+			GGRSEvent::NetworkResumed { addr } => (addr, PeerStatus::Synchronized),
+			GGRSEvent::Disconnected { addr } => (addr, PeerStatus::Disconnected),
+			_ => return,
+		};
+
+		let addr = addr.to_string();
+
+		match self
+			.peers
+			.iter_mut()
+			.find(|(peer_addr, _)| *peer_addr == addr)
+		{
+			Some((_, peer_status)) => *peer_status = status,
+			None => self.peers.push((addr, status)),
+		}
+	}
+
+	/// Whether anyone's stopped responding, or has been dropped from the
+	/// session
+	pub fn connection_lost(&self) -> bool {
+		self.peers
+			.iter()
+			.any(|(_, status)| matches!(status, PeerStatus::Interrupted | PeerStatus::Disconnected))
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &(String, PeerStatus)> { self.peers.iter() }
+}
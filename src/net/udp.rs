@@ -0,0 +1,104 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, UdpSocket};
+
+use ggrs::{P2PSession, SessionBuilder, UdpNonBlockingSocket};
+
+use super::{GGRSConfig, NetError};
+use crate::FPS;
+
+pub fn parse_remote_addr(addr: &str) -> Result<SocketAddr, NetError> {
+	addr.trim()
+		.parse()
+		.map_err(|_| NetError::BadAddress(addr.to_string()))
+}
+
+/// UDP doesn't have connections, but connecting a socket still asks the OS for
+/// a route to the address, which fails straight away if there isn't one
+fn check_reachable(remote: SocketAddr) -> Result<(), NetError> {
+	let unspecified: SocketAddr = match remote {
+		SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+		SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+	};
+
+	UdpSocket::bind(unspecified)
+		.and_then(|socket| socket.connect(remote))
+		.map_err(|e| NetError::Unreachable(remote, e))
+}
+
+/// Every peer's address is already known natively, so the session is started
+/// straight away
+pub struct Lobby {
+	session: Option<P2PSession<GGRSConfig>>,
+}
+
+impl Lobby {
+	pub fn try_start(&mut self) -> Option<P2PSession<GGRSConfig>> { self.session.take() }
+}
+
+pub fn init_net(conf: &GGRSConfig) -> Result<Lobby, NetError> {
+	Ok(Lobby {
+		session: Some(start_session(conf)?),
+	})
+}
+
+fn start_session(conf: &GGRSConfig) -> Result<P2PSession<GGRSConfig>, NetError> {
+	let num_players = conf.num_players();
+	let local_handles = conf.local_handles();
+
+	let mut remotes = match conf.multiplayer {
+		true => conf
+			.remote_addrs
+			.iter()
+			.map(|addr| {
+				let remote = parse_remote_addr(addr)?;
+				check_reachable(remote)?;
+
+				Ok(remote)
+			})
+			.collect::<Result<Vec<SocketAddr>, NetError>>()?,
+		false => Vec::new(),
+	}
+	.into_iter();
+
+	let local_sock = UdpNonBlockingSocket::bind_to_port(conf.local_port)
+		.map_err(|e| NetError::Bind(conf.local_port, e))?;
+
+	let mut session = conf.session_builder();
+
+	// Every other handle goes to the remote players, in order
+	for handle in 0..num_players {
+		let player = match local_handles.contains(&handle) {
+			true => ggrs::PlayerType::Local,
+			false => ggrs::PlayerType::Remote(remotes.next().unwrap()),
+		};
+
+		session = session.add_player(player, handle)?;
+	}
+
+	// Spectators' handles have to come after every player's
+	if let Some(spectator_port) = conf.spectator_port {
+		let spectator = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, spectator_port));
+
+		session = session.add_player(ggrs::PlayerType::Spectator(spectator), num_players)?;
+	}
+
+	Ok(session.start_p2p_session(local_sock)?)
+}
+
+/// A session with every player on this machine, for carrying on after the
+/// other players have left. Only the local player's handle gets any input
+pub fn init_solo_net(num_players: usize) -> Result<P2PSession<GGRSConfig>, NetError> {
+	// Nothing gets sent, so any port will do
+	let local_sock = UdpNonBlockingSocket::bind_to_port(0).map_err(|e| NetError::Bind(0, e))?;
+
+	let session = (0..num_players).try_fold(
+		SessionBuilder::<GGRSConfig>::new()
+			.with_num_players(num_players)
+			.with_fps(FPS as usize)
+			.unwrap(),
+		|session, handle| session.add_player(ggrs::PlayerType::Local, handle),
+	)?;
+
+	Ok(session
+		.with_sparse_saving_mode(true)
+		.start_p2p_session(local_sock)?)
+}
@@ -0,0 +1,88 @@
+use ggrs::{Message, NonBlockingSocket, P2PSession, SessionBuilder};
+use matchbox_socket::WebRtcSocket;
+
+use super::{GGRSConfig, NetError};
+use crate::FPS;
+
+/// Stands in for a socket in singleplayer, where there's nobody to talk to
+struct OfflineSocket;
+
+impl NonBlockingSocket<String> for OfflineSocket {
+	fn send_to(&mut self, _msg: &Message, _addr: &String) {}
+
+	fn receive_all_messages(&mut self) -> Vec<(String, Message)> { Vec::new() }
+}
+
+pub fn init_net(conf: &GGRSConfig) -> Result<Lobby, NetError> { Ok(Lobby::new(conf)) }
+
+/// A session with every player on this machine, for carrying on after the
+/// other players have left. Only the local player's handle gets any input
+pub fn init_solo_net(num_players: usize) -> Result<P2PSession<GGRSConfig>, NetError> {
+	let session = (0..num_players).try_fold(
+		SessionBuilder::<GGRSConfig>::new()
+			.with_num_players(num_players)
+			.with_fps(FPS as usize)
+			.unwrap(),
+		|session, handle| session.add_player(ggrs::PlayerType::Local, handle),
+	)?;
+
+	Ok(session
+		.with_sparse_saving_mode(true)
+		.start_p2p_session(OfflineSocket)?)
+}
+
+/// Waits for every peer to join the matchbox room, since a session can't be
+/// started until all of their IDs are known
+pub struct Lobby {
+	socket: Option<WebRtcSocket>,
+	conf: GGRSConfig,
+}
+
+impl Lobby {
+	pub fn new(conf: &GGRSConfig) -> Self {
+		let socket = conf.multiplayer.then(|| {
+			let (socket, message_loop) = WebRtcSocket::new(conf.room_url.as_str());
+			wasm_bindgen_futures::spawn_local(message_loop);
+
+			socket
+		});
+
+		Self {
+			socket,
+			conf: conf.clone(),
+		}
+	}
+
+	/// Returns the session once everyone has connected
+	pub fn try_start(&mut self) -> Option<P2PSession<GGRSConfig>> {
+		let session = self.conf.session_builder();
+
+		let Some(mut socket) = self.socket.take() else {
+			return Some(
+				session
+					.add_player(ggrs::PlayerType::Local, 0)
+					.unwrap()
+					.start_p2p_session(OfflineSocket)
+					.unwrap(),
+			);
+		};
+
+		socket.accept_new_connections();
+
+		if socket.connected_peers().len() + 1 < self.conf.num_players() {
+			self.socket = Some(socket);
+			return None;
+		}
+
+		// Players are sorted by ID, so every peer agrees on the handles
+		let session = socket
+			.players()
+			.into_iter()
+			.enumerate()
+			.fold(session, |session, (handle, player)| {
+				session.add_player(player, handle).unwrap()
+			});
+
+		Some(session.start_p2p_session(socket).unwrap())
+	}
+}
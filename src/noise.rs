@@ -0,0 +1,45 @@
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+use crate::map::TILE_SIZE;
+
+/// How far each kind of noise carries, in tiles
+pub const ATTACK_NOISE: f32 = 5.0;
+const DOOR_NOISE: f32 = 6.0;
+const SCRATCH_NOISE: f32 = 4.0;
+const FIGHT_NOISE: f32 = 8.0;
+const CRATE_NOISE: f32 = 8.0;
+const BREACH_NOISE: f32 = 12.0;
+
+/// Something monsters can hear, through walls and round corners
+#[derive(Copy, Clone, Debug)]
+pub struct Noise {
+	pub pos: Vec2,
+	/// In tiles
+	pub radius: f32,
+}
+
+impl Noise {
+	/// How loud what happened was, if it made any noise at all
+	pub fn from_event(event: &GameEvent) -> Option<Self> {
+		let (pos, radius) = match event {
+			GameEvent::Noise { pos, radius } => (*pos, *radius),
+			GameEvent::DamageDealt { pos, .. } => (*pos, FIGHT_NOISE),
+			GameEvent::DoorOpened { tile_pos } => (tile_center(*tile_pos), DOOR_NOISE),
+			GameEvent::DoorBroken { tile_pos } => (tile_center(*tile_pos), BREACH_NOISE),
+			GameEvent::DoorScratched { tile_pos } => (tile_center(*tile_pos), SCRATCH_NOISE),
+			GameEvent::CrateSmashed { tile_pos, .. } => (tile_center(*tile_pos), CRATE_NOISE),
+			_ => return None,
+		};
+
+		Some(Self { pos, radius })
+	}
+
+	pub fn heard_from(&self, pos: Vec2) -> bool {
+		self.pos.distance(pos) <= self.radius * TILE_SIZE as f32
+	}
+}
+
+pub fn tile_center(tile_pos: IVec2) -> Vec2 {
+	(tile_pos.as_vec2() + Vec2::splat(0.5)) * TILE_SIZE as f32
+}
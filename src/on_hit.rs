@@ -0,0 +1,60 @@
+use serde::Deserialize;
+
+use crate::enchantments::{Enchantable, Enchantment};
+use crate::events::GameEvent;
+use crate::map::FloorInfo;
+use crate::math::AsPolygon;
+use crate::player::{DamageInfo, Player};
+use crate::rng::Rng;
+
+/// Something extra that happens whenever a player's attack hurts a monster,
+/// on top of the damage. Items list theirs in `on_hit`
+#[derive(Clone, Deserialize)]
+pub enum OnHit {
+	/// Heals the attacker by this percent of the damage dealt, rounded down
+	Lifesteal(u16),
+	/// Enchants whatever was hit, this percent of the time
+	Enchant {
+		enchantment: Enchantment,
+		chance: u32,
+	},
+}
+
+/// Hurts a monster on behalf of a player, then runs the on-hit effects of
+/// everything they're holding. Every player attack that hurts monsters goes
+/// through here, so attacks never need to know about on-hit effects
+pub fn hit_monster(
+	index: usize, damage_info: DamageInfo, floor_info: &mut FloorInfo, players: &mut [Player],
+	events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	let attacker = damage_info.player;
+	let monster = &mut floor_info.monsters[index];
+	let (health_before, _) = monster.health();
+
+	monster.take_damage(damage_info, &floor_info.floor, events);
+	// After any knockback, so companions can find it again
+	let monster_pos = monster.center();
+
+	// What actually got through, after resistances and overkill
+	let dealt = health_before.saturating_sub(monster.health().0);
+
+	let Some(player) = players.get_mut(attacker) else {
+		return;
+	};
+
+	player.last_hit = Some(monster_pos);
+
+	let effects: Vec<OnHit> = player.inventory.on_hit().cloned().collect();
+
+	effects.into_iter().for_each(|effect| match effect {
+		OnHit::Lifesteal(percent) => player.heal(dealt.saturating_mul(percent) / 100),
+		OnHit::Enchant {
+			enchantment,
+			chance,
+		} => {
+			if rng.gen_range(0, 100) < chance {
+				floor_info.monsters[index].apply_enchantment(enchantment);
+			}
+		},
+	});
+}
@@ -0,0 +1,108 @@
+#[cfg(feature = "native")]
+use std::fs::{self, File};
+#[cfg(feature = "native")]
+use std::io::{self, BufWriter};
+#[cfg(feature = "native")]
+use std::path::PathBuf;
+#[cfg(feature = "native")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use macroquad::prelude::*;
+
+use crate::map::{Floor, TILE_SIZE};
+
+#[cfg(feature = "native")]
+const SCREENSHOT_DIR: &str = "screenshots";
+// How fast the camera moves, in pixels per second
+const CAMERA_SPEED: f32 = 400.0;
+
+/// A free camera for looking around the floor with the HUD hidden. It can only
+/// go over tiles someone has already seen, so it can't be used to scout ahead
+pub struct PhotoMode {
+	pub camera_pos: Vec2,
+}
+
+impl PhotoMode {
+	pub fn new(camera_pos: Vec2) -> Self { Self { camera_pos } }
+
+	pub fn update(&mut self, floor: &Floor) {
+		let mut direction = Vec2::ZERO;
+
+		if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
+			direction.y -= 1.0;
+		}
+
+		if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
+			direction.y += 1.0;
+		}
+
+		if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
+			direction.x -= 1.0;
+		}
+
+		if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
+			direction.x += 1.0;
+		}
+
+		self.camera_pos += direction.normalize_or_zero() * CAMERA_SPEED * get_frame_time();
+
+		if let Some((min, max)) = explored_bounds(floor) {
+			self.camera_pos = self.camera_pos.clamp(min, max);
+		}
+	}
+}
+
+/// The smallest rectangle around every tile that's been seen
+fn explored_bounds(floor: &Floor) -> Option<(Vec2, Vec2)> {
+	floor
+		.objects()
+		.iter()
+		.filter(|obj| obj.has_been_seen())
+		.map(|obj| (obj.tile_pos() * IVec2::splat(TILE_SIZE as i32)).as_vec2())
+		.fold(None, |bounds, pos| match bounds {
+			Some((min, max)) => Some((pos.min(min), pos.max(max))),
+			None => Some((pos, pos)),
+		})
+		.map(|(min, max)| (min, max + Vec2::splat(TILE_SIZE as f32)))
+}
+
+/// Saves whatever's on screen as a PNG, with the seed and floor stamped into
+/// it, so the same layout can be generated again
+#[cfg(feature = "native")]
+pub fn save_screenshot(seed: u64, floor: usize) -> io::Result<PathBuf> {
+	let image = get_screen_data();
+	let (width, height) = (image.width as usize, image.height as usize);
+
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+
+	fs::create_dir_all(SCREENSHOT_DIR)?;
+	let path = PathBuf::from(SCREENSHOT_DIR)
+		.join(format!("roguelite-{seed}-floor{floor}-{timestamp}.png"));
+
+	let mut encoder = png::Encoder::new(
+		BufWriter::new(File::create(&path)?),
+		width as u32,
+		height as u32,
+	);
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(png::BitDepth::Eight);
+	encoder.add_text_chunk("Software".to_string(), "Roguelite".to_string())?;
+	encoder.add_text_chunk("Seed".to_string(), seed.to_string())?;
+	encoder.add_text_chunk("Floor".to_string(), floor.to_string())?;
+
+	// The screen is read bottom row first
+	let rows: Vec<u8> = image
+		.bytes
+		.chunks_exact(width * 4)
+		.rev()
+		.flatten()
+		.copied()
+		.collect();
+
+	encoder.write_header()?.write_image_data(&rows)?;
+
+	Ok(path)
+}
@@ -5,29 +5,88 @@ use serde::{Deserialize, Serialize};
 
 use crate::attacks::*;
 use crate::draw::Drawable;
-use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
-use crate::items::ItemType::{self, *};
-use crate::items::{attack_with_item, ItemInfo};
-use crate::map::{pos_to_tile, Floor, FloorInfo};
-use crate::math::{aabb_collision, easy_polygon, AsPolygon, Polygon};
+use crate::enchantments::{retain_in_order, Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::{
+	attack_with_item,
+	EquipmentSlot,
+	ItemAttack,
+	ItemId,
+	ItemInfo,
+	ItemType,
+	Wearable,
+};
+use crate::localization::{tr, tr_args};
+use crate::map::{pos_to_tile, Floor, FloorInfo, TILE_SIZE};
+use crate::math::{aabb_collision, easy_polygon, serialize_sorted, AsPolygon, Polygon};
+use crate::monsters::MonsterKind;
+use crate::on_hit::OnHit;
+use crate::resistances::Resistance;
+use crate::rng::Rng;
 use macroquad::prelude::*;
 
 pub const PLAYER_SIZE: f32 = 12.0;
+/// How wide an arc in front of the player a raised shield covers, in radians
+pub const BLOCK_ARC: f32 = std::f32::consts::FRAC_PI_2 * 1.5;
+/// Blocked hits only do this fraction of their damage
+const BLOCKED_DAMAGE_DIVISOR: u16 = 4;
+/// Frames it takes to fully wind up an attack
+const FULL_CHARGE: u16 = 60;
+/// How many times harder a fully wound up attack hits
+const MAX_POWER: u16 = 3;
+/// Frames a rogue has to go unseen before they're hidden
+const STEALTH_TIME: u16 = 3 * 60;
+/// Monsters further away than this many tiles can't pick out a rogue
+const STEALTH_SIGHT_RANGE: f32 = 10.0;
+/// How many times harder a melee or knife hit from hiding is
+const BACKSTAB_POWER: u16 = 3;
+/// Frames a downed player has before they bleed out
+const BLEED_OUT_TIME: u16 = 30 * 60;
+/// Frames a teammate has to keep reviving a downed player for
+const REVIVE_TIME: u16 = 3 * 60;
+/// How close a teammate has to stand to revive someone, in tiles
+const REVIVE_RANGE: f32 = 1.5;
+/// How long nothing can hurt a player for after they've been revived
+const REVIVE_INVINCIBILITY: u16 = 60;
+/// Players stop earning XP once they get this far
+const MAX_LEVEL: u32 = 20;
+/// How many boons a player gets to pick from each time they level up
+const BOONS_OFFERED: usize = 3;
+const MAX_HP_BOON: u16 = 3;
+const MAX_MP_BOON: u16 = 2;
+const SPEED_BOON: f32 = 0.1;
+/// Speed boons stop being offered once a player's this fast
+const MAX_SPEED: f32 = 2.8;
+const WILLPOWER_BOON: u16 = 2;
 
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PlayerClass {
 	Warrior,
 	Wizard,
 	Rogue,
+	Cleric,
+	Ranger,
 }
 
 impl Display for PlayerClass {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_str(match self {
-			PlayerClass::Warrior => "Warrior",
-			PlayerClass::Wizard => "Wizard",
-			PlayerClass::Rogue => "Rogue",
-		})
+		f.write_str(&tr(match self {
+			PlayerClass::Warrior => "class-warrior",
+			PlayerClass::Wizard => "class-wizard",
+			PlayerClass::Rogue => "class-rogue",
+			PlayerClass::Cleric => "class-cleric",
+			PlayerClass::Ranger => "class-ranger",
+		}))
+	}
+}
+
+impl PlayerClass {
+	/// Spells the class can pick up as it levels, in the order they're learned
+	fn learnable_spells(self) -> &'static [Spell] {
+		match self {
+			PlayerClass::Wizard => &[Spell::Blink, Spell::FrostNova, Spell::SummonSkeleton],
+			_ => &[],
+		}
 	}
 }
 
@@ -40,6 +99,8 @@ impl TryFrom<&str> for PlayerClass {
 		match value.to_lowercase().as_str() {
 			"warrior" => Ok(PlayerClass::Warrior),
 			"wizard" => Ok(PlayerClass::Wizard),
+			"cleric" => Ok(PlayerClass::Cleric),
+			"ranger" => Ok(PlayerClass::Ranger),
 			_ => Err(PlayerClassError),
 		}
 	}
@@ -56,17 +117,49 @@ struct PointInfo {
 	time_til_regen: u16,
 }
 
-#[derive(Copy, Clone, Serialize)]
+#[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Spell {
 	BlindingLight,
+	Blink,
+	Fireball,
+	FrostNova,
 	MagicMissile,
+	RadiantBurst,
+	SummonSkeleton,
 }
 
 impl Display for Spell {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_str(match self {
-			Spell::BlindingLight => "Blinding Light",
-			Spell::MagicMissile => "Magic Missile",
+		f.write_str(&tr(match self {
+			Spell::BlindingLight => "spell-blinding-light",
+			Spell::Blink => "spell-blink",
+			Spell::Fireball => "spell-fireball",
+			Spell::FrostNova => "spell-frost-nova",
+			Spell::MagicMissile => "spell-magic-missile",
+			Spell::RadiantBurst => "spell-radiant-burst",
+			Spell::SummonSkeleton => "spell-summon-skeleton",
+		}))
+	}
+}
+
+/// Something a player can pick to get stronger when they level up
+#[derive(Copy, Clone, PartialEq, Serialize)]
+pub enum Boon {
+	MaxHp,
+	MaxMp,
+	Speed,
+	Willpower,
+	Spell(Spell),
+}
+
+impl Display for Boon {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&match self {
+			Boon::MaxHp => tr_args("boon-max-hp", [("amount", MAX_HP_BOON.into())]),
+			Boon::MaxMp => tr_args("boon-max-mp", [("amount", MAX_MP_BOON.into())]),
+			Boon::Speed => tr("boon-speed"),
+			Boon::Willpower => tr_args("boon-willpower", [("amount", WILLPOWER_BOON.into())]),
+			Boon::Spell(spell) => tr_args("boon-spell", [("spell", spell.to_string().into())]),
 		})
 	}
 }
@@ -87,22 +180,176 @@ pub enum SelectionType {
 pub struct PlayerInventory {
 	primary_item: Option<ItemInfo>,
 	secondary_item: Option<ItemInfo>,
+	/// Gear being worn, indexed by slot
+	equipment: [Option<ItemInfo>; EquipmentSlot::ALL.len()],
 	selected_item: Option<ItemSelectedInfo>,
 	pub items: Vec<ItemInfo>,
 }
 
 impl PlayerInventory {
+	pub fn contains(&self, item_type: ItemType) -> bool {
+		self.items
+			.iter()
+			.chain(self.primary_item.iter())
+			.chain(self.secondary_item.iter())
+			.chain(self.equipment.iter().flatten())
+			.any(|item| item.item_type == item_type)
+	}
+
+	/// What's being worn in a slot
+	pub fn equipped(&self, slot: EquipmentSlot) -> Option<&ItemInfo> {
+		self.equipment[slot as usize].as_ref()
+	}
+
+	/// What everything being worn does
+	fn wearables(&self) -> impl Iterator<Item = Wearable> + '_ {
+		self.equipment
+			.iter()
+			.flatten()
+			.filter_map(|item| item.item_type.definition()?.wearable)
+	}
+
+	/// Damage taken off every hit by what's being worn
+	fn armor(&self) -> u16 { self.wearables().map(|wearable| wearable.armor).sum() }
+
+	/// How much faster or slower what's being worn makes the player, as a
+	/// fraction of their speed
+	fn speed_bonus(&self) -> f32 { self.wearables().map(|wearable| wearable.speed).sum() }
+
+	/// How much faster what's being worn brings MP back, as a percent
+	fn mp_regen_bonus(&self) -> u16 { self.wearables().map(|wearable| wearable.mp_regen).sum() }
+
+	/// The first thing in the pack that could be worn, for players without a
+	/// mouse to pick with
+	pub fn first_wearable(&self) -> Option<usize> {
+		self.items
+			.iter()
+			.position(|item| wearable_slot(item).is_some())
+	}
+
+	/// Puts on the item in the pack at `index`, if it can be worn. Whatever was
+	/// in its slot goes back in the pack where it was
+	pub fn equip(&mut self, index: usize) {
+		let Some(slot) = self.items.get(index).and_then(wearable_slot) else {
+			return;
+		};
+
+		let item = self.items.remove(index);
+
+		if let Some(worn) = self.equipment[slot as usize].replace(item) {
+			self.items.insert(index, worn);
+		}
+	}
+
+	/// How many of an item the player's carrying, counting every stack
+	pub fn count(&self, item_type: ItemType) -> u32 {
+		self.items
+			.iter()
+			.chain(self.primary_item.iter())
+			.chain(self.secondary_item.iter())
+			.filter(|item| item.item_type == item_type)
+			.map(|item| item.stack_count.unwrap_or(1) as u32)
+			.sum()
+	}
+
+	/// Uses up one of an item the player's carrying, out of their pack first,
+	/// then their hands
+	fn take_one(&mut self, item_type: ItemType) {
+		let used_up = |item: &mut ItemInfo| match item.stack_count {
+			Some(count) if count > 1 => {
+				item.stack_count = Some(count - 1);
+				false
+			},
+			_ => true,
+		};
+
+		if let Some(index) = self
+			.items
+			.iter()
+			.position(|item| item.item_type == item_type)
+		{
+			if used_up(&mut self.items[index]) {
+				self.items.remove(index);
+			}
+
+			return;
+		}
+
+		// Empty stacks stay in the player's hands, so more can be picked up
+		// into them, the same as with throwing knives
+		if let Some(item) = [&mut self.primary_item, &mut self.secondary_item]
+			.into_iter()
+			.filter_map(|slot| slot.as_mut())
+			.find(|item| item.item_type == item_type && item.stack_count != Some(0))
+		{
+			item.stack_count = item.stack_count.map(|count| count - 1);
+		}
+	}
+
+	/// Takes an item away entirely, from wherever the player's keeping it
+	pub fn remove(&mut self, item_type: ItemType) {
+		if let Some(index) = self
+			.items
+			.iter()
+			.position(|item| item.item_type == item_type)
+		{
+			self.items.remove(index);
+			return;
+		}
+
+		if let Some(slot) = [&mut self.primary_item, &mut self.secondary_item]
+			.into_iter()
+			.chain(self.equipment.iter_mut())
+			.find(|slot| {
+				slot.as_ref()
+					.is_some_and(|item| item.item_type == item_type)
+			}) {
+			*slot = None;
+		}
+	}
+
+	/// Whatever the items in the player's hands, and the gear they're wearing,
+	/// protect them from
+	fn resistances_to(&self, damage_type: DamageType) -> impl Iterator<Item = Resistance> + '_ {
+		self.primary_item
+			.iter()
+			.chain(self.secondary_item.iter())
+			.chain(self.equipment.iter().flatten())
+			.filter_map(move |item| {
+				item.item_type
+					.definition()?
+					.resistances
+					.to_damage(damage_type)
+			})
+	}
+
+	/// Everything the items in the player's hands do when they hurt a monster
+	pub fn on_hit(&self) -> impl Iterator<Item = &OnHit> {
+		self.primary_item
+			.iter()
+			.chain(self.secondary_item.iter())
+			.filter_map(|item| item.item_type.definition())
+			.flat_map(|definition| definition.on_hit.iter())
+	}
+
 	fn new(primary_item: Option<ItemInfo>, secondary_item: Option<ItemInfo>) -> Self {
 		Self {
 			primary_item,
 			secondary_item,
+			equipment: Default::default(),
 			selected_item: None,
 			items: Vec::new(),
 		}
 	}
 
 	fn add_item(&mut self, new_item: ItemInfo) {
-		if new_item.stack_count.is_some() {
+		let max_stack = new_item
+			.item_type
+			.definition()
+			.and_then(|item| item.max_stack);
+
+		if let Some(max_stack) = max_stack {
+			// Add to an existing stack if there's one with room left
 			if let Some(existing_item) = self
 				.items
 				.iter_mut()
@@ -111,18 +358,85 @@ impl PlayerInventory {
 						.into_iter()
 						.filter_map(|item| item.as_mut()),
 				)
-				.find(|item| item.item_type == new_item.item_type)
-			{
-				existing_item.stack_count = Some(existing_item.stack_count.unwrap() + 1);
+				.find(|item| {
+					item.item_type == new_item.item_type &&
+						item.stack_count.unwrap_or(0) < max_stack
+				}) {
+				existing_item.stack_count = Some(existing_item.stack_count.unwrap_or(0) + 1);
+				return;
 			}
-		} else {
-			self.items.push(new_item);
+		}
+
+		// Wands go straight into an empty off hand, so anyone can cast from one
+		let is_wand = new_item
+			.item_type
+			.definition()
+			.is_some_and(|item| matches!(item.attack, Some(ItemAttack::Wand(_))));
+
+		if is_wand && self.secondary_item.is_none() {
+			self.secondary_item = Some(new_item);
+			return;
+		}
+
+		// and gear straight into an empty slot
+		if let Some(slot) = wearable_slot(&new_item) {
+			if self.equipment[slot as usize].is_none() {
+				self.equipment[slot as usize] = Some(new_item);
+				return;
+			}
+		}
+
+		self.items.push(new_item);
+	}
+
+	/// Takes one of whatever the player would throw, which is the selected
+	/// item if there is one, or their primary item otherwise
+	fn take_item_to_throw(&mut self) -> Option<ItemInfo> {
+		let selected_index = self.selected_item.as_ref().map(|info| info.index);
+
+		let item = match selected_index {
+			Some(index) => self.items.get_mut(index)?,
+			None => self.primary_item.as_mut()?,
+		};
+
+		match item.stack_count {
+			Some(0) => None,
+			Some(count) if count > 1 => {
+				item.stack_count = Some(count - 1);
+
+				let mut thrown = item.clone();
+				thrown.stack_count = Some(1);
+				Some(thrown)
+			},
+			_ => match selected_index {
+				Some(index) => {
+					self.selected_item = None;
+					Some(self.items.remove(index))
+				},
+				None => self.primary_item.take(),
+			},
 		}
 	}
 }
 
+/// Whether a player's still in the fight
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum LifeState {
+	Up,
+	/// Out of HP, but a teammate can still get them back up
+	Downed {
+		/// Frames left before they bleed out
+		bleed_out: u16,
+		/// Frames a teammate's spent reviving them. Starts over if they stop
+		revive_progress: u16,
+	},
+	/// Bled out, and won't be getting back up this run
+	Dead,
+}
+
 #[derive(Clone, Serialize)]
 pub struct Player {
+	class: PlayerClass,
 	pub angle: f32,
 	pub pos: Vec2,
 	speed: f32,
@@ -131,9 +445,17 @@ pub struct Player {
 	/// The ability to resist magical enchantments
 	willpower: u16,
 	invincibility_frames: u16,
+	/// Frames left with a shield raised
+	pub blocking: u16,
+	/// Frames left that the player can't move or attack for
+	pub rooted: u16,
 
 	pub primary_cooldown: u16,
 	pub secondary_cooldown: u16,
+	/// Frames the primary attack's been wound up for
+	charge: u16,
+	/// Frames since a monster last laid eyes on the player
+	unseen_for: u16,
 
 	spells: Vec<Spell>,
 
@@ -142,32 +464,61 @@ pub struct Player {
 
 	pub xp: u32,
 	pub level: u32,
+	/// Level ups the player hasn't picked a boon for yet
+	unspent_levels: u32,
+	/// What the player can pick from for their next unspent level up
+	boons_offered: Vec<Boon>,
 
 	pub gold: u32,
 	in_inventory: bool,
 	pub inventory: PlayerInventory,
 
+	#[serde(serialize_with = "serialize_sorted")]
 	enchantments: HashMap<EnchantmentKind, (Enchantment, u16)>,
+	/// Enchantments that never wear off
+	blessings: Vec<Enchantment>,
+	life: LifeState,
+	/// Where the last monster the player hurt was, for their companion to go
+	/// after
+	pub last_hit: Option<Vec2>,
+	/// Whether the player's companion has been killed on this floor
+	pub companion_lost: bool,
 }
 
 impl Player {
 	pub fn new(class: PlayerClass, pos: Vec2) -> Self {
 		let primary_item = Some(match class {
-			PlayerClass::Warrior => ItemInfo::new(ShortSword, None),
-			PlayerClass::Wizard => ItemInfo::new(WizardGlove, None),
+			PlayerClass::Warrior => ItemInfo::new(ItemType::from_key("short_sword"), None),
+			PlayerClass::Wizard => ItemInfo::new(ItemType::from_key("wizard_glove"), None),
 			PlayerClass::Rogue => {
-				let mut item = ItemInfo::new(ThrowingKnife, None);
+				let mut item = ItemInfo::new(ItemType::from_key("throwing_knife"), None);
 				item.stack_count = Some(5);
 
 				item
 			},
+			PlayerClass::Cleric => ItemInfo::new(ItemType::from_key("mace"), None),
+			PlayerClass::Ranger => ItemInfo::new(ItemType::from_key("bow"), None),
 		});
 
 		let secondary_item = match class {
-			PlayerClass::Wizard => Some(ItemInfo::new(WizardsDagger, None)),
-			_ => None,
+			PlayerClass::Warrior => Some(ItemInfo::new(ItemType::from_key("war_axe"), None)),
+			PlayerClass::Wizard => Some(ItemInfo::new(ItemType::from_key("wizards_dagger"), None)),
+			PlayerClass::Rogue => Some(ItemInfo::new(ItemType::from_key("knife_bandolier"), None)),
+			PlayerClass::Cleric => Some(ItemInfo::new(ItemType::from_key("holy_symbol"), None)),
+			// Leaves a hand free for a wand
+			PlayerClass::Ranger => None,
 		};
 
+		let mut inventory = PlayerInventory::new(primary_item, secondary_item);
+
+		// A bow's useless without something to shoot
+		if class == PlayerClass::Ranger {
+			let mut arrows = ItemInfo::new(ItemType::from_key("arrow"), None);
+			arrows.stack_count = Some(30);
+
+			inventory.add_item(arrows);
+		}
+
 		let hp = match class {
 			PlayerClass::Wizard => PointInfo {
 				points: 20,
@@ -191,6 +542,20 @@ impl Player {
 				regen_rate: 15 * 60,
 				..Default::default()
 			},
+			PlayerClass::Cleric => PointInfo {
+				points: 25,
+				max_points: 25,
+				// 12 seconds
+				regen_rate: 12 * 60,
+				..Default::default()
+			},
+			PlayerClass::Ranger => PointInfo {
+				points: 22,
+				max_points: 22,
+				// 15 seconds
+				regen_rate: 15 * 60,
+				..Default::default()
+			},
 		};
 
 		let mp = match class {
@@ -214,18 +579,36 @@ impl Player {
 				regen_rate: 9 * 60,
 				..Default::default()
 			},
+			PlayerClass::Cleric => PointInfo {
+				points: 5,
+				max_points: 5,
+				// 8 seconds
+				regen_rate: 8 * 60,
+				..Default::default()
+			},
+			PlayerClass::Ranger => PointInfo {
+				points: 4,
+				max_points: 4,
+				regen_rate: 9 * 60,
+				..Default::default()
+			},
 		};
 
 		let willpower = match class {
 			PlayerClass::Wizard => 20,
 			PlayerClass::Warrior => 10,
 			PlayerClass::Rogue => 15,
+			PlayerClass::Cleric => 18,
+			PlayerClass::Ranger => 12,
 		};
 
 		let spells = match class {
 			PlayerClass::Warrior => Vec::new(),
 			PlayerClass::Rogue => Vec::new(),
-			PlayerClass::Wizard => vec![Spell::MagicMissile, Spell::BlindingLight],
+			PlayerClass::Ranger => Vec::new(),
+			PlayerClass::Cleric => vec![Spell::RadiantBurst],
+			// The rest are learned by leveling up
+			PlayerClass::Wizard => vec![Spell::MagicMissile, Spell::BlindingLight, Spell::Fireball],
 		};
 
 		Self {
@@ -234,47 +617,113 @@ impl Player {
 			speed: 2.2,
 			primary_cooldown: 0,
 			secondary_cooldown: 0,
+			charge: 0,
+			unseen_for: 0,
 			hp,
 			mp,
 			willpower,
 			invincibility_frames: 0,
+			blocking: 0,
+			rooted: 0,
 			spells,
 			changing_spell: false,
 			time_til_change_spell: 0,
 			xp: 0,
 			level: 0,
+			unspent_levels: 0,
+			boons_offered: Vec::new(),
 			gold: 0,
 			in_inventory: false,
-			inventory: PlayerInventory::new(primary_item, secondary_item),
+			inventory,
 			enchantments: HashMap::new(),
+			blessings: Vec::new(),
+			life: LifeState::Up,
+			last_hit: None,
+			companion_lost: false,
+			class,
 		}
 	}
 
-	pub fn add_xp(&mut self, xp: u32) {
+	/// Returns whether the player leveled up. Enough XP at once can level them
+	/// up more than once, and each level up gets its own boon
+	pub fn add_xp(&mut self, xp: u32) -> bool {
+		let level_before = self.level;
 		self.xp += xp;
 
-		let xp_to_level_up = match self.level {
-			0 => 14,
-			1 => 16,
-			_ => todo!(),
-		};
+		loop {
+			let Some(xp_to_level_up) = xp_to_level_up(self.level) else {
+				self.xp = 0;
+				break;
+			};
 
-		if self.xp >= xp_to_level_up {
-			self.xp = 0;
+			if self.xp < xp_to_level_up {
+				break;
+			}
+
+			self.xp -= xp_to_level_up;
 			self.level += 1;
+			self.unspent_levels += 1;
 
-			self.mp.max_points += 2;
-			self.mp.points += 2;
+			log::info!("Leveled up to level {}", self.level);
+		}
+
+		self.level > level_before
+	}
 
-			self.hp.max_points += 1;
-			self.hp.points += 1;
+	pub fn boons_offered(&self) -> &[Boon] { &self.boons_offered }
 
-			println!("Leveled up!");
+	/// Everything the player could be offered on leveling up. Only the next
+	/// spell the class has to learn is ever offered
+	fn available_boons(&self) -> Vec<Boon> {
+		let mut boons = vec![Boon::MaxHp, Boon::MaxMp, Boon::Willpower];
+
+		if self.speed < MAX_SPEED {
+			boons.push(Boon::Speed);
+		}
+
+		if let Some(spell) = self
+			.class
+			.learnable_spells()
+			.iter()
+			.find(|spell| !self.spells.contains(spell))
+		{
+			boons.push(Boon::Spell(*spell));
+		}
+
+		boons
+	}
+
+	/// Takes one of the boons offered, by its index. Anything else that was
+	/// offered is gone
+	pub fn choose_boon(&mut self, choice: usize) {
+		let Some(boon) = self.boons_offered.get(choice).copied() else {
+			return;
+		};
+
+		match boon {
+			Boon::MaxHp => {
+				self.hp.max_points += MAX_HP_BOON;
+				self.hp.points += MAX_HP_BOON;
+			},
+			Boon::MaxMp => {
+				self.mp.max_points += MAX_MP_BOON;
+				self.mp.points += MAX_MP_BOON;
+			},
+			Boon::Speed => self.speed += SPEED_BOON,
+			Boon::Willpower => self.willpower += WILLPOWER_BOON,
+			Boon::Spell(spell) => self.spells.push(spell),
 		}
+
+		self.boons_offered.clear();
+		self.unspent_levels -= 1;
 	}
 
 	pub fn inventory(&self) -> &PlayerInventory { &self.inventory }
 
+	pub fn in_inventory(&self) -> bool { self.in_inventory }
+
+	pub fn give_item(&mut self, item: ItemInfo) { self.inventory.add_item(item); }
+
 	pub fn set_selected_item(&mut self, i: Option<ItemSelectedInfo>) {
 		self.inventory.selected_item = i;
 	}
@@ -298,6 +747,126 @@ impl Player {
 	#[inline]
 	pub fn hp(&self) -> u16 { self.hp.points }
 
+	#[inline]
+	pub fn max_hp(&self) -> u16 { self.hp.max_points }
+
+	pub fn set_hp(&mut self, hp: u16) { self.hp.points = hp.min(self.hp.max_points); }
+
+	pub fn life(&self) -> LifeState { self.life }
+
+	pub fn class(&self) -> PlayerClass { self.class }
+
+	pub fn stunned(&self) -> bool {
+		self.enchantments.contains_key(&EnchantmentKind::Stunned) ||
+			self.enchantments.contains_key(&EnchantmentKind::Frozen)
+	}
+
+	/// Whether a hit knocking the player towards `damage_direction` lands on
+	/// their raised shield
+	pub fn blocks(&self, damage_direction: f32) -> bool {
+		let facing = Vec2::new(self.angle.cos(), self.angle.sin());
+		let towards_source = -Vec2::new(damage_direction.cos(), damage_direction.sin());
+
+		self.blocking > 0 && facing.dot(towards_source) >= (BLOCK_ARC * 0.5).cos()
+	}
+
+	/// Whether the primary attack hits harder for being held down first. Only
+	/// slashes and magic missiles can be
+	pub fn can_wind_up(&self) -> bool {
+		let attack = self
+			.inventory
+			.primary_item
+			.as_ref()
+			.and_then(|item| item.item_type.definition())
+			.and_then(|item| item.attack);
+
+		match attack {
+			Some(ItemAttack::Slash) | Some(ItemAttack::Smash) => true,
+			Some(ItemAttack::Spell) => matches!(self.spells.first(), Some(Spell::MagicMissile)),
+			_ => false,
+		}
+	}
+
+	pub fn fully_charged(&self) -> bool { self.charge >= FULL_CHARGE }
+
+	/// How many times harder the next attack hits, from 1 when it hasn't been
+	/// wound up at all to `MAX_POWER` once it's fully charged
+	pub fn charge_power(&self) -> u16 { 1 + self.charge * (MAX_POWER - 1) / FULL_CHARGE }
+
+	/// Rogues that have gone unseen for long enough aren't noticed by monsters
+	/// until they strike or get hurt
+	pub fn hidden(&self) -> bool {
+		self.class == PlayerClass::Rogue && self.unseen_for >= STEALTH_TIME
+	}
+
+	/// What the player's bow shoots, if they're holding one, and how much of
+	/// it they've got left
+	pub fn ammo(&self) -> Option<(ItemType, u32)> {
+		let ammo = [&self.inventory.primary_item, &self.inventory.secondary_item]
+			.into_iter()
+			.flatten()
+			.find_map(|item| ammo_for(item.item_type))?;
+
+		Some((ammo, self.inventory.count(ammo)))
+	}
+
+	/// Whether monsters can spot the player, or be woken up by them
+	pub fn noticeable(&self) -> bool { self.hp() > 0 && !self.hidden() }
+
+	/// How many times harder the next melee or knife hit is, for striking from
+	/// hiding
+	pub fn backstab_power(&self) -> u16 {
+		match self.hidden() {
+			true => BACKSTAB_POWER,
+			false => 1,
+		}
+	}
+
+	/// Pays HP for something, as long as it won't kill the player
+	pub fn spend_hp(&mut self, amount: u16) -> bool {
+		match self.hp.points > amount {
+			true => {
+				self.hp.points -= amount;
+				true
+			},
+			false => false,
+		}
+	}
+
+	/// Raises max HP, or lowers it if `amount` is negative. It never goes below
+	/// 1
+	pub fn change_max_hp(&mut self, amount: i32) {
+		self.hp.max_points = (self.hp.max_points as i32 + amount).max(1) as u16;
+		self.hp.points = self.hp.points.min(self.hp.max_points);
+	}
+
+	/// Raises max MP, or lowers it if `amount` is negative
+	pub fn change_max_mp(&mut self, amount: i32) {
+		self.mp.max_points = (self.mp.max_points as i32 + amount).max(0) as u16;
+		self.mp.points = self.mp.points.min(self.mp.max_points);
+	}
+
+	pub fn bless(&mut self, enchantment: Enchantment) {
+		self.apply_enchantment(enchantment.clone());
+		self.blessings.push(enchantment);
+	}
+
+	pub fn heal(&mut self, amount: u16) {
+		self.hp.points = self
+			.hp
+			.points
+			.saturating_add(amount)
+			.min(self.hp.max_points);
+	}
+
+	pub fn restore_mp(&mut self, amount: u16) {
+		self.mp.points = self
+			.mp
+			.points
+			.saturating_add(amount)
+			.min(self.mp.max_points);
+	}
+
 	#[inline]
 	pub fn mp(&self) -> u16 { self.mp.points }
 
@@ -319,7 +888,7 @@ pub fn move_player(player: &mut Player, angle: f32, speed: Option<Vec2>, floor_i
 				None => 1.0,
 			};
 
-			let speed = player.speed * speed_mul;
+			let speed = player.speed * speed_mul * (1.0 + player.inventory.speed_bonus());
 			Vec2::splat(speed)
 		});
 
@@ -334,33 +903,96 @@ pub fn move_player(player: &mut Player, angle: f32, speed: Option<Vec2>, floor_i
 	}
 }
 
-pub fn damage_player(player: &mut Player, damage: u16, damage_direction: f32, floor: &Floor) {
+pub fn damage_player(
+	player: &mut Player, damage: u16, damage_type: DamageType, damage_direction: f32,
+	source: Option<MonsterKind>, floor: &Floor, events: &mut Vec<GameEvent>,
+) {
 	if player.invincibility_frames > 0 {
 		return;
 	}
 
+	// Traps and the like hurt the same however deep the players are
+	let damage = match source {
+		Some(_) => floor.scaling().damage(damage),
+		None => damage,
+	};
+
+	let damage = player
+		.inventory
+		.resistances_to(damage_type)
+		.fold(damage, |damage, resistance| resistance.apply(damage));
+
+	if damage == 0 {
+		return;
+	}
+
+	let damage = damage.saturating_sub(player.inventory.armor()).max(1);
+
+	player.unseen_for = 0;
+
+	let blocked = player.blocks(damage_direction);
+
+	let damage = match blocked {
+		true => (damage / BLOCKED_DAMAGE_DIVISOR).max(1),
+		false => damage,
+	};
+
 	player.hp.points = player.hp.points.saturating_sub(damage);
 
-	// Have the player "flinch" away from damage
-	move_player(
-		player,
-		damage_direction,
-		Some(Vec2::splat(PLAYER_SIZE)),
-		floor,
-	);
+	events.push(GameEvent::DamageDealt {
+		pos: player.center(),
+		damage,
+		to_player: true,
+		by: None,
+		from: source,
+	});
+
+	// Have the player "flinch" away from damage, unless they caught it on their
+	// shield
+	if !blocked {
+		move_player(
+			player,
+			damage_direction,
+			Some(Vec2::splat(PLAYER_SIZE)),
+			floor,
+		);
+	}
+
+	player.invincibility_frames = damage * 2;
+}
 
-	player.invincibility_frames = (damage as u16) * 2;
+/// How much more XP it takes to get from a level to the next, or None at the
+/// level cap
+fn xp_to_level_up(level: u32) -> Option<u32> {
+	(level < MAX_LEVEL).then(|| 14 + level * 2 + level * level / 2)
+}
+
+/// Rolls which boons each player gets to pick from, for players with a level
+/// up to spend who haven't been offered any yet
+pub fn offer_boons(players: &mut [Player], rng: &mut Rng) {
+	players.iter_mut().for_each(|player| {
+		if player.unspent_levels == 0 || !player.boons_offered.is_empty() {
+			return;
+		}
+
+		let mut boons = player.available_boons();
+		rng.shuffle(&mut boons);
+		boons.truncate(BOONS_OFFERED);
+
+		player.boons_offered = boons;
+	});
 }
 
 pub fn update_cooldowns(players: &mut [Player]) {
-	let regen = |point_info: &mut PointInfo| {
+	let regen = |point_info: &mut PointInfo, regen_bonus: u16| {
 		if point_info.points < point_info.max_points {
 			point_info.time_til_regen = point_info.time_til_regen.saturating_sub(1);
 
 			if point_info.time_til_regen == 0 {
 				point_info.points += 1;
 
-				point_info.time_til_regen = point_info.regen_rate;
+				point_info.time_til_regen =
+					(point_info.regen_rate as u32 * 100 / (100 + regen_bonus as u32)) as u16;
 			}
 		}
 	};
@@ -371,6 +1003,8 @@ pub fn update_cooldowns(players: &mut [Player]) {
 			player.secondary_cooldown = player.secondary_cooldown.saturating_sub(1);
 
 			player.invincibility_frames = player.invincibility_frames.saturating_sub(1);
+			player.blocking = player.blocking.saturating_sub(1);
+			player.rooted = player.rooted.saturating_sub(1);
 
 			player.time_til_change_spell = player.time_til_change_spell.saturating_sub(1);
 
@@ -382,8 +1016,8 @@ pub fn update_cooldowns(players: &mut [Player]) {
 				player.changing_spell = false;
 			}
 
-			regen(&mut player.hp);
-			regen(&mut player.mp);
+			regen(&mut player.hp, 0);
+			regen(&mut player.mp, player.inventory.mp_regen_bonus());
 		}
 	});
 }
@@ -397,7 +1031,7 @@ pub fn player_attack(
 		false => &player.secondary_cooldown,
 	};
 
-	if *cooldown != 0 {
+	if *cooldown != 0 || player.rooted != 0 {
 		return;
 	}
 
@@ -407,7 +1041,11 @@ pub fn player_attack(
 	};
 
 	if let Some(item) = item {
-		if item.item_type == ItemType::ThrowingKnife {
+		if item
+			.item_type
+			.definition()
+			.is_some_and(|item| item.consumed_on_attack)
+		{
 			if item.stack_count.unwrap() > 0 {
 				item.stack_count = Some(item.stack_count.unwrap() - 1);
 			} else {
@@ -415,27 +1053,160 @@ pub fn player_attack(
 			}
 		}
 
-		if let Some(attack) = attack_with_item(item.clone(), player, index, floor, is_primary) {
+		let item = item.clone();
+		let ammo = ammo_for(item.item_type);
+		// Wands cast for free until they run out of charges
+		let charged = item.charges.is_some_and(|charges| charges > 0);
+
+		let new_attacks = attack_with_item(item, player, index, floor, is_primary);
+
+		// Each attack uses up one of whatever the item shoots or throws
+		if ammo.is_some_and(|ammo| player.inventory.count(ammo) < new_attacks.len() as u32) {
+			return;
+		}
+
+		if !new_attacks.is_empty() {
 			let cooldown = match is_primary {
 				true => &mut player.primary_cooldown,
 				false => &mut player.secondary_cooldown,
 			};
 
-			if player.mp.points >= attack.mana_cost() {
-				player.mp.points -= attack.mana_cost();
+			// Several attacks at once cost as much, and take as long to recover
+			// from, as making them one after another
+			let mana_cost = match charged {
+				true => 0,
+				false => new_attacks.iter().map(AttackObj::mana_cost).sum(),
+			};
+
+			if player.mp.points >= mana_cost {
+				player.mp.points -= mana_cost;
 			} else {
 				return;
 			}
 
-			*cooldown = attack.cooldown();
+			*cooldown = new_attacks.iter().map(AttackObj::cooldown).sum();
+
+			if charged {
+				let item = match is_primary {
+					true => &mut player.inventory.primary_item,
+					false => &mut player.inventory.secondary_item,
+				};
+
+				if let Some(charges) = item.as_mut().and_then(|item| item.charges.as_mut()) {
+					*charges -= 1;
+				}
+			}
+
+			// Striking gives a rogue's position away
+			player.unseen_for = 0;
+
+			if let Some(ammo) = ammo {
+				new_attacks
+					.iter()
+					.for_each(|_| player.inventory.take_one(ammo));
+			}
 
-			attacks.push(attack);
+			attacks.extend(new_attacks);
 		}
 	}
 }
 
+/// Rogues sneak out of sight while no hostile monster nearby can see them
+pub fn update_stealth(players: &mut [Player], floor_info: &FloorInfo) {
+	let sight_range = STEALTH_SIGHT_RANGE * TILE_SIZE as f32;
+
+	players
+		.iter_mut()
+		.filter(|player| player.class == PlayerClass::Rogue && player.hp() > 0)
+		.for_each(|player| {
+			let center = player.center();
+
+			let seen = floor_info.monsters.iter().any(|m| {
+				m.hostile() &&
+					m.center().distance(center) <= sight_range &&
+					floor_info.floor.line_of_sight(m.center(), center)
+			});
+
+			player.unseen_for = match seen && !player.hidden() {
+				true => 0,
+				false => (player.unseen_for + 1).min(STEALTH_TIME),
+			};
+		});
+}
+
+/// Winds up the player's primary attack while they hold it down, and lets it
+/// go on the first frame they don't
+pub fn wind_up_attack(
+	player: &mut Player, index: Option<usize>, winding_up: bool, attacks: &mut Vec<AttackObj>,
+	floor: &FloorInfo,
+) {
+	match winding_up {
+		true => {
+			if player.primary_cooldown == 0 {
+				player.charge = (player.charge + 1).min(FULL_CHARGE);
+			}
+		},
+		false => {
+			if player.charge > 0 {
+				player_attack(player, index, attacks, floor, true);
+				player.charge = 0;
+			}
+		},
+	}
+}
+
+/// What an item uses up each time it attacks, if anything
+fn ammo_for(item_type: ItemType) -> Option<ItemType> {
+	let key = item_type.definition()?.ammo.as_deref()?;
+	ItemId::find(key).map(ItemType::Item)
+}
+
+/// Throws the player's selected or held item. Uses up the primary cooldown
+pub fn throw_item(
+	player: &mut Player, index: Option<usize>, attacks: &mut Vec<AttackObj>, floor: &FloorInfo,
+) {
+	if player.primary_cooldown != 0 {
+		return;
+	}
+
+	if let Some(item) = player.inventory.take_item_to_throw() {
+		let attack =
+			ThrownItem::new(player, index, player.angle, &floor.floor, true).with_item(item);
+		player.primary_cooldown = attack.cooldown();
+		player.unseen_for = 0;
+
+		attacks.push(AttackObj::ThrownItem(attack));
+	}
+}
+
+/// How something got hurt, since some monsters and some gear shrug off some
+/// kinds of hits better than others
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub enum DamageType {
+	Slash,
+	Pierce,
+	Blunt,
+	Magic,
+	Fire,
+	Ice,
+}
+
+impl DamageType {
+	pub fn name(&self) -> String {
+		tr(match self {
+			DamageType::Slash => "damage-slash",
+			DamageType::Pierce => "damage-pierce",
+			DamageType::Blunt => "damage-blunt",
+			DamageType::Magic => "damage-magic",
+			DamageType::Fire => "damage-fire",
+			DamageType::Ice => "damage-ice",
+		})
+	}
+}
+
 pub struct DamageInfo {
 	pub damage: u16,
+	pub damage_type: DamageType,
 	pub direction: f32,
 	pub player: usize,
 }
@@ -448,6 +1219,7 @@ pub enum DoorInteraction {
 
 pub fn interact_with_door<A: AsPolygon>(
 	entity: &A, door_interaction: DoorInteraction, floor_info: &mut FloorInfo,
+	events: &mut Vec<GameEvent>,
 ) {
 	// First, see if the player is in contact with a door
 	let entity_tile_pos = pos_to_tile(entity);
@@ -505,6 +1277,8 @@ pub fn interact_with_door<A: AsPolygon>(
 		});
 
 	if let Some(door_obj) = door {
+		let was_open = door_obj.door().unwrap().is_open;
+
 		match door_interaction {
 			DoorInteraction::Opening => door_obj.open_door(),
 			DoorInteraction::Closing => door_obj.close_door(),
@@ -513,9 +1287,228 @@ pub fn interact_with_door<A: AsPolygon>(
 				false => door_obj.open_door(),
 			},
 		};
+
+		if !was_open && door_obj.door().unwrap().is_open {
+			events.push(GameEvent::DoorOpened {
+				tile_pos: door_obj.tile_pos(),
+			});
+		}
+	}
+}
+
+/// How far from a broken door monsters get knocked back from, in tiles
+const BREACH_RADIUS: f32 = 2.0;
+
+/// Lets warriors shoulder charge through a closed door next to them. The door
+/// is broken for good, and any monsters just behind it are knocked back and
+/// stunned
+pub fn breach_door(player: &Player, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>) {
+	if player.class != PlayerClass::Warrior {
+		return;
+	}
+
+	let player_tile_pos = pos_to_tile(player);
+
+	let door = floor_info.floor.doors().find(|door| {
+		let tile_distance = (door.tile_pos() - player_tile_pos).abs();
+
+		tile_distance.cmple(IVec2::ONE).all() &&
+			!door.tile_pos().eq(&player_tile_pos) &&
+			!door.door().unwrap().is_open &&
+			!door.door().unwrap().locked()
+	});
+
+	let Some(door_obj) = door else {
+		return;
+	};
+
+	door_obj.break_door();
+
+	let tile_pos = door_obj.tile_pos();
+	let door_center = door_obj.center();
+	let direction = (door_center - player.center()).normalize_or_zero();
+
+	events.push(GameEvent::DoorBroken { tile_pos });
+
+	floor_info.monsters.iter_mut().for_each(|monster| {
+		let offset = monster.center() - door_center;
+
+		// Only monsters on the other side of the door get hit
+		if offset.length() <= BREACH_RADIUS * TILE_SIZE as f32 && offset.dot(direction) >= 0.0 {
+			monster.knock_back(direction * TILE_SIZE as f32, &floor_info.floor);
+			monster.apply_enchantment(Enchantment {
+				kind: EnchantmentKind::Stunned,
+				strength: 1,
+			});
+		}
+	});
+}
+
+/// Downs players who've run out of HP, bleeds out anyone who's been down too
+/// long, and gets them back up once a teammate's held revive next to them for
+/// long enough. `reviving` is whether each player's holding revive
+pub fn update_downed(players: &mut [Player], reviving: &[bool], events: &mut Vec<GameEvent>) {
+	let range = REVIVE_RANGE * TILE_SIZE as f32;
+
+	let being_revived: Vec<bool> = players
+		.iter()
+		.enumerate()
+		.map(|(i, downed)| {
+			players
+				.iter()
+				.zip(reviving)
+				.enumerate()
+				.any(|(j, (helper, reviving))| {
+					i != j &&
+						*reviving && helper.hp() > 0 &&
+						helper.center().distance(downed.center()) <= range
+				})
+		})
+		.collect();
+
+	players
+		.iter_mut()
+		.zip(being_revived)
+		.enumerate()
+		.for_each(|(i, (player, being_revived))| {
+			player.life = match player.life {
+				LifeState::Up => match player.hp() {
+					0 => {
+						events.push(GameEvent::PlayerDowned { player: i });
+						player.charge = 0;
+
+						LifeState::Downed {
+							bleed_out: BLEED_OUT_TIME,
+							revive_progress: 0,
+						}
+					},
+					_ => LifeState::Up,
+				},
+				// Healed some other way, like by a regeneration blessing
+				LifeState::Downed { .. } if player.hp() > 0 => LifeState::Up,
+				LifeState::Downed {
+					bleed_out,
+					revive_progress,
+				} => match being_revived {
+					true if revive_progress + 1 >= REVIVE_TIME => {
+						events.push(GameEvent::PlayerRevived { player: i });
+						player.hp.points = (player.hp.max_points / 4).max(1);
+						player.invincibility_frames =
+							player.invincibility_frames.max(REVIVE_INVINCIBILITY);
+
+						LifeState::Up
+					},
+					// Someone's holding the wound shut, so they don't bleed out any
+					// further
+					true => LifeState::Downed {
+						bleed_out,
+						revive_progress: revive_progress + 1,
+					},
+					false if bleed_out <= 1 => {
+						events.push(GameEvent::PlayerDied { player: i });
+
+						LifeState::Dead
+					},
+					false => LifeState::Downed {
+						bleed_out: bleed_out - 1,
+						revive_progress: 0,
+					},
+				},
+				// Nothing brings the dead back
+				LifeState::Dead => {
+					player.hp.points = 0;
+
+					LifeState::Dead
+				},
+			};
+		});
+}
+
+/// How far a dash goes, if nothing's in the way
+const DASH_DISTANCE: f32 = TILE_SIZE as f32 * 3.0;
+/// How far each step of a dash checks ahead for walls. Smaller than the
+/// player, so it can't skip through anything
+const DASH_STEP: f32 = PLAYER_SIZE * 0.25;
+const DASH_MANA_COST: u16 = 1;
+/// How long nothing can hurt the player for after dashing
+const DASH_INVINCIBILITY: u16 = 15;
+
+/// Lets any class throw themselves a few tiles at the cost of some MP, out of
+/// harm's way for a moment. The dash stops short at the first wall it meets
+pub fn dash(player: &mut Player, angle: f32, floor: &Floor) {
+	if player.rooted != 0 || player.mp.points < DASH_MANA_COST {
+		return;
+	}
+
+	player.mp.points -= DASH_MANA_COST;
+	player.invincibility_frames = player.invincibility_frames.max(DASH_INVINCIBILITY);
+
+	let step = Vec2::new(angle.cos(), angle.sin()) * DASH_STEP;
+	let steps = (DASH_DISTANCE / DASH_STEP) as u16;
+
+	for _ in 0..steps {
+		if floor.collision(player, step) {
+			break;
+		}
+
+		player.pos += step;
+	}
+}
+
+/// Opens a chest next to the player, if there is one
+pub fn open_chest(
+	player: &Player, player_index: usize, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>,
+	rng: &mut Rng,
+) {
+	if let Some(tile_pos) =
+		floor_info
+			.floor
+			.open_chest_near(pos_to_tile(player), &floor_info.chest_loot, rng)
+	{
+		events.push(GameEvent::ChestOpened {
+			player: player_index,
+			tile_pos,
+		});
+	}
+}
+
+pub fn smash_crate(
+	player: &Player, player_index: usize, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>,
+) {
+	if let Some(tile_pos) = floor_info.floor.smash_crate_near(pos_to_tile(player)) {
+		events.push(GameEvent::CrateSmashed {
+			player: player_index,
+			tile_pos,
+		});
 	}
 }
 
+/// Looks for hidden passages in the walls around the player, opening up any
+/// that are found
+pub fn search_walls(
+	player: &Player, player_index: usize, floor_info: &mut FloorInfo, events: &mut Vec<GameEvent>,
+) {
+	let player_tile_pos = pos_to_tile(player);
+
+	floor_info
+		.floor
+		.hidden_passages()
+		.filter(|wall| {
+			(wall.tile_pos() - player_tile_pos)
+				.abs()
+				.cmple(IVec2::ONE)
+				.all()
+		})
+		.for_each(|wall| {
+			if wall.reveal_passage() {
+				events.push(GameEvent::SecretFound {
+					player: player_index,
+					tile_pos: wall.tile_pos(),
+				});
+			}
+		});
+}
+
 impl AsPolygon for Player {
 	fn as_polygon(&self) -> Polygon {
 		const HALF_SIZE: Vec2 = Vec2::splat(PLAYER_SIZE * 0.5);
@@ -529,7 +1522,56 @@ impl Drawable for Player {
 	fn size(&self) -> Vec2 { Vec2::splat(PLAYER_SIZE) }
 
 	fn draw(&self) {
-		draw_rectangle(self.pos.x, self.pos.y, PLAYER_SIZE, PLAYER_SIZE, RED);
+		let color = match self.life {
+			LifeState::Up => RED,
+			LifeState::Downed { .. } => MAROON,
+			LifeState::Dead => DARKGRAY,
+		};
+
+		draw_rectangle(self.pos.x, self.pos.y, PLAYER_SIZE, PLAYER_SIZE, color);
+
+		// A bar that drains as they bleed out, and a ring that closes in as a
+		// teammate gets them back up
+		if let LifeState::Downed {
+			bleed_out,
+			revive_progress,
+		} = self.life
+		{
+			let width = PLAYER_SIZE * bleed_out as f32 / BLEED_OUT_TIME as f32;
+			draw_rectangle(self.pos.x, self.pos.y + PLAYER_SIZE + 2.0, width, 2.0, RED);
+
+			if revive_progress > 0 {
+				let center = self.center();
+				let progress = revive_progress as f32 / REVIVE_TIME as f32;
+
+				draw_circle_lines(
+					center.x,
+					center.y,
+					PLAYER_SIZE * (1.5 - progress * 0.75),
+					1.0,
+					GREEN,
+				);
+			}
+		}
+
+		// A ring that closes in as the attack winds up, and turns gold once it's
+		// fully charged
+		if self.charge > 0 {
+			let progress = self.charge as f32 / FULL_CHARGE as f32;
+			let center = self.center();
+
+			draw_circle_lines(
+				center.x,
+				center.y,
+				PLAYER_SIZE * (1.5 - progress * 0.75),
+				1.0,
+				match self.charge >= FULL_CHARGE {
+					true => GOLD,
+					false => WHITE,
+				},
+			);
+		}
+
 		draw_text(
 			&self.hp.points.to_string(),
 			self.pos.x,
@@ -542,37 +1584,64 @@ impl Drawable for Player {
 
 impl Enchantable for Player {
 	fn apply_enchantment(&mut self, enchantment: Enchantment) {
-		if self.enchantments.get(&enchantment.kind).is_none() {
-			let enchantment_time = match enchantment.kind {
-				EnchantmentKind::Blinded => 60,
-				EnchantmentKind::Sticky => 60,
-				EnchantmentKind::Regenerating => 60 * 8,
-			};
+		let enchantment_time = match enchantment.kind {
+			EnchantmentKind::Blinded => 60,
+			EnchantmentKind::Sticky => 60,
+			EnchantmentKind::Regenerating => 60 * 8,
+			EnchantmentKind::Stunned => 45,
+			EnchantmentKind::Burning => 60,
+			// Strong-willed players shake poison off sooner
+			EnchantmentKind::Poisoned => (60 * 5_u16).saturating_sub(self.willpower * 6),
+			EnchantmentKind::Chilled => 60 * 2,
+			EnchantmentKind::Frozen => 30,
+		};
 
-			self.enchantments
-				.insert(enchantment.kind, (enchantment, enchantment_time));
-		}
+		self.enchantments
+			.entry(enchantment.kind)
+			.or_insert((enchantment, enchantment_time));
 	}
 
 	fn update_enchantments(&mut self) {
-		self.enchantments
-			.retain(|enchantment_kind, (enchantment, time_til_removal)| {
+		// Blessings are applied again as soon as they wear off
+		self.blessings.clone().into_iter().for_each(|blessing| {
+			self.apply_enchantment(blessing);
+		});
+
+		retain_in_order(
+			&mut self.enchantments,
+			|enchantment_kind, (enchantment, time_til_removal)| {
 				// Regenerates the player's health every second
-				if *enchantment_kind == EnchantmentKind::Regenerating {
-					if *time_til_removal % (60 / enchantment.strength as u16) == 0 {
-						if self.hp.points < self.hp.max_points {
-							self.hp.points += 1;
-						}
-					}
+				if enchantment_kind == EnchantmentKind::Regenerating &&
+					*time_til_removal % (60 / enchantment.strength as u16) == 0 &&
+					self.hp.points < self.hp.max_points
+				{
+					self.hp.points += 1;
+				}
+
+				// Burns the player twice a second
+				if enchantment_kind == EnchantmentKind::Burning &&
+					*time_til_removal % (30 / enchantment.strength as u16) == 0
+				{
+					self.hp.points = self.hp.points.saturating_sub(1);
+				}
+
+				// Poison's slower, once a second
+				if enchantment_kind == EnchantmentKind::Poisoned &&
+					*time_til_removal % (60 / enchantment.strength as u16) == 0
+				{
+					self.hp.points = self.hp.points.saturating_sub(1);
 				}
 
 				*time_til_removal -= 1;
 				*time_til_removal != 0
-			});
+			},
+		);
 	}
 }
 
-pub fn pickup_items(player: &mut Player, floor: &mut Floor) {
+pub fn pickup_items(
+	player: &mut Player, player_index: usize, floor: &mut Floor, events: &mut Vec<GameEvent>,
+) {
 	let mut item = None;
 
 	'search: for i in 0..floor.objects().len() {
@@ -588,6 +1657,11 @@ pub fn pickup_items(player: &mut Player, floor: &mut Floor) {
 	}
 
 	if let Some(item) = item {
+		events.push(GameEvent::ItemPickedUp {
+			player: player_index,
+			item: item.item_type,
+		});
+
 		match item.item_type {
 			ItemType::Gold(gold) => player.gold += gold,
 			_ => player.inventory.add_item(item),
@@ -595,6 +1669,11 @@ pub fn pickup_items(player: &mut Player, floor: &mut Floor) {
 	}
 }
 
+/// Which slot an item's worn in, if it's gear
+fn wearable_slot(item: &ItemInfo) -> Option<EquipmentSlot> {
+	Some(item.item_type.definition()?.wearable?.slot)
+}
+
 pub fn toggle_inventory(player: &mut Player) { player.in_inventory = !player.in_inventory; }
 
 pub const ITEM_INVENTORY_SIZE: Vec2 = Vec2::splat(50.0);
@@ -605,6 +1684,36 @@ pub fn item_pos_from_index(i: usize) -> Vec2 {
 		(UVec2::new(i as u32 % 10, i as u32 / 10) * ITEM_INVENTORY_SIZE.as_uvec2()).as_vec2()
 }
 
+/// Where the gear worn in a slot is drawn, in a row above the pack
+fn equipment_pos(slot: EquipmentSlot) -> Vec2 {
+	Vec2::new(150.0 + slot as usize as f32 * ITEM_INVENTORY_SIZE.x, 120.0)
+}
+
+/// One box in the inventory, with whatever item's in it
+fn draw_inventory_box(item: Option<&ItemInfo>, pos: Vec2, color: Color) {
+	draw_rectangle_lines(
+		pos.x,
+		pos.y,
+		ITEM_INVENTORY_SIZE.x,
+		ITEM_INVENTORY_SIZE.y,
+		8.0,
+		color,
+	);
+
+	let Some(item) = item else {
+		return;
+	};
+
+	let texture_params = DrawTextureParams {
+		rotation: item.rotation(),
+		flip_x: item.flip_x(),
+		dest_size: Some(ITEM_INVENTORY_SIZE),
+		..Default::default()
+	};
+
+	draw_texture_ex(item.texture().unwrap(), pos.x, pos.y, WHITE, texture_params);
+}
+
 pub fn draw_inventory(player: &Player) {
 	if !player.in_inventory {
 		return;
@@ -613,23 +1722,30 @@ pub fn draw_inventory(player: &Player) {
 	draw_rectangle(100.0, 100.0, 650.0, 450.0, LIGHTGRAY);
 	draw_rectangle_lines(100.0, 100.0, 650.0, 450.0, 15.0, DARKGRAY);
 
+	EquipmentSlot::ALL.into_iter().for_each(|slot| {
+		let pos = equipment_pos(slot);
+		let item = player.inventory.equipped(slot);
+
+		draw_inventory_box(item, pos, DARKGRAY);
+
+		// Empty slots say what goes in them
+		if item.is_none() {
+			draw_text(
+				&slot.to_string(),
+				pos.x + 6.0,
+				pos.y + ITEM_INVENTORY_SIZE.y * 0.5,
+				14.0,
+				DARKGRAY,
+			);
+		}
+	});
+
 	player
 		.inventory
 		.items
 		.iter()
 		.enumerate()
 		.for_each(|(i, item)| {
-			let texture = item.texture().unwrap();
-
-			let texture_params = DrawTextureParams {
-				rotation: item.rotation(),
-				flip_x: item.flip_x(),
-				dest_size: Some(ITEM_INVENTORY_SIZE),
-				..Default::default()
-			};
-
-			let item_pos = item_pos_from_index(i);
-
 			let color = match player
 				.inventory
 				.selected_item
@@ -641,15 +1757,6 @@ pub fn draw_inventory(player: &Player) {
 				false => DARKGRAY,
 			};
 
-			draw_rectangle_lines(
-				item_pos.x,
-				item_pos.y,
-				ITEM_INVENTORY_SIZE.x,
-				ITEM_INVENTORY_SIZE.y,
-				8.0,
-				color,
-			);
-
-			draw_texture_ex(texture, item_pos.x, item_pos.y, WHITE, texture_params);
+			draw_inventory_box(Some(item), item_pos_from_index(i), color);
 		});
 }
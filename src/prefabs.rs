@@ -0,0 +1,97 @@
+use macroquad::prelude::*;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+#[cfg(feature = "native")]
+use crate::mods;
+
+const DEFAULT_PREFABS: &str = include_str!("../assets/data/prefabs.ron");
+
+/// Handcrafted rooms that get stamped over generated ones. Mods can change
+/// them or add their own with their own `data/prefabs.ron`
+pub static PREFABS: Lazy<Vec<Prefab>> = Lazy::new(|| {
+	#[allow(unused_mut)]
+	let mut prefabs: Vec<Prefab> = ron::from_str(DEFAULT_PREFABS).unwrap();
+
+	#[cfg(feature = "native")]
+	if let Some(mod_prefabs) = mods::load_data::<Vec<Prefab>>("prefabs") {
+		mod_prefabs.into_iter().for_each(|mod_prefab| {
+			match prefabs.iter_mut().find(|p| p.key == mod_prefab.key) {
+				Some(prefab) => *prefab = mod_prefab,
+				None => prefabs.push(mod_prefab),
+			}
+		});
+	}
+
+	prefabs
+});
+
+/// What a character in a prefab's layout turns its tile into
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PrefabTile {
+	/// ` `, leaves whatever was generated there
+	Unchanged,
+	/// `#`
+	Wall,
+	/// `.`, plain floor with nothing on it
+	Floor,
+	/// `^`
+	Trap,
+	/// `S`
+	Shrine,
+	/// `C`
+	Chest,
+	/// `$`
+	Gold,
+	/// `~`, one of the floor's hazards
+	Hazard,
+}
+
+impl PrefabTile {
+	fn from_char(c: char) -> Self {
+		match c {
+			'#' => PrefabTile::Wall,
+			'.' => PrefabTile::Floor,
+			'^' => PrefabTile::Trap,
+			'S' => PrefabTile::Shrine,
+			'C' => PrefabTile::Chest,
+			'$' => PrefabTile::Gold,
+			'~' => PrefabTile::Hazard,
+			_ => PrefabTile::Unchanged,
+		}
+	}
+}
+
+/// The inside of a room, without its walls, laid out as rows of characters.
+/// It's put in the middle of whichever room it's stamped over, and the room's
+/// doors are wherever the hallways happen to meet it
+#[derive(Clone, Deserialize)]
+pub struct Prefab {
+	pub key: String,
+	layout: Vec<String>,
+	/// The shallowest floor the prefab can show up on
+	#[serde(default)]
+	pub min_floor: usize,
+}
+
+impl Prefab {
+	pub fn size(&self) -> IVec2 {
+		let width = self
+			.layout
+			.iter()
+			.map(|row| row.chars().count())
+			.max()
+			.unwrap_or_default();
+
+		IVec2::new(width as i32, self.layout.len() as i32)
+	}
+
+	/// Every tile in the layout, from its top left corner
+	pub fn tiles(&self) -> impl Iterator<Item = (IVec2, PrefabTile)> + '_ {
+		self.layout.iter().enumerate().flat_map(|(y, row)| {
+			row.chars()
+				.enumerate()
+				.map(move |(x, c)| (IVec2::new(x as i32, y as i32), PrefabTile::from_char(c)))
+		})
+	}
+}
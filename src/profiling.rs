@@ -0,0 +1,40 @@
+//! Spans around the main systems, shown in an in-game flame graph. Without the
+//! `profiling` feature, all of this compiles to nothing
+
+#[cfg(feature = "profiling")]
+pub use puffin;
+
+/// Times the rest of the enclosing scope, under the given name
+#[macro_export]
+macro_rules! profile_scope {
+	($name:expr) => {
+		#[cfg(feature = "profiling")]
+		$crate::profiling::puffin::profile_scope!($name);
+	};
+}
+
+/// Spans are only recorded while the flame graph is open, since they aren't
+/// free
+pub fn set_profiling(_on: bool) {
+	#[cfg(feature = "profiling")]
+	puffin::set_scopes_on(_on);
+}
+
+/// Should be called once per rendered frame
+pub fn new_frame() {
+	#[cfg(feature = "profiling")]
+	puffin::GlobalProfiler::lock().new_frame();
+}
+
+/// Draws the flame graph, returning false once it's been closed
+#[cfg(feature = "profiling")]
+pub fn draw_profiler() -> bool {
+	let mut open = true;
+
+	egui_macroquad::ui(|egui_ctx| {
+		open = puffin_egui::profiler_window(egui_ctx);
+	});
+	egui_macroquad::draw();
+
+	open
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bestiary::Bestiary;
+use crate::config::{read_storage, write_storage, ConfigError};
+use crate::FPS;
+
+const PROGRESSION_KEY: &str = ".progression";
+
+/// Something earned across runs. Unlocks only change what's shown locally,
+/// since peers with different unlocks would otherwise desync
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unlock {
+	/// Beat the rat king
+	RatSlayer,
+	/// Beat the rat king within 20 minutes
+	Speedrunner,
+	/// Beat the rat king 5 times
+	Veteran,
+}
+
+impl Unlock {
+	const ALL: [Unlock; 3] = [Unlock::RatSlayer, Unlock::Speedrunner, Unlock::Veteran];
+
+	pub fn title_id(&self) -> &'static str {
+		match self {
+			Unlock::RatSlayer => "unlock-rat-slayer",
+			Unlock::Speedrunner => "unlock-speedrunner",
+			Unlock::Veteran => "unlock-veteran",
+		}
+	}
+
+	/// Checked after each win
+	fn earned(&self, progression: &Progression) -> bool {
+		match self {
+			Unlock::RatSlayer => progression.runs_won >= 1,
+			Unlock::Speedrunner => progression
+				.fastest_win
+				.is_some_and(|frames| frames <= (20.0 * 60.0 * FPS) as u64),
+			Unlock::Veteran => progression.runs_won >= 5,
+		}
+	}
+}
+
+/// Everything that's kept between runs
+#[derive(Default, Serialize, Deserialize)]
+pub struct Progression {
+	pub runs_won: u32,
+	/// In frames
+	pub fastest_win: Option<u64>,
+	pub unlocks: Vec<Unlock>,
+	#[serde(default)]
+	pub bestiary: Bestiary,
+	#[serde(default)]
+	pub best_arena_score: u32,
+}
+
+impl Progression {
+	pub fn load() -> Self {
+		read_storage(PROGRESSION_KEY)
+			.ok()
+			.and_then(|progression| ron::from_str(&progression).ok())
+			.unwrap_or_default()
+	}
+
+	pub fn save(&self) {
+		let saved = ron::to_string(self)
+			.map_err(ConfigError::from)
+			.and_then(|progression| write_storage(PROGRESSION_KEY, &progression));
+
+		if let Err(e) = saved {
+			log::error!("Couldn't save progression: {e:?}");
+		}
+	}
+
+	/// Records a win that took `frames`, returning anything that was unlocked
+	/// by it
+	pub fn record_win(&mut self, frames: u64) -> Vec<Unlock> {
+		self.runs_won += 1;
+		self.fastest_win = Some(
+			self.fastest_win
+				.map_or(frames, |fastest| fastest.min(frames)),
+		);
+
+		let new_unlocks: Vec<Unlock> = Unlock::ALL
+			.into_iter()
+			.filter(|unlock| !self.unlocks.contains(unlock) && unlock.earned(self))
+			.collect();
+
+		self.unlocks.extend(&new_unlocks);
+		self.save();
+
+		new_unlocks
+	}
+
+	/// Keeps the score if it's the best one so far
+	pub fn record_arena_score(&mut self, score: u32) {
+		if score > self.best_arena_score {
+			self.best_arena_score = score;
+			self.save();
+		}
+	}
+}
@@ -0,0 +1,178 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::draw::Drawable;
+use crate::items::ItemType;
+use crate::map::TILE_SIZE;
+
+/// Scattered across room floors just to be looked at. Nothing bumps into them
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Decoration {
+	Bones,
+	Crack,
+	Moss,
+}
+
+impl Decoration {
+	/// Drawn on top of the tile, underneath anything lying on it
+	pub fn draw(&self, tile_pos: IVec2) {
+		let tile_size = TILE_SIZE as f32;
+		let pos = (tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+
+		match self {
+			Decoration::Bones => {
+				draw_line(
+					pos.x + tile_size * 0.25,
+					pos.y + tile_size * 0.7,
+					pos.x + tile_size * 0.7,
+					pos.y + tile_size * 0.45,
+					2.0,
+					BEIGE,
+				);
+				draw_circle(pos.x + tile_size * 0.3, pos.y + tile_size * 0.3, 3.0, BEIGE);
+			},
+			Decoration::Crack => {
+				draw_line(
+					pos.x + tile_size * 0.2,
+					pos.y + tile_size * 0.2,
+					pos.x + tile_size * 0.5,
+					pos.y + tile_size * 0.55,
+					1.0,
+					DARKGRAY,
+				);
+				draw_line(
+					pos.x + tile_size * 0.5,
+					pos.y + tile_size * 0.55,
+					pos.x + tile_size * 0.8,
+					pos.y + tile_size * 0.6,
+					1.0,
+					DARKGRAY,
+				);
+			},
+			Decoration::Moss => {
+				let moss = Color::new(0.3, 0.55, 0.2, 0.7);
+
+				draw_circle(pos.x + tile_size * 0.35, pos.y + tile_size * 0.6, 5.0, moss);
+				draw_circle(pos.x + tile_size * 0.6, pos.y + tile_size * 0.45, 4.0, moss);
+			},
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum PropKind {
+	Pillar,
+	/// Can be smashed open, and might have something inside
+	Crate,
+	/// Two tiles wide, and only found up against walls
+	Bookshelf,
+}
+
+impl PropKind {
+	/// How many tiles the prop takes up
+	pub fn size(&self) -> IVec2 {
+		match self {
+			PropKind::Pillar | PropKind::Crate => IVec2::ONE,
+			PropKind::Bookshelf => IVec2::new(2, 1),
+		}
+	}
+}
+
+/// Furniture that gets in the way of people and sight. Props bigger than a
+/// tile are on every tile they cover, but only drawn from their top left one
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct Prop {
+	pub kind: PropKind,
+	/// The top left tile of the whole prop
+	origin: IVec2,
+	tile_pos: IVec2,
+	/// What falls out of a crate when it's smashed
+	contents: Option<ItemType>,
+}
+
+impl Prop {
+	pub fn new(kind: PropKind, origin: IVec2, tile_pos: IVec2) -> Self {
+		Self {
+			kind,
+			origin,
+			tile_pos,
+			contents: None,
+		}
+	}
+
+	pub fn with_contents(mut self, contents: Option<ItemType>) -> Self {
+		self.contents = contents;
+		self
+	}
+
+	pub fn tile_pos(&self) -> IVec2 { self.tile_pos }
+
+	pub fn contents(&self) -> Option<ItemType> { self.contents }
+
+	/// Every tile a prop of this kind takes up, starting from `origin`
+	pub fn tiles(kind: PropKind, origin: IVec2) -> impl Iterator<Item = IVec2> {
+		let size = kind.size();
+
+		(0..size.y).flat_map(move |y| (0..size.x).map(move |x| origin + IVec2::new(x, y)))
+	}
+}
+
+impl Drawable for Prop {
+	fn pos(&self) -> Vec2 { (self.origin * IVec2::splat(TILE_SIZE as i32)).as_vec2() }
+
+	fn size(&self) -> Vec2 { (self.kind.size() * IVec2::splat(TILE_SIZE as i32)).as_vec2() }
+
+	fn draw(&self) {
+		if self.tile_pos != self.origin {
+			return;
+		}
+
+		let pos = self.pos();
+		let size = self.size();
+
+		match self.kind {
+			PropKind::Pillar => {
+				draw_circle(
+					pos.x + size.x * 0.5,
+					pos.y + size.y * 0.5,
+					size.x * 0.4,
+					GRAY,
+				);
+				draw_circle_lines(
+					pos.x + size.x * 0.5,
+					pos.y + size.y * 0.5,
+					size.x * 0.4,
+					2.0,
+					DARKGRAY,
+				);
+			},
+			PropKind::Crate => {
+				let inset = size * 0.1;
+				let (x, y) = (pos.x + inset.x, pos.y + inset.y);
+				let (w, h) = (size.x - inset.x * 2.0, size.y - inset.y * 2.0);
+
+				draw_rectangle(x, y, w, h, BROWN);
+				draw_rectangle_lines(x, y, w, h, 2.0, DARKBROWN);
+				draw_line(x, y, x + w, y + h, 2.0, DARKBROWN);
+			},
+			PropKind::Bookshelf => {
+				draw_rectangle(pos.x, pos.y, size.x, size.y * 0.6, DARKBROWN);
+
+				// A row of book spines
+				[RED, DARKBLUE, DARKGREEN, MAROON, DARKPURPLE, GOLD]
+					.iter()
+					.enumerate()
+					.for_each(|(i, color)| {
+						let width = size.x / 7.0;
+						draw_rectangle(
+							pos.x + width * (i as f32 + 0.5),
+							pos.y + size.y * 0.1,
+							width * 0.8,
+							size.y * 0.4,
+							*color,
+						);
+					});
+			},
+		}
+	}
+}
@@ -0,0 +1,235 @@
+use std::fmt::Display;
+
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use serde::Serialize;
+
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::items::{ItemInfo, ItemType};
+use crate::localization::{tr, tr_args};
+use crate::map::{pos_to_tile, FloorInfo, TILE_SIZE};
+use crate::monsters::MonsterKind;
+use crate::player::Player;
+use crate::rng::Rng;
+
+#[derive(Clone, Serialize)]
+pub enum Objective {
+	/// Kill some monsters on the floor the quest was taken on
+	Kill(MonsterKind, u32),
+	/// Have any player pick up the item
+	Find(ItemType),
+}
+
+/// Given to every player once a quest is completed
+#[derive(Clone, Serialize)]
+pub struct Reward {
+	pub gold: u32,
+	pub xp: u32,
+	pub item: Option<ItemType>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Quest {
+	objective: Objective,
+	reward: Reward,
+	floor: usize,
+	progress: u32,
+	completed: bool,
+}
+
+impl Quest {
+	fn goal(&self) -> u32 {
+		match self.objective {
+			Objective::Kill(_, amount) => amount,
+			Objective::Find(_) => 1,
+		}
+	}
+
+	pub fn completed(&self) -> bool { self.completed }
+}
+
+impl Display for Quest {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let quest = match &self.objective {
+			Objective::Kill(monster, amount) => tr_args(
+				"quest-kill",
+				[
+					("amount", (*amount).into()),
+					("monster", monster.name(*amount).into()),
+					("floor", (self.floor + 1).into()),
+					("progress", self.progress.into()),
+				],
+			),
+			Objective::Find(item) => tr_args(
+				"quest-find",
+				[("item", ItemInfo::new(*item, None).to_string().into())],
+			),
+		};
+
+		f.write_str(&match self.completed {
+			true => tr_args("quest-done", [("quest", quest.into())]),
+			false => tr_args(
+				"quest-reward",
+				[
+					("quest", quest.into()),
+					("gold", self.reward.gold.into()),
+					("xp", self.reward.xp.into()),
+				],
+			),
+		})
+	}
+}
+
+/// Hands out a quest to whoever walks over it
+#[derive(Clone, Serialize)]
+pub struct NoticeBoard {
+	tile_pos: IVec2,
+	quest: Option<Quest>,
+}
+
+impl NoticeBoard {
+	/// Places the board on the open floor closest to just beside the floor's
+	/// spawn, with a random quest for that floor
+	pub fn generate(floor_info: &mut FloorInfo, floor_num: usize, rng: &mut Rng) -> Self {
+		let spawn_tile = (floor_info.current_spawn() / Vec2::splat(TILE_SIZE as f32)).as_ivec2();
+		let beside_spawn = spawn_tile + IVec2::new(2, 0);
+		let tile_pos = floor_info
+			.floor
+			.nearest_open_tile(beside_spawn)
+			.unwrap_or(spawn_tile);
+
+		let objective = match rng.gen_range(0, 2) {
+			0 => Objective::Kill(MonsterKind::SmallRat, rng.gen_range(5, 11)),
+			_ => {
+				let ring = ItemType::from_key("lost_ring");
+
+				// Hide the ring somewhere on the floor
+				let ring_pos = (floor_info.random_room_pos(None, rng) /
+					Vec2::splat(TILE_SIZE as f32))
+				.as_ivec2();
+				floor_info
+					.floor
+					.add_item_to_object(ItemInfo::new(ring, Some(ring_pos)));
+
+				Objective::Find(ring)
+			},
+		};
+
+		let quest = Quest {
+			objective,
+			reward: Reward {
+				gold: 20 * (floor_num as u32 + 1),
+				xp: 5 * (floor_num as u32 + 1),
+				item: Some(ItemType::from_key("potion_regeneration")),
+			},
+			floor: floor_num,
+			progress: 0,
+			completed: false,
+		};
+
+		Self {
+			tile_pos,
+			quest: Some(quest),
+		}
+	}
+
+	pub fn tile_pos(&self) -> IVec2 { self.tile_pos }
+}
+
+impl Drawable for NoticeBoard {
+	fn size(&self) -> Vec2 { Vec2::splat(TILE_SIZE as f32 * 0.6) }
+
+	fn pos(&self) -> Vec2 {
+		(self.tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2() +
+			Vec2::splat(TILE_SIZE as f32 * 0.2)
+	}
+
+	fn draw(&self) {
+		let size = self.size();
+		let pos = self.pos();
+
+		draw_rectangle(pos.x, pos.y, size.x, size.y, BROWN);
+
+		if self.quest.is_some() {
+			draw_text("!", pos.x + size.x / 3.0, pos.y + size.y, size.y, YELLOW);
+		}
+	}
+}
+
+pub fn update_quests(
+	quests: &mut Vec<Quest>, events: &mut Vec<GameEvent>, players: &mut [Player],
+	floor_info: &mut FloorInfo, floor_index: usize,
+) {
+	let mut level_ups = Vec::new();
+
+	if let Some(board) = &mut floor_info.notice_board {
+		if players.iter().any(|p| pos_to_tile(p) == board.tile_pos) {
+			quests.extend(board.quest.take());
+		}
+	}
+
+	quests
+		.iter_mut()
+		.filter(|q| !q.completed)
+		.for_each(|quest| {
+			quest.progress = match quest.objective {
+				Objective::Kill(kind, _) => match quest.floor == floor_index {
+					true => {
+						quest.progress +
+							events
+								.iter()
+								.filter(
+									|ev| matches!(ev, GameEvent::MonsterDied { kind: k, .. } if *k == kind),
+								)
+								.count() as u32
+					},
+					false => quest.progress,
+				},
+				Objective::Find(item) => {
+					players.iter().any(|p| p.inventory().contains(item)).into()
+				},
+			};
+
+			if quest.progress >= quest.goal() {
+				quest.completed = true;
+
+				players.iter_mut().enumerate().for_each(|(i, player)| {
+					player.gold += quest.reward.gold;
+
+					if player.add_xp(quest.reward.xp) {
+						level_ups.push(GameEvent::LevelUp {
+							player: i,
+							level: player.level,
+						});
+					}
+
+					if let Some(item) = quest.reward.item {
+						player.give_item(ItemInfo::new(item, None));
+					}
+				});
+
+				// Every find quest is for the same item, so it's handed over
+				// rather than counting towards the next one too
+				if let Objective::Find(item) = quest.objective {
+					if let Some(player) = players.iter_mut().find(|p| p.inventory().contains(item))
+					{
+						player.inventory.remove(item);
+					}
+				}
+			}
+		});
+
+	events.extend(level_ups);
+}
+
+pub fn draw_quest_log(quests: &[Quest]) {
+	root_ui().label(Vec2::new(10.0, 10.0), &tr("quest-log-title"));
+
+	match quests.is_empty() {
+		true => root_ui().label(Vec2::new(10.0, 20.0), &tr("quest-log-empty")),
+		false => quests.iter().enumerate().for_each(|(i, quest)| {
+			root_ui().label(Vec2::new(10.0, 20.0 + i as f32 * 10.0), &quest.to_string());
+		}),
+	}
+}
@@ -0,0 +1,92 @@
+use ggrs::{GGRSRequest, InputStatus};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{read_storage, write_storage, ConfigError};
+use crate::difficulty::Difficulty;
+use crate::init_game::{GameMode, GameState};
+use crate::input::PlayerInput;
+use crate::net::GGRSConfig;
+use crate::player::PlayerClass;
+
+const REPLAY_KEY: &str = ".last_replay";
+
+/// Everything needed to play a run back, since the simulation only depends on
+/// how it started and everyone's inputs
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+	seed: u64,
+	/// Older replays were all played on normal
+	#[serde(default)]
+	difficulty: Difficulty,
+	mode: GameMode,
+	class: PlayerClass,
+	num_players: usize,
+	#[serde(default)]
+	friendly_fire: bool,
+	/// Every player's input, for each frame after the lobby
+	inputs: Vec<Vec<PlayerInput>>,
+	/// How many frames have been played back so far
+	#[serde(skip)]
+	played: usize,
+}
+
+impl Replay {
+	pub fn new(
+		seed: u64, difficulty: Difficulty, mode: GameMode, class: PlayerClass, num_players: usize,
+		friendly_fire: bool,
+	) -> Self {
+		Self {
+			seed,
+			difficulty,
+			mode,
+			class,
+			num_players,
+			friendly_fire,
+			inputs: Vec::new(),
+			played: 0,
+		}
+	}
+
+	pub fn load() -> Result<Self, ConfigError> { Ok(ron::from_str(&read_storage(REPLAY_KEY)?)?) }
+
+	pub fn save(&self) -> Result<(), ConfigError> {
+		write_storage(REPLAY_KEY, &ron::to_string(self)?)
+	}
+
+	pub fn is_empty(&self) -> bool { self.inputs.is_empty() }
+
+	/// Keeps the inputs that `frame` is about to be run with. Rolling back runs
+	/// frames again, so whatever was recorded after it is thrown away
+	pub fn record(&mut self, frame: u64, inputs: &[PlayerInput]) {
+		self.inputs.truncate(frame as usize);
+		self.inputs.push(inputs.to_vec());
+	}
+
+	/// The state the run started from, once everyone was ready
+	pub fn initial_state(&self) -> GameState {
+		let mut game_state = GameState::new(
+			self.class,
+			self.num_players,
+			self.mode,
+			self.seed,
+			self.difficulty,
+		);
+
+		game_state.friendly_fire = self.friendly_fire;
+		game_state
+	}
+
+	/// Runs the next frame the same way the session would have. None once
+	/// there aren't any left
+	pub fn next_request(&mut self) -> Option<GGRSRequest<GGRSConfig>> {
+		let inputs = self.inputs.get(self.played)?;
+		self.played += 1;
+
+		Some(GGRSRequest::AdvanceFrame {
+			inputs: inputs
+				.iter()
+				.map(|input| (*input, InputStatus::Confirmed))
+				.collect(),
+		})
+	}
+}
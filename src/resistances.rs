@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::enchantments::EnchantmentKind;
+use crate::localization::{tr, tr_args};
+use crate::player::DamageType;
+
+/// How much an enchantment or a kind of damage affects a monster or player
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+pub enum Resistance {
+	/// Not at all
+	Immune,
+	/// Half as much
+	Resistant,
+	/// Twice as much
+	Vulnerable,
+}
+
+impl Resistance {
+	pub fn name(&self) -> String {
+		tr(match self {
+			Resistance::Immune => "resistance-immune",
+			Resistance::Resistant => "resistance-resistant",
+			Resistance::Vulnerable => "resistance-vulnerable",
+		})
+	}
+
+	/// Scales an amount of damage, or how many frames an enchantment lasts.
+	/// Resisting something never makes it do nothing
+	pub fn apply(&self, amount: u16) -> u16 {
+		match self {
+			Resistance::Immune => 0,
+			Resistance::Resistant => (amount / 2).max(amount.min(1)),
+			Resistance::Vulnerable => amount.saturating_mul(2),
+		}
+	}
+}
+
+/// What a monster, or whoever's holding an item, shrugs off and what they're
+/// weak to. Anything left out affects them as normal
+#[derive(Clone, Default, Deserialize)]
+pub struct Resistances {
+	#[serde(default)]
+	enchantments: BTreeMap<EnchantmentKind, Resistance>,
+	#[serde(default)]
+	damage: BTreeMap<DamageType, Resistance>,
+}
+
+impl Resistances {
+	pub fn is_empty(&self) -> bool { self.enchantments.is_empty() && self.damage.is_empty() }
+
+	pub fn to_enchantment(&self, kind: EnchantmentKind) -> Option<Resistance> {
+		self.enchantments.get(&kind).copied()
+	}
+
+	pub fn to_damage(&self, damage_type: DamageType) -> Option<Resistance> {
+		self.damage.get(&damage_type).copied()
+	}
+
+	/// Each resistance, like "Blinded (immune)", for the bestiary
+	pub fn describe(&self) -> Vec<String> {
+		let enchantments = self
+			.enchantments
+			.iter()
+			.map(|(kind, resistance)| (kind.name(), resistance));
+		let damage = self
+			.damage
+			.iter()
+			.map(|(damage_type, resistance)| (damage_type.name(), resistance));
+
+		enchantments
+			.chain(damage)
+			.map(|(name, resistance)| {
+				tr_args(
+					"bestiary-resistance",
+					[
+						("name", name.into()),
+						("resistance", resistance.name().into()),
+					],
+				)
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+
+/// A small xorshift PRNG that lives in the game state, so rolling back rolls
+/// it back too, and every peer gets the same numbers
+#[derive(Clone, Debug, Serialize)]
+pub struct Rng {
+	state: u64,
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Self {
+		// Xorshift gets stuck on zero, and nearby seeds would start out with
+		// nearby numbers without mixing them up first
+		Self {
+			state: splitmix64(seed).max(1),
+		}
+	}
+
+	/// A new generator for something that can't share this one, like monsters
+	/// moving in parallel. Different `stream`s give different numbers
+	pub fn fork(&self, stream: u64) -> Self { Self::new(self.state ^ splitmix64(stream)) }
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 7;
+		self.state ^= self.state << 17;
+
+		self.state
+	}
+
+	pub fn next_u32(&mut self) -> u32 { (self.next_u64() >> 32) as u32 }
+
+	/// Between 0 and 1
+	pub fn next_f32(&mut self) -> f32 { (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32 }
+
+	/// From `low` up to, but not including, `high`, like macroquad's
+	pub fn gen_range<T: RandomRange>(&mut self, low: T, high: T) -> T {
+		T::gen_range(self, low, high)
+	}
+
+	pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+		match slice.is_empty() {
+			true => None,
+			false => Some(&slice[self.gen_range(0, slice.len())]),
+		}
+	}
+
+	pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+		(1..slice.len()).rev().for_each(|i| {
+			slice.swap(i, self.gen_range(0, i + 1));
+		});
+	}
+}
+
+fn splitmix64(seed: u64) -> u64 {
+	let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+	z ^ (z >> 31)
+}
+
+pub trait RandomRange {
+	fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self;
+}
+
+macro_rules! impl_random_range_int {
+	($($t:ty),*) => {$(
+		impl RandomRange for $t {
+			fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+				if high <= low {
+					return low;
+				}
+
+				let range = high.abs_diff(low) as u64;
+				low.wrapping_add((rng.next_u64() % range) as Self)
+			}
+		}
+	)*};
+}
+
+impl_random_range_int!(i32, u32, usize);
+
+impl RandomRange for f32 {
+	fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+		low + rng.next_f32() * (high - low)
+	}
+}
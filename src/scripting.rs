@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+use macroquad::prelude::*;
+use once_cell::sync::Lazy;
+use rhai::{Engine, EvalAltResult, Scope, AST, FLOAT, INT};
+
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::ItemType;
+use crate::map::{pos_to_tile, EffectType, Floor, FloorInfo};
+use crate::math::AsPolygon;
+#[cfg(feature = "native")]
+use crate::mods;
+use crate::monsters::{GreenSlime, Monster, MonsterKind, MonsterObj, SmallRat};
+use crate::player::{damage_player, DamageType, Player};
+use crate::rng::Rng;
+use crate::FPS;
+
+const SCRIPTS_DIR: &str = "assets/scripts";
+// Keeps a runaway script from locking up the game loop
+const MAX_OPERATIONS: u64 = 50_000;
+/// How often the scripts folder is checked for changes, in seconds
+const RELOAD_INTERVAL: f64 = 1.0;
+/// How far behavior scripts can move a monster each frame, in pixels
+const MAX_SCRIPTED_SPEED: f32 = 4.0;
+/// The most tiles out from the player a script can leave a hazard
+const MAX_HAZARD_RADIUS: INT = 3;
+/// and the longest it can last, in seconds
+const MAX_HAZARD_TIME: FLOAT = 30.0;
+
+static SCRIPTS: Lazy<RwLock<Scripts>> = Lazy::new(|| {
+	let mut scripts = Scripts {
+		engine: new_engine(),
+		hooks: HashMap::new(),
+		checked_at: 0.0,
+	};
+
+	scripts.reload();
+
+	RwLock::new(scripts)
+});
+
+/// Something a script asked to happen to the player it ran for. Scripts never
+/// touch the game state directly, so the commands are applied afterwards by
+/// `apply_commands`
+#[derive(Clone)]
+pub enum ScriptCommand {
+	Damage(u16),
+	Heal(u16),
+	Enchant(Enchantment),
+	Teleport,
+	Spawn(fn(Vec2) -> MonsterObj, u16),
+	/// Covers the tiles around the player in something, for this many frames
+	Hazard(EffectType, i32, u16),
+}
+
+/// Exposed to scripts as `ctx`
+#[derive(Clone)]
+pub struct ScriptContext {
+	pos: Vec2,
+	hp: u16,
+	max_hp: u16,
+	commands: Vec<ScriptCommand>,
+}
+
+impl ScriptContext {
+	pub fn new(player: &Player) -> Self {
+		Self {
+			pos: player.pos(),
+			hp: player.hp(),
+			max_hp: player.max_hp(),
+			commands: Vec::new(),
+		}
+	}
+
+	fn push_amount(&mut self, amount: INT, command: fn(u16) -> ScriptCommand) {
+		self.commands
+			.push(command(amount.clamp(0, u16::MAX as INT) as u16));
+	}
+
+	fn enchant(&mut self, kind: &str, strength: INT) -> Result<(), Box<EvalAltResult>> {
+		self.commands
+			.push(ScriptCommand::Enchant(parse_enchantment(kind, strength)?));
+
+		Ok(())
+	}
+
+	/// Meant for traps, which can leave the same sorts of things on the floor
+	/// as the built in ones
+	fn hazard(
+		&mut self, kind: &str, radius: INT, seconds: FLOAT,
+	) -> Result<(), Box<EvalAltResult>> {
+		let effect_type = match kind {
+			"slimed" => EffectType::Slimed,
+			"water" => EffectType::Water,
+			"poison_gas" => EffectType::PoisonGas,
+			"fire" => EffectType::Fire,
+			_ => return Err(format!("unknown hazard: {kind}").into()),
+		};
+
+		self.commands.push(ScriptCommand::Hazard(
+			effect_type,
+			radius.clamp(0, MAX_HAZARD_RADIUS) as i32,
+			(seconds.clamp(0.0, MAX_HAZARD_TIME) * FPS) as u16,
+		));
+
+		Ok(())
+	}
+
+	fn spawn(&mut self, monster: &str, amount: INT) -> Result<(), Box<EvalAltResult>> {
+		let new_monster: fn(Vec2) -> MonsterObj = match monster {
+			"small_rat" => |pos| MonsterObj::SmallRat(SmallRat::new(pos)),
+			"green_slime" => |pos| MonsterObj::GreenSlime(GreenSlime::new(pos)),
+			_ => return Err(format!("unknown monster: {monster}").into()),
+		};
+
+		self.commands.push(ScriptCommand::Spawn(
+			new_monster,
+			amount.clamp(0, u16::MAX as INT) as u16,
+		));
+
+		Ok(())
+	}
+}
+
+/// Something a behavior script asked its monster to do, applied by
+/// `run_behavior` once the script's finished
+#[derive(Clone)]
+pub enum MonsterCommand {
+	Move(Vec2),
+	Enchant(Enchantment),
+}
+
+/// Exposed to behavior scripts as `monster`
+#[derive(Clone)]
+pub struct MonsterContext {
+	pos: Vec2,
+	hp: u16,
+	max_hp: u16,
+	/// Where the closest player it could go after is, if there's anyone
+	target: Option<Vec2>,
+	commands: Vec<MonsterCommand>,
+}
+
+impl MonsterContext {
+	fn new(monster: &MonsterObj, players: &[Player]) -> Self {
+		let pos = monster.center();
+		let (hp, max_hp) = monster.health();
+
+		let target = players
+			.iter()
+			.filter(|player| player.noticeable())
+			.map(|player| player.center())
+			.min_by(|p1, p2| p1.distance(pos).total_cmp(&p2.distance(pos)));
+
+		Self {
+			pos,
+			hp,
+			max_hp,
+			target,
+			commands: Vec::new(),
+		}
+	}
+
+	fn target_pos(&mut self) -> Vec2 { self.target.unwrap_or(self.pos) }
+
+	fn move_towards(&mut self, x: FLOAT, y: FLOAT, speed: FLOAT) {
+		let change = Vec2::new(x as f32, y as f32) - self.pos;
+		let speed = (speed as f32).clamp(0.0, MAX_SCRIPTED_SPEED);
+
+		self.commands
+			.push(MonsterCommand::Move(change.clamp_length_max(speed)));
+	}
+
+	fn enchant(&mut self, kind: &str, strength: INT) -> Result<(), Box<EvalAltResult>> {
+		self.commands
+			.push(MonsterCommand::Enchant(parse_enchantment(kind, strength)?));
+
+		Ok(())
+	}
+}
+
+fn parse_enchantment(kind: &str, strength: INT) -> Result<Enchantment, Box<EvalAltResult>> {
+	let kind = match kind {
+		"blinded" => EnchantmentKind::Blinded,
+		"sticky" => EnchantmentKind::Sticky,
+		"regenerating" => EnchantmentKind::Regenerating,
+		"stunned" => EnchantmentKind::Stunned,
+		"burning" => EnchantmentKind::Burning,
+		"poisoned" => EnchantmentKind::Poisoned,
+		_ => return Err(format!("unknown enchantment: {kind}").into()),
+	};
+
+	Ok(Enchantment {
+		kind,
+		strength: strength.clamp(1, u8::MAX as INT) as u8,
+	})
+}
+
+struct Script {
+	ast: AST,
+	path: PathBuf,
+	modified: SystemTime,
+}
+
+struct Scripts {
+	engine: Engine,
+	hooks: HashMap<String, Script>,
+	/// When the scripts folder was last checked for changes
+	checked_at: f64,
+}
+
+impl Scripts {
+	/// Compiles any scripts that are new or have changed since they were last
+	/// loaded, and forgets any that have been deleted
+	fn reload(&mut self) {
+		let mut paths: HashMap<String, PathBuf> = fs::read_dir(SCRIPTS_DIR)
+			.into_iter()
+			.flat_map(|dir| dir.flatten())
+			.map(|entry| {
+				(
+					entry.file_name().to_string_lossy().to_string(),
+					entry.path(),
+				)
+			})
+			.collect();
+
+		// Mods' scripts replace the game's own if they have the same name
+		#[cfg(feature = "native")]
+		paths.extend(mods::mod_files("scripts", "rhai"));
+
+		let mut found = Vec::new();
+
+		paths.into_values().for_each(|path| {
+			if path.extension().and_then(|e| e.to_str()) != Some("rhai") {
+				return;
+			}
+
+			let Some(name) = path.file_stem().and_then(|n| n.to_str()) else {
+				return;
+			};
+
+			let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+				return;
+			};
+
+			found.push(name.to_string());
+
+			if self
+				.hooks
+				.get(name)
+				.is_some_and(|s| s.modified == modified && s.path == path)
+			{
+				return;
+			}
+
+			match self.engine.compile_file(path.clone()) {
+				Ok(ast) => {
+					self.hooks.insert(
+						name.to_string(),
+						Script {
+							ast,
+							path,
+							modified,
+						},
+					);
+				},
+				Err(e) => log::error!("{}: {e}", path.display()),
+			}
+		});
+
+		self.hooks.retain(|name, _| found.contains(name));
+	}
+}
+
+fn new_engine() -> Engine {
+	let mut engine = Engine::new();
+	engine.set_max_operations(MAX_OPERATIONS);
+
+	engine
+		.register_type_with_name::<ScriptContext>("Context")
+		.register_get("x", |ctx: &mut ScriptContext| ctx.pos.x as FLOAT)
+		.register_get("y", |ctx: &mut ScriptContext| ctx.pos.y as FLOAT)
+		.register_get("hp", |ctx: &mut ScriptContext| ctx.hp as INT)
+		.register_get("max_hp", |ctx: &mut ScriptContext| ctx.max_hp as INT)
+		.register_fn("damage", |ctx: &mut ScriptContext, amount: INT| {
+			ctx.push_amount(amount, ScriptCommand::Damage)
+		})
+		.register_fn("heal", |ctx: &mut ScriptContext, amount: INT| {
+			ctx.push_amount(amount, ScriptCommand::Heal)
+		})
+		.register_fn("teleport", |ctx: &mut ScriptContext| {
+			ctx.commands.push(ScriptCommand::Teleport)
+		})
+		.register_fn("enchant", ScriptContext::enchant)
+		.register_fn("spawn", ScriptContext::spawn)
+		.register_fn("hazard", ScriptContext::hazard);
+
+	engine
+		.register_type_with_name::<MonsterContext>("Monster")
+		.register_get("x", |ctx: &mut MonsterContext| ctx.pos.x as FLOAT)
+		.register_get("y", |ctx: &mut MonsterContext| ctx.pos.y as FLOAT)
+		.register_get("hp", |ctx: &mut MonsterContext| ctx.hp as INT)
+		.register_get("max_hp", |ctx: &mut MonsterContext| ctx.max_hp as INT)
+		.register_get("has_target", |ctx: &mut MonsterContext| {
+			ctx.target.is_some()
+		})
+		.register_get("target_x", |ctx: &mut MonsterContext| {
+			ctx.target_pos().x as FLOAT
+		})
+		.register_get("target_y", |ctx: &mut MonsterContext| {
+			ctx.target_pos().y as FLOAT
+		})
+		.register_fn("move_towards", MonsterContext::move_towards)
+		.register_fn("enchant", MonsterContext::enchant);
+
+	engine
+}
+
+/// Picks up any changes made to `assets/scripts` since the last call. This is
+/// never done in multiplayer, since every peer needs the same scripts loaded or
+/// their games will desync
+pub fn reload_scripts() { SCRIPTS.write().unwrap().reload(); }
+
+/// Reloads any scripts that have been saved since the last check, so they can
+/// be tweaked while the game's running. Called every frame, but only looks at
+/// the scripts folder once every `RELOAD_INTERVAL`
+pub fn hot_reload() {
+	let now = get_time();
+
+	if now - SCRIPTS.read().unwrap().checked_at < RELOAD_INTERVAL {
+		return;
+	}
+
+	let mut scripts = SCRIPTS.write().unwrap();
+	scripts.checked_at = now;
+	scripts.reload();
+}
+
+pub fn has_hook(name: &str) -> bool { SCRIPTS.read().unwrap().hooks.contains_key(name) }
+
+/// Runs the script for a hook, returning None if there's no script for it or it
+/// failed, in which case the caller falls back to the built in behavior
+pub fn run_hook(name: &str, ctx: ScriptContext) -> Option<Vec<ScriptCommand>> {
+	run_script(name, "ctx", ctx).map(|ctx| ctx.commands)
+}
+
+/// Monsters with a `<kind>_behavior` script run it every frame instead of
+/// moving the way they usually would. Returns false if there's no script for
+/// the monster or it failed
+pub fn run_behavior(monster: &mut MonsterObj, players: &[Player], floor: &Floor) -> bool {
+	let hook = format!("{}_behavior", monster.kind().key());
+
+	let Some(ctx) = run_script(&hook, "monster", MonsterContext::new(monster, players)) else {
+		return false;
+	};
+
+	ctx.commands.into_iter().for_each(|command| match command {
+		MonsterCommand::Move(change) => monster.walk(change, floor),
+		MonsterCommand::Enchant(enchantment) => monster.apply_enchantment(enchantment),
+	});
+
+	true
+}
+
+/// Runs a script with `ctx` in scope as `var`, handing back whatever the
+/// script left in it
+fn run_script<T: Clone + Send + Sync + 'static>(name: &str, var: &str, ctx: T) -> Option<T> {
+	let scripts = SCRIPTS.read().unwrap();
+	let script = scripts.hooks.get(name)?;
+
+	let mut scope = Scope::new();
+	scope.push(var.to_string(), ctx);
+
+	match scripts.engine.run_ast_with_scope(&mut scope, &script.ast) {
+		Ok(()) => scope.get_value::<T>(var),
+		Err(e) => {
+			log::error!("{name}.rhai: {e}");
+			None
+		},
+	}
+}
+
+pub fn apply_commands(
+	commands: Vec<ScriptCommand>, player: &mut Player, floor_info: &mut FloorInfo,
+	events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	commands.into_iter().for_each(|command| match command {
+		ScriptCommand::Damage(damage) => damage_player(
+			player,
+			damage,
+			DamageType::Magic,
+			0.0,
+			None,
+			&floor_info.floor,
+			events,
+		),
+		ScriptCommand::Heal(amount) => player.heal(amount),
+		ScriptCommand::Enchant(enchantment) => player.apply_enchantment(enchantment),
+		ScriptCommand::Teleport => player.pos = floor_info.random_room_pos(None, rng),
+		ScriptCommand::Spawn(new_monster, amount) => {
+			let player_tile_pos = pos_to_tile(player);
+			let scaling = floor_info.floor.scaling();
+
+			let new_monsters: Vec<MonsterObj> = (0..amount)
+				.map(|_| {
+					new_monster(floor_info.random_room_pos(Some(player_tile_pos), rng))
+						.scaled(scaling)
+				})
+				.collect();
+
+			floor_info.monsters.extend(new_monsters);
+		},
+		ScriptCommand::Hazard(effect_type, radius, frames) => {
+			let tile_pos = pos_to_tile(player);
+
+			(-radius..=radius).for_each(|x| {
+				(-radius..=radius).for_each(|y| {
+					floor_info
+						.floor
+						.add_effect(tile_pos + IVec2::new(x, y), effect_type, frames);
+				});
+			});
+		},
+	});
+}
+
+/// Items' hooks are named after their key
+pub fn item_hook(item_type: &ItemType) -> Option<&'static str> {
+	item_type.definition().map(|item| item.key.as_str())
+}
+
+pub fn monster_death_hook(monster: &MonsterObj) -> &'static str {
+	match monster {
+		MonsterObj::SmallRat(_) => "small_rat_death",
+		MonsterObj::GreenSlime(_) => "green_slime_death",
+		MonsterObj::RatKing(_) => "rat_king_death",
+		MonsterObj::SlimeQueen(_) => "slime_queen_death",
+		MonsterObj::Spawner(obj) => match obj.kind() {
+			MonsterKind::RatNest => "rat_nest_death",
+			_ => "slime_pool_death",
+		},
+		MonsterObj::SkeletonArcher(_) => "skeleton_archer_death",
+		MonsterObj::Bat(_) => "bat_death",
+		MonsterObj::Mimic(_) => "mimic_death",
+		MonsterObj::Necromancer(_) => "necromancer_death",
+		MonsterObj::Zombie(_) => "zombie_death",
+		MonsterObj::GelatinousCube(_) => "gelatinous_cube_death",
+		MonsterObj::Spider(_) => "spider_death",
+		MonsterObj::Wolf(_) => "wolf_death",
+	}
+}
@@ -0,0 +1,103 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::draw::Drawable;
+use crate::events::GameEvent;
+use crate::items::{ItemInfo, ItemType};
+use crate::localization::tr_args;
+use crate::map::{pos_to_tile, FloorInfo, TILE_SIZE};
+use crate::player::Player;
+
+/// Everything shops can sell
+pub const SHOP_STOCK: [&str; 9] = [
+	"potion_regeneration",
+	"potion_mana",
+	"throwing_knife",
+	"short_sword",
+	"spear",
+	"warriors_shield",
+	"wizards_dagger",
+	"leather_cap",
+	"swift_boots",
+];
+
+/// An item for sale. Players buy it by interacting with it while they're next
+/// to it
+#[derive(Clone, Serialize)]
+pub struct ShopItem {
+	tile_pos: IVec2,
+	item_type: ItemType,
+	price: u32,
+	sold: bool,
+}
+
+impl ShopItem {
+	pub fn new(tile_pos: IVec2, item_type: ItemType) -> Self {
+		let price = item_type.definition().map_or(0, |item| item.price);
+
+		Self {
+			tile_pos,
+			item_type,
+			price,
+			sold: false,
+		}
+	}
+
+	pub fn tile_pos(&self) -> IVec2 { self.tile_pos }
+
+	pub fn sold(&self) -> bool { self.sold }
+
+	fn item(&self) -> ItemInfo { ItemInfo::new(self.item_type, Some(self.tile_pos)) }
+}
+
+impl Drawable for ShopItem {
+	fn size(&self) -> Vec2 { self.item().size() }
+
+	fn pos(&self) -> Vec2 { self.item().pos() }
+
+	fn draw(&self) {
+		if self.sold {
+			return;
+		}
+
+		self.item().draw();
+
+		let pos = (self.tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2();
+		let price = tr_args("shop-price", [("gold", self.price.into())]);
+
+		draw_text(&price, pos.x, pos.y - 2.0, 12.0, GOLD);
+	}
+}
+
+/// Buys the item for sale next to the player, if there is one and they can
+/// afford it
+pub fn buy_item(
+	player: &mut Player, player_index: usize, floor_info: &mut FloorInfo,
+	events: &mut Vec<GameEvent>,
+) {
+	let player_tile = pos_to_tile(player);
+
+	let Some(shop_item) = floor_info.shop.iter_mut().find(|shop_item| {
+		!shop_item.sold &&
+			(shop_item.tile_pos - player_tile)
+				.abs()
+				.cmple(IVec2::ONE)
+				.all()
+	}) else {
+		return;
+	};
+
+	if player.gold < shop_item.price {
+		return;
+	}
+
+	player.gold -= shop_item.price;
+	shop_item.sold = true;
+
+	events.push(GameEvent::ItemPickedUp {
+		player: player_index,
+		item: shop_item.item_type,
+	});
+
+	player.give_item(ItemInfo::new(shop_item.item_type, None));
+}
@@ -0,0 +1,213 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::draw::Drawable;
+use crate::enchantments::{Enchantable, Enchantment, EnchantmentKind};
+use crate::events::GameEvent;
+use crate::items::{ItemInfo, ItemType};
+use crate::localization::{tr, tr_args};
+use crate::map::{pos_to_tile, FloorInfo, TILE_SIZE};
+use crate::player::Player;
+use crate::rng::Rng;
+
+/// What a shrine asks for before it does anything
+#[derive(Copy, Clone, Debug, Serialize)]
+pub enum Offering {
+	Gold(u32),
+	Hp(u16),
+}
+
+impl Offering {
+	fn scaled(self, floor_num: usize) -> Self {
+		let scale = floor_num as u32 + 1;
+
+		match self {
+			Offering::Gold(gold) => Offering::Gold(gold * scale),
+			Offering::Hp(hp) => Offering::Hp(hp * scale as u16),
+		}
+	}
+
+	/// Takes the offering from the player, if they can afford it. Shrines
+	/// won't take a player's last HP
+	fn take_from(self, player: &mut Player) -> bool {
+		match self {
+			Offering::Gold(gold) => match player.gold >= gold {
+				true => {
+					player.gold -= gold;
+					true
+				},
+				false => false,
+			},
+			Offering::Hp(hp) => player.spend_hp(hp),
+		}
+	}
+}
+
+/// The blessing or curse a shrine gives in return for an offering
+#[derive(Copy, Clone, Debug, Serialize)]
+pub enum ShrineOutcome {
+	/// Regenerating, forever
+	Blessed,
+	MaxHpUp(u16),
+	MaxMpUp(u16),
+	Potion,
+	Blinded,
+	MaxHpDown(u16),
+}
+
+impl ShrineOutcome {
+	pub fn is_blessing(&self) -> bool {
+		!matches!(self, ShrineOutcome::Blinded | ShrineOutcome::MaxHpDown(_))
+	}
+
+	pub fn description(&self) -> String {
+		match self {
+			ShrineOutcome::Blessed => tr("shrine-blessed"),
+			ShrineOutcome::MaxHpUp(amount) => {
+				tr_args("shrine-max-hp-up", [("amount", (*amount).into())])
+			},
+			ShrineOutcome::MaxMpUp(amount) => {
+				tr_args("shrine-max-mp-up", [("amount", (*amount).into())])
+			},
+			ShrineOutcome::Potion => tr("shrine-potion"),
+			ShrineOutcome::Blinded => tr("shrine-blinded"),
+			ShrineOutcome::MaxHpDown(amount) => {
+				tr_args("shrine-max-hp-down", [("amount", (*amount).into())])
+			},
+		}
+	}
+
+	fn apply(self, player: &mut Player) {
+		match self {
+			ShrineOutcome::Blessed => player.bless(Enchantment {
+				kind: EnchantmentKind::Regenerating,
+				strength: 1,
+			}),
+			ShrineOutcome::MaxHpUp(amount) => player.change_max_hp(amount as i32),
+			ShrineOutcome::MaxMpUp(amount) => player.change_max_mp(amount as i32),
+			ShrineOutcome::Potion => player.give_item(ItemInfo::new(
+				ItemType::from_key("potion_regeneration"),
+				None,
+			)),
+			ShrineOutcome::Blinded => player.apply_enchantment(Enchantment {
+				kind: EnchantmentKind::Blinded,
+				strength: 1,
+			}),
+			ShrineOutcome::MaxHpDown(amount) => player.change_max_hp(-(amount as i32)),
+		}
+	}
+}
+
+/// Every kind of shrine that can be generated, and how likely each is.
+/// Offerings go up with each floor
+const SHRINE_TABLE: &[(Offering, u32)] = &[(Offering::Gold(15), 3), (Offering::Hp(4), 2)];
+
+/// Everything a shrine can do, and how likely each is
+const OUTCOME_TABLE: &[(ShrineOutcome, u32)] = &[
+	(ShrineOutcome::Blessed, 1),
+	(ShrineOutcome::MaxHpUp(3), 3),
+	(ShrineOutcome::MaxMpUp(3), 3),
+	(ShrineOutcome::Potion, 3),
+	(ShrineOutcome::Blinded, 2),
+	(ShrineOutcome::MaxHpDown(2), 2),
+];
+
+fn choose_weighted<T: Copy>(table: &[(T, u32)], rng: &mut Rng) -> T {
+	let total: u32 = table.iter().map(|(_, weight)| weight).sum();
+	let mut roll = rng.gen_range(0, total);
+
+	table
+		.iter()
+		.find(|(_, weight)| match roll < *weight {
+			true => true,
+			false => {
+				roll -= weight;
+				false
+			},
+		})
+		.unwrap()
+		.0
+}
+
+/// Gives a random blessing or curse to whoever makes an offering. Each shrine
+/// can only be used once
+#[derive(Clone, Debug, Serialize)]
+pub struct Shrine {
+	tile_pos: IVec2,
+	offering: Offering,
+	used: bool,
+}
+
+impl Shrine {
+	pub fn generate(tile_pos: IVec2, floor_num: usize, rng: &mut Rng) -> Self {
+		Self {
+			tile_pos,
+			offering: choose_weighted(SHRINE_TABLE, rng).scaled(floor_num),
+			used: false,
+		}
+	}
+
+	pub fn used(&self) -> bool { self.used }
+}
+
+impl Drawable for Shrine {
+	fn size(&self) -> Vec2 { Vec2::splat(TILE_SIZE as f32 * 0.6) }
+
+	fn pos(&self) -> Vec2 {
+		(self.tile_pos * IVec2::splat(TILE_SIZE as i32)).as_vec2() +
+			Vec2::splat(TILE_SIZE as f32 * 0.2)
+	}
+
+	fn draw(&self) {
+		let size = self.size();
+		let pos = self.pos();
+
+		let color = match self.used {
+			true => DARKGRAY,
+			false => PURPLE,
+		};
+
+		draw_rectangle(pos.x, pos.y, size.x, size.y, color);
+
+		if !self.used {
+			let cost = match self.offering {
+				Offering::Gold(gold) => tr_args("shrine-cost-gold", [("gold", gold.into())]),
+				Offering::Hp(hp) => tr_args("shrine-cost-hp", [("hp", hp.into())]),
+			};
+
+			draw_text(&cost, pos.x, pos.y - 2.0, 12.0, WHITE);
+		}
+	}
+}
+
+/// Makes an offering at a shrine next to the player, if there is one
+pub fn use_shrine(
+	player: &mut Player, player_index: usize, floor_info: &mut FloorInfo,
+	events: &mut Vec<GameEvent>, rng: &mut Rng,
+) {
+	let player_tile = pos_to_tile(player);
+
+	let Some(shrine) = floor_info.floor.shrines_mut().find(|shrine| {
+		!shrine.used &&
+			(shrine.tile_pos - player_tile)
+				.abs()
+				.cmple(IVec2::ONE)
+				.all()
+	}) else {
+		return;
+	};
+
+	if !shrine.offering.take_from(player) {
+		return;
+	}
+
+	shrine.used = true;
+
+	let outcome = choose_weighted(OUTCOME_TABLE, rng);
+	outcome.apply(player);
+
+	events.push(GameEvent::ShrineUsed {
+		player: player_index,
+		outcome,
+	});
+}
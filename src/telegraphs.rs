@@ -0,0 +1,116 @@
+use macroquad::prelude::*;
+use serde::Serialize;
+
+use crate::map::TILE_SIZE;
+
+/// What's about to get hit
+#[derive(Copy, Clone, Debug, Serialize)]
+pub enum TelegraphShape {
+	/// Something being fired or lunging in a straight line
+	Line { from: Vec2, angle: f32, length: f32 },
+	/// A spread of shots
+	Cone {
+		from: Vec2,
+		angle: f32,
+		/// From one edge to the other
+		spread: f32,
+		length: f32,
+	},
+	/// Everything around a point
+	Circle { center: Vec2, radius: f32 },
+}
+
+/// A warning drawn on the ground while a monster winds up an attack
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct Telegraph {
+	shape: TelegraphShape,
+	/// How far through the windup the monster is, from 0 to 1
+	charge: f32,
+}
+
+impl Telegraph {
+	/// Where the attack's coming from, for only showing warnings from monsters
+	/// the player can see
+	pub fn tile_pos(&self) -> IVec2 {
+		let origin = match self.shape {
+			TelegraphShape::Line { from, .. } | TelegraphShape::Cone { from, .. } => from,
+			TelegraphShape::Circle { center, .. } => center,
+		};
+
+		(origin / TILE_SIZE as f32).floor().as_ivec2()
+	}
+
+	/// Gets brighter the closer the attack is to landing
+	pub fn draw(&self) {
+		let color = Color::new(1.0, 0.2, 0.2, 0.15 + self.charge * 0.45);
+
+		match self.shape {
+			TelegraphShape::Line {
+				from,
+				angle,
+				length,
+			} => {
+				let end = from + Vec2::new(angle.cos(), angle.sin()) * length;
+				draw_line(from.x, from.y, end.x, end.y, 1.0 + self.charge * 2.0, color);
+			},
+			TelegraphShape::Cone {
+				from,
+				angle,
+				spread,
+				length,
+			} => {
+				// A fan of thin triangles, so it's round at the end
+				const SEGMENTS: usize = 8;
+				let first_angle = angle - spread / 2.0;
+
+				(0..SEGMENTS).for_each(|i| {
+					let angle1 = first_angle + spread * i as f32 / SEGMENTS as f32;
+					let angle2 = first_angle + spread * (i + 1) as f32 / SEGMENTS as f32;
+
+					draw_triangle(
+						from,
+						from + Vec2::new(angle1.cos(), angle1.sin()) * length,
+						from + Vec2::new(angle2.cos(), angle2.sin()) * length,
+						color,
+					);
+				});
+			},
+			TelegraphShape::Circle { center, radius } => {
+				draw_circle(center.x, center.y, radius, color);
+				// Closes in on the edge as the attack gets closer
+				draw_circle_lines(
+					center.x,
+					center.y,
+					radius * self.charge.max(0.1),
+					1.0,
+					color,
+				);
+			},
+		};
+	}
+}
+
+/// Every attack that's currently being wound up. Monsters put their warnings
+/// back every frame while they're winding up, so one that's interrupted just
+/// stops warning
+#[derive(Clone, Default, Serialize)]
+pub struct Telegraphs {
+	telegraphs: Vec<Telegraph>,
+}
+
+impl Telegraphs {
+	/// Warns about an attack that lands in `frames_left` out of a `windup`
+	/// frame long windup
+	pub fn warn(&mut self, shape: TelegraphShape, frames_left: u16, windup: u16) {
+		let charge = match windup {
+			0 => 1.0,
+			_ => 1.0 - frames_left as f32 / windup as f32,
+		};
+
+		self.telegraphs.push(Telegraph { shape, charge });
+	}
+
+	pub fn clear(&mut self) { self.telegraphs.clear(); }
+
+	pub fn iter(&self) -> impl Iterator<Item = &Telegraph> { self.telegraphs.iter() }
+}
@@ -0,0 +1,215 @@
+use egui::{FontId, RichText};
+use macroquad::prelude::*;
+
+use crate::init_game::GameState;
+use crate::localization::{tr, tr_args};
+use crate::progression::{Progression, Unlock};
+use crate::FPS;
+
+// How long the victory banner stays up for, in seconds
+const BANNER_TIME: f64 = 4.0;
+// How fast the credits scroll up, in pixels per second
+const CREDITS_SPEED: f32 = 45.0;
+const CREDITS_LINE_HEIGHT: f32 = 60.0;
+
+const CREDITS: &[&str] = &[
+	"credits-title",
+	"credits-made-by",
+	"credits-built-with",
+	"credits-thanks",
+];
+
+/// How the run went, shown once the credits are over, or once everyone's died
+pub struct RunSummary {
+	frames: u64,
+	floors: usize,
+	kills: u32,
+	gold: u32,
+	level: u32,
+	quests_completed: usize,
+	new_unlocks: Vec<Unlock>,
+}
+
+impl RunSummary {
+	/// `floors` is how many floors were cleared, and `frames` how long it took
+	pub fn new(
+		game_state: &GameState, frames: u64, floors: usize, new_unlocks: Vec<Unlock>,
+	) -> Self {
+		let players = &game_state.players;
+
+		Self {
+			frames,
+			floors,
+			kills: game_state.kills,
+			gold: players.iter().map(|p| p.gold).sum(),
+			level: players.iter().map(|p| p.level).max().unwrap_or_default(),
+			quests_completed: game_state.quests.iter().filter(|q| q.completed()).count(),
+			new_unlocks,
+		}
+	}
+
+	fn lines(&self) -> Vec<String> {
+		let seconds = (self.frames as f64 / FPS) as u64;
+		let time = format!("{}:{:02}", seconds / 60, seconds % 60);
+
+		[
+			tr_args("summary-time", [("time", time.into())]),
+			tr_args("summary-floors", [("floors", self.floors.into())]),
+			tr_args("summary-kills", [("kills", self.kills.into())]),
+			tr_args("summary-gold", [("gold", self.gold.into())]),
+			tr_args("summary-level", [("level", self.level.into())]),
+			tr_args("summary-quests", [("quests", self.quests_completed.into())]),
+		]
+		.into_iter()
+		.chain(self.new_unlocks.iter().map(|unlock| {
+			tr_args(
+				"summary-unlocked",
+				[("unlock", tr(unlock.title_id()).into())],
+			)
+		}))
+		.collect()
+	}
+
+	/// Draws the summary with a button back to the main menu, returning true
+	/// once it's been clicked
+	pub fn draw(&self) -> bool {
+		let mut done = false;
+
+		egui_macroquad::ui(|egui_ctx| {
+			egui_ctx.set_visuals(egui::Visuals::dark());
+
+			egui::CentralPanel::default().show(egui_ctx, |ui| {
+				ui.vertical_centered(|ui| {
+					ui.spacing_mut().button_padding = egui::Vec2::new(30.0, 15.5);
+
+					ui.label(
+						RichText::new(tr("summary-title"))
+							.strong()
+							.font(FontId::proportional(45.0)),
+					);
+
+					ui.add_space(25.0);
+
+					self.lines().into_iter().for_each(|line| {
+						ui.label(RichText::new(line).font(FontId::proportional(25.0)));
+					});
+
+					ui.add_space(25.0);
+
+					if ui
+						.button(
+							RichText::new(tr("menu-main-menu"))
+								.strong()
+								.font(FontId::proportional(30.0)),
+						)
+						.clicked()
+					{
+						done = true;
+					}
+				});
+			});
+		});
+
+		egui_macroquad::draw();
+
+		done
+	}
+}
+
+enum Stage {
+	Banner,
+	Credits,
+	Summary,
+}
+
+/// Everything shown after the rat king dies: a victory banner, then the
+/// credits, then a summary of the run
+pub struct VictoryScreen {
+	stage: Stage,
+	stage_started: f64,
+	summary: RunSummary,
+}
+
+impl VictoryScreen {
+	/// Records the win, and sums up the run
+	pub fn new(game_state: &GameState, progression: &mut Progression) -> Self {
+		let frames = game_state.won_on_frame.unwrap_or(game_state.frame);
+
+		Self {
+			stage: Stage::Banner,
+			stage_started: get_time(),
+			summary: RunSummary::new(
+				game_state,
+				frames,
+				game_state.map.current_floor_index() + 1,
+				progression.record_win(frames),
+			),
+		}
+	}
+
+	fn next_stage(&mut self, stage: Stage) {
+		self.stage = stage;
+		self.stage_started = get_time();
+	}
+
+	/// Draws the current stage, returning true once the player is done
+	/// looking at the summary
+	pub fn update(&mut self) -> bool {
+		clear_background(BLACK);
+
+		let elapsed = get_time() - self.stage_started;
+		let skipping =
+			get_last_key_pressed().is_some() || is_mouse_button_pressed(MouseButton::Left);
+
+		match self.stage {
+			Stage::Banner => {
+				let alpha = (elapsed / BANNER_TIME * 2.0).min(1.0) as f32;
+
+				draw_centered_text(
+					&tr("victory-title"),
+					screen_height() / 2.0,
+					80.0,
+					Color::new(1.0, 0.84, 0.0, alpha),
+				);
+				draw_centered_text(
+					&tr("victory-subtitle"),
+					screen_height() / 2.0 + 50.0,
+					30.0,
+					Color::new(1.0, 1.0, 1.0, alpha),
+				);
+
+				if elapsed > BANNER_TIME || skipping {
+					self.next_stage(Stage::Credits);
+				}
+			},
+			Stage::Credits => {
+				let scroll = elapsed as f32 * CREDITS_SPEED;
+
+				CREDITS.iter().enumerate().for_each(|(i, id)| {
+					let y = screen_height() + i as f32 * CREDITS_LINE_HEIGHT - scroll;
+					draw_centered_text(&tr(id), y, 35.0, WHITE);
+				});
+
+				let credits_height = CREDITS.len() as f32 * CREDITS_LINE_HEIGHT;
+
+				if scroll > screen_height() + credits_height || skipping {
+					self.next_stage(Stage::Summary);
+				}
+			},
+			Stage::Summary => return self.summary.draw(),
+		};
+
+		false
+	}
+}
+
+pub(crate) fn draw_centered_text(text: &str, y: f32, font_size: f32, color: Color) {
+	let size = measure_text(text, None, font_size as u16, 1.0);
+	draw_text(
+		text,
+		(screen_width() - size.width) / 2.0,
+		y,
+		font_size,
+		color,
+	);
+}